@@ -0,0 +1,73 @@
+// Run with `cargo bench`, or e.g. `cargo bench -- functions/` for a single group
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use swarm_optimizers::{bats, butterflies, functions::Functions, optimizer::Optimizer, vector::VectorN};
+
+const FUNCTION_NAMES: [&str; 6] = ["ackley", "schwefel", "brown", "rastrigin", "schwefel2", "solomon"];
+const POPULATION_SIZES: [usize; 3] = [10, 50, 250];
+
+fn bench_functions_of_size<const N: usize>(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("functions");
+    for name in FUNCTION_NAMES {
+        let function = Functions::<N>::make_from_name(name);
+        let input = VectorN::random_uniform(function.get_bounds(), &mut Xoshiro256PlusPlus::seed_from_u64(0));
+        group.bench_with_input(BenchmarkId::new(name, N), &input, |bencher, input| bencher.iter(|| function.calculate(std::hint::black_box(*input))));
+    }
+    group.finish();
+}
+
+fn bench_functions(criterion: &mut Criterion) {
+    bench_functions_of_size::<2>(criterion);
+    bench_functions_of_size::<10>(criterion);
+    bench_functions_of_size::<30>(criterion);
+}
+
+// The monomorphized objectives against the same ones on a Vec of the same length, the choice behind main.rs's DIMENSIONS
+fn bench_dispatch_of_size<const N: usize>(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group(format!("dispatch/{N}d"));
+    for name in FUNCTION_NAMES {
+        let function = Functions::<N>::make_from_name(name);
+        let input = VectorN::random_uniform(function.get_bounds(), &mut Xoshiro256PlusPlus::seed_from_u64(0));
+        let dynamic_input = input.coordinates.to_vec();
+        group.bench_with_input(BenchmarkId::new("const", name), &input, |bencher, input| bencher.iter(|| function.calculate(std::hint::black_box(*input))));
+        group.bench_with_input(BenchmarkId::new("dynamic", name), &dynamic_input, |bencher, input| bencher.iter(|| function.calculate_dynamic(std::hint::black_box(input))));
+    }
+    group.finish();
+}
+
+fn bench_dispatch(criterion: &mut Criterion) {
+    bench_dispatch_of_size::<2>(criterion);
+    bench_dispatch_of_size::<10>(criterion);
+    bench_dispatch_of_size::<20>(criterion);
+    bench_dispatch_of_size::<30>(criterion);
+    bench_dispatch_of_size::<50>(criterion);
+    bench_dispatch_of_size::<100>(criterion);
+}
+
+// A single do_iteration on a freshly reset world, on rastrigin
+fn bench_iterations_of_size<const N: usize>(criterion: &mut Criterion) {
+    let function = Functions::<N>::make_from_name("rastrigin");
+    let mut group = criterion.benchmark_group(format!("iteration/{N}d"));
+    for population_size in POPULATION_SIZES {
+        let world = bats::WorldState::new(population_size, function, function.get_bounds(), (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, Xoshiro256PlusPlus::seed_from_u64(0));
+        group.bench_with_input(BenchmarkId::new("bats", population_size), &world, |bencher, world| {
+            bencher.iter_batched_ref(|| world.clone(), |world| world.do_iteration(100), BatchSize::SmallInput);
+        });
+        let world = butterflies::WorldState::new(population_size, function, function.get_bounds(), 0.1, (0.1, 0.3), 0.8, Xoshiro256PlusPlus::seed_from_u64(0));
+        group.bench_with_input(BenchmarkId::new("butterflies", population_size), &world, |bencher, world| {
+            bencher.iter_batched_ref(|| world.clone(), |world| world.do_iteration(100), BatchSize::SmallInput);
+        });
+    }
+    group.finish();
+}
+
+fn bench_iterations(criterion: &mut Criterion) {
+    bench_iterations_of_size::<2>(criterion);
+    bench_iterations_of_size::<10>(criterion);
+    bench_iterations_of_size::<30>(criterion);
+}
+
+criterion_group!(benches, bench_functions, bench_dispatch, bench_iterations);
+criterion_main!(benches);
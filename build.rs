@@ -0,0 +1,17 @@
+// Regenerates include/swarm_optimizers.h from src/ffi.rs when building with --features ffi
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo::rerun-if-changed=src/ffi.rs");
+        println!("cargo::rerun-if-changed=cbindgen.toml");
+        let crate_directory = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{crate_directory}/cbindgen.toml")).unwrap();
+        cbindgen::Builder::new()
+            .with_crate(&crate_directory)
+            .with_config(config)
+            .generate()
+            .expect("Unable to generate the C header")
+            .write_to_file(format!("{crate_directory}/include/swarm_optimizers.h"));
+    }
+}
@@ -0,0 +1,112 @@
+#ifndef SWARM_OPTIMIZERS_H
+#define SWARM_OPTIMIZERS_H
+
+// Generated by build.rs from src/ffi.rs, do not edit
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum SwarmAlgorithm {
+  SWARM_ALGORITHM_BATS = 0,
+  SWARM_ALGORITHM_BUTTERFLIES = 1,
+} SwarmAlgorithm;
+
+typedef enum SwarmStatus {
+  SWARM_STATUS_OK = 0,
+  SWARM_STATUS_INVALID_ARGUMENT = 1,
+  SWARM_STATUS_PANICKED = 2,
+} SwarmStatus;
+
+// An optimizer with its objective, parameters and population. Not thread safe, one thread at a time may use it
+typedef struct SwarmOptimizer SwarmOptimizer;
+
+// The function to minimize. Gets the `dimensions` coordinates of a point and the `user_data` given at creation.
+// Called on the thread calling swarm_run, swarm_reset or the getters, and must not unwind (throw C++ exceptions)
+typedef double (*SwarmObjective)(const double *coordinates, size_t dimensions, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// An optimizer minimizing `objective` over [lower_bound, upper_bound] in every coordinate, with the defaults of
+// run_sweep.sh. The dimensions must be one of 2, 10, 20, 30, 50 or 100. Returns null on invalid arguments
+struct SwarmOptimizer *swarm_create(enum SwarmAlgorithm algorithm,
+                                    size_t dimensions,
+                                    SwarmObjective objective,
+                                    void *user_data,
+                                    double lower_bound,
+                                    double upper_bound,
+                                    uint64_t seed);
+
+// Like swarm_create, minimizing one of the benchmark functions of the command line ("ackley", "rastrigin", ...) in
+// its usual bounds
+//
+// # Safety
+// `function` is null or a nul terminated string
+struct SwarmOptimizer *swarm_create_benchmark(enum SwarmAlgorithm algorithm,
+                                              size_t dimensions,
+                                              const char *function,
+                                              uint64_t seed);
+
+// Sets a parameter by the name of its command line flag, e.g. "bat-count" or "local-search-chance". Starts over with
+// a new population from the original seed on the next use, so parameters should be set before running
+//
+// # Safety
+// `optimizer` comes from a create function and `name` is a nul terminated string
+enum SwarmStatus swarm_set_parameter(struct SwarmOptimizer *optimizer,
+                                     const char *name,
+                                     double value);
+
+// Continues the run for `iterations` iterations or until `eval_budget` more evaluations, exactly one of them non zero.
+// With a finite `target_value` the run stops once the best value is at most the target, and
+// `evaluations_to_target` (may be null) gets the evaluations it took, or 0 if the target wasn't reached
+//
+// # Safety
+// `optimizer` comes from a create function and `evaluations_to_target` is null or valid for writes
+enum SwarmStatus swarm_run(struct SwarmOptimizer *optimizer,
+                           size_t iterations,
+                           size_t eval_budget,
+                           double target_value,
+                           size_t *evaluations_to_target);
+
+// A fresh population, continuing the random sequence
+//
+// # Safety
+// `optimizer` comes from a create function
+enum SwarmStatus swarm_reset(struct SwarmOptimizer *optimizer);
+
+// The best value found so far, NaN on errors
+//
+// # Safety
+// `optimizer` comes from a create function
+double swarm_best_value(struct SwarmOptimizer *optimizer);
+
+// Copies the best solution found so far into `coordinates`, which has room for `length` values. `length` must be the
+// dimensions of the optimizer
+//
+// # Safety
+// `optimizer` comes from a create function and `coordinates` is valid for `length` writes
+enum SwarmStatus swarm_best_solution(struct SwarmOptimizer *optimizer,
+                                     double *coordinates,
+                                     size_t length);
+
+// Objective evaluations since creation or the last reset, 0 on errors
+//
+// # Safety
+// `optimizer` comes from a create function
+size_t swarm_evaluation_count(struct SwarmOptimizer *optimizer);
+
+// The message of the last failed call on this thread, or null. Valid until the next failing call on this thread
+const char *swarm_last_error(void);
+
+// Frees the optimizer. Null is ignored
+//
+// # Safety
+// `optimizer` comes from a create function and isn't used afterwards
+void swarm_destroy(struct SwarmOptimizer *optimizer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SWARM_OPTIMIZERS_H */
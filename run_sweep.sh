@@ -35,10 +35,10 @@ for pulse_rate_factor in "${pulse_rate_factors[@]}"
 do
 	for loudness_cooling_rate in "${loudness_cooling_rates[@]}"
 	do
-		./target/release/swarm_optimizers --functions=$functions --try-count $runs_per_set bats --bat-count $bat_count \
+		./target/release/swarm_optimizers --functions=$functions --try-count $runs_per_set --output-format jsonl bats --bat-count $bat_count \
 			--bat-num-iters $bat_iters --frequency-left-bound $frequency_left_bound --frequency-right-bound $frequency_right_bound \
 			--initial-pulse-rate $initial_pulse_rate --initial-loudness $initial_loudness --pulse-rate-factor $pulse_rate_factor \
-			--loudness-cooling-rate $loudness_cooling_rate > "output_bats/ratefactor_"$pulse_rate_factor"_coolingrate_"$loudness_cooling_rate".txt"
+			--loudness-cooling-rate $loudness_cooling_rate > "output_bats/ratefactor_"$pulse_rate_factor"_coolingrate_"$loudness_cooling_rate".jsonl"
 	done
 done
 
@@ -47,8 +47,8 @@ for fragrance_multipier in "${fragrance_multipiers[@]}"
 do
 	for local_search_chance in "${local_search_chances[@]}"
 	do
-		./target/release/swarm_optimizers --functions=$functions --try-count $runs_per_set butterflies --butterfly-count $butterfly_count \
+		./target/release/swarm_optimizers --functions=$functions --try-count $runs_per_set --output-format jsonl butterflies --butterfly-count $butterfly_count \
 			--butterfly-num-iters $butterfly_iters --fragrance-exponent-left-bound $fragrance_exponent_left_bound --fragrance-exponent-right-bound $fragrance_exponent_right_bound \
-			--fragrance-multiplier $fragrance_multipier --local-search-chance $local_search_chance > "output_butterflies/multiplier_"$fragrance_multipier"_searchchance_"$local_search_chance".txt"
+			--fragrance-multiplier $fragrance_multipier --local-search-chance $local_search_chance > "output_butterflies/multiplier_"$fragrance_multipier"_searchchance_"$local_search_chance".jsonl"
 	done
 done
\ No newline at end of file
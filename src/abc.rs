@@ -0,0 +1,321 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub bee_count: usize, // Food sources, with an employed and an onlooker bee for each
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub limit: usize, // Failed improvements after which a source is abandoned to a scout
+    pub parallel: bool, // Moves the employed bees and evaluates the sources on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.bee_count < 2 {
+            return Err("The colony needs at least two food sources, every move uses another one");
+        }
+        if self.limit == 0 {
+            return Err("The abandonment limit must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// v = x, with one coordinate moved by a random fraction in [-1, 1) of its difference to the partner
+fn neighbour<const N: usize>(position: VectorN<Real, N>, partner: VectorN<Real, N>, bounds: (Real, Real), random_source: &mut impl Rng) -> VectorN<Real, N> {
+    let dimension = random_source.gen_range(0..N);
+    let mut candidate = position;
+    candidate[dimension] += random_source.gen_range(-1.0..1.0) * (position[dimension] - partner[dimension]);
+    candidate.clamp(bounds);
+    return candidate;
+}
+
+// Any index but excluded
+fn other_index(count: usize, excluded: usize, random_source: &mut impl Rng) -> usize {
+    let index = random_source.gen_range(0..count - 1);
+    return if index >= excluded { index + 1 } else { index };
+}
+
+#[derive(Clone, Debug)]
+pub struct FoodSource<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    trials: usize, // Failed improvements in a row
+    candidate: VectorN<Real, N>, // Found by the employed bee in this iteration, kept if it's better
+    random_source: RngType, // Seeded from the population seed and the source's index, so employed bees can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> FoodSource<N, RngType> {
+    // Not evaluated yet, the world evaluates all sources at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, trials: 0, candidate: position, random_source };
+    }
+
+    fn search<F>(&mut self, parameters: &Parameters<N, F>, index: usize, previous_positions: &[VectorN<Real, N>]) {
+        let partner = previous_positions[other_index(previous_positions.len(), index, &mut self.random_source)];
+        self.candidate = neighbour(self.position, partner, parameters.bounds, &mut self.random_source);
+    }
+
+    // Greedy selection, counting the failures. The first of equally good positions stays
+    fn offer(&mut self, candidate: VectorN<Real, N>, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.function_value) {
+            self.position = candidate;
+            self.function_value = value;
+            self.trials = 0;
+        } else {
+            self.trials += 1;
+        }
+    }
+
+    // The share of the onlookers the source gets, higher for better sources
+    fn quality(&self) -> Real {
+        if self.function_value.is_nan() {
+            return 0.0;
+        }
+        if self.function_value >= 0.0 {
+            return 1.0 / (1.0 + self.function_value);
+        }
+        return 1.0 + self.function_value.abs();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    sources: Vec<FoodSource<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType, // Also picks the sources of the onlookers
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    previous_positions: Vec<VectorN<Real, N>>,
+    cumulative_qualities: Vec<Real>,
+    onlooker_sources: Vec<usize>,
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(bee_count: usize, function: F, bounds: (Real, Real), limit: usize, random_source: RngType) -> Self {
+        let parameters = Parameters {
+            bee_count, function, bounds, limit,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            sources: Vec::with_capacity(parameters.bee_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            previous_positions: Vec::with_capacity(parameters.bee_count),
+            cumulative_qualities: Vec::with_capacity(parameters.bee_count),
+            onlooker_sources: Vec::with_capacity(parameters.bee_count),
+            positions: Vec::with_capacity(parameters.bee_count),
+            values: Vec::with_capacity(parameters.bee_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // self.positions into self.values
+    fn evaluate_positions(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+        self.evaluation_count += self.positions.len();
+    }
+
+    // Every employed bee tries a neighbour of its source
+    fn employed_phase(&mut self) {
+        self.previous_positions.clear();
+        self.previous_positions.extend(self.sources.iter().map(|source| source.position));
+        let parameters = &*self.parameters;
+        let previous_positions = &self.previous_positions;
+        let search = |(index, source): (usize, &mut FoodSource<N, RngType>)| source.search(parameters, index, previous_positions);
+        if parameters.parallel {
+            self.sources.par_iter_mut().enumerate().for_each(search);
+        } else {
+            self.sources.iter_mut().enumerate().for_each(search);
+        }
+        self.positions.clear();
+        self.positions.extend(self.sources.iter().map(|source| source.candidate));
+        self.evaluate_positions();
+        for (source, &value) in self.sources.iter_mut().zip(&self.values) {
+            let candidate = source.candidate;
+            source.offer(candidate, value);
+        }
+    }
+
+    // The onlookers pick sources by roulette on their quality, then all of them are evaluated at once. Onlookers of
+    // the same source all search around its position from before the phase, and are offered to it in order
+    fn onlooker_phase(&mut self) {
+        self.cumulative_qualities.clear();
+        let mut total_quality = 0.0;
+        for source in &self.sources {
+            total_quality += source.quality();
+            self.cumulative_qualities.push(total_quality);
+        }
+        self.onlooker_sources.clear();
+        self.positions.clear();
+        for _ in 0..self.sources.len() {
+            let index = if total_quality > 0.0 && total_quality.is_finite() {
+                let target = self.random_generator.gen::<Real>() * total_quality;
+                self.cumulative_qualities.partition_point(|&quality| quality <= target).min(self.sources.len() - 1)
+            } else {
+                self.random_generator.gen_range(0..self.sources.len())
+            };
+            let partner = self.sources[other_index(self.sources.len(), index, &mut self.random_generator)].position;
+            self.positions.push(neighbour(self.sources[index].position, partner, self.parameters.bounds, &mut self.random_generator));
+            self.onlooker_sources.push(index);
+        }
+        self.evaluate_positions();
+        for ((&index, &candidate), &value) in self.onlooker_sources.iter().zip(&self.positions).zip(&self.values) {
+            self.sources[index].offer(candidate, value);
+        }
+    }
+
+    // The most exhausted source past the limit is replaced by a random one
+    fn scout_phase(&mut self) {
+        let limit = self.parameters.limit;
+        let Some(index) = (0..self.sources.len()).filter(|&index| self.sources[index].trials > limit).max_by_key(|&index| self.sources[index].trials) else {
+            return;
+        };
+        let source = &mut self.sources[index];
+        source.position = VectorN::random_uniform(self.parameters.bounds, &mut source.random_source);
+        source.trials = 0;
+        self.positions.clear();
+        self.positions.push(source.position);
+        self.evaluate_positions();
+        self.sources[index].function_value = self.values[0];
+    }
+
+    // Takes the first of equally good sources
+    fn update_best_known_solution(&mut self) {
+        let best_source = self.sources.iter().min_by_key(|source| Fitness::minimize(source.function_value)).unwrap();
+        if Fitness::minimize(best_source.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_source.function_value;
+            self.best_solution = best_source.position;
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.employed_phase();
+        self.onlooker_phase();
+        // The best is memorized before a scout can abandon it
+        self.update_best_known_solution();
+        self.scout_phase();
+        self.update_best_known_solution();
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.sources.iter().map(|source| source.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.sources.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.bee_count {
+            self.sources.push(FoodSource::new(&self.parameters, population_seed, index));
+        }
+        self.positions.clear();
+        self.positions.extend(self.sources.iter().map(|source| source.position));
+        self.evaluate_positions();
+        for (source, &value) in self.sources.iter_mut().zip(&self.values) {
+            source.function_value = value;
+        }
+        self.update_best_known_solution();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // The scout's evaluation is only needed in some iterations
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * self.sources.len() + 1;
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.sources.iter().map(|source| source.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn colony_test() {
+        let function = Functions::<10>::make_from_name("rastrigin");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), 50, Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(300);
+        assert!(world.best_solution_value() < initial_best);
+        // Every iteration has both bee phases, the scouts come out once sources get exhausted
+        assert!(world.evaluation_count() > 20 + 300 * 40 && world.evaluation_count() <= 20 + 300 * 41);
+    }
+}
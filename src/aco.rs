@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use rand::{distributions::{Distribution, WeightedIndex}, Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::{self, Real}, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub ant_count: usize, // New solutions sampled in every iteration
+    pub archive_size: usize, // k, the best solutions kept
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub q: Real, // How strongly the ants prefer the better solutions of the archive, smaller is greedier
+    pub xi: Real, // ξ, the width of the Gaussian kernels relative to the spread of the archive. Larger converges slower
+    pub parallel: bool, // Samples and evaluates the ants on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.ant_count == 0 {
+            return Err("There must be at least one ant");
+        }
+        if self.archive_size < 2 {
+            return Err("The archive needs at least two solutions, for the kernels to have a width");
+        }
+        if self.q.is_nan() || self.q <= 0.0 {
+            return Err("q must be positive");
+        }
+        if self.xi.is_nan() || self.xi <= 0.0 {
+            return Err("xi must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The weight of the solution of the given rank, 0 being the best. A Gaussian of the rank with a standard deviation of qk
+pub fn rank_weight(rank: usize, archive_size: usize, q: Real) -> f64 {
+    let width = real::to_f64(q) * archive_size as f64;
+    return (-(rank as f64).powi(2) / (2.0 * width * width)).exp() / (width * std::f64::consts::TAU.sqrt());
+}
+
+#[derive(Clone, Debug)]
+pub struct Ant<const N: usize, RngType: Rng> {
+    candidate: VectorN<Real, N>,
+    random_source: RngType, // Seeded from the population seed and the ant's index, so ants can sample in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Ant<N, RngType> {
+    fn new(population_seed: u64, index: usize) -> Self {
+        return Self { candidate: VectorN::default(), random_source: agent_random_source::<RngType>(population_seed, index) };
+    }
+
+    // From the kernel of a solution of the archive picked by its rank weight, centred on the solution and as wide as its
+    // spread
+    fn sample<F>(&mut self, parameters: &Parameters<N, F>, guide: &WeightedIndex<f64>, archive: &[(Real, VectorN<Real, N>)], spreads: &[VectorN<Real, N>]) {
+        let index = guide.sample(&mut self.random_source);
+        self.candidate = archive[index].1 + VectorN::random_from(&StandardNormal, &mut self.random_source) * spreads[index];
+        self.candidate.clamp(parameters.bounds);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    ants: Vec<Ant<N, RngType>>,
+    archive: Vec<(Real, VectorN<Real, N>)>, // The best solutions found so far, best first
+    guide: WeightedIndex<f64>, // Picks the rank of the solution an ant samples around, built once since the weights only depend on the rank
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    spreads: Vec<VectorN<Real, N>>, // The standard deviations of the kernel of each solution of the archive
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(ant_count: usize, archive_size: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            ant_count, archive_size, function, bounds,
+            q: 0.5,
+            xi: 0.85,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let weights = (0..parameters.archive_size).map(|rank| rank_weight(rank, parameters.archive_size, parameters.q));
+        let mut world = Self {
+            ants: Vec::with_capacity(parameters.ant_count),
+            archive: Vec::with_capacity(parameters.archive_size + parameters.ant_count),
+            guide: WeightedIndex::new(weights).unwrap(),
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            spreads: Vec::with_capacity(parameters.archive_size),
+            positions: Vec::with_capacity(parameters.archive_size.max(parameters.ant_count)),
+            values: Vec::with_capacity(parameters.archive_size.max(parameters.ant_count)),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // Best first
+    pub fn archive(&self) -> &[(Real, VectorN<Real, N>)] {
+        return &self.archive;
+    }
+
+    // Of self.positions, into self.values
+    fn evaluate_positions(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // ξ times the mean distance from each solution to the others, per coordinate
+    fn update_spreads(&mut self) {
+        let scale = self.parameters.xi / (self.archive.len() - 1) as Real;
+        self.spreads.clear();
+        for &(_, solution) in &self.archive {
+            let mut spread = VectorN::default();
+            for &(_, other) in &self.archive {
+                for dimension in 0..N {
+                    spread[dimension] += (other[dimension] - solution[dimension]).abs();
+                }
+            }
+            self.spreads.push(spread * scale);
+        }
+    }
+
+    fn sample_ants(&mut self) {
+        self.update_spreads();
+        let parameters = &*self.parameters;
+        let guide = &self.guide;
+        let archive = &self.archive;
+        let spreads = &self.spreads;
+        let sample = |ant: &mut Ant<N, RngType>| ant.sample(parameters, guide, archive, spreads);
+        if parameters.parallel {
+            self.ants.par_iter_mut().for_each(sample);
+        } else {
+            self.ants.iter_mut().for_each(sample);
+        }
+        self.positions.clear();
+        self.positions.extend(self.ants.iter().map(|ant| ant.candidate));
+    }
+
+    // The best of the archive and the new solutions stay. The sort is stable, so older solutions stay ahead of equally
+    // good new ones
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.archive.first().map_or(Real::INFINITY, |solution| solution.0);
+        self.evaluation_count += values.len();
+        self.archive.extend(values.iter().copied().zip(self.positions.iter().copied()));
+        self.archive.sort_by_key(|&(value, _)| Fitness::minimize(value));
+        self.archive.truncate(self.parameters.archive_size);
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.positions.iter().copied(), previous_best_value, self.archive[0].1, self.archive[0].0);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.sample_ants();
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.sample_ants();
+        positions.extend_from_slice(&self.positions);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    // The archive starts out filled with uniformly random solutions
+    fn reset(&mut self) {
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.archive.clear();
+        self.ants.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.ant_count {
+            self.ants.push(Ant::new(population_seed, index));
+        }
+        self.positions.clear();
+        for _ in 0..self.parameters.archive_size {
+            self.positions.push(VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator));
+        }
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.archive[0].1;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.archive[0].0;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.ants.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.archive.iter().map(|&(_, solution)| solution));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{fitness::Fitness, functions::Functions, optimizer::Optimizer};
+
+    use super::{rank_weight, WorldState};
+
+    #[test]
+    fn archive_test() {
+        assert!(rank_weight(0, 50, 0.5) > rank_weight(1, 50, 0.5));
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(10, 50, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert_eq!(world.archive().len(), 50);
+        assert!(world.archive().is_sorted_by_key(|&(value, _)| Fitness::minimize(value)));
+        assert_eq!(world.evaluation_count(), 50 + 10 * 200);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
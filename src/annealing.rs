@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub initial_temperature: Real,
+    pub cooling_rate: Real, // The temperature is multiplied by it after every iteration, between 0 and 1
+    // Standard deviation of the moves as a fraction of the width of the bounds, shrinking geometrically from initial_radius
+    // to min_radius over the planned run. It doesn't follow the temperature, which is near 0 after a few hundred iterations
+    pub initial_radius: Real,
+    pub min_radius: Real,
+    pub moves_per_iteration: usize, // Tried at the same temperature, one after another
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.initial_temperature.is_nan() || self.initial_temperature <= 0.0 {
+            return Err("Initial temperature must be positive");
+        }
+        if self.cooling_rate.is_nan() || self.cooling_rate <= 0.0 || self.cooling_rate >= 1.0 {
+            return Err("Cooling rate must be between 0 and 1, exclusive");
+        }
+        if self.initial_radius.is_nan() || self.initial_radius <= 0.0 {
+            return Err("Initial radius must be positive");
+        }
+        if self.min_radius.is_nan() || self.min_radius <= 0.0 || self.min_radius > self.initial_radius {
+            return Err("Minimum radius must be positive and can't be above the initial one");
+        }
+        if self.moves_per_iteration == 0 {
+            return Err("There must be at least one move per iteration");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    position: VectorN<Real, N>,
+    function_value: Real,
+    temperature: Real,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(function: F, bounds: (Real, Real), initial_temperature: Real, cooling_rate: Real, random_source: RngType) -> Self {
+        let parameters = Parameters {
+            function, bounds, initial_temperature, cooling_rate,
+            initial_radius: 0.1,
+            min_radius: 0.001,
+            moves_per_iteration: 1,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            position: VectorN::default(),
+            function_value: Real::INFINITY,
+            temperature: parameters.initial_temperature,
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    pub fn temperature(&self) -> Real {
+        return self.temperature;
+    }
+
+    // A Gaussian step, accepted if it's better or by the Metropolis criterion. Never accepts NaN. Runs longer than planned
+    // keep the minimum radius
+    fn try_move(&mut self, iteration_count: usize) {
+        let parameters = &*self.parameters;
+        let progress = (self.iteration as Real / iteration_count.max(1) as Real).min(1.0);
+        let radius_fraction = parameters.initial_radius * (parameters.min_radius / parameters.initial_radius).powf(progress);
+        let radius = radius_fraction * (parameters.bounds.1 - parameters.bounds.0);
+        let mut candidate = VectorN::random_gaussian(self.position, radius, &mut self.random_generator);
+        candidate.clamp(parameters.bounds);
+        let value = parameters.function.evaluate(candidate);
+        self.evaluation_count += 1;
+        let accepted = if Fitness::minimize(value) <= Fitness::minimize(self.function_value) {
+            !value.is_nan()
+        } else {
+            self.random_generator.gen::<Real>() < (-(value - self.function_value) / self.temperature).exp()
+        };
+        if accepted {
+            self.position = candidate;
+            self.function_value = value;
+        }
+        if Fitness::minimize(value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution = candidate;
+            self.best_solution_value = value;
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        for _ in 0..self.parameters.moves_per_iteration {
+            self.try_move(iteration_count);
+        }
+        self.temperature *= self.parameters.cooling_rate;
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, std::iter::once(self.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.iteration = 0;
+        self.temperature = self.parameters.initial_temperature;
+        self.position = VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator);
+        self.function_value = self.parameters.function.evaluate(self.position);
+        self.evaluation_count = 1;
+        self.best_solution = self.position;
+        self.best_solution_value = self.function_value;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.parameters.moves_per_iteration;
+    }
+
+    // The current solution, which may be worse than the best one
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.push(self.position);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::{Optimizer, RunLength}, random_search, real::Real};
+
+    use super::WorldState;
+
+    #[test]
+    fn cooling_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(function, function.get_bounds(), 10.0, 0.99, Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(1000);
+        assert!((world.temperature() - 10.0 * 0.99f64.powi(1000) as Real).abs() < 1e-5);
+        assert_eq!(world.evaluation_count(), 1001);
+        assert!(world.best_solution_value() < initial_best);
+    }
+
+    #[test]
+    fn beats_random_search_test() {
+        // Long after the temperature is near 0 the moves still get anywhere, on multimodal functions too
+        for name in ["ackley", "rastrigin"] {
+            let function = Functions::<10>::make_from_name(name);
+            let (mut annealing_total, mut random_total) = (0.0, 0.0);
+            for seed in 0..5 {
+                let mut annealing = WorldState::<10, _>::new(function, function.get_bounds(), 10.0, 0.99, Xoshiro256PlusPlus::seed_from_u64(seed));
+                let mut random = random_search::WorldState::<10, _>::new(30, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(seed));
+                annealing.run(RunLength::Evaluations(20_000), None);
+                random.run(RunLength::Evaluations(20_000), None);
+                annealing_total += annealing.best_solution_value();
+                random_total += random.best_solution_value();
+            }
+            assert!(annealing_total < random_total / 2.0, "{name}: annealing averages {}, random search {}", annealing_total / 5.0, random_total / 5.0);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// The optimizers as argmin solvers, built with `--features argmin`. The problem is any argmin cost function of a
+// Vec<f64>, the state a PopulationState with the best solution as its individual and the agents as its population:
+//
+//     let parameters = bats::Parameters { bat_count: 20, function: (), bounds: (-5.0, 5.0), ... };
+//     let result = Executor::new(problem, SwarmSolver::<20, _, _>::new(parameters, 1))
+//         .configure(|state| state.max_iters(1000).target_cost(1e-6))
+//         .run()?;
+//
+// The function of the parameters is ignored. The solvers hold their worlds, which can't be serialized, so checkpointing
+// isn't supported
+
+use std::sync::Arc;
+
+use argmin::core::{CostFunction, Error, PopulationState, Problem, Solver, State, KV};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{bats, butterflies, functions::Function, optimizer::{FromParameters, Optimizer}, real::{self, Real}, vector::VectorN};
+
+// The cost function as seen by the worlds. They only evaluate through it for their initial population, every iteration
+// is evaluated through the executor's problem
+#[derive(Clone, Debug)]
+pub struct ArgminObjective<O>(O);
+
+impl<const N: usize, O: CostFunction<Param = Vec<f64>, Output = f64>> Function<N> for ArgminObjective<O> {
+    fn evaluate(&self, input: VectorN<Real, N>) -> Real {
+        let value = self.0.cost(&coordinates(&input)).unwrap_or_else(|error| panic!("The cost function failed on the initial population: {error}"));
+        return real::from_f64(value);
+    }
+}
+
+fn coordinates<const N: usize>(input: &VectorN<Real, N>) -> Vec<f64> {
+    return input.coordinates.iter().map(|&coordinate| real::to_f64(coordinate)).collect();
+}
+
+// Parameters an argmin solver can be made of, building the world once the problem is known
+pub trait SwarmParameters<const N: usize> {
+    type World<O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync>: Optimizer<N>;
+    const NAME: &'static str;
+
+    fn check(&self) -> Result<(), &'static str>;
+    fn build<O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync>(&self, function: ArgminObjective<O>, seed: u64) -> Self::World<O>;
+}
+
+impl<const N: usize, F> SwarmParameters<N> for bats::Parameters<N, F> {
+    type World<O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync> = bats::WorldState<N, Xoshiro256PlusPlus, ArgminObjective<O>>;
+    const NAME: &'static str = "Bat algorithm";
+
+    fn check(&self) -> Result<(), &'static str> {
+        return bats::Parameters::check(self);
+    }
+
+    fn build<O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync>(&self, function: ArgminObjective<O>, seed: u64) -> Self::World<O> {
+        let parameters = bats::Parameters {
+            bat_count: self.bat_count,
+            function,
+            bounds: self.bounds,
+            frequency_bounds: self.frequency_bounds,
+            initial_pulse_rate: self.initial_pulse_rate,
+            pulse_rate_factor: self.pulse_rate_factor,
+            initial_loudness: self.initial_loudness,
+            loudness_cool_factor: self.loudness_cool_factor,
+            movement: self.movement,
+            initial_velocity: self.initial_velocity,
+            improvement_tolerance: self.improvement_tolerance,
+            scale_walk_to_bounds: self.scale_walk_to_bounds,
+            parallel: self.parallel,
+        };
+        return bats::WorldState::from_parameters(Arc::new(parameters), seed);
+    }
+}
+
+impl<const N: usize, F> SwarmParameters<N> for butterflies::Parameters<N, F> {
+    type World<O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync> = butterflies::WorldState<N, Xoshiro256PlusPlus, ArgminObjective<O>>;
+    const NAME: &'static str = "Butterfly optimization algorithm";
+
+    fn check(&self) -> Result<(), &'static str> {
+        return butterflies::Parameters::check(self);
+    }
+
+    fn build<O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync>(&self, function: ArgminObjective<O>, seed: u64) -> Self::World<O> {
+        let parameters = butterflies::Parameters {
+            population_size: self.population_size,
+            function,
+            bounds: self.bounds,
+            fragrance_multiplier: self.fragrance_multiplier,
+            fragrance_exponent_bounds: self.fragrance_exponent_bounds,
+            local_search_chance: self.local_search_chance,
+            movement: self.movement,
+            initial_velocity: self.initial_velocity,
+            improvement_tolerance: self.improvement_tolerance,
+            parallel: self.parallel,
+        };
+        return butterflies::WorldState::from_parameters(Arc::new(parameters), seed);
+    }
+}
+
+// Needs a problem that can be cloned, the world keeps a copy for its initial population
+pub struct SwarmSolver<const N: usize, P: SwarmParameters<N>, O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync> {
+    parameters: P,
+    seed: u64,
+    world: Option<P::World<O>>, // Built by init
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, P: SwarmParameters<N>, O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync> SwarmSolver<N, P, O> {
+    pub fn new(parameters: P, seed: u64) -> Self {
+        return Self { parameters, seed, world: None, positions: Vec::new(), values: Vec::new() };
+    }
+
+    // The population after the last iteration, with the best solution and value
+    fn updated_state(&mut self, state: PopulationState<Vec<f64>, f64>) -> PopulationState<Vec<f64>, f64> {
+        let world = self.world.as_ref().unwrap();
+        self.positions.clear();
+        world.population(&mut self.positions);
+        return state
+            .individual(coordinates(&world.best_solution()))
+            .cost(real::to_f64(world.best_solution_value()))
+            .population(self.positions.iter().map(coordinates).collect());
+    }
+}
+
+impl<const N: usize, P: SwarmParameters<N>, O: CostFunction<Param = Vec<f64>, Output = f64> + Clone + Send + Sync> Solver<O, PopulationState<Vec<f64>, f64>> for SwarmSolver<N, P, O> {
+    fn name(&self) -> &str {
+        return P::NAME;
+    }
+
+    fn init(&mut self, problem: &mut Problem<O>, state: PopulationState<Vec<f64>, f64>) -> Result<(PopulationState<Vec<f64>, f64>, Option<KV>), Error> {
+        self.parameters.check().map_err(Error::msg)?;
+        let operator = problem.problem.as_ref().ok_or_else(|| Error::msg("The executor has no problem"))?;
+        let world = self.parameters.build(ArgminObjective(operator.clone()), self.seed);
+        *problem.counts.entry("cost_count").or_insert(0) += world.evaluation_count() as u64;
+        self.world = Some(world);
+        return Ok((self.updated_state(state), None));
+    }
+
+    fn next_iter(&mut self, problem: &mut Problem<O>, state: PopulationState<Vec<f64>, f64>) -> Result<(PopulationState<Vec<f64>, f64>, Option<KV>), Error> {
+        let world = self.world.as_mut().ok_or_else(|| Error::msg("next_iter called before init"))?;
+        self.positions.clear();
+        world.propose(state.get_max_iters().try_into().unwrap_or(usize::MAX), &mut self.positions);
+        let parameters = self.positions.iter().map(coordinates).collect::<Vec<_>>();
+        self.values.clear();
+        self.values.extend(problem.bulk_cost(&parameters)?.into_iter().map(real::from_f64));
+        world.accept(&self.values);
+        return Ok((self.updated_state(state), None));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use argmin::core::{CostFunction, Error, Executor, State};
+
+    use crate::{bats, butterflies, optimizer::{InitialVelocity, Movement}};
+
+    use super::SwarmSolver;
+
+    #[derive(Clone)]
+    struct Sphere;
+
+    impl CostFunction for Sphere {
+        type Param = Vec<f64>;
+        type Output = f64;
+
+        fn cost(&self, param: &Vec<f64>) -> Result<f64, Error> {
+            return Ok(param.iter().map(|coordinate| coordinate * coordinate).sum());
+        }
+    }
+
+    #[test]
+    fn bats_executor_test() {
+        let parameters = bats::Parameters {
+            bat_count: 20,
+            function: (),
+            bounds: (-5.0, 5.0),
+            frequency_bounds: (0.0, 2.0),
+            initial_pulse_rate: 0.5,
+            pulse_rate_factor: 0.9,
+            initial_loudness: 1.0,
+            loudness_cool_factor: 0.9,
+            movement: Movement::Negated,
+            initial_velocity: InitialVelocity::PositiveUnit,
+            improvement_tolerance: 0.0,
+            scale_walk_to_bounds: false,
+            parallel: false,
+        };
+        let result = Executor::new(Sphere, SwarmSolver::<10, _, _>::new(parameters, 1)).configure(|state| state.max_iters(200)).run().unwrap();
+        assert_eq!(result.state.get_iter(), 200);
+        assert_eq!(result.problem.counts["cost_count"], 20 * 201);
+        assert_eq!(result.state.get_population().unwrap().len(), 20);
+        assert!(result.state.get_best_cost() < 5.0); // The best of the initial population is around 40
+    }
+
+    #[test]
+    fn butterflies_target_test() {
+        let parameters = butterflies::Parameters {
+            population_size: 20,
+            function: (),
+            bounds: (-5.0, 5.0),
+            fragrance_multiplier: 0.1,
+            fragrance_exponent_bounds: (0.1, 0.3),
+            local_search_chance: 0.8,
+            movement: Movement::Standard,
+            initial_velocity: InitialVelocity::Zero,
+            improvement_tolerance: 0.0,
+            parallel: false,
+        };
+        let result = Executor::new(Sphere, SwarmSolver::<2, _, _>::new(parameters, 1)).configure(|state| state.max_iters(10_000).target_cost(1e-3)).run().unwrap();
+        assert!(result.state.get_best_cost() <= 1e-3);
+        assert!(result.state.get_iter() < 10_000);
+    }
+}
@@ -0,0 +1,49 @@
+use std::io;
+
+use serde_json::{Map, Value};
+use swarm_optimizers::tuning::{self, Score, TuningTarget};
+
+use crate::{apply_overrides, check_eval_budget, parse_algorithm_arguments, pool::WorkerPool, queue_batch, target_value_for, BatchOutputs, Config, OptimizationAlgorithmCommand};
+
+// Runs the batches of every function with the parameters of an ask over the command line's configuration
+struct BatchTarget<'a> {
+    config: &'a Config,
+    base_command: OptimizationAlgorithmCommand,
+    pool: WorkerPool,
+}
+
+impl TuningTarget for BatchTarget<'_> {
+    fn evaluate(&self, parameters: &Map<String, Value>) -> Result<Score, String> {
+        let config = self.config;
+        // Every command is built before anything is queued, so invalid parameters don't leave runs behind
+        let commands = config.functions.iter().map(|function_name| {
+            let ask_overrides = parameters.iter().map(|(parameter, value)| format!("{function_name}:{parameter}={value}")).collect::<Vec<_>>();
+            let command = apply_overrides(&apply_overrides(&self.base_command, &config.overrides, function_name), &ask_overrides, function_name);
+            check_eval_budget(&command, function_name, config.eval_budget, config.world_options())?;
+            return Ok(command);
+        }).collect::<Result<Vec<_>, String>>()?;
+        let pending_batches = config.functions.iter().zip(&commands).map(|(function_name, command)| {
+            let target_value = target_value_for(&config.target_values, function_name);
+            return queue_batch(&self.pool, command, config.eval_budget, function_name, target_value, config.try_count.unwrap(), config.world_options(), BatchOutputs::default());
+        }).collect::<Vec<_>>();
+
+        let mut best_values = Vec::new();
+        let mut evaluations = 0;
+        for pending in pending_batches {
+            for run in pending.wait().runs {
+                best_values.push(run.best_value);
+                evaluations += run.evaluations;
+            }
+        }
+        return Ok(Score::from_results(&best_values, evaluations));
+    }
+}
+
+pub fn run(config: &Config, algorithm_arguments: &[String], thread_count: usize) {
+    let target = BatchTarget {
+        config,
+        base_command: parse_algorithm_arguments(algorithm_arguments),
+        pool: WorkerPool::new(thread_count),
+    };
+    tuning::serve(&target, io::stdin().lock(), io::stdout().lock()).unwrap_or_else(|error| panic!("Lost the tuner: {error}"));
+}
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, nested_iteration, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// The cell-to-cell signalling of swarming: the depth and width of the attractant, the height and width of the repellent.
+// Passino's values, which cancel out at a distance of 0
+const ATTRACTANT: (Real, Real) = (0.1, 0.2);
+const REPELLENT: (Real, Real) = (0.1, 10.0);
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub bacterium_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub chemotactic_steps: usize, // Nc, iterations between reproductions
+    pub swim_length: usize, // Ns, the most steps in the same direction after a tumble
+    pub reproduction_steps: usize, // Nre, reproductions between elimination-dispersal events
+    pub elimination_probability: Real, // Ped, of a bacterium being dispersed to a random position in such an event
+    pub step_size: Real, // C, as a fraction of the width of the bounds
+    pub parallel: bool, // Moves and evaluates the bacteria on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.bacterium_count < 2 {
+            return Err("There must be at least two bacteria, for the healthier half to split");
+        }
+        if self.chemotactic_steps == 0 || self.reproduction_steps == 0 {
+            return Err("There must be at least one chemotactic step and one reproduction");
+        }
+        if !(0.0..=1.0).contains(&self.elimination_probability) {
+            return Err("Elimination probability must be between 0 and 1");
+        }
+        if self.step_size.is_nan() || self.step_size <= 0.0 {
+            return Err("Step size must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// J_cc, what the other bacteria add to the cost at position. The swarm includes the bacterium itself
+pub fn swarming_cost<const N: usize>(position: VectorN<Real, N>, swarm: &[VectorN<Real, N>]) -> Real {
+    return swarm.iter().map(|&other| {
+        let squared_distance = (position - other).iter().map(|coordinate| coordinate * coordinate).sum::<Real>();
+        return -ATTRACTANT.0 * (-ATTRACTANT.1 * squared_distance).exp() + REPELLENT.0 * (-REPELLENT.1 * squared_distance).exp();
+    }).sum();
+}
+
+#[derive(Clone, Debug)]
+pub struct Bacterium<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    health: Real, // The costs summed over the chemotactic steps since the last reproduction, lower is healthier
+    step_evaluations: usize, // Of the last chemotactic step
+    step_best: (Real, VectorN<Real, N>), // The best position evaluated in the last chemotactic step, the bacterium may have tumbled away from it
+    random_source: RngType, // Seeded from the population seed and the bacterium's index, so bacteria can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Bacterium<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole colony at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, health: 0.0, step_evaluations: 0, step_best: (Real::INFINITY, position), random_source };
+    }
+
+    // Moves and evaluates, returning the cost with swarming
+    fn move_to<F: Function<N>>(&mut self, parameters: &Parameters<N, F>, swarm: &[VectorN<Real, N>], mut position: VectorN<Real, N>) -> Real {
+        position.clamp(parameters.bounds);
+        self.position = position;
+        self.function_value = parameters.function.evaluate(position);
+        self.step_evaluations += 1;
+        if Fitness::minimize(self.function_value) < Fitness::minimize(self.step_best.0) {
+            self.step_best = (self.function_value, position);
+        }
+        return self.function_value + swarming_cost(position, swarm);
+    }
+
+    // A tumble in a random direction, then swimming on in it for as long as the cost keeps falling
+    fn chemotaxis<F: Function<N>>(&mut self, parameters: &Parameters<N, F>, swarm: &[VectorN<Real, N>]) {
+        let step = parameters.step_size * (parameters.bounds.1 - parameters.bounds.0);
+        self.step_evaluations = 0;
+        self.step_best = (Real::INFINITY, self.position);
+        let mut last_cost = self.function_value + swarming_cost(self.position, swarm);
+        let direction: VectorN<Real, N> = VectorN::random_uniform((-1.0, 1.0), &mut self.random_source);
+        let direction = direction / direction.norm_l2().max(Real::MIN_POSITIVE);
+        let mut cost = self.move_to(parameters, swarm, self.position + direction * step);
+        for _ in 0..parameters.swim_length {
+            if cost >= last_cost {
+                break;
+            }
+            last_cost = cost;
+            cost = self.move_to(parameters, swarm, self.position + direction * step);
+        }
+        self.health += cost;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    colony: Vec<Bacterium<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset, one chemotactic step each
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+    dispersed: Vec<usize>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(bacterium_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            bacterium_count, function, bounds,
+            chemotactic_steps: 50,
+            swim_length: 4,
+            reproduction_steps: 4,
+            elimination_probability: 0.25,
+            step_size: 0.01,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            colony: Vec::with_capacity(parameters.bacterium_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.bacterium_count),
+            values: Vec::with_capacity(parameters.bacterium_count),
+            dispersed: Vec::with_capacity(parameters.bacterium_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn update_best(&mut self, value: Real, position: VectorN<Real, N>) {
+        if Fitness::minimize(value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = value;
+            self.best_solution = position;
+        }
+    }
+
+    // Evaluates the bacteria at the given indices together
+    fn evaluate_bacteria(&mut self, indices: &[usize]) {
+        self.positions.clear();
+        self.positions.extend(indices.iter().map(|&index| self.colony[index].position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+        self.evaluation_count += indices.len();
+        for (&index, &value) in indices.iter().zip(&self.values) {
+            self.colony[index].function_value = value;
+        }
+        // Takes the first of equally good bacteria
+        if let Some((&value, &position)) = self.values.iter().zip(&self.positions).min_by_key(|&(&value, _)| Fitness::minimize(value)) {
+            self.update_best(value, position);
+        }
+    }
+
+    // Every bacterium tumbles and swims against the positions of the colony at the start of the step
+    fn chemotactic_step(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.colony.iter().map(|bacterium| bacterium.position));
+        let parameters = &*self.parameters;
+        let swarm = &self.positions;
+        let chemotaxis = |bacterium: &mut Bacterium<N, RngType>| bacterium.chemotaxis(parameters, swarm);
+        if parameters.parallel {
+            self.colony.par_iter_mut().for_each(chemotaxis);
+        } else {
+            self.colony.iter_mut().for_each(chemotaxis);
+        }
+        self.evaluation_count += self.colony.iter().map(|bacterium| bacterium.step_evaluations).sum::<usize>();
+        // Takes the first of equally good bacteria
+        let (value, position) = self.colony.iter().map(|bacterium| bacterium.step_best).min_by_key(|&(value, _)| Fitness::minimize(value)).unwrap();
+        self.update_best(value, position);
+    }
+
+    // The healthier half splits in two in place of the other half, an odd middle bacterium stays
+    fn reproduce(&mut self) {
+        self.colony.sort_by_key(|bacterium| Fitness::minimize(bacterium.health));
+        let half = self.colony.len() / 2;
+        let survivor_count = self.colony.len() - half;
+        for index in 0..half {
+            self.colony[survivor_count + index].position = self.colony[index].position;
+            self.colony[survivor_count + index].function_value = self.colony[index].function_value;
+        }
+        for bacterium in &mut self.colony {
+            bacterium.health = 0.0;
+        }
+    }
+
+    // Every bacterium moves to a random position with the elimination probability
+    fn eliminate_and_disperse(&mut self) {
+        self.dispersed.clear();
+        for (index, bacterium) in self.colony.iter_mut().enumerate() {
+            if bacterium.random_source.gen::<Real>() < self.parameters.elimination_probability {
+                bacterium.position = VectorN::random_uniform(self.parameters.bounds, &mut bacterium.random_source);
+                self.dispersed.push(index);
+            }
+        }
+        let dispersed = std::mem::take(&mut self.dispersed);
+        self.evaluate_bacteria(&dispersed);
+        self.dispersed = dispersed;
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // One chemotactic step, followed by a reproduction after the last one of its loop and by an elimination-dispersal
+    // event after the last reproduction. Swimming bacteria evaluate their own moves, so the iteration can't be split into
+    // a proposal and its values
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        let parameters = &*self.parameters;
+        let [reproduction, chemotaxis] = nested_iteration(self.iteration, [parameters.reproduction_steps, parameters.chemotactic_steps]);
+        let (last_chemotaxis, last_reproduction) = (chemotaxis + 1 == parameters.chemotactic_steps, reproduction + 1 == parameters.reproduction_steps);
+        self.chemotactic_step();
+        if last_chemotaxis {
+            self.reproduce();
+            if last_reproduction {
+                self.eliminate_and_disperse();
+            }
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.colony.iter().map(|bacterium| bacterium.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.colony.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.bacterium_count {
+            self.colony.push(Bacterium::new(&self.parameters, population_seed, index));
+        }
+        let mut all = std::mem::take(&mut self.dispersed);
+        all.clear();
+        all.extend(0..self.colony.len());
+        self.evaluate_bacteria(&all);
+        self.dispersed = all;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // A tumble and a full swim for every bacterium, then all of them dispersed
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.colony.len() * (self.parameters.swim_length + 2);
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.colony.iter().map(|bacterium| bacterium.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn foraging_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        // Past the first elimination-dispersal event
+        world.do_all_iterations(250);
+        assert!((20 + 20 * 250..=20 + 20 * 6 * 250).contains(&world.evaluation_count()));
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
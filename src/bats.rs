@@ -1,158 +1,393 @@
-use rand::{distributions::{Distribution, Uniform}, rngs::StdRng, Rng};
+use std::sync::Arc;
 
-use crate::{functions::Functions, vector::VectorN};
+use rand::{distributions::{Distribution, Uniform}, Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{functions::{Function, Functions}, fitness::Fitness, optimizer::{agent_random_source, FromParameters, InitialVelocity, Movement, Optimizer}, real::Real, vector::VectorN};
 
 #[derive(Clone, Debug)]
-pub struct Bat<const N: usize> {
-    position: VectorN<N>,
-    velocity: VectorN<N>,
-    frequency_bounds: (f64, f64),
-    original_pulse_rate: f64, // Should be between 0 and 1. Anything higher will be weird
-    current_pulse_rate: f64, // Expresses the chance for a random walk using the loudness. Approaches original_pulse_rate
-    pulse_rate_factor: f64,
-    loudness: f64, // Loudness is the radius of random walk of the bat - similar to temperature in simulated annealing. Shrinks to 0.
-    loudness_cool_factor: f64,
-    best_solution_value: f64,
-    bounds: (f64, f64),
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub bat_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub frequency_bounds: (Real, Real),
+    pub initial_pulse_rate: Real, // Should be between 0 and 1. Anything higher will be weird
+    pub pulse_rate_factor: Real,
+    pub initial_loudness: Real,
+    pub loudness_cool_factor: Real,
+    pub movement: Movement, // Negated keeps the bats from diverging, see Movement
+    pub initial_velocity: InitialVelocity,
+    pub improvement_tolerance: Real, // For the personal and global bests, smaller improvements don't count
+    pub scale_walk_to_bounds: bool, // The random walk is then relative to the width of the bounds instead of absolute
+    pub parallel: bool, // Moves and evaluates the bats on the rayon pool
 }
 
-impl<const N: usize> Bat<N> {
-    fn new<RngType: Rng>(lower_bound: f64, upper_bound: f64, min_frequency: f64, max_frequency: f64, pulse_rate: f64, pulse_rate_factor: f64, loudness: f64, loudness_cool_factor: f64, random_source: &mut RngType) -> Self {
-        let mut coords_array = [0.0; N];
-        let mut speed_array = [0.0; N];
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.frequency_bounds.0 >= self.frequency_bounds.1 {
+            return Err("Incorrect order of frequency bounds or zero size");
+        }
+        if self.bat_count == 0 {
+            return Err("The population can't be empty");
+        }
+        self.movement.check()?;
+        self.initial_velocity.check()?;
+        if self.improvement_tolerance.is_nan() || self.improvement_tolerance < 0.0 {
+            return Err("Improvement tolerance can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// Of the best solution in the published velocity update, v += (x - x*) * f
+const PUBLISHED_SIGN: Real = -1.0;
+
+// Built once per world instead of on every draw
+#[derive(Clone, Debug)]
+struct Distributions {
+    frequency: Uniform<Real>,
+    walk: Uniform<Real>, // Step of the random walk, before scaling with the average loudness
+}
 
-        let range = Uniform::from(lower_bound..upper_bound);
+impl Distributions {
+    fn new<const N: usize, F>(parameters: &Parameters<N, F>) -> Self {
+        let walk_range = if parameters.scale_walk_to_bounds { parameters.bounds.1 - parameters.bounds.0 } else { 1.0 };
+        return Self {
+            frequency: Uniform::new(parameters.frequency_bounds.0, parameters.frequency_bounds.1),
+            walk: Uniform::new(-walk_range, walk_range),
+        };
+    }
+}
 
-        coords_array.fill_with(|| { range.sample(random_source) });
-        speed_array.fill_with(|| { random_source.gen::<f64>() });        
+#[derive(Clone, Debug)]
+pub struct Bat<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    velocity: VectorN<Real, N>,
+    current_pulse_rate: Real, // Expresses the chance for a random walk using the loudness. Approaches initial_pulse_rate
+    loudness: Real, // Loudness is the radius of random walk of the bat - similar to temperature in simulated annealing. Shrinks to 0.
+    best_solution_value: Real,
+    random_source: RngType, // Seeded from the population seed and the bat's index, so bats can move in parallel and still be reproducible
+}
 
+impl<const N: usize, RngType: Rng + SeedableRng> Bat<N, RngType> {
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
         return Self {
-            position: VectorN::new(coords_array),
-            velocity: VectorN::new(speed_array),
-            current_pulse_rate: pulse_rate,
-            original_pulse_rate: pulse_rate,
-            frequency_bounds: (min_frequency, max_frequency),
-            pulse_rate_factor, loudness, loudness_cool_factor,
-            best_solution_value: f64::INFINITY,
-            bounds: (lower_bound, upper_bound)
+            position: VectorN::random_uniform(parameters.bounds, &mut random_source),
+            velocity: parameters.initial_velocity.sample(parameters.bounds, &mut random_source),
+            current_pulse_rate: parameters.initial_pulse_rate,
+            loudness: parameters.initial_loudness,
+            best_solution_value: Real::INFINITY,
+            random_source,
         };
     }
 
-    fn move_bat<RngType: Rng>(&mut self, global_best_solution: VectorN<N>, random_source: &mut RngType, average_loudness: f64) {
-        let frequency = random_source.gen_range(self.frequency_bounds.0..self.frequency_bounds.1);
-        self.velocity += (global_best_solution - self.position) * frequency;
-        self.position += self.velocity; // According to all formulas this should be adding, not subtracting. However, adding produces awful results and makes bats divergent
-        if random_source.gen::<f64>() < self.current_pulse_rate {
-            self.position += random_source.gen_range(-1.0..1.0) * average_loudness;
+    fn move_bat<F>(&mut self, parameters: &Parameters<N, F>, distributions: &Distributions, global_best_solution: VectorN<Real, N>, average_loudness: Real) {
+        let frequency = distributions.frequency.sample(&mut self.random_source);
+        let attraction = parameters.movement.attraction(global_best_solution - self.position, frequency, PUBLISHED_SIGN);
+        match parameters.movement {
+            Movement::InertiaDamped(weight) => self.velocity = self.velocity * weight + attraction,
+            _ => self.velocity += attraction,
         }
-        self.position.clamp(self.bounds);
+        self.position += self.velocity;
+        if self.random_source.gen::<Real>() < self.current_pulse_rate {
+            self.position += distributions.walk.sample(&mut self.random_source) * average_loudness;
+        }
+        self.position.clamp(parameters.bounds);
     }
     // Should only be called if the fitness improves
-    fn update_parameters(&mut self, iteration_number: usize) {
-        self.loudness *= self.loudness_cool_factor;
-        self.current_pulse_rate = self.original_pulse_rate * (1.0 - (-self.pulse_rate_factor * iteration_number as f64).exp());
+    fn update_parameters<F>(&mut self, parameters: &Parameters<N, F>, iteration_number: usize) {
+        self.loudness *= parameters.loudness_cool_factor;
+        self.current_pulse_rate = parameters.initial_pulse_rate * (1.0 - (-parameters.pulse_rate_factor * iteration_number as Real).exp());
     }
 
-    fn reset<RngType: Rng>(&mut self, lower_bound: f64, upper_bound: f64, pulse_rate: f64, loudness: f64, random_source: &mut RngType) {
-        let range = Uniform::from(lower_bound..upper_bound);
-
-        self.position.coordinates.fill_with(|| { range.sample(random_source) });
-        self.velocity.coordinates.fill_with(|| { random_source.gen::<f64>() });
-        self.best_solution_value = f64::INFINITY;
-        self.current_pulse_rate = pulse_rate;
-        self.original_pulse_rate = pulse_rate;
-        self.loudness = loudness;
+    fn update_personal_best<F>(&mut self, parameters: &Parameters<N, F>, value: Real, iteration_number: usize) {
+        if Fitness::minimize(value).improves_on(&Fitness::minimize(self.best_solution_value), parameters.improvement_tolerance) {
+            self.best_solution_value = value;
+            self.update_parameters(parameters, iteration_number);
+        }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct WorldState<const N: usize, RngType: Rng> {
-    bats: Vec<Bat<N>>,
-    function: Functions<N>,
-    pub best_solution: VectorN<N>,
-    pub best_solution_value: f64,
-    bounds: (f64, f64), // lower, upper
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    bats: Vec<Bat<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
     random_generator: RngType,
-    initial_pulse_rate: f64,
-    initial_loudness: f64,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    distributions: Distributions,
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
 }
 
-impl<const N: usize> WorldState<N, StdRng> {
-    pub fn new(bat_count: usize, function: Functions<N>, bounds: (f64, f64), frequency_bounds: (f64, f64), initial_pulse_rate: f64, pulse_rate_factor: f64, initial_loudness: f64, loudness_cool_factor: f64, mut random_source: rand::rngs::StdRng) -> Self {
-        if bounds.0 >= bounds.1 {
-            panic!("Incorrect order of bounds or zero size");
+impl<const N: usize, RngType: Rng + SeedableRng + Send, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(bat_count: usize, function: F, bounds: (Real, Real), frequency_bounds: (Real, Real), initial_pulse_rate: Real, pulse_rate_factor: Real, initial_loudness: Real, loudness_cool_factor: Real, random_source: RngType) -> Self {
+        let parameters = Parameters {
+            bat_count, function, bounds, frequency_bounds,
+            initial_pulse_rate, pulse_rate_factor, initial_loudness, loudness_cool_factor,
+            movement: Movement::Negated,
+            initial_velocity: InitialVelocity::PositiveUnit,
+            improvement_tolerance: 0.0,
+            scale_walk_to_bounds: false,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            bats: Vec::with_capacity(parameters.bat_count),
+            distributions: Distributions::new(&parameters),
+            positions: Vec::with_capacity(parameters.bat_count),
+            values: Vec::with_capacity(parameters.bat_count),
+            parameters,
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+        };
+        world.reset();
+        return world;
+    }
+
+    // Only pays off for expensive functions or large populations, the results are the same either way
+    pub fn set_parallel(&mut self, parallel: bool) where F: Clone {
+        Arc::make_mut(&mut self.parameters).parallel = parallel;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // Changes the parameters mid-run, e.g. for schedules steered from outside. The bats keep their state, so the initial
+    // values only take effect from the next reset. Panics on invalid parameters or a different bat count
+    pub fn modify_parameters(&mut self, modify: impl FnOnce(&mut Parameters<N, F>)) where F: Clone {
+        let parameters = Arc::make_mut(&mut self.parameters);
+        let bat_count = parameters.bat_count;
+        modify(parameters);
+        if parameters.bat_count != bat_count {
+            panic!("The bat count can't change mid-run");
         }
-        if frequency_bounds.0 >= frequency_bounds.1 {
-            panic!("Incorrect order of frequency bounds or zero size");
+        parameters.validate();
+        self.distributions = Distributions::new(parameters);
+    }
+
+    pub fn set_frequency_bounds(&mut self, frequency_bounds: (Real, Real)) where F: Clone {
+        self.modify_parameters(|parameters| parameters.frequency_bounds = frequency_bounds);
+    }
+
+    pub fn set_initial_pulse_rate(&mut self, initial_pulse_rate: Real) where F: Clone {
+        self.modify_parameters(|parameters| parameters.initial_pulse_rate = initial_pulse_rate);
+    }
+
+    pub fn set_pulse_rate_factor(&mut self, pulse_rate_factor: Real) where F: Clone {
+        self.modify_parameters(|parameters| parameters.pulse_rate_factor = pulse_rate_factor);
+    }
+
+    pub fn set_loudness_cool_factor(&mut self, loudness_cool_factor: Real) where F: Clone {
+        self.modify_parameters(|parameters| parameters.loudness_cool_factor = loudness_cool_factor);
+    }
+
+    // The current loudness of every bat, in order
+    pub fn loudness(&self) -> impl Iterator<Item = Real> + '_ {
+        return self.bats.iter().map(|bat| bat.loudness);
+    }
+
+    // Of every bat, until they improve and cool down again
+    pub fn set_loudness(&mut self, loudness: Real) {
+        for bat in &mut self.bats {
+            bat.loudness = loudness;
         }
+    }
+
+    // The current pulse rate of every bat, in order
+    pub fn pulse_rates(&self) -> impl Iterator<Item = Real> + '_ {
+        return self.bats.iter().map(|bat| bat.current_pulse_rate);
+    }
 
-        let mut bats = Vec::with_capacity(bat_count);
-        for _ in 0..bat_count {
-            bats.push(Bat::new(
-                bounds.0, bounds.1, frequency_bounds.0, frequency_bounds.1,
-                initial_pulse_rate, pulse_rate_factor, initial_loudness, loudness_cool_factor, &mut random_source,
-            ));
+    // Of every bat, until they improve and their pulse rates follow the schedule again
+    pub fn set_pulse_rate(&mut self, pulse_rate: Real) {
+        for bat in &mut self.bats {
+            bat.current_pulse_rate = pulse_rate;
         }
+    }
 
-        let mut best_solution = VectorN::default();
-        let mut best_solution_value = f64::INFINITY;
-        for bat in &mut bats {
-            let bat_value = function.calculate(bat.position);
-            if bat_value < best_solution_value {
-                best_solution = bat.position;
-                best_solution_value = bat_value;
+    pub fn move_bats(&mut self) {
+        let average_loudness = self.bats.iter().map(|bat| bat.loudness).reduce(|acc, loudness| acc + loudness).unwrap() / (self.bats.len() as Real);
+        let best_solution = self.best_solution;
+        let parameters = &*self.parameters;
+        let distributions = &self.distributions;
+        if parameters.parallel {
+            self.bats.par_iter_mut().for_each(|bat| bat.move_bat(parameters, distributions, best_solution, average_loudness));
+        } else {
+            for bat in &mut self.bats {
+                bat.move_bat(parameters, distributions, best_solution, average_loudness);
             }
         }
+    }
 
-        return Self {
-            bats, function, best_solution, best_solution_value, bounds,
-            random_generator: random_source,
-            initial_pulse_rate, initial_loudness,
-        };
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.bats.iter().map(|bat| bat.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
     }
 
-    pub fn reset(&mut self) {
-        self.best_solution = VectorN::default();
-        self.best_solution_value = f64::INFINITY;
-        for bat in &mut self.bats {
-            bat.reset(self.bounds.0, self.bounds.1, self.initial_pulse_rate, self.initial_loudness, &mut self.random_generator);
-            let bat_value = self.function.calculate(bat.position);
-            if bat_value < self.best_solution_value {
-                self.best_solution_value = bat_value;
+    fn evaluate_initial_population(&mut self) {
+        self.evaluation_count = self.bats.len();
+        self.evaluate_positions();
+        for (bat, &value) in self.bats.iter().zip(&self.values) {
+            if Fitness::minimize(value) < Fitness::minimize(self.best_solution_value) {
+                self.best_solution_value = value;
                 self.best_solution = bat.position;
             }
         }
     }
-    
-    pub fn move_bats(&mut self) {
-        let average_loudness = self.bats.iter().map(|bat| bat.loudness).reduce(|acc, loudness| acc + loudness).unwrap() / (self.bats.len() as f64);
-        for bat in &mut self.bats {
-            bat.move_bat(self.best_solution, &mut self.random_generator, average_loudness);
-        }
+
+    // Evaluates the moved bats, which ends the iteration
+    pub fn update_best_known_solution(&mut self) {
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
     }
 
-    pub fn update_best_known_solution(&mut self, iter_number: usize) {
-        for bat in &mut self.bats {
-            let bat_value = self.function.calculate(bat.position);
-            if bat_value < self.best_solution_value {
-                self.best_solution_value = bat_value;
-                self.best_solution = bat.position;
+    // The values of the moved bats, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.bats.len();
+        // Takes the first of equally good bats
+        let mut iteration_best: Option<(Real, VectorN<Real, N>)> = None;
+        for (bat, &value) in self.bats.iter_mut().zip(values) {
+            bat.update_personal_best(&self.parameters, value, self.iteration);
+            if iteration_best.is_none_or(|(best_value, _)| Fitness::minimize(value) < Fitness::minimize(best_value)) {
+                iteration_best = Some((value, bat.position));
             }
-            if bat_value < bat.best_solution_value {
-                bat.best_solution_value = bat_value;
-                bat.update_parameters(iter_number);
+        }
+        if let Some((value, position)) = iteration_best {
+            if Fitness::minimize(value).improves_on(&Fitness::minimize(self.best_solution_value), self.parameters.improvement_tolerance) {
+                self.best_solution_value = value;
+                self.best_solution = position;
             }
         }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.bats.iter().map(|bat| bat.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
     }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Send, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
 
-    pub fn do_iteration(&mut self, iter_number: usize) {
+impl<const N: usize, RngType: Rng + SeedableRng + Send, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
         self.move_bats();
-        self.update_best_known_solution(iter_number);
+        self.update_best_known_solution();
     }
 
-    pub fn do_all_iterations(&mut self, iterations: usize) {
-        for iter in 0..iterations {
-            self.do_iteration(iter);
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_bats();
+        positions.extend(self.bats.iter().map(|bat| bat.position));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+    }
+
+    fn reset(&mut self) {
+        self.best_solution = VectorN::default();
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.bats.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.bat_count {
+            self.bats.push(Bat::new(&self.parameters, population_seed, index));
         }
+        self.evaluate_initial_population();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
     }
-}
\ No newline at end of file
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.bats.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.bats.iter().map(|bat| bat.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    fn world() -> WorldState<10, Xoshiro256PlusPlus> {
+        let function = Functions::<10>::make_from_name("rastrigin");
+        return WorldState::new(20, function, function.get_bounds(), (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, Xoshiro256PlusPlus::seed_from_u64(0));
+    }
+
+    #[test]
+    fn steering_test() {
+        let mut world = world();
+        world.do_all_iterations(10);
+        world.set_frequency_bounds((0.5, 1.0));
+        world.set_loudness(0.25);
+        world.set_pulse_rate(0.0);
+        assert_eq!(world.parameters().frequency_bounds, (0.5, 1.0));
+        assert!(world.loudness().all(|loudness| loudness == 0.25) && world.pulse_rates().all(|pulse_rate| pulse_rate == 0.0));
+        world.do_all_iterations(10);
+        assert_eq!(world.iteration(), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "Incorrect order of frequency bounds")]
+    fn invalid_steering_test() {
+        world().set_frequency_bounds((1.0, 0.5));
+    }
+}
@@ -1,139 +1,373 @@
-use rand::{distributions::{Distribution, Uniform}, prelude::SliceRandom, Rng};
+use std::sync::Arc;
 
-use crate::{functions::Functions, vector::VectorN};
+use rand::{distributions::{Bernoulli, Distribution, Uniform}, Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 
+use crate::{functions::{Function, Functions}, fitness::Fitness, optimizer::{agent_random_source, FromParameters, InitialVelocity, Movement, Optimizer}, real::{self, Real}, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub population_size: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub fragrance_multiplier: Real,
+    pub fragrance_exponent_bounds: (Real, Real), // progresses with iterations
+    pub local_search_chance: Real, // between 0 and 1
+    pub movement: Movement, // Standard is the published formula, see Movement
+    pub initial_velocity: InitialVelocity, // Only carried over by Movement::InertiaDamped
+    pub improvement_tolerance: Real, // For the global best, smaller improvements don't count
+    pub parallel: bool, // Moves and evaluates the butterflies on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.fragrance_exponent_bounds.0 > self.fragrance_exponent_bounds.1 {
+            return Err("Incorrect order of fragrance bounds");
+        }
+        if self.population_size == 0 {
+            return Err("The population can't be empty");
+        }
+        if !(0.0..=1.0).contains(&self.local_search_chance) {
+            return Err("Local search chance must be between 0 and 1");
+        }
+        self.movement.check()?;
+        self.initial_velocity.check()?;
+        if self.improvement_tolerance.is_nan() || self.improvement_tolerance < 0.0 {
+            return Err("Improvement tolerance can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// Built once per world instead of on every draw
 #[derive(Clone, Debug)]
-pub struct Butterfly<const N: usize> {
-    position: VectorN<N>,
-    fragrance_multiplier: f64,
-    fragrance_value: f64, // modification as per slide 15
-    optimization_function: Functions<N>,
-    function_value: f64,
-    function_bounds: (f64, f64),
+struct Distributions {
+    local_search: Bernoulli,
+    partner: Uniform<usize>, // Index into the previous population
 }
 
-impl<const N: usize> Butterfly<N> {
-    fn new<RngType: Rng>(function_bounds: (f64, f64), optimization_function: Functions<N>, fragrance_multiplier: f64, random_source: &mut RngType) -> Self {
-        let mut coords_array = [0.0; N];
+impl Distributions {
+    fn new<const N: usize, F>(parameters: &Parameters<N, F>) -> Self {
+        return Self {
+            local_search: Bernoulli::new(real::to_f64(parameters.local_search_chance)).unwrap(),
+            partner: Uniform::new(0, parameters.population_size),
+        };
+    }
+}
 
-        let range = Uniform::from(function_bounds.0..function_bounds.1);
+#[derive(Clone, Debug)]
+pub struct Butterfly<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    velocity: VectorN<Real, N>, // The last move, only carried over by Movement::InertiaDamped
+    fragrance_value: Real, // modification as per slide 15
+    function_value: Real,
+    random_source: RngType, // Seeded from the population seed and the butterfly's index, so butterflies can move in parallel and still be reproducible
+}
 
-        coords_array.fill_with(|| range.sample(random_source));
-        
-        let position = VectorN::new(coords_array);
-        let function_value = optimization_function.calculate(position);
+impl<const N: usize, RngType: Rng + SeedableRng> Butterfly<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole population at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        let velocity = parameters.initial_velocity.sample(parameters.bounds, &mut random_source);
 
         return Self {
-            position, fragrance_multiplier,
-            fragrance_value: function_value / (function_value + f64::EPSILON),
-            function_bounds, optimization_function, function_value
+            position,
+            velocity,
+            fragrance_value: Real::NAN,
+            function_value: Real::INFINITY,
+            random_source
         };
     }
 
-    fn move_butterfly_global<RngType: Rng>(&mut self, best_butterfly_position: VectorN<N>, fragrance_exponent: f64, best_iter_solution: f64, random_source: &mut RngType) {
-        self.position += (best_butterfly_position * random_source.gen::<f64>().powi(2) - self.position) * (self.fragrance_multiplier * self.fragrance_value.powf(fragrance_exponent));
-        self.position.clamp(self.function_bounds);
-        self.function_value = self.optimization_function.calculate(self.position);
-        self.fragrance_value = self.function_value / (best_iter_solution + f64::EPSILON);
+    // Either towards the best butterfly or, with local_search_chance, between two random ones of the previous iteration
+    fn move_butterfly<F>(&mut self, parameters: &Parameters<N, F>, distributions: &Distributions, previous_positions: &[VectorN<Real, N>], best_of_previous_iter: VectorN<Real, N>, fragrance_exponent: Real) {
+        if distributions.local_search.sample(&mut self.random_source) {
+            let first_position = previous_positions[distributions.partner.sample(&mut self.random_source)];
+            let second_position = previous_positions[distributions.partner.sample(&mut self.random_source)];
+            self.move_butterfly_local(parameters, first_position, second_position, fragrance_exponent);
+        } else {
+            self.move_butterfly_global(parameters, best_of_previous_iter, fragrance_exponent);
+        }
     }
 
-    fn move_butterfly_local<RngType: Rng>(&mut self, random_butterfly_position_1: VectorN<N>, random_butterfly_position_2: VectorN<N>, fragrance_exponent: f64, best_iter_solution: f64, random_source: &mut RngType) {
-        self.position += (random_butterfly_position_1 * random_source.gen::<f64>().powi(2) - random_butterfly_position_2) * (self.fragrance_multiplier * self.fragrance_value.powf(fragrance_exponent));
-        self.position.clamp(self.function_bounds);
-        self.function_value = self.optimization_function.calculate(self.position);
-        self.fragrance_value = self.function_value / (best_iter_solution + f64::EPSILON);
+    fn move_butterfly_global<F>(&mut self, parameters: &Parameters<N, F>, best_butterfly_position: VectorN<Real, N>, fragrance_exponent: Real) {
+        let towards_best = best_butterfly_position * self.random_source.gen::<Real>().powi(2) - self.position;
+        self.move_towards(parameters, towards_best, fragrance_exponent);
     }
 
-    fn reset<RngType: Rng>(&mut self, random_source: &mut RngType) {
-        let range = Uniform::from(self.function_bounds.0..self.function_bounds.1);
-        self.position.coordinates.fill_with(|| range.sample(random_source));
-        self.function_value = self.optimization_function.calculate(self.position);
-        self.fragrance_value = self.function_value / (self.function_value + f64::EPSILON);
+    fn move_butterfly_local<F>(&mut self, parameters: &Parameters<N, F>, random_butterfly_position_1: VectorN<Real, N>, random_butterfly_position_2: VectorN<Real, N>, fragrance_exponent: Real) {
+        let towards_partner = random_butterfly_position_1 * self.random_source.gen::<Real>().powi(2) - random_butterfly_position_2;
+        self.move_towards(parameters, towards_partner, fragrance_exponent);
+    }
+
+    // By the fragrance, which scales the attraction in both moves
+    fn move_towards<F>(&mut self, parameters: &Parameters<N, F>, towards_target: VectorN<Real, N>, fragrance_exponent: Real) {
+        let attraction = parameters.movement.attraction(towards_target, parameters.fragrance_multiplier * self.fragrance_value.powf(fragrance_exponent), 1.0);
+        self.velocity = match parameters.movement {
+            Movement::InertiaDamped(weight) => self.velocity * weight + attraction,
+            _ => attraction,
+        };
+        self.position += self.velocity;
+        self.position.clamp(parameters.bounds);
+    }
+
+    // The fragrance is relative to the best value of the previous iteration, or to the butterfly's own value initially
+    fn set_function_value(&mut self, function_value: Real, best_iter_solution: Real) {
+        self.function_value = function_value;
+        self.fragrance_value = function_value / (best_iter_solution + Real::EPSILON);
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct WorldState<const N: usize, RngType: Rng> {
-    population: Vec<Butterfly<N>>,
-    pub best_solution: VectorN<N>,
-    pub best_solution_value: f64,
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    population: Vec<Butterfly<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
     random_generator: RngType,
-    fragrance_exponent_bounds: (f64, f64), // progresses with iterations
-    local_search_chance: f64, // between 0 and 1
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    previous_iteration_best: Real, // Best value before the current move, for the fragrances of the moved population
+    distributions: Distributions,
+    previous_positions: Vec<VectorN<Real, N>>,
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
 }
 
-impl<const N: usize> WorldState<N, rand::rngs::StdRng> {
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
     pub fn new(pop_size: usize, 
-        function: Functions<N>,
-        bounds: (f64, f64), 
-        fragrance_multiplier: f64, 
-        fragrance_exponent_bounds: (f64, f64), 
-        local_search_chance: f64, 
-        mut random_source: rand::rngs::StdRng
+        function: F,
+        bounds: (Real, Real), 
+        fragrance_multiplier: Real, 
+        fragrance_exponent_bounds: (Real, Real), 
+        local_search_chance: Real, 
+        random_source: RngType
 	) -> Self {
-        if bounds.0 >= bounds.1 {
-            panic!("Incorrect order of bounds or zero size");
-        }
-        if fragrance_exponent_bounds.0 > fragrance_exponent_bounds.1 {
-            panic!("Incorrect order of fragrance bounds");
+        let parameters = Parameters {
+            population_size: pop_size,
+            function, bounds, fragrance_multiplier, fragrance_exponent_bounds, local_search_chance,
+            movement: Movement::Standard,
+            initial_velocity: InitialVelocity::Zero,
+            improvement_tolerance: 0.0,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            population: Vec::with_capacity(parameters.population_size),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            previous_iteration_best: Real::INFINITY,
+            distributions: Distributions::new(&parameters),
+            previous_positions: Vec::with_capacity(parameters.population_size),
+            positions: Vec::with_capacity(parameters.population_size),
+            values: Vec::with_capacity(parameters.population_size),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    // Only pays off for expensive functions or large populations, the results are the same either way
+    pub fn set_parallel(&mut self, parallel: bool) where F: Clone {
+        Arc::make_mut(&mut self.parameters).parallel = parallel;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // Changes the parameters mid-run, e.g. for schedules steered from outside. Panics on invalid parameters or a
+    // different population size
+    pub fn modify_parameters(&mut self, modify: impl FnOnce(&mut Parameters<N, F>)) where F: Clone {
+        let parameters = Arc::make_mut(&mut self.parameters);
+        let population_size = parameters.population_size;
+        modify(parameters);
+        if parameters.population_size != population_size {
+            panic!("The population size can't change mid-run");
         }
+        parameters.validate();
+        self.distributions = Distributions::new(parameters);
+    }
 
-        let mut butterflies = Vec::with_capacity(pop_size);
+    pub fn set_fragrance_multiplier(&mut self, fragrance_multiplier: Real) where F: Clone {
+        self.modify_parameters(|parameters| parameters.fragrance_multiplier = fragrance_multiplier);
+    }
 
-        let mut best_solution = VectorN::default();
-        let mut best_solution_value = f64::INFINITY;
+    pub fn set_fragrance_exponent_bounds(&mut self, fragrance_exponent_bounds: (Real, Real)) where F: Clone {
+        self.modify_parameters(|parameters| parameters.fragrance_exponent_bounds = fragrance_exponent_bounds);
+    }
 
-        for _ in 0..pop_size {
-            let butterfly = Butterfly::new(bounds, function, fragrance_multiplier, &mut random_source);
-            if butterfly.function_value < best_solution_value {
-                best_solution_value = butterfly.function_value;
-                best_solution = butterfly.position;
-            }
-            butterflies.push(butterfly);
+    pub fn set_local_search_chance(&mut self, local_search_chance: Real) where F: Clone {
+        self.modify_parameters(|parameters| parameters.local_search_chance = local_search_chance);
+    }
+
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.population.iter().map(|butterfly| butterfly.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
         }
-        
-        return Self {
-            population: butterflies,
-            best_solution, best_solution_value,
-            random_generator: random_source,
-            fragrance_exponent_bounds, local_search_chance
-        };
     }
 
-    pub fn reset(&mut self) {
-        self.best_solution_value = f64::INFINITY;
+    fn move_population(&mut self, iteration_count: usize) {
+        self.previous_positions.clear();
+        self.previous_positions.extend(self.population.iter().map(|butterfly| butterfly.position));
+        let best_of_previous_iter = self.population.iter().min_by_key(|butterfly| Fitness::minimize(butterfly.function_value)).unwrap();
+        let best_position = best_of_previous_iter.position;
+        self.previous_iteration_best = best_of_previous_iter.function_value;
+        let parameters = &*self.parameters;
+        let distributions = &self.distributions;
+        let previous_positions = &self.previous_positions;
+        let exponent_value = parameters.fragrance_exponent_bounds.0 + (parameters.fragrance_exponent_bounds.1 - parameters.fragrance_exponent_bounds.0) * (self.iteration / iteration_count) as Real;
+        let move_butterfly = |butterfly: &mut Butterfly<N, RngType>| butterfly.move_butterfly(parameters, distributions, previous_positions, best_position, exponent_value);
+        if parameters.parallel {
+            self.population.par_iter_mut().for_each(move_butterfly);
+        } else {
+            self.population.iter_mut().for_each(move_butterfly);
+        }
+    }
 
-        for butterfly in &mut self.population {
-            butterfly.reset(&mut self.random_generator);
-            if butterfly.function_value < self.best_solution_value {
-                self.best_solution_value = butterfly.function_value;
-                self.best_solution = butterfly.position;
+    // The values of the moved population, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.population.len();
+        // Takes the first of equally good butterflies
+        let mut iteration_best: Option<(Real, VectorN<Real, N>)> = None;
+        for (butterfly, &value) in self.population.iter_mut().zip(values) {
+            butterfly.set_function_value(value, self.previous_iteration_best);
+            if iteration_best.is_none_or(|(best_value, _)| Fitness::minimize(value) < Fitness::minimize(best_value)) {
+                iteration_best = Some((value, butterfly.position));
             }
         }
+        if let Some((value, position)) = iteration_best {
+            if Fitness::minimize(value).improves_on(&Fitness::minimize(self.best_solution_value), self.parameters.improvement_tolerance) {
+                self.best_solution_value = value;
+                self.best_solution = position;
+            }
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.population.iter().map(|butterfly| butterfly.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
     }
 
-    pub fn do_iteration(&mut self, iteration_number: usize, iteration_count: usize) {
-        let old_butterflies = self.population.clone();
-        let best_butterfly_of_previous_iter = old_butterflies.iter().min_by(|first, second| first.function_value.partial_cmp(&second.function_value).unwrap()).unwrap();
-        let exponent_value = self.fragrance_exponent_bounds.0 + (self.fragrance_exponent_bounds.1 - self.fragrance_exponent_bounds.0) * (iteration_number / iteration_count) as f64;
-        for butterfly in &mut self.population {
-            if self.random_generator.gen_bool(self.local_search_chance) {
-                let first_butterfly = old_butterflies.choose(&mut self.random_generator).unwrap();
-                let second_butterfly = old_butterflies.choose(&mut self.random_generator).unwrap();
-                butterfly.move_butterfly_local(first_butterfly.position, second_butterfly.position, exponent_value, best_butterfly_of_previous_iter.function_value, &mut self.random_generator);
-            } else {
-                butterfly.move_butterfly_global(best_butterfly_of_previous_iter.position, exponent_value, best_butterfly_of_previous_iter.function_value, &mut self.random_generator);
-            }
-            if butterfly.function_value < self.best_solution_value {
-                self.best_solution_value = butterfly.function_value;
+    fn evaluate_initial_population(&mut self) {
+        self.evaluation_count = self.population.len();
+        self.evaluate_positions();
+        for (butterfly, &value) in self.population.iter_mut().zip(&self.values) {
+            butterfly.set_function_value(value, value);
+            if Fitness::minimize(value) < Fitness::minimize(self.best_solution_value) {
+                self.best_solution_value = value;
                 self.best_solution = butterfly.position;
             }
         }
     }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_population(iteration_count);
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_population(iteration_count);
+        positions.extend(self.population.iter().map(|butterfly| butterfly.position));
+    }
 
-    pub fn do_all_iterations(&mut self, iteration_count: usize) {
-        for iteration in 0..iteration_count {
-            self.do_iteration(iteration, iteration_count);
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.population.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.population_size {
+            self.population.push(Butterfly::new(&self.parameters, population_seed, index));
         }
+        self.evaluate_initial_population();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
     }
-}
\ No newline at end of file
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.population.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.population.iter().map(|butterfly| butterfly.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn steering_test() {
+        let function = Functions::<10>::make_from_name("rastrigin");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), 0.1, (0.1, 0.3), 0.8, Xoshiro256PlusPlus::seed_from_u64(0));
+        world.do_all_iterations(10);
+        world.set_local_search_chance(1.0);
+        world.set_fragrance_exponent_bounds((0.2, 0.2));
+        world.do_all_iterations(10);
+        assert_eq!((world.parameters().local_search_chance, world.parameters().fragrance_exponent_bounds), (1.0, (0.2, 0.2)));
+        let result = std::panic::catch_unwind(move || world.set_local_search_chance(1.5));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub sample_count: usize, // Per generation
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub elite_fraction: Real, // Of the samples the distribution is fitted to, at least one is always taken
+    pub smoothing: Real, // Weight of the fitted distribution against the previous one, 1 replaces it outright
+    pub parallel: bool, // Evaluates the samples on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.sample_count == 0 {
+            return Err("There must be at least one sample per generation");
+        }
+        if !(self.elite_fraction > 0.0 && self.elite_fraction <= 1.0) {
+            return Err("Elite fraction must be in (0, 1]");
+        }
+        if !(self.smoothing > 0.0 && self.smoothing <= 1.0) {
+            return Err("Smoothing must be in (0, 1]");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+
+    // The elite of a generation, at least one sample
+    pub fn elite_count(&self) -> usize {
+        return ((self.elite_fraction * self.sample_count as Real).round() as usize).clamp(1, self.sample_count);
+    }
+}
+
+// The mean and standard deviation of every coordinate of the points, by maximum likelihood
+pub fn fit_normal<const N: usize>(points: &[VectorN<Real, N>]) -> (VectorN<Real, N>, VectorN<Real, N>) {
+    let count = points.len() as Real;
+    let mean = points.iter().fold(VectorN::default(), |sum, &point| sum + point) / count;
+    let variance = points.iter().fold(VectorN::default(), |sum, &point| sum + (point - mean) * (point - mean)) / count;
+    return (mean, VectorN::new(variance.coordinates.map(|variance: Real| variance.sqrt())));
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    mean: VectorN<Real, N>, // Of the sampling distribution
+    deviation: VectorN<Real, N>, // Standard deviation of every coordinate, they're sampled independently
+    samples: Vec<(Real, VectorN<Real, N>)>, // Of the last generation, sorted best first once evaluated
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(sample_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            sample_count, function, bounds,
+            elite_fraction: 0.2,
+            smoothing: 0.7,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            mean: VectorN::default(),
+            deviation: VectorN::default(),
+            samples: Vec::with_capacity(parameters.sample_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.sample_count),
+            values: Vec::with_capacity(parameters.sample_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // The mean and standard deviations of the sampling distribution
+    pub fn distribution(&self) -> (VectorN<Real, N>, VectorN<Real, N>) {
+        return (self.mean, self.deviation);
+    }
+
+    // Into self.positions, clamped to the bounds
+    fn draw_samples(&mut self) {
+        self.positions.clear();
+        for _ in 0..self.parameters.sample_count {
+            let noise: VectorN<Real, N> = VectorN::random_from(&StandardNormal, &mut self.random_generator);
+            let mut sample = self.mean + noise * self.deviation;
+            sample.clamp(self.parameters.bounds);
+            self.positions.push(sample);
+        }
+    }
+
+    fn evaluate_samples(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // The values of the samples, in order. Moves the distribution towards the one fitted to the elite
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.positions.len();
+        self.samples.clear();
+        self.samples.extend(values.iter().copied().zip(self.positions.iter().copied()));
+        // Stable, so the first of equally good samples is the best
+        self.samples.sort_by_key(|&(value, _)| Fitness::minimize(value));
+        if Fitness::minimize(self.samples[0].0) < Fitness::minimize(self.best_solution_value) {
+            (self.best_solution_value, self.best_solution) = self.samples[0];
+        }
+        self.positions.clear();
+        self.positions.extend(self.samples[..self.parameters.elite_count()].iter().map(|&(_, sample)| sample));
+        let (elite_mean, elite_deviation) = fit_normal(&self.positions);
+        let smoothing = self.parameters.smoothing;
+        self.mean = elite_mean * smoothing + self.mean * (1.0 - smoothing);
+        self.deviation = elite_deviation * smoothing + self.deviation * (1.0 - smoothing);
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.samples.iter().map(|&(_, sample)| sample), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.draw_samples();
+        self.evaluate_samples();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.draw_samples();
+        positions.extend_from_slice(&self.positions);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    // The first generation is centred on a random point with half the width of the bounds as deviation, so it covers
+    // most of them
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        let (lower, upper) = self.parameters.bounds;
+        self.mean = VectorN::random_uniform((lower, upper), &mut self.random_generator);
+        self.deviation = VectorN::new([(upper - lower) / 2.0; N]);
+        self.draw_samples();
+        self.evaluate_samples();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.parameters.sample_count;
+    }
+
+    // The samples of the last generation
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.samples.iter().map(|&(_, sample)| sample));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer, vector::VectorN};
+
+    use super::{fit_normal, Parameters, WorldState};
+
+    #[test]
+    fn elite_test() {
+        let (mean, deviation) = fit_normal(&[VectorN::new([1.0, 0.0]), VectorN::new([3.0, 0.0])]);
+        assert_eq!(mean.coordinates, [2.0, 0.0]);
+        assert_eq!(deviation.coordinates, [1.0, 0.0]);
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(50, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        let initial_spread = world.distribution().1.norm_l2();
+        world.do_all_iterations(50);
+        assert_eq!(world.evaluation_count(), 50 + 50 * 50);
+        // At least one sample is always in the elite
+        assert_eq!(world.parameters().elite_count(), 10);
+        assert_eq!(Parameters { elite_fraction: 0.001, ..world.parameters().clone() }.elite_count(), 1);
+        assert!(world.best_solution_value() < initial_best);
+        assert!(world.distribution().1.norm_l2() < initial_spread);
+    }
+}
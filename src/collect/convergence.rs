@@ -0,0 +1,68 @@
+use clap::ValueEnum;
+
+use super::{group_cells, group_columns, percentile, standard_deviation, table::Table, Group};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    Iterations, // The n-th point of every trace
+    Evaluations, // Every evaluation count any run reached, the other runs contribute their best value at that point
+}
+
+// The best value of a run once it used that many evaluations, None before its first iteration was done
+fn value_at_evaluations(trace: &[(usize, f64)], evaluations: usize) -> Option<f64> {
+    let index = trace.partition_point(|&(trace_evaluations, _)| trace_evaluations <= evaluations);
+    if index == 0 {
+        return None;
+    }
+    return Some(trace[index - 1].1);
+}
+
+// One row per group and point of the curve
+pub fn convergence_table(groups: &[Group], parameter_names: &[&String], alignment: Alignment) -> Table {
+    let mut columns = group_columns(parameter_names);
+    columns.push(match alignment {
+        Alignment::Iterations => "iteration".to_string(),
+        Alignment::Evaluations => "evaluations".to_string(),
+    });
+    for column in ["run_count", "mean", "std_dev", "median", "min", "max"] {
+        columns.push(column.to_string());
+    }
+
+    let mut rows = Vec::new();
+    for group in groups.iter().filter(|group| !group.traces.is_empty()) {
+        let points = match alignment {
+            Alignment::Iterations => (1..=group.traces.iter().map(Vec::len).max().unwrap()).collect::<Vec<_>>(),
+            Alignment::Evaluations => {
+                let mut points = group.traces.iter().flat_map(|trace| trace.iter().map(|&(evaluations, _)| evaluations)).collect::<Vec<_>>();
+                points.sort();
+                points.dedup();
+                points
+            },
+        };
+
+        for point in points {
+            // Runs stopped early at the target keep their final value
+            let mut values = group.traces.iter().filter_map(|trace| match alignment {
+                Alignment::Iterations => return trace.get(point - 1).or(trace.last()).map(|&(_, value)| value),
+                Alignment::Evaluations => return value_at_evaluations(trace, point),
+            }).collect::<Vec<_>>();
+            if values.is_empty() {
+                continue;
+            }
+            values.sort_by(f64::total_cmp);
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let mut row = group_cells(group, parameter_names);
+            row.extend([
+                serde_json::json!(point),
+                serde_json::json!(values.len()),
+                serde_json::json!(mean),
+                serde_json::json!(standard_deviation(&values, mean)),
+                serde_json::json!(percentile(&values, 50.0)),
+                serde_json::json!(values[0]),
+                serde_json::json!(values[values.len() - 1]),
+            ]);
+            rows.push(row);
+        }
+    }
+    return Table { columns, rows };
+}
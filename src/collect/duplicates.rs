@@ -0,0 +1,55 @@
+// Runs of the same configuration with the same seed, e.g. when the output directories of several machines overlap because
+// a sweep was restarted elsewhere, or the same directory was passed twice
+
+use std::{collections::HashMap, path::PathBuf};
+
+use clap::ValueEnum;
+
+use crate::output::BatchSummary;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    Error, // Stop, the inputs were most likely made by different versions of the optimizer
+    First, // Keep the run read first, in --input order
+}
+
+// (algorithm, function, configuration, eval_budget, seed)
+type RunKey = (String, String, String, Option<usize>, u64);
+
+// The runs kept so far, with the file each came from and its best value
+#[derive(Default)]
+pub struct SeenRuns {
+    runs: HashMap<RunKey, (PathBuf, f64)>,
+}
+
+impl SeenRuns {
+    // Drops the runs that were already seen. Identical copies are dropped silently, ones with a different result according to
+    // the policy. Returns the number of runs dropped
+    pub fn deduplicate(&mut self, summaries: &mut Vec<(PathBuf, BatchSummary)>, policy: ConflictPolicy) -> usize {
+        let mut dropped = 0;
+        for (filename, summary) in summaries.iter_mut() {
+            let configuration = serde_json::Value::Object(summary.configuration.clone()).to_string();
+            summary.result.runs.retain(|run| {
+                let key = (summary.algorithm.clone(), summary.function.clone(), configuration.clone(), summary.eval_budget, run.seed);
+                match self.runs.get(&key) {
+                    None => {
+                        self.runs.insert(key, (filename.clone(), run.best_value));
+                        return true;
+                    },
+                    Some((_, best_value)) if *best_value == run.best_value => {},
+                    Some((first_filename, best_value)) => match policy {
+                        ConflictPolicy::Error => panic!(
+                            "Seed {} of {} on {} has result {} in {} but {} in {}, use --on-conflict first to keep the first one",
+                            run.seed, summary.algorithm, summary.function, best_value, first_filename.display(), run.best_value, filename.display(),
+                        ),
+                        ConflictPolicy::First => {},
+                    },
+                }
+                dropped += 1;
+                return false;
+            });
+        }
+        summaries.retain(|(_, summary)| !summary.result.runs.is_empty());
+        return dropped;
+    }
+}
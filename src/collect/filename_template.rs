@@ -0,0 +1,87 @@
+// Parameters encoded in file names, e.g. `out_{fragrance_multiplier}_{fn}_{local_search_chance}.jsonl`. `{fn}` is the
+// function name and `{_}` matches anything without becoming a parameter, e.g. a timestamp
+
+enum Piece {
+    Literal(String),
+    Parameter(String),
+    Wildcard,
+}
+
+pub struct FilenameTemplate {
+    pieces: Vec<Piece>,
+}
+
+impl FilenameTemplate {
+    pub fn parse(template: &str) -> Self {
+        let mut pieces = Vec::new();
+        let mut rest = template;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest.find('}').unwrap_or_else(|| panic!("Unclosed `{{` in filename template `{}`", template));
+                    if let Some(Piece::Parameter(_) | Piece::Wildcard) = pieces.last() {
+                        panic!("Parameters in filename template `{}` must be separated by some text", template);
+                    }
+                    let name = &rest[1..end];
+                    if name.is_empty() || name.contains('{') {
+                        panic!("Invalid parameter `{{{}}}` in filename template `{}`", name, template);
+                    }
+                    if name == "_" {
+                        pieces.push(Piece::Wildcard);
+                    } else if pieces.iter().any(|piece| matches!(piece, Piece::Parameter(existing) if existing == name)) {
+                        panic!("Parameter `{}` appears twice in filename template `{}`", name, template);
+                    } else {
+                        pieces.push(Piece::Parameter(name.to_string()));
+                    }
+                    rest = &rest[end + 1..];
+                },
+                Some(start) if rest[..start].contains('}') => panic!("Unopened `}}` in filename template `{}`", template),
+                Some(start) => {
+                    pieces.push(Piece::Literal(rest[..start].to_string()));
+                    rest = &rest[start..];
+                },
+                None if rest.contains('}') => panic!("Unopened `}}` in filename template `{}`", template),
+                None => {
+                    pieces.push(Piece::Literal(rest.to_string()));
+                    rest = "";
+                },
+            }
+        }
+        if !pieces.iter().any(|piece| matches!(piece, Piece::Parameter(_))) {
+            panic!("Filename template `{}` has no parameters", template);
+        }
+        return Self { pieces };
+    }
+
+    // The parameter values in template order, or None if the file name has a different shape
+    pub fn extract(&self, filename: &str) -> Option<Vec<(String, String)>> {
+        let mut values = Vec::new();
+        if match_pieces(&self.pieces, filename, &mut values) {
+            return Some(values);
+        }
+        return None;
+    }
+}
+
+// A parameter takes the shortest value that lets the rest of the template match
+fn match_pieces(pieces: &[Piece], text: &str, values: &mut Vec<(String, String)>) -> bool {
+    match pieces.first() {
+        None => return text.is_empty(),
+        Some(Piece::Literal(literal)) => return text.starts_with(literal.as_str()) && match_pieces(&pieces[1..], &text[literal.len()..], values),
+        Some(Piece::Parameter(_) | Piece::Wildcard) if text.is_empty() => return false,
+        Some(piece) => {
+            for (end, _) in text.char_indices().skip(1).chain(std::iter::once((text.len(), ' '))) {
+                if let Piece::Parameter(name) = piece {
+                    values.push((name.clone(), text[..end].to_string()));
+                }
+                if match_pieces(&pieces[1..], &text[end..], values) {
+                    return true;
+                }
+                if let Piece::Parameter(_) = piece {
+                    values.pop();
+                }
+            }
+            return false;
+        },
+    }
+}
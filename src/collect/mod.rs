@@ -0,0 +1,388 @@
+mod convergence;
+mod duplicates;
+mod filename_template;
+mod pivot;
+mod plots;
+mod ranking;
+mod report;
+mod sqlite;
+mod table;
+
+use std::{collections::HashMap, io::Write, path::{Path, PathBuf}, time::Duration};
+
+use clap::Args;
+use swarm_optimizers::experiment::standard_deviation;
+use convergence::Alignment;
+use duplicates::{ConflictPolicy, SeenRuns};
+use filename_template::FilenameTemplate;
+use plots::PlotFormat;
+use table::{Table, TableFormat};
+
+use crate::output::BatchSummary;
+
+const PERCENTILES: [f64; 4] = [5.0, 25.0, 75.0, 95.0];
+
+#[derive(Args, Clone, Debug)]
+pub struct CollectArguments {
+    // Files to read, defaults to the output directories of run_sweep.sh. Runs found more than once, with the same
+    // configuration and seed, are only counted once, so the directories of several machines can be merged
+    #[arg(long = "input", default_values_t = ["./output_bats/*".to_string(), "./output_butterflies/*".to_string()])]
+    inputs: Vec<String>,
+
+    // What to do with a duplicated run whose copies disagree on the result
+    #[arg(long = "on-conflict", value_enum, default_value_t = ConflictPolicy::Error)]
+    on_conflict: ConflictPolicy,
+
+    // Parameters encoded in the file names, e.g. `ratefactor_{pulse_rate_factor}_run_{run}_{_}.jsonl`. They become extra
+    // columns, for whatever the summaries don't record themselves. `{fn}` is checked against the function of each summary
+    // and `{_}` skips a part of the name
+    #[arg(long = "filename-template")]
+    filename_template: Option<String>,
+
+    // Writes the statistics here instead of to stdout
+    #[arg(long = "output")]
+    output: Option<PathBuf>,
+
+    #[arg(long = "output-format", value_enum, default_value_t = TableFormat::Csv)]
+    output_format: TableFormat,
+
+    // Also draws box plots and heatmaps into this directory
+    #[arg(long = "plot")]
+    plot: Option<PathBuf>,
+
+    #[arg(long = "plot-format", value_enum, default_value_t = PlotFormat::Svg)]
+    plot_format: PlotFormat,
+
+    // The two swept parameters shown in heatmaps as `x_parameter,y_parameter`, needed when more than two were swept
+    #[arg(long = "heatmap")]
+    heatmap: Option<String>,
+
+    // Also writes mean and spread of the best value over the course of the runs, in --output-format. Needs summaries
+    // from the main binary's `--output-trace`
+    #[arg(long = "convergence")]
+    convergence: Option<PathBuf>,
+
+    // What the points of the convergence curves are, runs of different lengths continue with their final value
+    #[arg(long = "align", value_enum, default_value_t = Alignment::Evaluations)]
+    align: Alignment,
+
+    // Also writes, for every function, the configurations ordered by median with a Wilcoxon rank-sum test against the
+    // best one, in --output-format
+    #[arg(long = "ranking")]
+    ranking: Option<PathBuf>,
+
+    // Significance level of the ranking, after the Holm correction
+    #[arg(long = "alpha", default_value_t = 0.05)]
+    alpha: f64,
+
+    // Also writes one row per configuration with a column per function, in --output-format
+    #[arg(long = "pivot")]
+    pivot: Option<PathBuf>,
+
+    // The summary column shown in the pivot table, e.g. `avg_solution` or `p95`
+    #[arg(long = "pivot-statistic", default_value = "median")]
+    pivot_statistic: String,
+
+    // Also writes a single HTML file with the summary, the ranking and the plots
+    #[arg(long = "report")]
+    report: Option<PathBuf>,
+
+    // Keeps running and updates every output as summaries are added to the inputs, e.g. while run_sweep.sh is still going.
+    // The table is rewritten each time, so it needs --output
+    #[arg(long = "watch", requires = "output")]
+    watch: bool,
+
+    // Seconds between looking for new summaries
+    #[arg(long = "watch-interval", default_value_t = 5.0, requires = "watch")]
+    watch_interval: f64,
+
+    // Also loads every run into an SQLite file with the same tables as the main binary's `--store`
+    #[arg(long = "sqlite")]
+    sqlite: Option<PathBuf>,
+}
+
+// All runs of one function with one algorithm configuration, possibly spread over several summaries
+struct Group {
+    algorithm: String,
+    configuration: serde_json::Map<String, serde_json::Value>,
+    eval_budget: Option<usize>,
+    function: String,
+    results: Vec<f64>,
+    traces: Vec<Vec<(usize, f64)>>,
+}
+
+// A `json` file holds a single array, a `jsonl` file one summary per line
+fn read_summaries(contents: &str) -> Vec<BatchSummary> {
+    if contents.trim_start().starts_with('[') {
+        return serde_json::from_str(contents).unwrap();
+    }
+    return contents.lines().filter(|line| !line.trim().is_empty()).map(|line| serde_json::from_str(line).unwrap()).collect();
+}
+
+// The columns identifying a group, shared by every table written
+fn group_columns(parameter_names: &[&String]) -> Vec<String> {
+    let mut columns = vec!["algorithm".to_string()];
+    columns.extend(parameter_names.iter().map(|name| name.to_string()));
+    columns.push("eval_budget".to_string());
+    columns.push("fn_name".to_string());
+    return columns;
+}
+
+// Parameters of other algorithms are left empty
+fn group_cells(group: &Group, parameter_names: &[&String]) -> Vec<serde_json::Value> {
+    let mut cells = vec![serde_json::json!(group.algorithm)];
+    cells.extend(parameter_names.iter().map(|name| group.configuration.get(*name).cloned().unwrap_or_default()));
+    cells.push(serde_json::json!(group.eval_budget));
+    cells.push(serde_json::json!(group.function));
+    return cells;
+}
+
+fn create_output(path: &std::path::Path) -> std::fs::File {
+    return std::fs::File::create(path).unwrap_or_else(|error| panic!("Could not create {}: {}", path.display(), error));
+}
+
+// Numbers stay numbers, so they compare equal to the summary's own values and sort properly in plots
+fn parse_parameter_value(value: &str) -> serde_json::Value {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => return serde_json::json!(number),
+        _ => return serde_json::Value::String(value.to_string()),
+    }
+}
+
+fn same_parameter_value(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => return a == b,
+        _ => return a == b,
+    }
+}
+
+// Linear interpolation between the closest ranks, `sorted` must not be empty
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = percent / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    return sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64);
+}
+
+// Adds the parameters encoded in the file name to every summary of the file
+fn apply_filename_template(filename: &Path, template: &FilenameTemplate, summaries: &mut [BatchSummary]) {
+    let name = filename.file_name().unwrap().to_string_lossy();
+    let parameters = template.extract(&name).unwrap_or_else(|| panic!("{} does not match --filename-template", filename.display()));
+    for summary in summaries {
+        for (parameter, value) in &parameters {
+            if parameter == "fn" {
+                if value != &summary.function {
+                    panic!("{}: the file name says the function is {}, but it holds a summary for {}", filename.display(), value, summary.function);
+                }
+                continue;
+            }
+            let value = parse_parameter_value(value);
+            match summary.configuration.get(parameter) {
+                Some(existing) if !same_parameter_value(existing, &value) => {
+                    panic!("{}: the file name says {}={}, but the summary for {} has {}", filename.display(), parameter, value, summary.function, existing);
+                },
+                Some(_) => {},
+                None => {
+                    summary.configuration.insert(parameter.clone(), value);
+                },
+            }
+        }
+    }
+}
+
+fn input_files(inputs: &[String]) -> Vec<PathBuf> {
+    let mut filenames = Vec::new();
+    for input_glob in inputs {
+        for filename in glob::glob(input_glob).unwrap_or_else(|error| panic!("Invalid input pattern `{}`: {}", input_glob, error)) {
+            let filename = filename.unwrap();
+            if filename.is_file() {
+                filenames.push(filename);
+            }
+        }
+    }
+    return filenames;
+}
+
+// Writes every requested output except the SQLite file
+fn write_outputs(config: &CollectArguments, summaries: &[(PathBuf, BatchSummary)]) {
+    let heatmap_axes = config.heatmap.as_ref().map(|axes| {
+        return axes.split_once(',').unwrap_or_else(|| panic!("Invalid --heatmap: `{}`, expected `x_parameter,y_parameter`", axes));
+    });
+
+    let mut groups: Vec<Group> = Vec::new();
+    for (_, summary) in summaries {
+        let results = summary.result.runs.iter().map(|run| run.best_value);
+        let traces = summary.result.runs.iter().filter_map(|run| run.trace.clone());
+        let existing = groups.iter_mut().find(|group| {
+            return group.algorithm == summary.algorithm && group.function == summary.function && group.configuration == summary.configuration && group.eval_budget == summary.eval_budget;
+        });
+        match existing {
+            Some(group) => {
+                group.results.extend(results);
+                group.traces.extend(traces);
+            },
+            None => groups.push(Group {
+                algorithm: summary.algorithm.clone(),
+                configuration: summary.configuration.clone(),
+                eval_budget: summary.eval_budget,
+                function: summary.function.clone(),
+                results: results.collect(),
+                traces: traces.collect(),
+            }),
+        }
+    }
+    // Each algorithm has its own parameters
+    let mut parameter_names: Vec<&String> = Vec::new();
+    for group in &groups {
+        for name in group.configuration.keys() {
+            if !parameter_names.contains(&name) {
+                parameter_names.push(name);
+            }
+        }
+    }
+
+    let mut columns = group_columns(&parameter_names);
+    for column in ["max_solution", "avg_solution", "min_solution", "run_count", "std_dev", "median"] {
+        columns.push(column.to_string());
+    }
+    columns.extend(PERCENTILES.iter().map(|percent| format!("p{}", percent)));
+
+    let mut rows = Vec::new();
+    for group in &groups {
+        if group.results.is_empty() {
+            continue;
+        }
+        let mut results = group.results.clone();
+        results.sort_by(f64::total_cmp);
+        let average = results.iter().sum::<f64>() / results.len() as f64;
+        let mut row = group_cells(group, &parameter_names);
+        row.extend([
+            serde_json::json!(results[results.len() - 1]),
+            serde_json::json!(average),
+            serde_json::json!(results[0]),
+            serde_json::json!(results.len()),
+            serde_json::json!(standard_deviation(&results, average)),
+            serde_json::json!(percentile(&results, 50.0)),
+        ]);
+        row.extend(PERCENTILES.iter().map(|&percent| serde_json::json!(percentile(&results, percent))));
+        rows.push(row);
+    }
+
+    let table = Table { columns, rows };
+    let mut output: Box<dyn Write + Send> = match &config.output {
+        Some(path) => Box::new(create_output(path)),
+        None => Box::new(std::io::stdout()),
+    };
+    table.write(config.output_format, &mut output).unwrap();
+
+    if let Some(path) = &config.convergence {
+        let table = convergence::convergence_table(&groups, &parameter_names, config.align);
+        table.write(config.output_format, &mut create_output(path)).unwrap();
+    }
+    if let Some(path) = &config.pivot {
+        let table = pivot::pivot_table(&table, &config.pivot_statistic);
+        table.write(config.output_format, &mut create_output(path)).unwrap();
+    }
+    let ranking = (config.ranking.is_some() || config.report.is_some()).then(|| ranking::ranking_table(&groups, &parameter_names, config.alpha));
+    if let (Some(path), Some(ranking)) = (&config.ranking, &ranking) {
+        ranking.write(config.output_format, &mut create_output(path)).unwrap();
+    }
+    let plots = (config.plot.is_some() || config.report.is_some()).then(|| plots::plan_plots(&groups, heatmap_axes));
+    if let (Some(directory), Some(plots)) = (&config.plot, &plots) {
+        plots::draw_plots(plots, directory, config.plot_format);
+    }
+    if let (Some(path), Some(ranking), Some(plots)) = (&config.report, &ranking, &plots) {
+        report::write_report(path, &config.inputs, &table, ranking, plots);
+    }
+}
+
+pub fn run(config: &CollectArguments) {
+    let filename_template = config.filename_template.as_deref().map(FilenameTemplate::parse);
+    if config.watch {
+        watch(config, filename_template.as_ref());
+        return;
+    }
+
+    let mut summaries = Vec::new();
+    for filename in input_files(&config.inputs) {
+        let contents = std::fs::read_to_string(&filename).unwrap();
+        let mut file_summaries = read_summaries(&contents);
+        if let Some(template) = &filename_template {
+            apply_filename_template(&filename, template, &mut file_summaries);
+        }
+        summaries.extend(file_summaries.into_iter().map(|summary| (filename.clone(), summary)));
+    }
+    let duplicate_count = SeenRuns::default().deduplicate(&mut summaries, config.on_conflict);
+    if duplicate_count > 0 {
+        eprintln!("Skipped {} duplicated runs", duplicate_count);
+    }
+
+    write_outputs(config, &summaries);
+    if let Some(path) = &config.sqlite {
+        sqlite::write(path, &summaries);
+    }
+}
+
+// What has been read of an input file so far
+struct WatchedFile {
+    length: u64,
+    summary_count: usize,
+}
+
+// The summaries of a file still being written: the complete lines of a `jsonl` file, nothing of a `json` file until the
+// array is closed
+fn read_finished_summaries(contents: &str) -> Vec<BatchSummary> {
+    if contents.trim_start().starts_with('[') {
+        return serde_json::from_str(contents).unwrap_or_default();
+    }
+    let finished = &contents[..contents.rfind('\n').map_or(0, |end| end + 1)];
+    return read_summaries(finished);
+}
+
+// Re-reads the inputs every --watch-interval seconds and rewrites the outputs whenever a summary was added, until interrupted.
+// Files only ever grow while the main binary writes them, so the summaries read before are kept and the SQLite file only
+// receives the new ones
+fn watch(config: &CollectArguments, filename_template: Option<&FilenameTemplate>) {
+    let mut files: HashMap<PathBuf, WatchedFile> = HashMap::new();
+    let mut summaries: Vec<(PathBuf, BatchSummary)> = Vec::new();
+    let mut seen_runs = SeenRuns::default();
+    loop {
+        let mut new_summaries = Vec::new();
+        for filename in input_files(&config.inputs) {
+            let length = std::fs::metadata(&filename).map_or(0, |metadata| metadata.len());
+            let watched = files.entry(filename.clone()).or_insert(WatchedFile { length: 0, summary_count: 0 });
+            if watched.length == length {
+                continue;
+            }
+            let Ok(contents) = std::fs::read_to_string(&filename) else {
+                continue; // Possibly cut in the middle of a character, try again next time
+            };
+            let mut file_summaries = read_finished_summaries(&contents);
+            // Only once the file was read, an unreadable one is read again even if it doesn't grow
+            watched.length = length;
+            if file_summaries.len() <= watched.summary_count {
+                continue;
+            }
+            let mut file_summaries = file_summaries.split_off(watched.summary_count);
+            watched.summary_count += file_summaries.len();
+            if let Some(template) = filename_template {
+                apply_filename_template(&filename, template, &mut file_summaries);
+            }
+            new_summaries.extend(file_summaries.into_iter().map(|summary| (filename.clone(), summary)));
+        }
+
+        let duplicate_count = seen_runs.deduplicate(&mut new_summaries, config.on_conflict);
+        if duplicate_count > 0 {
+            eprintln!("Skipped {} duplicated runs", duplicate_count);
+        }
+        if !new_summaries.is_empty() {
+            eprintln!("Collected {} new summaries from {} files", new_summaries.len(), files.len());
+            if let Some(path) = &config.sqlite {
+                sqlite::write(path, &new_summaries);
+            }
+            summaries.extend(new_summaries);
+            write_outputs(config, &summaries);
+        }
+        std::thread::sleep(Duration::from_secs_f64(config.watch_interval));
+    }
+}
@@ -0,0 +1,41 @@
+use super::table::Table;
+
+// One row per configuration and one column per function, holding a single statistic of the summary table, the layout
+// of most comparison tables in papers. Functions a configuration was not run on are left empty
+pub fn pivot_table(summary: &Table, statistic: &str) -> Table {
+    let Some(statistic_index) = summary.columns.iter().position(|column| column == statistic) else {
+        panic!("Unknown --pivot-statistic `{}`, expected one of: {}", statistic, summary.columns.join(", "));
+    };
+    // The columns before fn_name identify the configuration
+    let function_index = summary.columns.iter().position(|column| column == "fn_name").unwrap();
+    if statistic_index <= function_index {
+        panic!("--pivot-statistic must be a statistic, not `{}`", statistic);
+    }
+
+    let mut functions: Vec<&serde_json::Value> = Vec::new();
+    for row in &summary.rows {
+        if !functions.contains(&&row[function_index]) {
+            functions.push(&row[function_index]);
+        }
+    }
+
+    let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
+    for row in &summary.rows {
+        let configuration = &row[..function_index];
+        let position = match rows.iter().position(|pivot_row| &pivot_row[..function_index] == configuration) {
+            Some(position) => position,
+            None => {
+                let mut pivot_row = configuration.to_vec();
+                pivot_row.resize(function_index + functions.len(), serde_json::Value::Null);
+                rows.push(pivot_row);
+                rows.len() - 1
+            },
+        };
+        let column = function_index + functions.iter().position(|function| *function == &row[function_index]).unwrap();
+        rows[position][column] = row[statistic_index].clone();
+    }
+
+    let mut columns = summary.columns[..function_index].to_vec();
+    columns.extend(functions.iter().map(|function| function.as_str().unwrap().to_string()));
+    return Table { columns, rows };
+}
@@ -0,0 +1,237 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use plotters::{coord::Shift, prelude::*, style::text_anchor::{HPos, Pos, VPos}};
+
+use super::Group;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotFormat {
+    Svg,
+    Png,
+}
+
+impl PlotFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            PlotFormat::Svg => return "svg",
+            PlotFormat::Png => return "png",
+        }
+    }
+}
+
+// Parameters taking more than one value among the groups, the only ones worth showing in labels
+fn varying_parameters(groups: &[&Group]) -> Vec<String> {
+    let mut varying = Vec::new();
+    for group in groups {
+        for (name, value) in &group.configuration {
+            if !varying.contains(name) && groups.iter().any(|other| other.configuration.get(name) != Some(value)) {
+                varying.push(name.clone());
+            }
+        }
+    }
+    return varying;
+}
+
+// Only lists the parameters that differ between the configurations of the group's algorithm
+fn group_label(group: &Group, groups: &[&Group]) -> String {
+    let same_algorithm = groups.iter().copied().filter(|other| other.algorithm == group.algorithm).collect::<Vec<_>>();
+    let mut label = group.algorithm.clone();
+    for name in &varying_parameters(&same_algorithm) {
+        if let Some(value) = group.configuration.get(name) {
+            label += &format!(" {}={}", name, value);
+        }
+    }
+    return label;
+}
+
+enum PlotKind<'a> {
+    BoxPlot,
+    Heatmap { algorithm: &'a str, x_parameter: String, y_parameter: String },
+}
+
+pub struct Plot<'a> {
+    kind: PlotKind<'a>,
+    function: &'a str,
+    groups: Vec<&'a Group>,
+}
+
+impl Plot<'_> {
+    pub fn name(&self) -> String {
+        match &self.kind {
+            PlotKind::BoxPlot => return format!("boxplot_{}", self.function),
+            PlotKind::Heatmap { algorithm, .. } => return format!("heatmap_{}_{}", algorithm, self.function),
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        match self.kind {
+            PlotKind::BoxPlot => return (1024, 120 + 30 * self.groups.len() as u32),
+            PlotKind::Heatmap { .. } => return (800, 700),
+        }
+    }
+
+    fn draw<Backend: DrawingBackend>(&self, area: &DrawingArea<Backend, Shift>) {
+        match &self.kind {
+            PlotKind::BoxPlot => draw_box_plot(area, self.function, &self.groups),
+            PlotKind::Heatmap { algorithm, x_parameter, y_parameter } => {
+                let title = format!("{} on {}, median result", algorithm, self.function);
+                draw_heatmap(area, &title, &self.groups, x_parameter, y_parameter);
+            },
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        self.draw(&SVGBackend::with_string(&mut svg, self.size()).into_drawing_area());
+        return svg;
+    }
+}
+
+// A box plot of every configuration for each function, and a heatmap of the median for each algorithm and function
+// over two swept parameters. Those are `heatmap_axes` if given, otherwise the only two parameters that were swept, if that is the case
+pub fn plan_plots<'a>(groups: &'a [Group], heatmap_axes: Option<(&str, &str)>) -> Vec<Plot<'a>> {
+    let mut functions: Vec<&String> = Vec::new();
+    for group in groups {
+        if !functions.contains(&&group.function) {
+            functions.push(&group.function);
+        }
+    }
+
+    let mut plots = Vec::new();
+    for function in functions {
+        let function_groups = groups.iter().filter(|group| &group.function == function && !group.results.is_empty()).collect::<Vec<_>>();
+        plots.push(Plot { kind: PlotKind::BoxPlot, function, groups: function_groups.clone() });
+
+        let mut algorithms: Vec<&String> = Vec::new();
+        for group in &function_groups {
+            if !algorithms.contains(&&group.algorithm) {
+                algorithms.push(&group.algorithm);
+            }
+        }
+        for algorithm in algorithms {
+            let algorithm_groups = function_groups.iter().copied().filter(|group| &group.algorithm == algorithm).collect::<Vec<_>>();
+            let swept = varying_parameters(&algorithm_groups);
+            let (x_parameter, y_parameter) = match heatmap_axes {
+                Some((x_parameter, y_parameter)) => {
+                    if !algorithm_groups.iter().all(|group| group.configuration.contains_key(x_parameter) && group.configuration.contains_key(y_parameter)) {
+                        continue;
+                    }
+                    (x_parameter.to_string(), y_parameter.to_string())
+                },
+                None if swept.len() == 2 => (swept[0].clone(), swept[1].clone()),
+                None => {
+                    eprintln!("Skipping the {} heatmap for {}: {} parameters were swept, choose two with --heatmap", algorithm, function, swept.len());
+                    continue;
+                },
+            };
+            plots.push(Plot { kind: PlotKind::Heatmap { algorithm, x_parameter, y_parameter }, function, groups: algorithm_groups });
+        }
+    }
+    return plots;
+}
+
+pub fn draw_plots(plots: &[Plot], directory: &Path, format: PlotFormat) {
+    std::fs::create_dir_all(directory).unwrap_or_else(|error| panic!("Could not create {}: {}", directory.display(), error));
+    for plot in plots {
+        let path = directory.join(format!("{}.{}", plot.name(), format.extension()));
+        match format {
+            PlotFormat::Svg => plot.draw(&SVGBackend::new(&path, plot.size()).into_drawing_area()),
+            PlotFormat::Png => plot.draw(&BitMapBackend::new(&path, plot.size()).into_drawing_area()),
+        }
+    }
+}
+
+fn draw_box_plot<Backend: DrawingBackend>(area: &DrawingArea<Backend, Shift>, function: &str, groups: &[&Group]) {
+    let labels = groups.iter().map(|group| group_label(group, groups)).collect::<Vec<_>>();
+    let quartiles = groups.iter().map(|group| Quartiles::new(&group.results)).collect::<Vec<_>>();
+    let min = quartiles.iter().map(|quartiles| quartiles.values()[0]).fold(f32::INFINITY, f32::min);
+    let max = quartiles.iter().map(|quartiles| quartiles.values()[4]).fold(f32::NEG_INFINITY, f32::max);
+    let margin = ((max - min) * 0.05).max(f32::EPSILON);
+
+    area.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(area)
+        .caption(function, ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size((7 * labels.iter().map(String::len).max().unwrap_or(0) as u32).min(area.dim_in_pixel().0 / 2))
+        .build_cartesian_2d(min - margin..max + margin, labels[..].into_segmented())
+        .unwrap();
+    chart.configure_mesh()
+        .x_desc("Result")
+        .y_labels(labels.len())
+        .y_label_formatter(&|segment| match segment {
+            SegmentValue::CenterOf(label) | SegmentValue::Exact(label) => return label.to_string(),
+            SegmentValue::Last => return String::new(),
+        })
+        .light_line_style(WHITE)
+        .draw()
+        .unwrap();
+    chart.draw_series(labels.iter().zip(&quartiles).map(|(label, quartiles)| {
+        return Boxplot::new_horizontal(SegmentValue::CenterOf(label), quartiles).width(16).whisker_width(0.5);
+    })).unwrap();
+    area.present().unwrap();
+}
+
+fn draw_heatmap<Backend: DrawingBackend>(area: &DrawingArea<Backend, Shift>, title: &str, groups: &[&Group], x_parameter: &str, y_parameter: &str) {
+    let axis_values = |parameter: &str| {
+        let mut values = groups.iter().filter_map(|group| group.configuration.get(parameter)).cloned().collect::<Vec<_>>();
+        values.sort_by(|a, b| a.as_f64().unwrap_or(0.0).total_cmp(&b.as_f64().unwrap_or(0.0)));
+        values.dedup();
+        return values;
+    };
+    let x_values = axis_values(x_parameter);
+    let y_values = axis_values(y_parameter);
+
+    // Groups differing only in parameters that are not on the axes end up in the same cell
+    let mut cells = Vec::new();
+    for (x, x_value) in x_values.iter().enumerate() {
+        for (y, y_value) in y_values.iter().enumerate() {
+            let mut results = groups.iter()
+                .filter(|group| group.configuration.get(x_parameter) == Some(x_value) && group.configuration.get(y_parameter) == Some(y_value))
+                .flat_map(|group| group.results.iter().copied())
+                .collect::<Vec<_>>();
+            if !results.is_empty() {
+                results.sort_by(f64::total_cmp);
+                cells.push((x, y, super::percentile(&results, 50.0)));
+            }
+        }
+    }
+    let min = cells.iter().map(|cell| cell.2).fold(f64::INFINITY, f64::min);
+    let max = cells.iter().map(|cell| cell.2).fold(f64::NEG_INFINITY, f64::max);
+
+    area.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(area)
+        .caption(title, ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(50)
+        .y_label_area_size(70)
+        .build_cartesian_2d((0..x_values.len() - 1).into_segmented(), (0..y_values.len() - 1).into_segmented())
+        .unwrap();
+    let axis_label = |values: &[serde_json::Value], segment: &SegmentValue<usize>| {
+        return match segment {
+            SegmentValue::CenterOf(index) | SegmentValue::Exact(index) => values.get(*index).map(|value| value.to_string()).unwrap_or_default(),
+            SegmentValue::Last => String::new(),
+        };
+    };
+    chart.configure_mesh()
+        .disable_mesh()
+        .x_desc(x_parameter)
+        .y_desc(y_parameter)
+        .x_labels(x_values.len())
+        .y_labels(y_values.len())
+        .x_label_formatter(&|segment| axis_label(&x_values, segment))
+        .y_label_formatter(&|segment| axis_label(&y_values, segment))
+        .draw()
+        .unwrap();
+    chart.draw_series(cells.iter().map(|&(x, y, median)| {
+        let color = ViridisRGB::get_color_normalized(median as f32, min as f32, max.max(min + f64::EPSILON) as f32);
+        return Rectangle::new([(SegmentValue::Exact(x), SegmentValue::Exact(y)), (SegmentValue::Exact(x + 1), SegmentValue::Exact(y + 1))], color.filled());
+    })).unwrap();
+    chart.draw_series(cells.iter().map(|&(x, y, median)| {
+        // Dark text on the bright end of the color map
+        let text_color = if median - min > (max - min) * 0.6 { &BLACK } else { &WHITE };
+        return Text::new(format!("{:.3e}", median), (SegmentValue::CenterOf(x), SegmentValue::CenterOf(y)), ("sans-serif", 14).into_font().color(text_color).pos(Pos::new(HPos::Center, VPos::Center)));
+    })).unwrap();
+    area.present().unwrap();
+}
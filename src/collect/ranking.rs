@@ -0,0 +1,107 @@
+use super::{group_cells, group_columns, percentile, table::Table, Group};
+
+// Complementary error function, Abramowitz and Stegun 7.1.26. The absolute error is below 1.5e-7, plenty for p-values
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let polynomial = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let result = polynomial * (-z * z).exp();
+    if x < 0.0 {
+        return 2.0 - result;
+    }
+    return result;
+}
+
+// Two-sided Wilcoxon rank-sum (Mann-Whitney U) test using the normal approximation with tie and continuity corrections
+pub fn rank_sum_p_value(a: &[f64], b: &[f64]) -> f64 {
+    let mut combined = a.iter().map(|&value| (value, true)).chain(b.iter().map(|&value| (value, false))).collect::<Vec<_>>();
+    combined.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    let n = combined.len() as f64;
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < combined.len() {
+        let mut end = start;
+        while end < combined.len() && combined[end].0 == combined[start].0 {
+            end += 1;
+        }
+        // Tied values share the average of their ranks
+        let average_rank = (start + end + 1) as f64 / 2.0;
+        rank_sum_a += average_rank * combined[start..end].iter().filter(|(_, from_a)| *from_a).count() as f64;
+        let tied = (end - start) as f64;
+        tie_term += tied * tied * tied - tied;
+        start = end;
+    }
+
+    let n_a = a.len() as f64;
+    let n_b = b.len() as f64;
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let mean = n_a * n_b / 2.0;
+    let variance = n_a * n_b / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 1.0; // Everything is tied
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    return erfc(z / std::f64::consts::SQRT_2).min(1.0);
+}
+
+// Holm-Bonferroni step-down adjustment, in the order the p-values were given
+pub fn holm_adjust(p_values: &[f64]) -> Vec<f64> {
+    let mut order = (0..p_values.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+    let mut adjusted = vec![0.0; p_values.len()];
+    let mut running_max: f64 = 0.0;
+    for (position, &index) in order.iter().enumerate() {
+        running_max = running_max.max(((p_values.len() - position) as f64 * p_values[index]).min(1.0));
+        adjusted[index] = running_max;
+    }
+    return adjusted;
+}
+
+// For every function the configurations ordered by median, each compared against the best one. The Holm correction
+// covers the comparisons within one function
+pub fn ranking_table(groups: &[Group], parameter_names: &[&String], alpha: f64) -> Table {
+    let mut columns = vec!["rank".to_string()];
+    columns.extend(group_columns(parameter_names));
+    for column in ["run_count", "median", "p_value_vs_best", "holm_p_value", "significantly_worse"] {
+        columns.push(column.to_string());
+    }
+
+    let mut functions: Vec<&String> = Vec::new();
+    for group in groups {
+        if !functions.contains(&&group.function) {
+            functions.push(&group.function);
+        }
+    }
+
+    let mut rows = Vec::new();
+    for function in functions {
+        let mut ranked = groups.iter().filter(|group| &group.function == function && !group.results.is_empty()).map(|group| {
+            let mut results = group.results.clone();
+            results.sort_by(f64::total_cmp);
+            let median = percentile(&results, 50.0);
+            return (group, median);
+        }).collect::<Vec<_>>();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let best = ranked[0].0;
+        let p_values = ranked[1..].iter().map(|(group, _)| rank_sum_p_value(&best.results, &group.results)).collect::<Vec<_>>();
+        let adjusted = holm_adjust(&p_values);
+        for (index, (group, median)) in ranked.iter().enumerate() {
+            let mut row = vec![serde_json::json!(index + 1)];
+            row.extend(group_cells(group, parameter_names));
+            row.push(serde_json::json!(group.results.len()));
+            row.push(serde_json::json!(median));
+            if index == 0 {
+                row.extend([serde_json::Value::Null, serde_json::Value::Null, serde_json::Value::Null]);
+            } else {
+                row.push(serde_json::json!(p_values[index - 1]));
+                row.push(serde_json::json!(adjusted[index - 1]));
+                row.push(serde_json::json!(adjusted[index - 1] < alpha));
+            }
+            rows.push(row);
+        }
+    }
+    return Table { columns, rows };
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use super::{plots::Plot, table::{escape_html, Table}};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; font-size: 0.85em; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: right; }
+th { background: #eee; position: sticky; top: 0; }
+.table { overflow-x: auto; }
+svg { display: block; max-width: 100%; height: auto; margin-bottom: 1em; }
+";
+
+// A single HTML file with everything inline, so it can be sent around as it is
+pub fn write_report(path: &Path, inputs: &[String], summary: &Table, ranking: &Table, plots: &[Plot]) {
+    let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Sweep report</title>\n<style>{}</style>\n</head>\n<body>\n", STYLE);
+    html += "<h1>Sweep report</h1>\n<p>Collected from ";
+    html += &inputs.iter().map(|input| format!("<code>{}</code>", escape_html(input))).collect::<Vec<_>>().join(", ");
+    html += "</p>\n";
+
+    html += "<h2>Summary</h2>\n<div class=\"table\">\n";
+    html += &summary.to_html();
+    html += "</div>\n<h2>Ranking</h2>\n<p>Per function, ordered by median. Every configuration is compared to the best one with a Wilcoxon rank-sum test, Holm-corrected.</p>\n<div class=\"table\">\n";
+    html += &ranking.to_html();
+    html += "</div>\n<h2>Plots</h2>\n";
+    for plot in plots {
+        html += &format!("<h3>{}</h3>\n", escape_html(&plot.name()));
+        html += &plot.to_svg();
+        html += "\n";
+    }
+    html += "</body>\n</html>\n";
+
+    std::fs::write(path, html).unwrap_or_else(|error| panic!("Could not write {}: {}", path.display(), error));
+}
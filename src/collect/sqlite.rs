@@ -0,0 +1,46 @@
+// Writes the collected summaries using the tables of the main binary's `--store` (see its store.rs), so both kinds of
+// files can be queried the same way. Every summary becomes one batch, with the file it was read from as `command_line`
+// and that file's modification time as `started_at`. Traces, from `--output-trace`, go into the convergence table
+
+use std::{path::{Path, PathBuf}, time::UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+
+use crate::{output::BatchSummary, store::SCHEMA};
+
+fn modification_time(path: &Path) -> i64 {
+    let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).unwrap();
+    return modified.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+}
+
+// Appends to the file if it already exists
+pub fn write(path: &Path, summaries: &[(PathBuf, BatchSummary)]) {
+    let mut connection = Connection::open(path).unwrap_or_else(|error| panic!("Could not open {}: {}", path.display(), error));
+    connection.execute_batch(SCHEMA).unwrap();
+    let transaction = connection.transaction().unwrap();
+    for (filename, summary) in summaries {
+        transaction.execute(
+            "INSERT INTO batches (started_at, command_line, algorithm, configuration, eval_budget) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                modification_time(filename), filename.display().to_string(), summary.algorithm,
+                serde_json::Value::Object(summary.configuration.clone()).to_string(), summary.eval_budget.map(|budget| budget as i64),
+            ],
+        ).unwrap();
+        let batch_id = transaction.last_insert_rowid();
+        let mut statement = transaction.prepare_cached(
+            "INSERT INTO runs (batch_id, function, seed, best_value, best_solution, evaluations, target_value, evaluations_to_target) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+        ).unwrap();
+        for run in &summary.result.runs {
+            statement.execute(params![
+                batch_id, summary.function, run.seed as i64, run.best_value, serde_json::to_string(&run.best_solution).unwrap(),
+                run.evaluations as i64, summary.target_value, run.evaluations_to_target.map(|evaluations| evaluations as i64),
+            ]).unwrap();
+            let run_id = transaction.last_insert_rowid();
+            let mut trace_statement = transaction.prepare_cached("INSERT INTO convergence (run_id, evaluations, best_value) VALUES (?1, ?2, ?3)").unwrap();
+            for (evaluations, best_value) in run.trace.iter().flatten() {
+                trace_statement.execute(params![run_id, *evaluations as i64, best_value]).unwrap();
+            }
+        }
+    }
+    transaction.commit().unwrap();
+}
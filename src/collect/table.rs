@@ -0,0 +1,147 @@
+use std::{io::Write, sync::Arc};
+
+use arrow::{array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray}, datatypes::{DataType, Field, Schema}};
+use clap::ValueEnum;
+use parquet::arrow::ArrowWriter;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+    Json, // An array with an object per row
+    Parquet,
+    Arrow, // Arrow IPC file
+}
+
+// The aggregated statistics, one row per group. Missing values are nulls
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+fn cell_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => return String::new(),
+        serde_json::Value::String(text) => return text.clone(),
+        other => return other.to_string(),
+    }
+}
+
+fn delimited_cell(value: &serde_json::Value, delimiter: char) -> String {
+    let text = cell_text(value);
+    if text.contains([delimiter, '"', '\n']) {
+        return format!("\"{}\"", text.replace('"', "\"\""));
+    }
+    return text;
+}
+
+// Keeps the columns in table order, which serde_json::Map would sort
+struct JsonRow<'a> {
+    columns: &'a [String],
+    values: &'a [serde_json::Value],
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (column, value) in self.columns.iter().zip(self.values) {
+            map.serialize_entry(column, value)?;
+        }
+        return map.end();
+    }
+}
+
+// The narrowest type holding every value of the column, text if they disagree
+fn column_type(values: &[&serde_json::Value]) -> DataType {
+    let present = values.iter().filter(|value| !value.is_null()).collect::<Vec<_>>();
+    if present.iter().all(|value| value.is_i64() || value.is_u64() && value.as_i64().is_some()) {
+        return DataType::Int64;
+    }
+    if present.iter().all(|value| value.is_number()) {
+        return DataType::Float64;
+    }
+    if present.iter().all(|value| value.is_boolean()) {
+        return DataType::Boolean;
+    }
+    return DataType::Utf8;
+}
+
+fn column_array(values: &[&serde_json::Value], data_type: &DataType) -> ArrayRef {
+    match data_type {
+        DataType::Int64 => return Arc::new(values.iter().map(|value| value.as_i64()).collect::<Int64Array>()),
+        DataType::Float64 => return Arc::new(values.iter().map(|value| value.as_f64()).collect::<Float64Array>()),
+        DataType::Boolean => return Arc::new(values.iter().map(|value| value.as_bool()).collect::<BooleanArray>()),
+        _ => return Arc::new(values.iter().map(|value| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(text) => Some(text.clone()),
+            other => Some(other.to_string()),
+        }).collect::<StringArray>()),
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+impl Table {
+    pub fn to_html(&self) -> String {
+        let mut html = "<table>\n<thead><tr>".to_string();
+        for column in &self.columns {
+            html += &format!("<th>{}</th>", escape_html(column));
+        }
+        html += "</tr></thead>\n<tbody>\n";
+        for row in &self.rows {
+            html += "<tr>";
+            for value in row {
+                html += &format!("<td>{}</td>", escape_html(&cell_text(value)));
+            }
+            html += "</tr>\n";
+        }
+        html += "</tbody>\n</table>\n";
+        return html;
+    }
+
+    fn record_batch(&self) -> RecordBatch {
+        let mut fields = Vec::with_capacity(self.columns.len());
+        let mut arrays = Vec::with_capacity(self.columns.len());
+        for (index, column) in self.columns.iter().enumerate() {
+            let values = self.rows.iter().map(|row| &row[index]).collect::<Vec<_>>();
+            let data_type = column_type(&values);
+            arrays.push(column_array(&values, &data_type));
+            fields.push(Field::new(column, data_type, true));
+        }
+        return RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap();
+    }
+
+    pub fn write(&self, format: TableFormat, writer: &mut (dyn Write + Send)) -> std::io::Result<()> {
+        match format {
+            TableFormat::Csv | TableFormat::Tsv => {
+                let delimiter = if format == TableFormat::Csv { ',' } else { '\t' };
+                writeln!(writer, "{}", self.columns.join(&delimiter.to_string()))?;
+                for row in &self.rows {
+                    let cells = row.iter().map(|value| delimited_cell(value, delimiter)).collect::<Vec<_>>();
+                    writeln!(writer, "{}", cells.join(&delimiter.to_string()))?;
+                }
+            },
+            TableFormat::Json => {
+                let objects = self.rows.iter().map(|row| JsonRow { columns: &self.columns, values: row }).collect::<Vec<_>>();
+                serde_json::to_writer_pretty(&mut *writer, &objects)?;
+                writeln!(writer)?;
+            },
+            TableFormat::Parquet => {
+                let batch = self.record_batch();
+                let mut parquet_writer = ArrowWriter::try_new(writer, batch.schema(), None).map_err(std::io::Error::other)?;
+                parquet_writer.write(&batch).map_err(std::io::Error::other)?;
+                parquet_writer.close().map_err(std::io::Error::other)?;
+            },
+            TableFormat::Arrow => {
+                let batch = self.record_batch();
+                let mut arrow_writer = arrow::ipc::writer::FileWriter::try_new(writer, &batch.schema()).map_err(std::io::Error::other)?;
+                arrow_writer.write(&batch).map_err(std::io::Error::other)?;
+                arrow_writer.finish().map_err(std::io::Error::other)?;
+            },
+        }
+        return Ok(());
+    }
+}
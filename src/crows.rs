@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub crow_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub awareness_probability: Real, // AP, that the followed crow notices and leads its follower to a random place. 0.1 in the paper
+    pub flight_length: Real, // fl, how far past the hiding place a crow may fly. 2 in the paper
+    pub parallel: bool, // Moves and evaluates the crows on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.crow_count < 2 {
+            return Err("There must be at least two crows, to follow each other");
+        }
+        if !(0.0..=1.0).contains(&self.awareness_probability) {
+            return Err("Awareness probability must be between 0 and 1");
+        }
+        if self.flight_length.is_nan() || self.flight_length <= 0.0 {
+            return Err("Flight length must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Crow<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    hiding_place: VectorN<Real, N>, // The best position the crow has been to, its memory
+    hiding_place_value: Real,
+    random_source: RngType, // Seeded from the population seed and the crow's index, so crows can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Crow<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole flock at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, hiding_place: position, hiding_place_value: Real::INFINITY, random_source };
+    }
+
+    // Follows a random other crow towards its hiding place, unless that crow notices and the follower ends up anywhere
+    fn move_crow<F>(&mut self, parameters: &Parameters<N, F>, index: usize, hiding_places: &[VectorN<Real, N>]) {
+        let mut followed = self.random_source.gen_range(0..hiding_places.len() - 1);
+        if followed >= index {
+            followed += 1;
+        }
+        if self.random_source.gen::<Real>() >= parameters.awareness_probability {
+            let step = self.random_source.gen::<Real>() * parameters.flight_length;
+            self.position += (hiding_places[followed] - self.position) * step;
+            self.position.clamp(parameters.bounds);
+        } else {
+            self.position = VectorN::random_uniform(parameters.bounds, &mut self.random_source);
+        }
+    }
+
+    // The crow stays where it flew and remembers the place if it's better than its hiding place
+    fn take_value(&mut self, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.hiding_place_value) {
+            self.hiding_place = self.position;
+            self.hiding_place_value = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    flock: Vec<Crow<N, RngType>>,
+    pub best_solution: VectorN<Real, N>, // The best hiding place
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(crow_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            crow_count, function, bounds,
+            awareness_probability: 0.1,
+            flight_length: 2.0,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            flock: Vec::with_capacity(parameters.crow_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.crow_count),
+            values: Vec::with_capacity(parameters.crow_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_flock(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.flock.iter().map(|crow| crow.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // Against the hiding places before the move, so every crow sees the same memories
+    fn move_flock(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.flock.iter().map(|crow| crow.hiding_place));
+        let parameters = &*self.parameters;
+        let hiding_places = &self.positions;
+        let move_crow = |(index, crow): (usize, &mut Crow<N, RngType>)| crow.move_crow(parameters, index, hiding_places);
+        if parameters.parallel {
+            self.flock.par_iter_mut().enumerate().for_each(move_crow);
+        } else {
+            self.flock.iter_mut().enumerate().for_each(move_crow);
+        }
+    }
+
+    // The values of the crows, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.flock.len();
+        for (crow, &value) in self.flock.iter_mut().zip(values) {
+            crow.take_value(value);
+        }
+        // Takes the first of equally good hiding places
+        let best_crow = self.flock.iter().min_by_key(|crow| Fitness::minimize(crow.hiding_place_value)).unwrap();
+        if Fitness::minimize(best_crow.hiding_place_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_crow.hiding_place_value;
+            self.best_solution = best_crow.hiding_place;
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.flock.iter().map(|crow| crow.position), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.move_flock();
+        self.evaluate_flock();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_flock();
+        positions.extend(self.flock.iter().map(|crow| crow.position));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.flock.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.crow_count {
+            self.flock.push(Crow::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_flock();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.flock.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.flock.iter().map(|crow| crow.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn memory_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(100);
+        assert_eq!(world.evaluation_count(), 20 + 20 * 100);
+        assert!(world.best_solution_value() < initial_best);
+        // The best hiding place is remembered by a crow, even if no crow is there any more
+        assert!(world.flock.iter().any(|crow| crow.hiding_place_value == world.best_solution_value()));
+    }
+}
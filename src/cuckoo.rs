@@ -0,0 +1,291 @@
+use std::sync::Arc;
+
+use rand::{distributions::{Bernoulli, Distribution, Uniform}, Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::{self, Real}, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub nest_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub abandon_probability: Real, // pa, the chance of every coordinate of a nest to be rebuilt in an iteration
+    pub step_scale: Real, // alpha, of the Lévy flights
+    pub levy_exponent: Real, // beta, the stability index of the Lévy flights, in (0, 2]
+    pub parallel: bool, // Moves and evaluates the cuckoos on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.nest_count == 0 {
+            return Err("There must be at least one nest");
+        }
+        if !(0.0..=1.0).contains(&self.abandon_probability) {
+            return Err("Abandon probability must be between 0 and 1");
+        }
+        if self.step_scale.is_nan() || self.step_scale <= 0.0 {
+            return Err("Step scale must be positive");
+        }
+        if !(self.levy_exponent > 0.0 && self.levy_exponent <= 2.0) {
+            return Err("Lévy exponent must be in (0, 2]");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// Built once per world instead of on every draw
+#[derive(Clone, Debug)]
+struct Distributions {
+    abandon: Bernoulli,
+    partner: Uniform<usize>, // Index into the nests before the abandonment
+}
+
+impl Distributions {
+    fn new<const N: usize, F>(parameters: &Parameters<N, F>) -> Self {
+        return Self {
+            abandon: Bernoulli::new(real::to_f64(parameters.abandon_probability)).unwrap(),
+            partner: Uniform::new(0, parameters.nest_count),
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Nest<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    candidate: VectorN<Real, N>, // The egg laid in this iteration, kept if it's better
+    random_source: RngType, // Seeded from the population seed and the nest's index, so nests can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Nest<N, RngType> {
+    // Not evaluated yet, the world evaluates all nests at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, candidate: position, random_source };
+    }
+
+    // A Lévy flight scaled by the distance to the best nest, so the best nest stays where it is
+    fn lay_egg<F>(&mut self, parameters: &Parameters<N, F>, best_position: VectorN<Real, N>) {
+        let step = VectorN::random_levy(parameters.levy_exponent, parameters.step_scale, &mut self.random_source);
+        self.candidate = self.position + step * (self.position - best_position);
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // Every coordinate moves with the abandon probability, by a random fraction of the difference of two random nests
+    fn rebuild<F>(&mut self, parameters: &Parameters<N, F>, distributions: &Distributions, previous_positions: &[VectorN<Real, N>]) {
+        let first_position = previous_positions[distributions.partner.sample(&mut self.random_source)];
+        let second_position = previous_positions[distributions.partner.sample(&mut self.random_source)];
+        self.candidate = self.position;
+        for dimension in 0..N {
+            if distributions.abandon.sample(&mut self.random_source) {
+                self.candidate[dimension] += self.random_source.gen::<Real>() * (first_position[dimension] - second_position[dimension]);
+            }
+        }
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // Keeps the candidate if it's better, the first of equally good positions stays
+    fn take_candidate(&mut self, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.function_value) {
+            self.position = self.candidate;
+            self.function_value = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    nests: Vec<Nest<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    distributions: Distributions,
+    previous_positions: Vec<VectorN<Real, N>>,
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(nest_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            nest_count, function, bounds,
+            abandon_probability: 0.25,
+            step_scale: 0.01,
+            levy_exponent: 1.5,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            nests: Vec::with_capacity(parameters.nest_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            distributions: Distributions::new(&parameters),
+            previous_positions: Vec::with_capacity(parameters.nest_count),
+            positions: Vec::with_capacity(parameters.nest_count),
+            values: Vec::with_capacity(parameters.nest_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // The candidates of all nests into self.values, keeping the better ones
+    fn evaluate_candidates(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.nests.iter().map(|nest| nest.candidate));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+        self.evaluation_count += self.nests.len();
+        for (nest, &value) in self.nests.iter_mut().zip(&self.values) {
+            nest.take_candidate(value);
+        }
+    }
+
+    fn lay_eggs(&mut self) {
+        let parameters = &*self.parameters;
+        let best_position = self.best_solution;
+        let lay_egg = |nest: &mut Nest<N, RngType>| nest.lay_egg(parameters, best_position);
+        if parameters.parallel {
+            self.nests.par_iter_mut().for_each(lay_egg);
+        } else {
+            self.nests.iter_mut().for_each(lay_egg);
+        }
+    }
+
+    fn rebuild_nests(&mut self) {
+        self.previous_positions.clear();
+        self.previous_positions.extend(self.nests.iter().map(|nest| nest.position));
+        let parameters = &*self.parameters;
+        let distributions = &self.distributions;
+        let previous_positions = &self.previous_positions;
+        let rebuild = |nest: &mut Nest<N, RngType>| nest.rebuild(parameters, distributions, previous_positions);
+        if parameters.parallel {
+            self.nests.par_iter_mut().for_each(rebuild);
+        } else {
+            self.nests.iter_mut().for_each(rebuild);
+        }
+    }
+
+    // Takes the first of equally good nests
+    fn update_best_known_solution(&mut self) {
+        let best_nest = self.nests.iter().min_by_key(|nest| Fitness::minimize(nest.function_value)).unwrap();
+        if Fitness::minimize(best_nest.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_nest.function_value;
+            self.best_solution = best_nest.position;
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // Lévy flights, then the abandonment, each evaluating every nest
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.lay_eggs();
+        self.evaluate_candidates();
+        self.rebuild_nests();
+        self.evaluate_candidates();
+        self.update_best_known_solution();
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.nests.iter().map(|nest| nest.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.nests.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.nest_count {
+            self.nests.push(Nest::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_candidates();
+        self.update_best_known_solution();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * self.nests.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.nests.iter().map(|nest| nest.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::{Optimizer, RunLength}};
+
+    use super::WorldState;
+
+    #[test]
+    fn nests_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        // Two evaluations of every nest per iteration
+        world.run(RunLength::Evaluations(4000), None);
+        assert_eq!((world.iteration(), world.evaluation_count()), (99, 20 + 99 * 40));
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,186 @@
+use std::{collections::VecDeque, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, thread::JoinHandle, time::{Duration, Instant}};
+
+use ratatui::{crossterm::event::{self, Event, KeyCode}, layout::{Constraint, Layout}, style::{Color, Style}, text::Line, widgets::{Block, Gauge, Paragraph, Sparkline}, Frame};
+use serde::Serialize;
+
+const SPARKLINE_LENGTH: usize = 100;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+struct FunctionProgress {
+    name: String,
+    total_runs: usize,
+    completed_runs: usize,
+    evaluations: usize,
+    best_value: f64,
+    recent_results: VecDeque<f64>, // Final values of the last runs, newest at the back
+    started: Option<Instant>,
+    last_update: Option<Instant>,
+}
+
+// Shared between the worker threads, which report finished runs, and the thread drawing the terminal UI
+#[derive(Clone)]
+pub struct Dashboard {
+    functions: Arc<Mutex<Vec<FunctionProgress>>>,
+    finished: Arc<AtomicBool>,
+}
+
+// The state of a function at one point, for front ends other than the terminal
+#[derive(Clone, Debug, Serialize)]
+pub struct ProgressSnapshot {
+    pub completed_runs: usize,
+    pub total_runs: usize,
+    pub evaluations: usize,
+    pub evaluations_per_second: f64, // Between the start of the first run and the end of the last finished one
+    pub best_value: f64, // Infinite, serialized as null, until the first run finishes
+}
+
+impl FunctionProgress {
+    fn snapshot(&self) -> ProgressSnapshot {
+        let evaluations_per_second = match (self.started, self.last_update) {
+            (Some(started), Some(last_update)) => self.evaluations as f64 / (last_update - started).as_secs_f64().max(f64::EPSILON),
+            _ => 0.0,
+        };
+        return ProgressSnapshot {
+            completed_runs: self.completed_runs,
+            total_runs: self.total_runs,
+            evaluations: self.evaluations,
+            evaluations_per_second,
+            best_value: self.best_value,
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct ProgressReporter {
+    functions: Arc<Mutex<Vec<FunctionProgress>>>,
+    function_index: usize,
+}
+
+impl ProgressReporter {
+    // Called by every run, the runs of a function may start at any point of the sweep
+    pub fn start(&self) {
+        let mut functions = self.functions.lock().unwrap();
+        let progress = &mut functions[self.function_index];
+        progress.started.get_or_insert_with(Instant::now);
+    }
+
+    pub fn report_run(&self, result: f64, evaluations: usize) {
+        let mut functions = self.functions.lock().unwrap();
+        let progress = &mut functions[self.function_index];
+        progress.last_update = Some(Instant::now());
+        progress.completed_runs += 1;
+        progress.evaluations += evaluations;
+        if result < progress.best_value {
+            progress.best_value = result;
+        }
+        if progress.recent_results.len() == SPARKLINE_LENGTH {
+            progress.recent_results.pop_front();
+        }
+        progress.recent_results.push_back(result);
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        return self.functions.lock().unwrap()[self.function_index].snapshot();
+    }
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        return Self {
+            functions: Arc::new(Mutex::new(Vec::new())),
+            finished: Arc::new(AtomicBool::new(false)),
+        };
+    }
+
+    pub fn add_function(&self, name: &str, total_runs: usize) -> ProgressReporter {
+        let mut functions = self.functions.lock().unwrap();
+        functions.push(FunctionProgress {
+            name: name.to_string(),
+            total_runs,
+            completed_runs: 0,
+            evaluations: 0,
+            best_value: f64::INFINITY,
+            recent_results: VecDeque::with_capacity(SPARKLINE_LENGTH),
+            started: None,
+            last_update: None,
+        });
+        return ProgressReporter {
+            functions: self.functions.clone(),
+            function_index: functions.len() - 1,
+        };
+    }
+
+    // Every function added so far with its name, in the order they were added
+    pub fn snapshots(&self) -> Vec<(String, ProgressSnapshot)> {
+        return self.functions.lock().unwrap().iter().map(|progress| (progress.name.clone(), progress.snapshot())).collect();
+    }
+
+    // Takes over the terminal until finish() is called. Pressing q aborts the whole sweep
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let dashboard = self.clone();
+        return std::thread::spawn(move || {
+            let mut terminal = ratatui::init();
+            let sweep_start = Instant::now();
+            while !dashboard.finished.load(Ordering::Relaxed) {
+                terminal.draw(|frame| dashboard.draw(frame, sweep_start)).unwrap();
+                if event::poll(REFRESH_INTERVAL).unwrap() {
+                    if let Event::Key(key) = event::read().unwrap() {
+                        if key.code == KeyCode::Char('q') {
+                            ratatui::restore();
+                            std::process::exit(130);
+                        }
+                    }
+                }
+            }
+            ratatui::restore();
+        });
+    }
+
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    fn draw(&self, frame: &mut Frame, sweep_start: Instant) {
+        let functions = self.functions.lock().unwrap();
+        let total_evaluations = functions.iter().map(|progress| progress.evaluations).sum::<usize>();
+        let elapsed = sweep_start.elapsed().as_secs_f64();
+
+        let mut constraints = vec![Constraint::Length(1)];
+        constraints.extend(functions.iter().map(|_| Constraint::Length(3)));
+        constraints.push(Constraint::Min(0));
+        let rows = Layout::vertical(constraints).split(frame.area());
+
+        frame.render_widget(Line::from(format!(
+            "Elapsed {:.1}s, {:.0} evaluations/s overall. Press q to abort.",
+            elapsed, total_evaluations as f64 / elapsed.max(f64::EPSILON)
+        )), rows[0]);
+
+        for (progress, &area) in functions.iter().zip(&rows[1..]) {
+            let block = Block::bordered().title(progress.name.as_str());
+            let columns = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(35), Constraint::Percentage(35)]).split(block.inner(area));
+            frame.render_widget(block, area);
+
+            let ratio = progress.completed_runs as f64 / progress.total_runs.max(1) as f64;
+            frame.render_widget(Gauge::default()
+                .gauge_style(Style::default().fg(Color::Green))
+                .ratio(ratio.min(1.0))
+                .label(format!("{}/{} runs", progress.completed_runs, progress.total_runs)), columns[0]);
+
+            let runs_per_second = match (progress.started, progress.last_update) {
+                (Some(started), Some(last_update)) => progress.completed_runs as f64 / (last_update - started).as_secs_f64().max(f64::EPSILON),
+                _ => 0.0,
+            };
+            frame.render_widget(Paragraph::new(format!(" best {:.6e}, {:.1} runs/s", progress.best_value, runs_per_second)), columns[1]);
+
+            frame.render_widget(Sparkline::default().data(scale_for_sparkline(&progress.recent_results)).style(Style::default().fg(Color::Cyan)), columns[2]);
+        }
+    }
+}
+
+// Sparklines only take integers, so results are mapped onto 0..=100 relative to the shown window
+fn scale_for_sparkline(results: &VecDeque<f64>) -> Vec<u64> {
+    let min = results.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = results.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(f64::EPSILON);
+    return results.iter().map(|result| (((result - min) / range) * 100.0) as u64).collect();
+}
@@ -0,0 +1,226 @@
+use std::{collections::VecDeque, io::{BufRead, BufReader, Write}, net::{TcpListener, TcpStream}, sync::{Arc, Condvar, Mutex}};
+
+use clap::{error::ErrorKind, CommandFactory, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+use crate::{apply_overrides, check_eval_budget, output::{BatchSummary, SummaryPrinter}, parse_algorithm_arguments, pool::WorkerPool, queue_batch, target_value_for, BatchOutputs, BatchRunData, Config};
+
+// The protocol is one JSON object per line: the coordinator sends a RunSpecification, the worker answers with a BatchRunData
+
+// Full command line of a batch run over a single function, parsed by the worker exactly like its own arguments
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct RunSpecification {
+    arguments: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct Job {
+    function_index: usize,
+    specification: RunSpecification,
+}
+
+struct JobQueue {
+    pending: VecDeque<Job>,
+    unfinished_count: usize, // Includes jobs currently handed out to workers
+    results: Vec<Option<BatchRunData>>,
+}
+
+pub fn serve(config: &Config, listen_address: &str, chunk_size: usize, algorithm_arguments: &[String]) {
+    let tries = config.try_count.unwrap_or_else(|| panic!("serve requires --try-count"));
+
+    let mut pending = VecDeque::new();
+    for (function_index, function_name) in config.functions.iter().enumerate() {
+        let mut remaining_tries = tries;
+        while remaining_tries > 0 {
+            let runs = remaining_tries.min(chunk_size);
+            remaining_tries -= runs;
+            let arguments = worker_arguments(config, function_name, runs, algorithm_arguments);
+            pending.push_back(Job { function_index, specification: RunSpecification { arguments } });
+        }
+    }
+    // Catch mistakes in the algorithm flags here rather than on every worker
+    let command = parse_algorithm_arguments(algorithm_arguments);
+    for function_name in &config.functions {
+        let function_command = apply_overrides(&command, &config.overrides, function_name);
+        check_eval_budget(&function_command, function_name, config.eval_budget, config.world_options()).unwrap_or_else(|message| Config::command().error(ErrorKind::InvalidValue, message).exit());
+    }
+
+    let queue = Arc::new((Mutex::new(JobQueue {
+        unfinished_count: pending.len(),
+        pending,
+        results: vec![None; config.functions.len()],
+    }), Condvar::new()));
+
+    let listener = TcpListener::bind(listen_address).unwrap_or_else(|error| panic!("Could not listen on {listen_address}: {error}"));
+    eprintln!("Waiting for workers on {}", listener.local_addr().unwrap());
+    let accept_queue = queue.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let queue = accept_queue.clone();
+            std::thread::spawn(move || handle_worker(stream, &queue));
+        }
+    });
+
+    let (lock, condvar) = &*queue;
+    let mut state = condvar.wait_while(lock.lock().unwrap(), |state| state.unfinished_count > 0).unwrap();
+    let mut printer = SummaryPrinter::new(config.output_format, false);
+    for (function_name, result) in config.functions.iter().zip(state.results.iter_mut()) {
+        let result = result.take().unwrap();
+        let command = apply_overrides(&command, &config.overrides, function_name);
+        printer.add(BatchSummary::new(function_name, &command, config.dimensions, config.eval_budget, target_value_for(&config.target_values, function_name), result));
+    }
+    printer.finish();
+}
+
+// The command line of a chunk of runs, as the worker parses it
+fn worker_arguments(config: &Config, function_name: &str, runs: usize, algorithm_arguments: &[String]) -> Vec<String> {
+    let mut arguments = vec![
+        env!("CARGO_BIN_NAME").to_string(),
+        format!("--functions={function_name}"),
+        format!("--try-count={runs}"),
+    ];
+    if !config.target_values.is_empty() {
+        arguments.push(format!("--target-value={}", config.target_values.join(",")));
+    }
+    // One argument per entry, the worker would split a value with commas in it again
+    for entry in &config.overrides {
+        arguments.push(format!("--override={entry}"));
+    }
+    if let Some(eval_budget) = config.eval_budget {
+        arguments.push(format!("--eval-budget={eval_budget}"));
+    }
+    if config.output_trace {
+        arguments.push("--output-trace".to_string());
+    }
+    if config.parallel_agents {
+        arguments.push("--parallel-agents".to_string());
+    }
+    #[cfg(feature = "gpu")]
+    if config.gpu {
+        arguments.push("--gpu".to_string());
+    }
+    arguments.push(format!("--dimensions={}", config.dimensions));
+    arguments.push(format!("--lockstep={}", config.lockstep));
+    arguments.push(format!("--rng={}", config.rng.to_possible_value().unwrap().get_name()));
+    if let Some((lower, upper)) = config.remote.bounds {
+        arguments.push(format!("--objective-bounds={lower},{upper}"));
+    }
+    if config.remote.batch {
+        arguments.push("--objective-batch".to_string());
+    }
+    arguments.push(format!("--objective-concurrency={}", config.remote.concurrency));
+    arguments.push(format!("--objective-timeout={}", config.remote.timeout));
+    arguments.push(format!("--objective-retries={}", config.remote.retries));
+    arguments.extend(algorithm_arguments.iter().cloned());
+    return arguments;
+}
+
+fn handle_worker(stream: TcpStream, queue: &(Mutex<JobQueue>, Condvar)) {
+    let (lock, condvar) = queue;
+    let worker_address = stream.peer_addr().map(|address| address.to_string()).unwrap_or_default();
+    eprintln!("Worker {} connected", worker_address);
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    loop {
+        let job = {
+            let mut state = condvar.wait_while(lock.lock().unwrap(), |state| state.pending.is_empty() && state.unfinished_count > 0).unwrap();
+            match state.pending.pop_front() {
+                Some(job) => job,
+                None => return, // Everything is done
+            }
+        };
+
+        match exchange_job(&mut reader, &mut writer, &job.specification) {
+            Ok(result) => {
+                let mut state = lock.lock().unwrap();
+                let function_result = &mut state.results[job.function_index];
+                match function_result {
+                    Some(function_result) => *function_result += result,
+                    None => *function_result = Some(result),
+                }
+                state.unfinished_count -= 1;
+                condvar.notify_all();
+            },
+            Err(error) => {
+                // Give the job to someone else
+                eprintln!("Worker {} lost: {}", worker_address, error);
+                lock.lock().unwrap().pending.push_back(job);
+                condvar.notify_all();
+                return;
+            },
+        }
+    }
+}
+
+fn exchange_job(reader: &mut BufReader<TcpStream>, writer: &mut TcpStream, specification: &RunSpecification) -> std::io::Result<BatchRunData> {
+    let mut message = serde_json::to_string(specification)?;
+    message.push('\n');
+    writer.write_all(message.as_bytes())?;
+
+    let mut response = String::new();
+    if reader.read_line(&mut response)? == 0 {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    return Ok(serde_json::from_str(&response)?);
+}
+
+// Runs whatever the coordinator sends until it closes the connection
+pub fn run_worker(coordinator_address: &str, thread_count: usize) {
+    let stream = TcpStream::connect(coordinator_address).unwrap_or_else(|error| panic!("Could not connect to {coordinator_address}: {error}"));
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let pool = WorkerPool::new(thread_count);
+    let mut message = String::new();
+    loop {
+        message.clear();
+        if reader.read_line(&mut message).unwrap() == 0 {
+            return;
+        }
+        let specification: RunSpecification = serde_json::from_str(&message).unwrap();
+        let config = Config::try_parse_arguments(&specification.arguments).unwrap_or_else(|error| panic!("Invalid run specification {:?}: {}", specification.arguments, error));
+        let function_name = &config.functions[0];
+
+        let result = queue_batch(
+            &pool,
+            &apply_overrides(&config.command, &config.overrides, function_name),
+            config.eval_budget,
+            function_name,
+            target_value_for(&config.target_values, function_name),
+            config.try_count.unwrap(),
+            config.world_options(),
+            BatchOutputs { record_traces: config.output_trace, ..BatchOutputs::default() },
+        ).wait();
+
+        let mut response = serde_json::to_string(&result).unwrap();
+        response.push('\n');
+        writer.write_all(response.as_bytes()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Config, OptimizationAlgorithmCommand};
+
+    use super::worker_arguments;
+
+    #[test]
+    fn override_forwarding_test() {
+        let arguments = [
+            "swarm_optimizers", "--functions=ackley,rastrigin", "--try-count=4", "--eval-budget=1000",
+            "--override=ackley:bounds=[-5,5],rastrigin:movement=negated", "--override", r#"ackley:labels={"a":1,"b":"x,y"}"#,
+            "serve", "--listen=127.0.0.1:0", "jaya", "--jaya-count", "5",
+        ];
+        let config = Config::try_parse_arguments(arguments).unwrap();
+        let expected = ["ackley:bounds=[-5,5]", "rastrigin:movement=negated", r#"ackley:labels={"a":1,"b":"x,y"}"#];
+        assert_eq!(config.overrides, expected);
+        let OptimizationAlgorithmCommand::Serve { algorithm_arguments, .. } = &config.command else {
+            panic!("Expected the serve subcommand");
+        };
+        let worker_config = Config::try_parse_arguments(worker_arguments(&config, "ackley", 2, algorithm_arguments)).unwrap();
+        assert_eq!(worker_config.overrides, expected);
+        assert_eq!((worker_config.functions, worker_config.try_count), (vec!["ackley".to_string()], Some(2)));
+    }
+}
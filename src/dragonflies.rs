@@ -0,0 +1,320 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub dragonfly_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    // The mean weights at the start. All but the food weight fall to 0 by the middle of the run, and all but the enemy
+    // weight are scaled by a random factor in [0, 2) on every move, as in the reference implementation
+    pub separation_weight: Real, // s, away from the neighbours
+    pub alignment_weight: Real, // a, along the steps of the neighbours
+    pub cohesion_weight: Real, // c, towards the centre of the neighbours
+    pub food_weight: Real, // f, towards the best position found so far
+    pub enemy_weight: Real, // e, away from the worst position found so far
+    pub parallel: bool, // Moves and evaluates the dragonflies on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.dragonfly_count == 0 {
+            return Err("There must be at least one dragonfly");
+        }
+        let weights = [self.separation_weight, self.alignment_weight, self.cohesion_weight, self.food_weight, self.enemy_weight];
+        if weights.iter().any(|weight| weight.is_nan() || *weight < 0.0) {
+            return Err("Weights can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// Where the whole swarm was before the move, read by every dragonfly
+struct Swarm<'a, const N: usize> {
+    positions: &'a [VectorN<Real, N>],
+    steps: &'a [VectorN<Real, N>],
+    food: VectorN<Real, N>,
+    enemy: VectorN<Real, N>,
+    radius: Real,
+    inertia: Real,
+    decay: Real, // Of the separation, alignment, cohesion and enemy weights
+}
+
+#[derive(Clone, Debug)]
+pub struct Dragonfly<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    step: VectorN<Real, N>, // ΔX, the last move
+    random_source: RngType, // Seeded from the population seed and the dragonfly's index, so dragonflies can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Dragonfly<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole swarm at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, step: VectorN::default(), random_source };
+    }
+
+    // With neighbours, by the weighted separation, alignment, cohesion, food attraction and enemy distraction plus the
+    // inertia of the last step, limited to a tenth of the width of the bounds. Alone, by a Lévy flight
+    fn move_dragonfly<F>(&mut self, parameters: &Parameters<N, F>, index: usize, swarm: &Swarm<N>) {
+        let mut separation = VectorN::default();
+        let mut alignment = VectorN::default();
+        let mut cohesion = VectorN::default();
+        let mut neighbour_count = 0;
+        for (other, (&position, &step)) in swarm.positions.iter().zip(swarm.steps).enumerate() {
+            if other != index && (position - self.position).norm_l2() <= swarm.radius {
+                separation -= self.position - position;
+                alignment += step;
+                cohesion += position;
+                neighbour_count += 1;
+            }
+        }
+        if neighbour_count == 0 {
+            let flight = VectorN::random_levy(1.5, 0.01, &mut self.random_source);
+            self.position += flight * self.position;
+            self.step = VectorN::default();
+        } else {
+            alignment /= neighbour_count as Real;
+            cohesion = cohesion / neighbour_count as Real - self.position;
+            let food = swarm.food - self.position;
+            let enemy = swarm.enemy + self.position;
+            let mut weight = |weight: Real| 2.0 * self.random_source.gen::<Real>() * weight;
+            let separation_weight = weight(parameters.separation_weight * swarm.decay);
+            let alignment_weight = weight(parameters.alignment_weight * swarm.decay);
+            let cohesion_weight = weight(parameters.cohesion_weight * swarm.decay);
+            let food_weight = weight(parameters.food_weight);
+            self.step = separation * separation_weight + alignment * alignment_weight + cohesion * cohesion_weight + food * food_weight
+                + enemy * (parameters.enemy_weight * swarm.decay) + self.step * swarm.inertia;
+            let step_limit = (parameters.bounds.1 - parameters.bounds.0) / 10.0;
+            self.step.clamp((-step_limit, step_limit));
+            self.position += self.step;
+        }
+        self.position.clamp(parameters.bounds);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    swarm: Vec<Dragonfly<N, RngType>>,
+    pub best_solution: VectorN<Real, N>, // The food
+    pub best_solution_value: Real,
+    enemy: VectorN<Real, N>, // The worst position found so far
+    enemy_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    steps: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(dragonfly_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            dragonfly_count, function, bounds,
+            separation_weight: 0.1,
+            alignment_weight: 0.1,
+            cohesion_weight: 0.1,
+            food_weight: 1.0,
+            enemy_weight: 0.1,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            swarm: Vec::with_capacity(parameters.dragonfly_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            enemy: VectorN::default(),
+            enemy_value: Real::NEG_INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.dragonfly_count),
+            steps: Vec::with_capacity(parameters.dragonfly_count),
+            values: Vec::with_capacity(parameters.dragonfly_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // The worst position found so far and its value
+    pub fn enemy(&self) -> (VectorN<Real, N>, Real) {
+        return (self.enemy, self.enemy_value);
+    }
+
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.swarm.iter().map(|dragonfly| dragonfly.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // The neighbourhood grows from a quarter of the width of the bounds per coordinate to over the whole space, and the
+    // inertia falls from 0.9 to 0.4, so the swarm goes from small static groups to one migrating swarm
+    fn move_swarm(&mut self, iteration_count: usize) {
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let width = self.parameters.bounds.1 - self.parameters.bounds.0;
+        self.positions.clear();
+        self.positions.extend(self.swarm.iter().map(|dragonfly| dragonfly.position));
+        self.steps.clear();
+        self.steps.extend(self.swarm.iter().map(|dragonfly| dragonfly.step));
+        let parameters = &*self.parameters;
+        let swarm = Swarm {
+            positions: &self.positions,
+            steps: &self.steps,
+            food: self.best_solution,
+            enemy: self.enemy,
+            radius: width * (0.25 + 2.0 * progress) * (N as Real).sqrt(),
+            inertia: 0.9 - 0.5 * progress,
+            decay: (1.0 - 2.0 * progress).max(0.0),
+        };
+        let move_dragonfly = |(index, dragonfly): (usize, &mut Dragonfly<N, RngType>)| dragonfly.move_dragonfly(parameters, index, &swarm);
+        if parameters.parallel {
+            self.swarm.par_iter_mut().enumerate().for_each(move_dragonfly);
+        } else {
+            self.swarm.iter_mut().enumerate().for_each(move_dragonfly);
+        }
+    }
+
+    // The values of the swarm, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.swarm.len();
+        // Takes the first of equally good, or equally bad, dragonflies
+        let (best_index, &best_value) = values.iter().enumerate().min_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap();
+        if Fitness::minimize(best_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_value;
+            self.best_solution = self.swarm[best_index].position;
+        }
+        let (worst_index, &worst_value) = values.iter().enumerate().rev().filter(|(_, value)| !value.is_nan()).max_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap_or((0, &Real::NEG_INFINITY));
+        if worst_value > self.enemy_value {
+            self.enemy_value = worst_value;
+            self.enemy = self.swarm[worst_index].position;
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.swarm.iter().map(|dragonfly| dragonfly.position), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_swarm(iteration_count);
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_swarm(iteration_count);
+        positions.extend(self.swarm.iter().map(|dragonfly| dragonfly.position));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.enemy_value = Real::NEG_INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.swarm.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.dragonfly_count {
+            self.swarm.push(Dragonfly::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.swarm.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.swarm.iter().map(|dragonfly| dragonfly.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn swarm_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(30, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        let initial_enemy = world.enemy().1;
+        world.do_all_iterations(200);
+        assert!(world.best_solution_value() < initial_best);
+        assert!(world.enemy().1 >= initial_enemy);
+        assert!(world.enemy().1 >= world.best_solution_value());
+    }
+}
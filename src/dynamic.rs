@@ -0,0 +1,1540 @@
+// Optimizers whose dimensions, objective and parameters are only known at run time, shared by the language bindings. The
+// dimensions are limited to optimizer::DIMENSIONS, the parameters are named like the command line flags
+#![cfg_attr(not(all(feature = "ffi", feature = "wasm")), allow(dead_code))] // Each binding uses only part of it
+
+use std::{panic::{self, AssertUnwindSafe}, sync::Arc};
+
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{abc, aco, annealing, bacteria, bats, butterflies, cem, crows, cuckoo, dragonflies, flower_pollination, fss, functions::{Function, Functions}, grasshoppers, grey_wolf, gsa, harris_hawks, iwo, jaya, krill, moth_flame, nelder_mead, optimizer::{FromParameters, InitialVelocity, Movement, Optimizer, RunLength, DIMENSIONS}, pattern_search, random_search, real::{self, Real}, salps, sine_cosine, tlbo, umda, vector::VectorN};
+
+pub(crate) type Callable = Arc<dyn Fn(&[Real]) -> Real + Send + Sync>;
+
+#[derive(Clone)]
+pub(crate) enum Objective<const N: usize> {
+    Builtin(Functions<N>), // Keeps the batch evaluation of the benchmarks, including the GPU
+    Callable(Callable),
+}
+
+impl<const N: usize> Function<N> for Objective<N> {
+    fn evaluate(&self, input: VectorN<Real, N>) -> Real {
+        match self {
+            Self::Builtin(function) => return function.calculate(input),
+            Self::Callable(callable) => return callable(&input.coordinates),
+        }
+    }
+
+    fn evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        match self {
+            Self::Builtin(function) => function.evaluate_batch_into(inputs, values),
+            Self::Callable(callable) => {
+                values.clear();
+                values.extend(inputs.iter().map(|input| callable(&input.coordinates)));
+            },
+        }
+    }
+}
+
+// The objective before the dimensions are known
+#[derive(Clone)]
+pub(crate) enum ObjectiveSpecification {
+    Builtin(String),
+    Callable(Callable),
+}
+
+impl ObjectiveSpecification {
+    pub(crate) fn builtin(name: &str) -> Result<Self, String> {
+        if Functions::<1>::from_name(name).is_none() {
+            return Err(format!("Nonexistent function passed: `{name}`"));
+        }
+        return Ok(Self::Builtin(name.to_string()));
+    }
+
+    fn for_dimensions<const N: usize>(&self) -> Objective<N> {
+        match self {
+            Self::Builtin(name) => return Objective::Builtin(Functions::make_from_name(name)),
+            Self::Callable(callable) => return Objective::Callable(callable.clone()),
+        }
+    }
+
+    // The benchmarks have their own search box, callables have to be given one
+    pub(crate) fn bounds(&self, bounds: Option<(f64, f64)>) -> Result<(Real, Real), String> {
+        match (self, bounds) {
+            (_, Some(bounds)) => return Ok((real::from_f64(bounds.0), real::from_f64(bounds.1))),
+            (Self::Builtin(name), None) => return Ok(Functions::<1>::make_from_name(name).get_bounds()),
+            (Self::Callable(_), None) => return Err("bounds are required for a callable objective".to_string()),
+        }
+    }
+}
+
+// An optimizer of any of the compiled dimensions
+pub(crate) trait DynamicWorld: Send + Sync {
+    // Calls the observer with the evaluation count and the best value after every iteration
+    fn run(&mut self, length: RunLength, target_value: Option<Real>, observer: &mut dyn FnMut(usize, f64)) -> Option<usize>;
+    fn do_iteration(&mut self, iteration_count: usize);
+    fn population(&self) -> Vec<f64>; // The coordinates of every agent, one after another
+    fn reset(&mut self);
+    fn best_solution(&self) -> Vec<f64>;
+    fn best_solution_value(&self) -> f64;
+    fn evaluation_count(&self) -> usize;
+}
+
+struct SizedWorld<const N: usize, World>(World);
+
+impl<const N: usize, World: Optimizer<N> + Send + Sync> DynamicWorld for SizedWorld<N, World> {
+    fn run(&mut self, length: RunLength, target_value: Option<Real>, observer: &mut dyn FnMut(usize, f64)) -> Option<usize> {
+        return self.0.run_observed(length, target_value, |world| observer(world.evaluation_count(), real::to_f64(world.best_solution_value())));
+    }
+
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.0.do_iteration(iteration_count);
+    }
+
+    fn population(&self) -> Vec<f64> {
+        let mut positions = Vec::new();
+        self.0.population(&mut positions);
+        return positions.iter().flat_map(|position| position.coordinates.map(real::to_f64)).collect();
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn best_solution(&self) -> Vec<f64> {
+        return self.0.best_solution().coordinates.map(real::to_f64).to_vec();
+    }
+
+    fn best_solution_value(&self) -> f64 {
+        return real::to_f64(self.0.best_solution_value());
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.0.evaluation_count();
+    }
+}
+
+// The parameters of an algorithm, building its world once the dimensions are fixed
+pub(crate) trait WorldFactory {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String>;
+}
+
+// Invalid parameters are reported as errors instead of panics. Other panics are caught too where they unwind, wasm32
+// aborts on them
+pub(crate) fn build_world(factory: &impl WorldFactory, objective: &ObjectiveSpecification, dimensions: usize, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+    let build = || match dimensions {
+        2 => return factory.make::<2>(objective.for_dimensions(), bounds, seed),
+        10 => return factory.make::<10>(objective.for_dimensions(), bounds, seed),
+        20 => return factory.make::<20>(objective.for_dimensions(), bounds, seed),
+        30 => return factory.make::<30>(objective.for_dimensions(), bounds, seed),
+        50 => return factory.make::<50>(objective.for_dimensions(), bounds, seed),
+        100 => return factory.make::<100>(objective.for_dimensions(), bounds, seed),
+        _ => return Err(format!("supported dimensions are {DIMENSIONS:?}")),
+    };
+    return panic::catch_unwind(AssertUnwindSafe(build)).unwrap_or_else(|payload| Err(panic_message(payload)));
+}
+
+pub(crate) fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    return "The optimizer panicked".to_string();
+}
+
+fn count(name: &str, value: f64) -> Result<usize, String> {
+    if value < 0.0 || value.fract() != 0.0 {
+        return Err(format!("{name} must be a whole number, got {value}"));
+    }
+    return Ok(value as usize);
+}
+
+// The parameters are set by the names of the command line flags, the defaults are the ones of run_sweep.sh
+#[derive(Clone, Debug)]
+pub(crate) struct BatSettings {
+    pub(crate) bat_count: usize,
+    pub(crate) frequency_bounds: (Real, Real),
+    pub(crate) initial_pulse_rate: Real,
+    pub(crate) pulse_rate_factor: Real,
+    pub(crate) initial_loudness: Real,
+    pub(crate) loudness_cooling_rate: Real,
+    pub(crate) scale_walk_to_bounds: bool,
+    pub(crate) improvement_tolerance: Real,
+}
+
+impl BatSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "bat-count" => self.bat_count = count(name, value)?,
+            "frequency-left-bound" => self.frequency_bounds.0 = real::from_f64(value),
+            "frequency-right-bound" => self.frequency_bounds.1 = real::from_f64(value),
+            "initial-pulse-rate" => self.initial_pulse_rate = real::from_f64(value),
+            "pulse-rate-factor" => self.pulse_rate_factor = real::from_f64(value),
+            "initial-loudness" => self.initial_loudness = real::from_f64(value),
+            "loudness-cooling-rate" => self.loudness_cooling_rate = real::from_f64(value),
+            "scale-walk-to-bounds" => self.scale_walk_to_bounds = value != 0.0,
+            "improvement-tolerance" => self.improvement_tolerance = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of bats: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for BatSettings {
+    fn default() -> Self {
+        return Self {
+            bat_count: 20,
+            frequency_bounds: (0.0, 1.0),
+            initial_pulse_rate: 0.7,
+            pulse_rate_factor: 0.5,
+            initial_loudness: 1.4,
+            loudness_cooling_rate: 0.5,
+            scale_walk_to_bounds: false,
+            improvement_tolerance: 0.0,
+        };
+    }
+}
+
+impl WorldFactory for BatSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = bats::Parameters {
+            bat_count: self.bat_count,
+            function,
+            bounds,
+            frequency_bounds: self.frequency_bounds,
+            initial_pulse_rate: self.initial_pulse_rate,
+            pulse_rate_factor: self.pulse_rate_factor,
+            initial_loudness: self.initial_loudness,
+            loudness_cool_factor: self.loudness_cooling_rate,
+            movement: Movement::Negated,
+            initial_velocity: InitialVelocity::PositiveUnit,
+            improvement_tolerance: self.improvement_tolerance,
+            scale_walk_to_bounds: self.scale_walk_to_bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(bats::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ButterflySettings {
+    pub(crate) butterfly_count: usize,
+    pub(crate) fragrance_multiplier: Real,
+    pub(crate) fragrance_exponent_bounds: (Real, Real),
+    pub(crate) local_search_chance: Real,
+    pub(crate) improvement_tolerance: Real,
+}
+
+impl ButterflySettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "butterfly-count" => self.butterfly_count = count(name, value)?,
+            "fragrance-multiplier" => self.fragrance_multiplier = real::from_f64(value),
+            "fragrance-exponent-left-bound" => self.fragrance_exponent_bounds.0 = real::from_f64(value),
+            "fragrance-exponent-right-bound" => self.fragrance_exponent_bounds.1 = real::from_f64(value),
+            "local-search-chance" => self.local_search_chance = real::from_f64(value),
+            "improvement-tolerance" => self.improvement_tolerance = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of butterflies: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for ButterflySettings {
+    fn default() -> Self {
+        return Self {
+            butterfly_count: 20,
+            fragrance_multiplier: 0.5,
+            fragrance_exponent_bounds: (0.1, 0.3),
+            local_search_chance: 0.5,
+            improvement_tolerance: 0.0,
+        };
+    }
+}
+
+impl WorldFactory for ButterflySettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = butterflies::Parameters {
+            population_size: self.butterfly_count,
+            function,
+            bounds,
+            fragrance_multiplier: self.fragrance_multiplier,
+            fragrance_exponent_bounds: self.fragrance_exponent_bounds,
+            local_search_chance: self.local_search_chance,
+            movement: Movement::Standard,
+            initial_velocity: InitialVelocity::Zero,
+            improvement_tolerance: self.improvement_tolerance,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(butterflies::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct GreyWolfSettings {
+    pub(crate) wolf_count: usize,
+    pub(crate) a_bounds: (Real, Real),
+}
+
+impl GreyWolfSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "wolf-count" => self.wolf_count = count(name, value)?,
+            "initial-a" => self.a_bounds.0 = real::from_f64(value),
+            "final-a" => self.a_bounds.1 = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of grey wolves: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for GreyWolfSettings {
+    fn default() -> Self {
+        return Self {
+            wolf_count: 20,
+            a_bounds: (2.0, 0.0),
+        };
+    }
+}
+
+impl WorldFactory for GreyWolfSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = grey_wolf::Parameters {
+            wolf_count: self.wolf_count,
+            function,
+            bounds,
+            a_bounds: self.a_bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(grey_wolf::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CuckooSettings {
+    pub(crate) nest_count: usize,
+    pub(crate) abandon_probability: Real,
+    pub(crate) step_scale: Real,
+    pub(crate) levy_exponent: Real,
+}
+
+impl CuckooSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "nest-count" => self.nest_count = count(name, value)?,
+            "abandon-probability" => self.abandon_probability = real::from_f64(value),
+            "step-scale" => self.step_scale = real::from_f64(value),
+            "levy-exponent" => self.levy_exponent = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of cuckoos: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for CuckooSettings {
+    fn default() -> Self {
+        return Self {
+            nest_count: 20,
+            abandon_probability: 0.25,
+            step_scale: 0.01,
+            levy_exponent: 1.5,
+        };
+    }
+}
+
+impl WorldFactory for CuckooSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = cuckoo::Parameters {
+            nest_count: self.nest_count,
+            function,
+            bounds,
+            abandon_probability: self.abandon_probability,
+            step_scale: self.step_scale,
+            levy_exponent: self.levy_exponent,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(cuckoo::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct AbcSettings {
+    pub(crate) bee_count: usize,
+    pub(crate) limit: usize,
+}
+
+impl AbcSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "bee-count" => self.bee_count = count(name, value)?,
+            "limit" => self.limit = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of the bee colony: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for AbcSettings {
+    fn default() -> Self {
+        return Self {
+            bee_count: 20,
+            limit: 100,
+        };
+    }
+}
+
+impl WorldFactory for AbcSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = abc::Parameters {
+            bee_count: self.bee_count,
+            function,
+            bounds,
+            limit: self.limit,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(abc::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct AnnealingSettings {
+    pub(crate) initial_temperature: Real,
+    pub(crate) cooling_rate: Real,
+    pub(crate) initial_radius: Real,
+    pub(crate) min_radius: Real,
+    pub(crate) moves_per_iteration: usize,
+}
+
+impl AnnealingSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "initial-temperature" => self.initial_temperature = real::from_f64(value),
+            "cooling-rate" => self.cooling_rate = real::from_f64(value),
+            "initial-radius" => self.initial_radius = real::from_f64(value),
+            "min-radius" => self.min_radius = real::from_f64(value),
+            "moves-per-iteration" => self.moves_per_iteration = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of annealing: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for AnnealingSettings {
+    fn default() -> Self {
+        return Self {
+            initial_temperature: 10.0,
+            cooling_rate: 0.99,
+            initial_radius: 0.1,
+            min_radius: 0.001,
+            moves_per_iteration: 1,
+        };
+    }
+}
+
+impl WorldFactory for AnnealingSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = annealing::Parameters {
+            function,
+            bounds,
+            initial_temperature: self.initial_temperature,
+            cooling_rate: self.cooling_rate,
+            initial_radius: self.initial_radius,
+            min_radius: self.min_radius,
+            moves_per_iteration: self.moves_per_iteration,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(annealing::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FlowerPollinationSettings {
+    pub(crate) flower_count: usize,
+    pub(crate) switch_probability: Real,
+    pub(crate) step_scale: Real,
+    pub(crate) levy_exponent: Real,
+}
+
+impl FlowerPollinationSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "flower-count" => self.flower_count = count(name, value)?,
+            "switch-probability" => self.switch_probability = real::from_f64(value),
+            "step-scale" => self.step_scale = real::from_f64(value),
+            "levy-exponent" => self.levy_exponent = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of flower pollination: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for FlowerPollinationSettings {
+    fn default() -> Self {
+        return Self {
+            flower_count: 20,
+            switch_probability: 0.8,
+            step_scale: 0.01,
+            levy_exponent: 1.5,
+        };
+    }
+}
+
+impl WorldFactory for FlowerPollinationSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = flower_pollination::Parameters {
+            flower_count: self.flower_count,
+            function,
+            bounds,
+            switch_probability: self.switch_probability,
+            step_scale: self.step_scale,
+            levy_exponent: self.levy_exponent,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(flower_pollination::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MothFlameSettings {
+    pub(crate) moth_count: usize,
+    pub(crate) spiral_shape: Real,
+}
+
+impl MothFlameSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "moth-count" => self.moth_count = count(name, value)?,
+            "spiral-shape" => self.spiral_shape = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of moths: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for MothFlameSettings {
+    fn default() -> Self {
+        return Self {
+            moth_count: 20,
+            spiral_shape: 1.0,
+        };
+    }
+}
+
+impl WorldFactory for MothFlameSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = moth_flame::Parameters {
+            moth_count: self.moth_count,
+            function,
+            bounds,
+            spiral_shape: self.spiral_shape,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(moth_flame::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SalpSettings {
+    pub(crate) salp_count: usize,
+}
+
+impl SalpSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "salp-count" => self.salp_count = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of salps: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for SalpSettings {
+    fn default() -> Self {
+        return Self {
+            salp_count: 30,
+        };
+    }
+}
+
+impl WorldFactory for SalpSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = salps::Parameters {
+            salp_count: self.salp_count,
+            function,
+            bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(salps::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct AcoSettings {
+    pub(crate) ant_count: usize,
+    pub(crate) archive_size: usize,
+    pub(crate) q: Real,
+    pub(crate) xi: Real,
+}
+
+impl AcoSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "ant-count" => self.ant_count = count(name, value)?,
+            "archive-size" => self.archive_size = count(name, value)?,
+            "q" => self.q = real::from_f64(value),
+            "xi" => self.xi = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of ants: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for AcoSettings {
+    fn default() -> Self {
+        return Self {
+            ant_count: 10,
+            archive_size: 50,
+            q: 0.5,
+            xi: 0.85,
+        };
+    }
+}
+
+impl WorldFactory for AcoSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = aco::Parameters {
+            ant_count: self.ant_count,
+            archive_size: self.archive_size,
+            function,
+            bounds,
+            q: self.q,
+            xi: self.xi,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(aco::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SineCosineSettings {
+    pub(crate) agent_count: usize,
+    pub(crate) amplitude: Real,
+}
+
+impl SineCosineSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "agent-count" => self.agent_count = count(name, value)?,
+            "amplitude" => self.amplitude = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of sine cosine: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for SineCosineSettings {
+    fn default() -> Self {
+        return Self {
+            agent_count: 30,
+            amplitude: 2.0,
+        };
+    }
+}
+
+impl WorldFactory for SineCosineSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = sine_cosine::Parameters {
+            agent_count: self.agent_count,
+            function,
+            bounds,
+            amplitude: self.amplitude,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(sine_cosine::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct GsaSettings {
+    pub(crate) agent_count: usize,
+    pub(crate) initial_gravity: Real,
+    pub(crate) gravity_decay: Real,
+}
+
+impl GsaSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "agent-count" => self.agent_count = count(name, value)?,
+            "initial-gravity" => self.initial_gravity = real::from_f64(value),
+            "gravity-decay" => self.gravity_decay = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of gravitational search: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for GsaSettings {
+    fn default() -> Self {
+        return Self {
+            agent_count: 30,
+            initial_gravity: 100.0,
+            gravity_decay: 20.0,
+        };
+    }
+}
+
+impl WorldFactory for GsaSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = gsa::Parameters {
+            agent_count: self.agent_count,
+            function,
+            bounds,
+            initial_gravity: self.initial_gravity,
+            gravity_decay: self.gravity_decay,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(gsa::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct DragonflySettings {
+    pub(crate) dragonfly_count: usize,
+    pub(crate) separation_weight: Real,
+    pub(crate) alignment_weight: Real,
+    pub(crate) cohesion_weight: Real,
+    pub(crate) food_weight: Real,
+    pub(crate) enemy_weight: Real,
+}
+
+impl DragonflySettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "dragonfly-count" => self.dragonfly_count = count(name, value)?,
+            "separation-weight" => self.separation_weight = real::from_f64(value),
+            "alignment-weight" => self.alignment_weight = real::from_f64(value),
+            "cohesion-weight" => self.cohesion_weight = real::from_f64(value),
+            "food-weight" => self.food_weight = real::from_f64(value),
+            "enemy-weight" => self.enemy_weight = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of dragonflies: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for DragonflySettings {
+    fn default() -> Self {
+        return Self {
+            dragonfly_count: 30,
+            separation_weight: 0.1,
+            alignment_weight: 0.1,
+            cohesion_weight: 0.1,
+            food_weight: 1.0,
+            enemy_weight: 0.1,
+        };
+    }
+}
+
+impl WorldFactory for DragonflySettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = dragonflies::Parameters {
+            dragonfly_count: self.dragonfly_count,
+            function,
+            bounds,
+            separation_weight: self.separation_weight,
+            alignment_weight: self.alignment_weight,
+            cohesion_weight: self.cohesion_weight,
+            food_weight: self.food_weight,
+            enemy_weight: self.enemy_weight,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(dragonflies::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct KrillSettings {
+    pub(crate) krill_count: usize,
+    pub(crate) max_induced_speed: Real,
+    pub(crate) foraging_speed: Real,
+    pub(crate) max_diffusion: Real,
+    pub(crate) time_constant: Real,
+}
+
+impl KrillSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "krill-count" => self.krill_count = count(name, value)?,
+            "max-induced-speed" => self.max_induced_speed = real::from_f64(value),
+            "foraging-speed" => self.foraging_speed = real::from_f64(value),
+            "max-diffusion" => self.max_diffusion = real::from_f64(value),
+            "time-constant" => self.time_constant = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of krill: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for KrillSettings {
+    fn default() -> Self {
+        return Self {
+            krill_count: 25,
+            max_induced_speed: 0.01,
+            foraging_speed: 0.02,
+            max_diffusion: 0.005,
+            time_constant: 0.5,
+        };
+    }
+}
+
+impl WorldFactory for KrillSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = krill::Parameters {
+            krill_count: self.krill_count,
+            function,
+            bounds,
+            max_induced_speed: self.max_induced_speed,
+            foraging_speed: self.foraging_speed,
+            max_diffusion: self.max_diffusion,
+            time_constant: self.time_constant,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(krill::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FssSettings {
+    pub(crate) fish_count: usize,
+    pub(crate) weight_scale: Real,
+    pub(crate) initial_step: Real,
+    pub(crate) final_step: Real,
+}
+
+impl FssSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "fish-count" => self.fish_count = count(name, value)?,
+            "weight-scale" => self.weight_scale = real::from_f64(value),
+            "initial-step" => self.initial_step = real::from_f64(value),
+            "final-step" => self.final_step = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of fish: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for FssSettings {
+    fn default() -> Self {
+        return Self {
+            fish_count: 30,
+            weight_scale: 5000.0,
+            initial_step: 0.1,
+            final_step: 0.001,
+        };
+    }
+}
+
+impl WorldFactory for FssSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = fss::Parameters {
+            fish_count: self.fish_count,
+            function,
+            bounds,
+            weight_scale: self.weight_scale,
+            step_bounds: (self.initial_step, self.final_step),
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(fss::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct TlboSettings {
+    pub(crate) tlbo_count: usize,
+}
+
+impl TlboSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "tlbo-count" => self.tlbo_count = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of teaching-learning: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for TlboSettings {
+    fn default() -> Self {
+        return Self {
+            tlbo_count: 20,
+        };
+    }
+}
+
+impl WorldFactory for TlboSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = tlbo::Parameters {
+            learner_count: self.tlbo_count,
+            function,
+            bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(tlbo::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct HarrisHawksSettings {
+    pub(crate) hawk_count: usize,
+}
+
+impl HarrisHawksSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "hawk-count" => self.hawk_count = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of hawks: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for HarrisHawksSettings {
+    fn default() -> Self {
+        return Self {
+            hawk_count: 30,
+        };
+    }
+}
+
+impl WorldFactory for HarrisHawksSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = harris_hawks::Parameters {
+            hawk_count: self.hawk_count,
+            function,
+            bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(harris_hawks::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct NelderMeadSettings {
+    pub(crate) initial_size: Real,
+    pub(crate) tolerance: Real,
+}
+
+impl NelderMeadSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "initial-size" => self.initial_size = real::from_f64(value),
+            "tolerance" => self.tolerance = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of Nelder-Mead: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for NelderMeadSettings {
+    fn default() -> Self {
+        return Self {
+            initial_size: 0.1,
+            tolerance: 1e-10,
+        };
+    }
+}
+
+impl WorldFactory for NelderMeadSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = nelder_mead::Parameters {
+            function,
+            bounds,
+            initial_size: self.initial_size,
+            tolerance: self.tolerance,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(nelder_mead::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RandomSearchSettings {
+    pub(crate) sample_count: usize,
+}
+
+impl RandomSearchSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "sample-count" => self.sample_count = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of random search: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for RandomSearchSettings {
+    fn default() -> Self {
+        return Self {
+            sample_count: 100,
+        };
+    }
+}
+
+impl WorldFactory for RandomSearchSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = random_search::Parameters {
+            sample_count: self.sample_count,
+            function,
+            bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(random_search::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct PatternSearchSettings {
+    pub(crate) initial_step: Real,
+    pub(crate) shrink: Real,
+    pub(crate) tolerance: Real,
+}
+
+impl PatternSearchSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "initial-step" => self.initial_step = real::from_f64(value),
+            "shrink" => self.shrink = real::from_f64(value),
+            "tolerance" => self.tolerance = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of pattern search: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for PatternSearchSettings {
+    fn default() -> Self {
+        return Self {
+            initial_step: 0.1,
+            shrink: 0.5,
+            tolerance: 1e-10,
+        };
+    }
+}
+
+impl WorldFactory for PatternSearchSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = pattern_search::Parameters {
+            function,
+            bounds,
+            initial_step: self.initial_step,
+            shrink: self.shrink,
+            tolerance: self.tolerance,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(pattern_search::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct GrasshopperSettings {
+    pub(crate) grasshopper_count: usize,
+    pub(crate) attraction_intensity: Real,
+    pub(crate) attractive_length_scale: Real,
+}
+
+impl GrasshopperSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "grasshopper-count" => self.grasshopper_count = count(name, value)?,
+            "attraction-intensity" => self.attraction_intensity = real::from_f64(value),
+            "attractive-length-scale" => self.attractive_length_scale = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of grasshoppers: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for GrasshopperSettings {
+    fn default() -> Self {
+        return Self {
+            grasshopper_count: 30,
+            attraction_intensity: 0.5,
+            attractive_length_scale: 1.5,
+        };
+    }
+}
+
+impl WorldFactory for GrasshopperSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = grasshoppers::Parameters {
+            grasshopper_count: self.grasshopper_count,
+            function,
+            bounds,
+            attraction_intensity: self.attraction_intensity,
+            attractive_length_scale: self.attractive_length_scale,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(grasshoppers::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct BacteriaSettings {
+    pub(crate) bacterium_count: usize,
+    pub(crate) chemotactic_steps: usize,
+    pub(crate) swim_length: usize,
+    pub(crate) reproduction_steps: usize,
+    pub(crate) elimination_probability: Real,
+    pub(crate) step_size: Real,
+}
+
+impl BacteriaSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "bacterium-count" => self.bacterium_count = count(name, value)?,
+            "chemotactic-steps" => self.chemotactic_steps = count(name, value)?,
+            "swim-length" => self.swim_length = count(name, value)?,
+            "reproduction-steps" => self.reproduction_steps = count(name, value)?,
+            "elimination-probability" => self.elimination_probability = real::from_f64(value),
+            "step-size" => self.step_size = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of bacteria: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for BacteriaSettings {
+    fn default() -> Self {
+        return Self {
+            bacterium_count: 50,
+            chemotactic_steps: 50,
+            swim_length: 4,
+            reproduction_steps: 4,
+            elimination_probability: 0.25,
+            step_size: 0.01,
+        };
+    }
+}
+
+impl WorldFactory for BacteriaSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = bacteria::Parameters {
+            bacterium_count: self.bacterium_count,
+            function,
+            bounds,
+            chemotactic_steps: self.chemotactic_steps,
+            swim_length: self.swim_length,
+            reproduction_steps: self.reproduction_steps,
+            elimination_probability: self.elimination_probability,
+            step_size: self.step_size,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(bacteria::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct IwoSettings {
+    pub(crate) weed_count: usize,
+    pub(crate) max_weed_count: usize,
+    pub(crate) min_seeds: usize,
+    pub(crate) max_seeds: usize,
+    pub(crate) initial_sigma: Real,
+    pub(crate) final_sigma: Real,
+    pub(crate) modulation_index: Real,
+}
+
+impl IwoSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "weed-count" => self.weed_count = count(name, value)?,
+            "max-weed-count" => self.max_weed_count = count(name, value)?,
+            "min-seeds" => self.min_seeds = count(name, value)?,
+            "max-seeds" => self.max_seeds = count(name, value)?,
+            "initial-sigma" => self.initial_sigma = real::from_f64(value),
+            "final-sigma" => self.final_sigma = real::from_f64(value),
+            "modulation-index" => self.modulation_index = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of weeds: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for IwoSettings {
+    fn default() -> Self {
+        return Self {
+            weed_count: 10,
+            max_weed_count: 30,
+            min_seeds: 0,
+            max_seeds: 5,
+            initial_sigma: 0.05,
+            final_sigma: 0.00001,
+            modulation_index: 3.0,
+        };
+    }
+}
+
+impl WorldFactory for IwoSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = iwo::Parameters {
+            initial_count: self.weed_count,
+            max_population: self.max_weed_count,
+            function,
+            bounds,
+            seed_range: (self.min_seeds, self.max_seeds),
+            sigma_bounds: (self.initial_sigma, self.final_sigma),
+            modulation_index: self.modulation_index,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(iwo::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct JayaSettings {
+    pub(crate) jaya_count: usize,
+}
+
+impl JayaSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "jaya-count" => self.jaya_count = count(name, value)?,
+            _ => return Err(format!("Unknown parameter of Jaya: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for JayaSettings {
+    fn default() -> Self {
+        return Self {
+            jaya_count: 20,
+        };
+    }
+}
+
+impl WorldFactory for JayaSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = jaya::Parameters {
+            agent_count: self.jaya_count,
+            function,
+            bounds,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(jaya::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CrowSettings {
+    pub(crate) crow_count: usize,
+    pub(crate) awareness_probability: Real,
+    pub(crate) flight_length: Real,
+}
+
+impl CrowSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "crow-count" => self.crow_count = count(name, value)?,
+            "awareness-probability" => self.awareness_probability = real::from_f64(value),
+            "flight-length" => self.flight_length = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of crows: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for CrowSettings {
+    fn default() -> Self {
+        return Self {
+            crow_count: 20,
+            awareness_probability: 0.1,
+            flight_length: 2.0,
+        };
+    }
+}
+
+impl WorldFactory for CrowSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = crows::Parameters {
+            crow_count: self.crow_count,
+            function,
+            bounds,
+            awareness_probability: self.awareness_probability,
+            flight_length: self.flight_length,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(crows::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CemSettings {
+    pub(crate) sample_count: usize,
+    pub(crate) elite_fraction: Real,
+    pub(crate) smoothing: Real,
+}
+
+impl CemSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "sample-count" => self.sample_count = count(name, value)?,
+            "elite-fraction" => self.elite_fraction = real::from_f64(value),
+            "smoothing" => self.smoothing = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of the cross-entropy method: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for CemSettings {
+    fn default() -> Self {
+        return Self {
+            sample_count: 50,
+            elite_fraction: 0.2,
+            smoothing: 0.7,
+        };
+    }
+}
+
+impl WorldFactory for CemSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = cem::Parameters {
+            sample_count: self.sample_count,
+            function,
+            bounds,
+            elite_fraction: self.elite_fraction,
+            smoothing: self.smoothing,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(cem::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct UmdaSettings {
+    pub(crate) population_size: usize,
+    pub(crate) selection_ratio: Real,
+}
+
+impl UmdaSettings {
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "population-size" => self.population_size = count(name, value)?,
+            "selection-ratio" => self.selection_ratio = real::from_f64(value),
+            _ => return Err(format!("Unknown parameter of UMDA: `{name}`")),
+        }
+        return Ok(());
+    }
+}
+
+impl Default for UmdaSettings {
+    fn default() -> Self {
+        return Self {
+            population_size: 50,
+            selection_ratio: 0.5,
+        };
+    }
+}
+
+impl WorldFactory for UmdaSettings {
+    fn make<const N: usize>(&self, function: Objective<N>, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        let parameters = umda::Parameters {
+            population_size: self.population_size,
+            function,
+            bounds,
+            selection_ratio: self.selection_ratio,
+            parallel: false,
+        };
+        parameters.check()?;
+        return Ok(Box::new(SizedWorld(umda::WorldState::<N, Xoshiro256PlusPlus, _>::from_parameters(Arc::new(parameters), seed))));
+    }
+}
+
+// The algorithm chosen at run time, for the bindings that configure optimizers one parameter at a time
+#[derive(Clone, Debug)]
+pub(crate) enum Settings {
+    Bats(BatSettings),
+    Butterflies(ButterflySettings),
+    GreyWolf(GreyWolfSettings),
+    Cuckoo(CuckooSettings),
+    Abc(AbcSettings),
+    Annealing(AnnealingSettings),
+    FlowerPollination(FlowerPollinationSettings),
+    MothFlame(MothFlameSettings),
+    Salps(SalpSettings),
+    Aco(AcoSettings),
+    SineCosine(SineCosineSettings),
+    Gsa(GsaSettings),
+    Dragonflies(DragonflySettings),
+    Krill(KrillSettings),
+    Fss(FssSettings),
+    Tlbo(TlboSettings),
+    HarrisHawks(HarrisHawksSettings),
+    NelderMead(NelderMeadSettings),
+    RandomSearch(RandomSearchSettings),
+    PatternSearch(PatternSearchSettings),
+    Grasshoppers(GrasshopperSettings),
+    Bacteria(BacteriaSettings),
+    Iwo(IwoSettings),
+    Jaya(JayaSettings),
+    Crows(CrowSettings),
+    Cem(CemSettings),
+    Umda(UmdaSettings),
+}
+
+impl Settings {
+    // By subcommand name, with the defaults
+    pub(crate) fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "bats" => return Ok(Self::Bats(BatSettings::default())),
+            "butterflies" => return Ok(Self::Butterflies(ButterflySettings::default())),
+            "grey-wolf" => return Ok(Self::GreyWolf(GreyWolfSettings::default())),
+            "cuckoo" => return Ok(Self::Cuckoo(CuckooSettings::default())),
+            "abc" => return Ok(Self::Abc(AbcSettings::default())),
+            "annealing" => return Ok(Self::Annealing(AnnealingSettings::default())),
+            "flower-pollination" => return Ok(Self::FlowerPollination(FlowerPollinationSettings::default())),
+            "moth-flame" => return Ok(Self::MothFlame(MothFlameSettings::default())),
+            "salps" => return Ok(Self::Salps(SalpSettings::default())),
+            "aco" => return Ok(Self::Aco(AcoSettings::default())),
+            "sine-cosine" => return Ok(Self::SineCosine(SineCosineSettings::default())),
+            "gsa" => return Ok(Self::Gsa(GsaSettings::default())),
+            "dragonflies" => return Ok(Self::Dragonflies(DragonflySettings::default())),
+            "krill" => return Ok(Self::Krill(KrillSettings::default())),
+            "fss" => return Ok(Self::Fss(FssSettings::default())),
+            "tlbo" => return Ok(Self::Tlbo(TlboSettings::default())),
+            "harris-hawks" => return Ok(Self::HarrisHawks(HarrisHawksSettings::default())),
+            "nelder-mead" => return Ok(Self::NelderMead(NelderMeadSettings::default())),
+            "random-search" => return Ok(Self::RandomSearch(RandomSearchSettings::default())),
+            "pattern-search" => return Ok(Self::PatternSearch(PatternSearchSettings::default())),
+            "grasshoppers" => return Ok(Self::Grasshoppers(GrasshopperSettings::default())),
+            "bacteria" => return Ok(Self::Bacteria(BacteriaSettings::default())),
+            "iwo" => return Ok(Self::Iwo(IwoSettings::default())),
+            "jaya" => return Ok(Self::Jaya(JayaSettings::default())),
+            "crows" => return Ok(Self::Crows(CrowSettings::default())),
+            "cem" => return Ok(Self::Cem(CemSettings::default())),
+            "umda" => return Ok(Self::Umda(UmdaSettings::default())),
+            _ => return Err(format!("Unknown algorithm: `{name}`")),
+        }
+    }
+
+    fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match self {
+            Self::Bats(settings) => return settings.set(name, value),
+            Self::Butterflies(settings) => return settings.set(name, value),
+            Self::GreyWolf(settings) => return settings.set(name, value),
+            Self::Cuckoo(settings) => return settings.set(name, value),
+            Self::Abc(settings) => return settings.set(name, value),
+            Self::Annealing(settings) => return settings.set(name, value),
+            Self::FlowerPollination(settings) => return settings.set(name, value),
+            Self::MothFlame(settings) => return settings.set(name, value),
+            Self::Salps(settings) => return settings.set(name, value),
+            Self::Aco(settings) => return settings.set(name, value),
+            Self::SineCosine(settings) => return settings.set(name, value),
+            Self::Gsa(settings) => return settings.set(name, value),
+            Self::Dragonflies(settings) => return settings.set(name, value),
+            Self::Krill(settings) => return settings.set(name, value),
+            Self::Fss(settings) => return settings.set(name, value),
+            Self::Tlbo(settings) => return settings.set(name, value),
+            Self::HarrisHawks(settings) => return settings.set(name, value),
+            Self::NelderMead(settings) => return settings.set(name, value),
+            Self::RandomSearch(settings) => return settings.set(name, value),
+            Self::PatternSearch(settings) => return settings.set(name, value),
+            Self::Grasshoppers(settings) => return settings.set(name, value),
+            Self::Bacteria(settings) => return settings.set(name, value),
+            Self::Iwo(settings) => return settings.set(name, value),
+            Self::Jaya(settings) => return settings.set(name, value),
+            Self::Crows(settings) => return settings.set(name, value),
+            Self::Cem(settings) => return settings.set(name, value),
+            Self::Umda(settings) => return settings.set(name, value),
+        }
+    }
+
+    fn build_world(&self, objective: &ObjectiveSpecification, dimensions: usize, bounds: (Real, Real), seed: u64) -> Result<Box<dyn DynamicWorld>, String> {
+        match self {
+            Self::Bats(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Butterflies(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::GreyWolf(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Cuckoo(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Abc(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Annealing(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::FlowerPollination(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::MothFlame(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Salps(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Aco(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::SineCosine(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Gsa(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Dragonflies(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Krill(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Fss(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Tlbo(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::HarrisHawks(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::NelderMead(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::RandomSearch(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::PatternSearch(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Grasshoppers(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Bacteria(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Iwo(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Jaya(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Crows(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Cem(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+            Self::Umda(settings) => return build_world(settings, objective, dimensions, bounds, seed),
+        }
+    }
+}
+
+// An optimizer whose world is built on first use, so the parameters can be set before the population is evaluated
+pub(crate) struct ConfiguredOptimizer {
+    settings: Settings,
+    objective: ObjectiveSpecification,
+    pub(crate) dimensions: usize,
+    bounds: (Real, Real),
+    seed: u64,
+    world: Option<Box<dyn DynamicWorld>>,
+}
+
+impl ConfiguredOptimizer {
+    pub(crate) fn new(settings: Settings, objective: ObjectiveSpecification, dimensions: usize, bounds: (Real, Real), seed: u64) -> Self {
+        return Self { settings, objective, dimensions, bounds, seed, world: None };
+    }
+
+    // Starts over with a new population from the original seed on the next use
+    pub(crate) fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        self.settings.set(name, value)?;
+        self.world = None;
+        return Ok(());
+    }
+
+    pub(crate) fn world(&mut self) -> Result<&mut dyn DynamicWorld, String> {
+        if self.world.is_none() {
+            self.world = Some(self.settings.build_world(&self.objective, self.dimensions, self.bounds, self.seed)?);
+        }
+        return Ok(self.world.as_deref_mut().unwrap());
+    }
+}
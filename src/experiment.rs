@@ -0,0 +1,276 @@
+// Batches of runs from code, like the batch mode of the command line: every algorithm runs on every function, once per
+// seed. The function and bounds of the algorithms' parameters are replaced by those of each function:
+//
+//     let experiment = Experiment {
+//         functions: vec!["ackley".to_string(), "rastrigin".to_string()],
+//         algorithms: vec![("bats".to_string(), Algorithm::Bats(bat_parameters))],
+//         seeds: (0..30).collect(),
+//         length: RunLength::Evaluations(100_000),
+//         target_value: Some(1e-3),
+//         parallel: true,
+//     };
+//     let results = experiment.run();
+//     println!("{}", results.summary("ackley", "bats").unwrap().mean);
+//
+// The results don't depend on `parallel`, every run is seeded by its seed alone
+
+use std::sync::Arc;
+
+use rand_xoshiro::Xoshiro256PlusPlus;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{abc, aco, annealing, bacteria, bats, butterflies, cem, crows, cuckoo, dragonflies, fitness::Fitness, flower_pollination, fss, functions::Functions, grasshoppers, grey_wolf, gsa, harris_hawks, iwo, jaya, krill, moth_flame, nelder_mead, optimizer::{FromParameters, RunLength}, pattern_search, random_search, real::{self, Real}, salps, sine_cosine, tlbo, umda, vector::VectorN};
+
+// One variant for every algorithm, named like the subcommands of the command line
+#[derive(Clone, Debug)]
+pub enum Algorithm<const N: usize> {
+    Bats(bats::Parameters<N>),
+    Butterflies(butterflies::Parameters<N>),
+    GreyWolf(grey_wolf::Parameters<N>),
+    Cuckoo(cuckoo::Parameters<N>),
+    Abc(abc::Parameters<N>),
+    Annealing(annealing::Parameters<N>),
+    FlowerPollination(flower_pollination::Parameters<N>),
+    MothFlame(moth_flame::Parameters<N>),
+    Salps(salps::Parameters<N>),
+    Aco(aco::Parameters<N>),
+    SineCosine(sine_cosine::Parameters<N>),
+    Gsa(gsa::Parameters<N>),
+    Dragonflies(dragonflies::Parameters<N>),
+    Krill(krill::Parameters<N>),
+    Fss(fss::Parameters<N>),
+    Tlbo(tlbo::Parameters<N>),
+    HarrisHawks(harris_hawks::Parameters<N>),
+    NelderMead(nelder_mead::Parameters<N>),
+    RandomSearch(random_search::Parameters<N>),
+    PatternSearch(pattern_search::Parameters<N>),
+    Grasshoppers(grasshoppers::Parameters<N>),
+    Bacteria(bacteria::Parameters<N>),
+    Iwo(iwo::Parameters<N>),
+    Jaya(jaya::Parameters<N>),
+    Crows(crows::Parameters<N>),
+    Cem(cem::Parameters<N>),
+    Umda(umda::Parameters<N>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Experiment<const N: usize> {
+    pub functions: Vec<String>, // Names of the built-in functions
+    pub algorithms: Vec<(String, Algorithm<N>)>, // Named for the results
+    pub seeds: Vec<u64>, // One run per seed, for every function and algorithm
+    pub length: RunLength,
+    pub target_value: Option<Real>, // Runs stop once they reach it
+    pub parallel: bool, // Spreads the runs over the rayon pool
+}
+
+// The outcome of a single run
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub function: String,
+    pub algorithm: String,
+    pub seed: u64,
+    pub best_value: f64,
+    pub best_solution: Vec<f64>,
+    pub evaluations: usize,
+    pub evaluations_to_target: Option<usize>, // None if the target wasn't reached or there was none
+}
+
+// The runs of an algorithm on a function
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub function: String,
+    pub algorithm: String,
+    pub run_count: usize,
+    pub best: f64,
+    pub worst: f64, // NaN if any run ended on NaN
+    pub mean: f64,
+    pub median: f64,
+    pub standard_deviation: f64, // Sample, the same as in the summaries of collect
+    pub success_count: usize, // Runs that reached the target value
+    pub mean_evaluations_to_target: Option<f64>, // Over the successful runs, None without any
+}
+
+impl Summary {
+    // From runs of the same function and algorithm, at least one
+    pub fn from_runs(runs: &[Run]) -> Self {
+        if runs.is_empty() {
+            panic!("A summary needs at least one run");
+        }
+        let mut sorted = runs.iter().map(|run| Fitness::minimize(run.best_value)).collect::<Vec<_>>();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) { (sorted[middle - 1].value() + sorted[middle].value()) / 2.0 } else { sorted[middle].value() };
+        let run_count = runs.len();
+        let mean = runs.iter().map(|run| run.best_value).sum::<f64>() / run_count as f64;
+        let successes = runs.iter().filter_map(|run| run.evaluations_to_target).collect::<Vec<_>>();
+        return Self {
+            function: runs[0].function.clone(),
+            algorithm: runs[0].algorithm.clone(),
+            run_count,
+            best: sorted[0].value(),
+            worst: sorted[run_count - 1].value(),
+            mean,
+            median,
+            standard_deviation: standard_deviation(&runs.iter().map(|run| run.best_value).collect::<Vec<_>>(), mean),
+            success_count: successes.len(),
+            mean_evaluations_to_target: (!successes.is_empty()).then(|| successes.iter().sum::<usize>() as f64 / successes.len() as f64),
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExperimentResults {
+    pub runs: Vec<Run>, // By function, then algorithm, then seed, in the order of the experiment
+    pub summaries: Vec<Summary>, // One for every function and algorithm, in the same order
+}
+
+impl ExperimentResults {
+    pub fn summary(&self, function: &str, algorithm: &str) -> Option<&Summary> {
+        return self.summaries.iter().find(|summary| summary.function == function && summary.algorithm == algorithm);
+    }
+}
+
+// Sample standard deviation, 0 for a single value
+pub fn standard_deviation(values: &[f64], mean: f64) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let squared_deviations = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>();
+    return (squared_deviations / (values.len() - 1) as f64).sqrt();
+}
+
+// The best value, the best solution, the evaluation count and the evaluations to the target of a run
+fn run_world<const N: usize, World: FromParameters<N>>(parameters: World::Parameters, seed: u64, length: RunLength, target_value: Option<Real>) -> (Real, VectorN<Real, N>, usize, Option<usize>) {
+    let mut world = World::from_parameters(Arc::new(parameters), seed);
+    let evaluations_to_target = world.run(length, target_value);
+    return (world.best_solution_value(), world.best_solution(), world.evaluation_count(), evaluations_to_target);
+}
+
+impl<const N: usize> Experiment<N> {
+    // Panics on unknown functions, invalid parameters or an experiment without seeds
+    pub fn run(&self) -> ExperimentResults {
+        if self.seeds.is_empty() {
+            panic!("An experiment needs at least one seed");
+        }
+        let tasks = self.functions.iter().flat_map(|function_name| {
+            let function = Functions::<N>::make_from_name(function_name);
+            return self.algorithms.iter().flat_map(move |(algorithm_name, algorithm)| {
+                return self.seeds.iter().map(move |&seed| (function_name.as_str(), function, algorithm_name.as_str(), algorithm, seed));
+            });
+        }).collect::<Vec<_>>();
+
+        // The function and bounds of the parameters are replaced by those of the task's function
+        let run_task = |&(function_name, function, algorithm_name, algorithm, seed): &(&str, Functions<N>, &str, &Algorithm<N>, u64)| {
+            let bounds = function.get_bounds();
+            let (best_value, best_solution, evaluations, evaluations_to_target) = match algorithm {
+                Algorithm::Bats(parameters) => run_world::<N, bats::WorldState<N, Xoshiro256PlusPlus>>(bats::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Butterflies(parameters) => run_world::<N, butterflies::WorldState<N, Xoshiro256PlusPlus>>(butterflies::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::GreyWolf(parameters) => run_world::<N, grey_wolf::WorldState<N, Xoshiro256PlusPlus>>(grey_wolf::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Cuckoo(parameters) => run_world::<N, cuckoo::WorldState<N, Xoshiro256PlusPlus>>(cuckoo::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Abc(parameters) => run_world::<N, abc::WorldState<N, Xoshiro256PlusPlus>>(abc::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Annealing(parameters) => run_world::<N, annealing::WorldState<N, Xoshiro256PlusPlus>>(annealing::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::FlowerPollination(parameters) => run_world::<N, flower_pollination::WorldState<N, Xoshiro256PlusPlus>>(flower_pollination::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::MothFlame(parameters) => run_world::<N, moth_flame::WorldState<N, Xoshiro256PlusPlus>>(moth_flame::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Salps(parameters) => run_world::<N, salps::WorldState<N, Xoshiro256PlusPlus>>(salps::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Aco(parameters) => run_world::<N, aco::WorldState<N, Xoshiro256PlusPlus>>(aco::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::SineCosine(parameters) => run_world::<N, sine_cosine::WorldState<N, Xoshiro256PlusPlus>>(sine_cosine::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Gsa(parameters) => run_world::<N, gsa::WorldState<N, Xoshiro256PlusPlus>>(gsa::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Dragonflies(parameters) => run_world::<N, dragonflies::WorldState<N, Xoshiro256PlusPlus>>(dragonflies::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Krill(parameters) => run_world::<N, krill::WorldState<N, Xoshiro256PlusPlus>>(krill::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Fss(parameters) => run_world::<N, fss::WorldState<N, Xoshiro256PlusPlus>>(fss::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Tlbo(parameters) => run_world::<N, tlbo::WorldState<N, Xoshiro256PlusPlus>>(tlbo::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::HarrisHawks(parameters) => run_world::<N, harris_hawks::WorldState<N, Xoshiro256PlusPlus>>(harris_hawks::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::NelderMead(parameters) => run_world::<N, nelder_mead::WorldState<N, Xoshiro256PlusPlus>>(nelder_mead::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::RandomSearch(parameters) => run_world::<N, random_search::WorldState<N, Xoshiro256PlusPlus>>(random_search::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::PatternSearch(parameters) => run_world::<N, pattern_search::WorldState<N, Xoshiro256PlusPlus>>(pattern_search::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Grasshoppers(parameters) => run_world::<N, grasshoppers::WorldState<N, Xoshiro256PlusPlus>>(grasshoppers::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Bacteria(parameters) => run_world::<N, bacteria::WorldState<N, Xoshiro256PlusPlus>>(bacteria::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Iwo(parameters) => run_world::<N, iwo::WorldState<N, Xoshiro256PlusPlus>>(iwo::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Jaya(parameters) => run_world::<N, jaya::WorldState<N, Xoshiro256PlusPlus>>(jaya::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Crows(parameters) => run_world::<N, crows::WorldState<N, Xoshiro256PlusPlus>>(crows::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Cem(parameters) => run_world::<N, cem::WorldState<N, Xoshiro256PlusPlus>>(cem::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+                Algorithm::Umda(parameters) => run_world::<N, umda::WorldState<N, Xoshiro256PlusPlus>>(umda::Parameters { function, bounds, ..parameters.clone() }, seed, self.length, self.target_value),
+            };
+            return Run {
+                function: function_name.to_string(),
+                algorithm: algorithm_name.to_string(),
+                seed,
+                best_value: real::to_f64(best_value),
+                best_solution: best_solution.coordinates.map(real::to_f64).to_vec(),
+                evaluations,
+                evaluations_to_target,
+            };
+        };
+        let runs = if self.parallel {
+            tasks.into_par_iter().map(|task| run_task(&task)).collect::<Vec<_>>()
+        } else {
+            tasks.iter().map(run_task).collect::<Vec<_>>()
+        };
+        let summaries = runs.chunks(self.seeds.len()).map(Summary::from_runs).collect();
+        return ExperimentResults { runs, summaries };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{bats, butterflies, functions::Functions, grey_wolf, optimizer::{InitialVelocity, Movement, RunLength}};
+
+    use super::{standard_deviation, Algorithm, Experiment};
+
+    fn experiment(parallel: bool) -> Experiment<10> {
+        // The function and bounds are replaced for every function of the experiment
+        let function = Functions::<10>::Ackley;
+        let bats = bats::Parameters {
+            bat_count: 20, function, bounds: function.get_bounds(), frequency_bounds: (0.0, 1.0), initial_pulse_rate: 0.5,
+            pulse_rate_factor: 0.1, initial_loudness: 1.0, loudness_cool_factor: 0.9, movement: Movement::Negated,
+            initial_velocity: InitialVelocity::PositiveUnit, improvement_tolerance: 0.0, scale_walk_to_bounds: false, parallel: false,
+        };
+        let butterflies = butterflies::Parameters {
+            population_size: 20, function, bounds: function.get_bounds(), fragrance_multiplier: 0.1, fragrance_exponent_bounds: (0.1, 0.3),
+            local_search_chance: 0.8, movement: Movement::Standard, initial_velocity: InitialVelocity::Zero, improvement_tolerance: 0.0, parallel: false,
+        };
+        let grey_wolves = grey_wolf::WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0)).parameters().clone();
+        return Experiment {
+            functions: vec!["ackley".to_string(), "rastrigin".to_string()],
+            algorithms: vec![
+                ("bats".to_string(), Algorithm::Bats(bats)),
+                ("butterflies".to_string(), Algorithm::Butterflies(butterflies)),
+                ("grey-wolf".to_string(), Algorithm::GreyWolf(grey_wolves)),
+            ],
+            seeds: vec![1, 2, 3],
+            length: RunLength::Evaluations(2000),
+            target_value: None,
+            parallel,
+        };
+    }
+
+    #[test]
+    fn experiment_test() {
+        let results = experiment(false).run();
+        assert_eq!((results.runs.len(), results.summaries.len()), (18, 6));
+        assert!(results.runs.iter().all(|run| run.evaluations <= 2000 && run.evaluations_to_target.is_none()));
+        let summary = results.summary("rastrigin", "butterflies").unwrap();
+        assert_eq!((summary.run_count, summary.success_count, summary.mean_evaluations_to_target), (3, 0, None));
+        assert!(summary.best <= summary.median && summary.median <= summary.worst);
+        assert!(results.summary("rastrigin", "pso").is_none());
+        let wolves = results.runs.iter().filter(|run| run.function == "rastrigin" && run.algorithm == "grey-wolf").map(|run| run.best_value).collect::<Vec<_>>();
+        let summary = results.summary("rastrigin", "grey-wolf").unwrap();
+        assert_eq!(summary.standard_deviation, standard_deviation(&wolves, summary.mean));
+
+        assert_eq!(standard_deviation(&[1.0, 3.0], 2.0), 2.0_f64.sqrt());
+        assert_eq!(standard_deviation(&[5.0], 5.0), 0.0);
+
+        // Reached by the initial population
+        let results = Experiment { target_value: Some(1e6), ..experiment(false) }.run();
+        assert!(results.summaries.iter().all(|summary| summary.success_count == 3 && summary.mean_evaluations_to_target == Some(20.0)));
+
+        // The same runs in parallel
+        let results = experiment(false).run();
+        let parallel_results = experiment(true).run();
+        let values = |runs: &[super::Run]| runs.iter().map(|run| (run.function.clone(), run.algorithm.clone(), run.seed, run.best_value)).collect::<Vec<_>>();
+        assert_eq!(values(&results.runs), values(&parallel_results.runs));
+    }
+}
@@ -0,0 +1,270 @@
+// The C API, built with `--features ffi`. The header is include/swarm_optimizers.h, regenerated by build.rs from the
+// `///` comments below, and the library is the cdylib (libswarm_optimizers.so). Only the declarations here are
+// stable, everything behind the opaque handle may change
+
+use std::{cell::RefCell, ffi::{c_char, c_void, CStr, CString}, panic::{self, AssertUnwindSafe}, ptr, sync::Arc};
+
+use crate::{dynamic::{panic_message, BatSettings, ButterflySettings, ConfiguredOptimizer, ObjectiveSpecification, Settings}, optimizer::RunLength, real::{self, Real}};
+
+/// The function to minimize. Gets the `dimensions` coordinates of a point and the `user_data` given at creation.
+/// Called on the thread calling swarm_run, swarm_reset or the getters, and must not unwind (throw C++ exceptions)
+pub type SwarmObjective = Option<extern "C" fn(coordinates: *const f64, dimensions: usize, user_data: *mut c_void) -> f64>;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwarmAlgorithm {
+    Bats = 0,
+    Butterflies = 1,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwarmStatus {
+    Ok = 0,
+    InvalidArgument = 1, // The message is in swarm_last_error
+    Panicked = 2, // A bug in the optimizer, the message is in swarm_last_error and the handle should only be destroyed
+}
+
+struct CObjective {
+    function: extern "C" fn(*const f64, usize, *mut c_void) -> f64,
+    user_data: *mut c_void,
+}
+
+// The caller vouches for the user data when passing it. The worlds are sequential, so the objective is only called on
+// the thread that called into the library
+unsafe impl Send for CObjective {}
+unsafe impl Sync for CObjective {}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+    static COORDINATES: RefCell<Vec<f64>> = const { RefCell::new(Vec::new()) }; // So evaluations don't allocate
+}
+
+impl CObjective {
+    fn call(&self, coordinates: &[Real]) -> Real {
+        return COORDINATES.with_borrow_mut(|buffer| {
+            buffer.clear();
+            buffer.extend(coordinates.iter().map(|&coordinate| real::to_f64(coordinate)));
+            return real::from_f64((self.function)(buffer.as_ptr(), buffer.len(), self.user_data));
+        });
+    }
+}
+
+impl SwarmAlgorithm {
+    // With the defaults of run_sweep.sh
+    fn settings(self) -> Settings {
+        match self {
+            Self::Bats => return Settings::Bats(BatSettings::default()),
+            Self::Butterflies => return Settings::Butterflies(ButterflySettings::default()),
+        }
+    }
+}
+
+/// An optimizer with its objective, parameters and population. Not thread safe, one thread at a time may use it
+pub struct SwarmOptimizer(ConfiguredOptimizer);
+
+fn set_last_error(message: String) {
+    // Messages come from Rust strings, which may only contain a nul if a panic message does
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with_borrow_mut(|last_error| *last_error = Some(message));
+}
+
+// Runs the body with panics and errors turned into statuses
+fn guard(body: impl FnOnce() -> Result<(), String>) -> SwarmStatus {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return SwarmStatus::Ok,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            return SwarmStatus::InvalidArgument;
+        },
+        Err(payload) => {
+            set_last_error(panic_message(payload));
+            return SwarmStatus::Panicked;
+        },
+    }
+}
+
+fn create(algorithm: SwarmAlgorithm, dimensions: usize, objective: ObjectiveSpecification, bounds: (Real, Real), seed: u64) -> *mut SwarmOptimizer {
+    let optimizer = ConfiguredOptimizer::new(algorithm.settings(), objective, dimensions, bounds, seed);
+    return Box::into_raw(Box::new(SwarmOptimizer(optimizer)));
+}
+
+/// An optimizer minimizing `objective` over [lower_bound, upper_bound] in every coordinate, with the defaults of
+/// run_sweep.sh. The dimensions must be one of 2, 10, 20, 30, 50 or 100. Returns null on invalid arguments
+#[no_mangle]
+pub extern "C" fn swarm_create(algorithm: SwarmAlgorithm, dimensions: usize, objective: SwarmObjective, user_data: *mut c_void, lower_bound: f64, upper_bound: f64, seed: u64) -> *mut SwarmOptimizer {
+    let Some(function) = objective else {
+        set_last_error("The objective is null".to_string());
+        return ptr::null_mut();
+    };
+    if lower_bound >= upper_bound || lower_bound.is_nan() || upper_bound.is_nan() {
+        set_last_error("Incorrect order of bounds or zero size".to_string());
+        return ptr::null_mut();
+    }
+    let objective = Arc::new(CObjective { function, user_data });
+    let objective = ObjectiveSpecification::Callable(Arc::new(move |coordinates: &[Real]| objective.call(coordinates)));
+    return create(algorithm, dimensions, objective, (real::from_f64(lower_bound), real::from_f64(upper_bound)), seed);
+}
+
+/// Like swarm_create, minimizing one of the benchmark functions of the command line ("ackley", "rastrigin", ...) in
+/// its usual bounds
+///
+/// # Safety
+/// `function` is null or a nul terminated string
+#[no_mangle]
+pub unsafe extern "C" fn swarm_create_benchmark(algorithm: SwarmAlgorithm, dimensions: usize, function: *const c_char, seed: u64) -> *mut SwarmOptimizer {
+    if function.is_null() {
+        set_last_error("Null argument".to_string());
+        return ptr::null_mut();
+    }
+    let objective = CStr::from_ptr(function).to_str().map_err(|_| "The function name is not UTF-8".to_string()).and_then(ObjectiveSpecification::builtin);
+    match objective.and_then(|objective| Ok((objective.bounds(None)?, objective))) {
+        Ok((bounds, objective)) => return create(algorithm, dimensions, objective, bounds, seed),
+        Err(message) => {
+            set_last_error(message);
+            return ptr::null_mut();
+        },
+    }
+}
+
+/// Sets a parameter by the name of its command line flag, e.g. "bat-count" or "local-search-chance". Starts over with
+/// a new population from the original seed on the next use, so parameters should be set before running
+///
+/// # Safety
+/// `optimizer` comes from a create function and `name` is a nul terminated string
+#[no_mangle]
+pub unsafe extern "C" fn swarm_set_parameter(optimizer: *mut SwarmOptimizer, name: *const c_char, value: f64) -> SwarmStatus {
+    if optimizer.is_null() || name.is_null() {
+        set_last_error("Null argument".to_string());
+        return SwarmStatus::InvalidArgument;
+    }
+    let optimizer = &mut *optimizer;
+    let name = CStr::from_ptr(name);
+    return guard(|| {
+        let name = name.to_str().map_err(|_| "The parameter name is not UTF-8".to_string())?;
+        return optimizer.0.set(name, value);
+    });
+}
+
+/// Continues the run for `iterations` iterations or until `eval_budget` more evaluations, exactly one of them non zero.
+/// With a finite `target_value` the run stops once the best value is at most the target, and
+/// `evaluations_to_target` (may be null) gets the evaluations it took, or 0 if the target wasn't reached
+///
+/// # Safety
+/// `optimizer` comes from a create function and `evaluations_to_target` is null or valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn swarm_run(optimizer: *mut SwarmOptimizer, iterations: usize, eval_budget: usize, target_value: f64, evaluations_to_target: *mut usize) -> SwarmStatus {
+    if optimizer.is_null() {
+        set_last_error("Null argument".to_string());
+        return SwarmStatus::InvalidArgument;
+    }
+    let optimizer = &mut *optimizer;
+    return guard(|| {
+        let length = match (iterations, eval_budget) {
+            (0, 0) | (1.., 1..) => return Err("Exactly one of iterations and eval_budget must be non zero".to_string()),
+            (iterations, 0) => RunLength::Iterations(iterations),
+            (_, budget) => RunLength::Evaluations(budget),
+        };
+        let target_value = target_value.is_finite().then(|| real::from_f64(target_value));
+        let reached = optimizer.0.world()?.run(length, target_value, &mut |_, _| {});
+        if !evaluations_to_target.is_null() {
+            *evaluations_to_target = reached.unwrap_or(0);
+        }
+        return Ok(());
+    });
+}
+
+/// A fresh population, continuing the random sequence
+///
+/// # Safety
+/// `optimizer` comes from a create function
+#[no_mangle]
+pub unsafe extern "C" fn swarm_reset(optimizer: *mut SwarmOptimizer) -> SwarmStatus {
+    if optimizer.is_null() {
+        set_last_error("Null argument".to_string());
+        return SwarmStatus::InvalidArgument;
+    }
+    let optimizer = &mut *optimizer;
+    return guard(|| {
+        optimizer.0.world()?.reset();
+        return Ok(());
+    });
+}
+
+/// The best value found so far, NaN on errors
+///
+/// # Safety
+/// `optimizer` comes from a create function
+#[no_mangle]
+pub unsafe extern "C" fn swarm_best_value(optimizer: *mut SwarmOptimizer) -> f64 {
+    let mut value = f64::NAN;
+    if optimizer.is_null() {
+        set_last_error("Null argument".to_string());
+        return value;
+    }
+    let optimizer = &mut *optimizer;
+    guard(|| {
+        value = optimizer.0.world()?.best_solution_value();
+        return Ok(());
+    });
+    return value;
+}
+
+/// Copies the best solution found so far into `coordinates`, which has room for `length` values. `length` must be the
+/// dimensions of the optimizer
+///
+/// # Safety
+/// `optimizer` comes from a create function and `coordinates` is valid for `length` writes
+#[no_mangle]
+pub unsafe extern "C" fn swarm_best_solution(optimizer: *mut SwarmOptimizer, coordinates: *mut f64, length: usize) -> SwarmStatus {
+    if optimizer.is_null() || coordinates.is_null() {
+        set_last_error("Null argument".to_string());
+        return SwarmStatus::InvalidArgument;
+    }
+    let optimizer = &mut *optimizer;
+    if length != optimizer.0.dimensions {
+        set_last_error(format!("The optimizer has {} dimensions, got room for {}", optimizer.0.dimensions, length));
+        return SwarmStatus::InvalidArgument;
+    }
+    let coordinates = std::slice::from_raw_parts_mut(coordinates, length);
+    return guard(|| {
+        coordinates.copy_from_slice(&optimizer.0.world()?.best_solution());
+        return Ok(());
+    });
+}
+
+/// Objective evaluations since creation or the last reset, 0 on errors
+///
+/// # Safety
+/// `optimizer` comes from a create function
+#[no_mangle]
+pub unsafe extern "C" fn swarm_evaluation_count(optimizer: *mut SwarmOptimizer) -> usize {
+    let mut count = 0;
+    if optimizer.is_null() {
+        set_last_error("Null argument".to_string());
+        return count;
+    }
+    let optimizer = &mut *optimizer;
+    guard(|| {
+        count = optimizer.0.world()?.evaluation_count();
+        return Ok(());
+    });
+    return count;
+}
+
+/// The message of the last failed call on this thread, or null. Valid until the next failing call on this thread
+#[no_mangle]
+pub extern "C" fn swarm_last_error() -> *const c_char {
+    return LAST_ERROR.with_borrow(|last_error| last_error.as_ref().map_or(ptr::null(), |message| message.as_ptr()));
+}
+
+/// Frees the optimizer. Null is ignored
+///
+/// # Safety
+/// `optimizer` comes from a create function and isn't used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn swarm_destroy(optimizer: *mut SwarmOptimizer) {
+    if !optimizer.is_null() {
+        drop(Box::from_raw(optimizer));
+    }
+}
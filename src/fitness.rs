@@ -0,0 +1,153 @@
+// How solutions compare, for the bests of the worlds and the summaries of the batches. Lower is better in the Ord of a
+// Fitness, so the best comes first when sorting:
+//
+//     Fitness::minimize(1.0) < Fitness::minimize(2.0)
+//     Fitness::maximize(2.0) < Fitness::maximize(1.0)
+//     Fitness::minimize(5.0) < Fitness::minimize(1.0).with_violation(0.5)
+//
+// A smaller constraint violation always wins, the values only decide between equal violations. NaN values and violations
+// are worse than anything else, and equal to each other
+
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Minimize,
+    Maximize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Fitness {
+    value: f64,
+    violation: f64, // 0 for feasible solutions
+    direction: Direction,
+}
+
+// NaN last
+fn compare_numbers(first: f64, second: f64) -> Ordering {
+    match (first.is_nan(), second.is_nan()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => return first.partial_cmp(&second).unwrap(),
+    }
+}
+
+impl Fitness {
+    pub fn new(value: impl Into<f64>, direction: Direction) -> Self {
+        return Self { value: value.into(), violation: 0.0, direction };
+    }
+
+    pub fn minimize(value: impl Into<f64>) -> Self {
+        return Self::new(value, Direction::Minimize);
+    }
+
+    pub fn maximize(value: impl Into<f64>) -> Self {
+        return Self::new(value, Direction::Maximize);
+    }
+
+    // By how much the solution breaks its constraints
+    pub fn with_violation(self, violation: impl Into<f64>) -> Self {
+        let violation = violation.into();
+        if violation < 0.0 {
+            panic!("Constraint violation can't be negative");
+        }
+        return Self { violation, ..self };
+    }
+
+    pub fn value(&self) -> f64 {
+        return self.value;
+    }
+
+    pub fn violation(&self) -> f64 {
+        return self.violation;
+    }
+
+    pub fn direction(&self) -> Direction {
+        return self.direction;
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        return self.violation == 0.0;
+    }
+
+    // Better than other by more than tolerance in value. Smaller improvements are ties, which other wins. Any reduction
+    // of the violation is an improvement
+    pub fn improves_on(&self, other: &Self, tolerance: impl Into<f64>) -> bool {
+        if self.cmp(other).is_ge() {
+            return false;
+        }
+        if compare_numbers(self.violation, other.violation).is_eq() && !other.value.is_nan() {
+            return (self.value - other.value).abs() > tolerance.into();
+        }
+        return true;
+    }
+}
+
+impl Ord for Fitness {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.direction != other.direction {
+            panic!("Can't compare a fitness to be minimized with one to be maximized");
+        }
+        let values = match self.direction {
+            Direction::Minimize => compare_numbers(self.value, other.value),
+            Direction::Maximize => compare_numbers(-self.value, -other.value),
+        };
+        return compare_numbers(self.violation, other.violation).then(values);
+    }
+}
+
+impl PartialOrd for Fitness {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl PartialEq for Fitness {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other).is_eq();
+    }
+}
+
+impl Eq for Fitness {}
+
+#[cfg(test)]
+mod test {
+    use super::Fitness;
+
+    #[test]
+    fn ordering_test() {
+        assert!(Fitness::minimize(1.0) < Fitness::minimize(2.0));
+        assert!(Fitness::maximize(2.0) < Fitness::maximize(1.0));
+        assert!(Fitness::minimize(-0.0) == Fitness::minimize(0.0));
+        // NaN of either sign is the worst, also when maximizing
+        assert!(Fitness::minimize(f64::INFINITY) < Fitness::minimize(f64::NAN) && Fitness::minimize(f64::INFINITY) < Fitness::minimize(-f64::NAN));
+        assert!(Fitness::maximize(f64::NEG_INFINITY) < Fitness::maximize(f64::NAN));
+        assert!(Fitness::minimize(f64::NAN) == Fitness::minimize(-f64::NAN));
+        // Feasibility first
+        assert!(Fitness::minimize(5.0) < Fitness::minimize(1.0).with_violation(0.5));
+        assert!(Fitness::minimize(5.0).with_violation(0.1) < Fitness::minimize(1.0).with_violation(0.5));
+        assert!(Fitness::minimize(1.0).with_violation(0.5) < Fitness::minimize(1.0).with_violation(f64::NAN));
+
+        let mut values = [3.0, f64::NAN, 1.0, 2.0].map(Fitness::minimize);
+        values.sort();
+        assert_eq!(values.map(|fitness| fitness.value())[..3], [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn improvement_test() {
+        assert!(Fitness::minimize(1.0).improves_on(&Fitness::minimize(f64::INFINITY), 0.5));
+        assert!(Fitness::minimize(1.0).improves_on(&Fitness::minimize(f64::NAN), 0.5));
+        assert!(Fitness::minimize(1.0).improves_on(&Fitness::minimize(2.0), 0.5));
+        assert!(!Fitness::minimize(1.6).improves_on(&Fitness::minimize(2.0), 0.5));
+        assert!(!Fitness::minimize(2.0).improves_on(&Fitness::minimize(2.0), 0.0));
+        assert!(Fitness::maximize(3.0).improves_on(&Fitness::maximize(2.0), 0.5));
+        assert!(Fitness::minimize(2.0).improves_on(&Fitness::minimize(1.0).with_violation(0.1), 10.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't compare")]
+    fn mixed_directions_test() {
+        let _ = Fitness::minimize(1.0) < Fitness::maximize(1.0);
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use rand::{distributions::{Bernoulli, Distribution, Uniform}, Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::{self, Real}, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub flower_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub switch_probability: Real, // p, the chance of global pollination, between 0 and 1
+    pub step_scale: Real, // Of the Lévy flights of global pollination
+    pub levy_exponent: Real, // The stability index of the Lévy flights, in (0, 2]
+    pub parallel: bool, // Moves and evaluates the flowers on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.flower_count == 0 {
+            return Err("There must be at least one flower");
+        }
+        if !(0.0..=1.0).contains(&self.switch_probability) {
+            return Err("Switch probability must be between 0 and 1");
+        }
+        if self.step_scale.is_nan() || self.step_scale <= 0.0 {
+            return Err("Step scale must be positive");
+        }
+        if !(self.levy_exponent > 0.0 && self.levy_exponent <= 2.0) {
+            return Err("Lévy exponent must be in (0, 2]");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// Built once per world instead of on every draw
+#[derive(Clone, Debug)]
+struct Distributions {
+    global_pollination: Bernoulli,
+    partner: Uniform<usize>, // Index into the flowers before the move
+}
+
+impl Distributions {
+    fn new<const N: usize, F>(parameters: &Parameters<N, F>) -> Self {
+        return Self {
+            global_pollination: Bernoulli::new(real::to_f64(parameters.switch_probability)).unwrap(),
+            partner: Uniform::new(0, parameters.flower_count),
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Flower<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    candidate: VectorN<Real, N>, // Pollinated in this iteration, kept if it's better
+    random_source: RngType, // Seeded from the population seed and the flower's index, so flowers can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Flower<N, RngType> {
+    // Not evaluated yet, the world evaluates all flowers at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, candidate: position, random_source };
+    }
+
+    // Globally by a Lévy flight towards the best flower, with the switch probability, or locally by a random fraction of
+    // the difference of two random flowers
+    fn pollinate<F>(&mut self, parameters: &Parameters<N, F>, distributions: &Distributions, previous_positions: &[VectorN<Real, N>], best_position: VectorN<Real, N>) {
+        if distributions.global_pollination.sample(&mut self.random_source) {
+            let step = VectorN::random_levy(parameters.levy_exponent, parameters.step_scale, &mut self.random_source);
+            self.candidate = self.position + step * (best_position - self.position);
+        } else {
+            let first_position = previous_positions[distributions.partner.sample(&mut self.random_source)];
+            let second_position = previous_positions[distributions.partner.sample(&mut self.random_source)];
+            self.candidate = self.position + (first_position - second_position) * self.random_source.gen::<Real>();
+        }
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // Keeps the candidate if it's better, the first of equally good positions stays
+    fn take_candidate(&mut self, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.function_value) {
+            self.position = self.candidate;
+            self.function_value = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    flowers: Vec<Flower<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    distributions: Distributions,
+    previous_positions: Vec<VectorN<Real, N>>,
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(flower_count: usize, function: F, bounds: (Real, Real), switch_probability: Real, random_source: RngType) -> Self {
+        let parameters = Parameters {
+            flower_count, function, bounds, switch_probability,
+            step_scale: 0.01,
+            levy_exponent: 1.5,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            flowers: Vec::with_capacity(parameters.flower_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            distributions: Distributions::new(&parameters),
+            previous_positions: Vec::with_capacity(parameters.flower_count),
+            positions: Vec::with_capacity(parameters.flower_count),
+            values: Vec::with_capacity(parameters.flower_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_candidates(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.flowers.iter().map(|flower| flower.candidate));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    fn pollinate(&mut self) {
+        self.previous_positions.clear();
+        self.previous_positions.extend(self.flowers.iter().map(|flower| flower.position));
+        let parameters = &*self.parameters;
+        let distributions = &self.distributions;
+        let previous_positions = &self.previous_positions;
+        let best_position = self.best_solution;
+        let pollinate = |flower: &mut Flower<N, RngType>| flower.pollinate(parameters, distributions, previous_positions, best_position);
+        if parameters.parallel {
+            self.flowers.par_iter_mut().for_each(pollinate);
+        } else {
+            self.flowers.iter_mut().for_each(pollinate);
+        }
+    }
+
+    // The values of the candidates, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.flowers.len();
+        for (flower, &value) in self.flowers.iter_mut().zip(values) {
+            flower.take_candidate(value);
+        }
+        // Takes the first of equally good flowers
+        let best_flower = self.flowers.iter().min_by_key(|flower| Fitness::minimize(flower.function_value)).unwrap();
+        if Fitness::minimize(best_flower.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_flower.function_value;
+            self.best_solution = best_flower.position;
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.flowers.iter().map(|flower| flower.position), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.pollinate();
+        self.evaluate_candidates();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.pollinate();
+        positions.extend(self.flowers.iter().map(|flower| flower.candidate));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.flowers.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.flower_count {
+            self.flowers.push(Flower::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_candidates();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.flowers.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.flowers.iter().map(|flower| flower.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::{run_lockstep, Optimizer, RunLength}};
+
+    use super::WorldState;
+
+    #[test]
+    fn pollination_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let world = |seed| WorldState::<10, _>::new(20, function, function.get_bounds(), 0.8, Xoshiro256PlusPlus::seed_from_u64(seed));
+        let mut sequential = (0..3).map(world).collect::<Vec<_>>();
+        let initial_bests = sequential.iter().map(|world| world.best_solution_value()).collect::<Vec<_>>();
+        for world in &mut sequential {
+            world.do_all_iterations(100);
+        }
+        // The same runs with the iterations split in two halves
+        let mut lockstep = (0..3).map(world).collect::<Vec<_>>();
+        run_lockstep(&mut lockstep, &function, RunLength::Iterations(100), None, |_, _| {});
+        for ((sequential, lockstep), initial_best) in sequential.iter().zip(&lockstep).zip(initial_bests) {
+            assert!(sequential.best_solution_value() < initial_best);
+            assert_eq!(sequential.best_solution_value(), lockstep.best_solution_value());
+        }
+    }
+}
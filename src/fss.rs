@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub fish_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub weight_scale: Real, // The largest weight a fish can reach, they start at half of it and can't go below 1
+    pub step_bounds: (Real, Real), // The individual step at the first iteration and after the last, as fractions of the width of the bounds. The volitive step is twice as large
+    pub parallel: bool, // Moves and evaluates the fish on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.fish_count == 0 {
+            return Err("There must be at least one fish");
+        }
+        if self.weight_scale.is_nan() || self.weight_scale <= 1.0 {
+            return Err("Weight scale must be greater than 1");
+        }
+        if !(self.step_bounds.0 > 0.0 && self.step_bounds.1 > 0.0) {
+            return Err("Steps must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The mean of the positions, weighted by the weights
+pub fn barycenter<const N: usize>(positions: &[VectorN<Real, N>], weights: &[Real]) -> VectorN<Real, N> {
+    let mut barycenter = VectorN::default();
+    for (&position, &weight) in positions.iter().zip(weights) {
+        barycenter += position * weight;
+    }
+    return barycenter / weights.iter().sum::<Real>();
+}
+
+#[derive(Clone, Debug)]
+pub struct Fish<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    weight: Real,
+    candidate: VectorN<Real, N>, // Of the individual movement
+    displacement: VectorN<Real, N>, // Δx of the last individual movement, 0 if the fish stayed
+    improvement: Real, // Δf of the last individual movement, 0 if the fish stayed
+    random_source: RngType, // Seeded from the population seed and the fish's index, so fish can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Fish<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole school at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self {
+            position,
+            function_value: Real::INFINITY,
+            weight: parameters.weight_scale / 2.0,
+            candidate: position,
+            displacement: VectorN::default(),
+            improvement: 0.0,
+            random_source,
+        };
+    }
+
+    // A uniformly random step of up to the given size in every coordinate
+    fn propose_individual<F>(&mut self, parameters: &Parameters<N, F>, step: Real) {
+        self.candidate = self.position + VectorN::random_uniform((-step, step), &mut self.random_source);
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // The fish only swims there if it's better
+    fn take_individual(&mut self, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.function_value) {
+            self.displacement = self.candidate - self.position;
+            self.improvement = self.function_value - value;
+            self.position = self.candidate;
+            self.function_value = value;
+        } else {
+            self.displacement = VectorN::default();
+            self.improvement = 0.0;
+        }
+    }
+
+    // A random fraction of the step towards the barycenter if the school got heavier, away from it if it didn't
+    fn move_volitive<F>(&mut self, parameters: &Parameters<N, F>, barycenter: VectorN<Real, N>, step: Real, contract: bool) {
+        let offset = self.position - barycenter;
+        let distance = offset.norm_l2();
+        if distance > 0.0 {
+            let signed_step = if contract { -step } else { step };
+            self.position += offset * (signed_step * self.random_source.gen::<Real>() / distance);
+            self.position.clamp(parameters.bounds);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    school: Vec<Fish<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    total_weight: Real, // After the last feeding
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    weights: Vec<Real>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(fish_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            fish_count, function, bounds,
+            weight_scale: 5000.0,
+            step_bounds: (0.1, 0.001),
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            school: Vec::with_capacity(parameters.fish_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            total_weight: 0.0,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.fish_count),
+            weights: Vec::with_capacity(parameters.fish_count),
+            values: Vec::with_capacity(parameters.fish_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // The summed weights of the school
+    pub fn total_weight(&self) -> Real {
+        return self.total_weight;
+    }
+
+    // Of self.positions, into self.values
+    fn evaluate_positions(&mut self) {
+        self.evaluation_count += self.positions.len();
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    fn update_best(&mut self) {
+        // Takes the first of equally good fish
+        let best_fish = self.school.iter().min_by_key(|fish| Fitness::minimize(fish.function_value)).unwrap();
+        if Fitness::minimize(best_fish.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_fish.function_value;
+            self.best_solution = best_fish.position;
+        }
+    }
+
+    fn individual_movement(&mut self, step: Real) {
+        let parameters = &*self.parameters;
+        let propose = |fish: &mut Fish<N, RngType>| fish.propose_individual(parameters, step);
+        if parameters.parallel {
+            self.school.par_iter_mut().for_each(propose);
+        } else {
+            self.school.iter_mut().for_each(propose);
+        }
+        self.positions.clear();
+        self.positions.extend(self.school.iter().map(|fish| fish.candidate));
+        self.evaluate_positions();
+        for (fish, &value) in self.school.iter_mut().zip(&self.values) {
+            fish.take_individual(value);
+        }
+    }
+
+    // Every fish gains weight in proportion to how much it improved, relative to the largest improvement
+    fn feeding(&mut self) {
+        let largest_improvement = self.school.iter().map(|fish| fish.improvement).fold(0.0, Real::max);
+        if largest_improvement > 0.0 && largest_improvement.is_finite() {
+            for fish in &mut self.school {
+                fish.weight = (fish.weight + fish.improvement / largest_improvement).clamp(1.0, self.parameters.weight_scale);
+            }
+        }
+    }
+
+    // The whole school follows the displacements of the fish that improved, weighted by how much they did
+    fn collective_instinctive_movement(&mut self) {
+        let mut direction = VectorN::default();
+        let mut total_improvement = 0.0;
+        for fish in &self.school {
+            direction += fish.displacement * fish.improvement;
+            total_improvement += fish.improvement;
+        }
+        if total_improvement > 0.0 && total_improvement.is_finite() {
+            direction /= total_improvement;
+            for fish in &mut self.school {
+                fish.position += direction;
+                fish.position.clamp(self.parameters.bounds);
+            }
+        }
+    }
+
+    fn collective_volitive_movement(&mut self, step: Real) {
+        self.positions.clear();
+        self.positions.extend(self.school.iter().map(|fish| fish.position));
+        self.weights.clear();
+        self.weights.extend(self.school.iter().map(|fish| fish.weight));
+        let barycenter = barycenter(&self.positions, &self.weights);
+        let total_weight = self.weights.iter().sum::<Real>();
+        let contract = total_weight > self.total_weight;
+        self.total_weight = total_weight;
+        let parameters = &*self.parameters;
+        let move_volitive = |fish: &mut Fish<N, RngType>| fish.move_volitive(parameters, barycenter, step, contract);
+        if parameters.parallel {
+            self.school.par_iter_mut().for_each(move_volitive);
+        } else {
+            self.school.iter_mut().for_each(move_volitive);
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // The school is evaluated after the individual movement and after the collective ones, so the iteration can't be
+    // split into a proposal and its values
+    fn do_iteration(&mut self, iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let (initial_step, final_step) = self.parameters.step_bounds;
+        let step = (initial_step + (final_step - initial_step) * progress) * (self.parameters.bounds.1 - self.parameters.bounds.0);
+        self.individual_movement(step);
+        self.update_best();
+        self.feeding();
+        self.collective_instinctive_movement();
+        self.collective_volitive_movement(2.0 * step);
+        self.positions.clear();
+        self.positions.extend(self.school.iter().map(|fish| fish.position));
+        self.evaluate_positions();
+        for (fish, &value) in self.school.iter_mut().zip(&self.values) {
+            fish.function_value = value;
+        }
+        self.update_best();
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.school.iter().map(|fish| fish.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.school.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.fish_count {
+            self.school.push(Fish::new(&self.parameters, population_seed, index));
+        }
+        self.total_weight = self.school.iter().map(|fish| fish.weight).sum();
+        self.positions.clear();
+        self.positions.extend(self.school.iter().map(|fish| fish.position));
+        self.evaluate_positions();
+        for (fish, &value) in self.school.iter_mut().zip(&self.values) {
+            fish.function_value = value;
+        }
+        self.update_best();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * self.school.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.school.iter().map(|fish| fish.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer, vector::VectorN};
+
+    use super::{barycenter, WorldState};
+
+    #[test]
+    fn school_test() {
+        let positions = [VectorN::new([0.0, 0.0]), VectorN::new([4.0, 8.0])];
+        let centre = barycenter(&positions, &[3.0, 1.0]);
+        assert_eq!((centre[0], centre[1]), (1.0, 2.0));
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(30, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert_eq!(world.evaluation_count(), 30 + 60 * 200);
+        assert!((1.0..=5000.0 * 30.0).contains(&world.total_weight()));
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -1,51 +1,93 @@
-use std::f64::consts::{E, TAU};
+use rayon::{iter::{IndexedParallelIterator, ParallelIterator}, slice::{ParallelSlice, ParallelSliceMut}};
 
+use crate::real::{consts::{E, TAU}, Real};
 use crate::vector::VectorN;
 use crate::vector::QuickFold;
 
+// What the optimizers minimize, Functions for the built in benchmarks. Whole populations are passed to the batch methods,
+// objectives with a per-call overhead (another process, a GPU) can override them
 pub trait Function<const N: usize> {
-	fn get_function(&self) -> fn(input: VectorN<N>) -> f64;
-	fn get_bounds(&self) -> (f64, f64);
+	fn evaluate(&self, input: VectorN<Real, N>) -> Real;
+
+	// Replaces the contents of values, which doesn't allocate once it has grown to the population size
+	fn evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+		values.clear();
+		values.extend(inputs.iter().map(|&input| self.evaluate(input)));
+	}
+
+	// The same, with the inputs split into one batch per rayon thread
+	fn par_evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) where Self: Sync {
+		par_evaluate_each(self, inputs, values);
+	}
+}
+
+fn par_evaluate_each<const N: usize, F: Function<N> + Sync + ?Sized>(function: &F, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+	values.clear();
+	values.resize(inputs.len(), 0.0);
+	let chunk_size = inputs.len().div_ceil(rayon::current_num_threads()).max(1);
+	values.par_chunks_mut(chunk_size).zip(inputs.par_chunks(chunk_size)).for_each(|(values, inputs)| {
+		for (value, &input) in values.iter_mut().zip(inputs) {
+			*value = function.evaluate(input);
+		}
+	});
 }
 
+// Each function reads the coordinates once, without building intermediate arrays. They take slices so the same code
+// serves the dynamic dimension path, for a fixed N it is inlined and the length is a constant again
+
 // functions 1
-fn ackley<const N: usize>(input: VectorN<N>) -> f64 {
-	return -20.0 * (-0.2 * ((N as f64).recip() * input.coordinates.map(|a| a.powi(2)).sum()).sqrt()).exp() -
-		((N as f64).recip() * input.coordinates.map(|a| (TAU * a).cos()).sum()).exp() +
-		E + 20.0;
+fn ackley(input: &[Real]) -> Real {
+	let mut squares = 0.0;
+	let mut cosines = 0.0;
+	for &a in input {
+		squares += a * a;
+		cosines += (TAU * a).cos();
+	}
+	let recip = (input.len() as Real).recip();
+	return -20.0 * (-0.2 * (recip * squares).sqrt()).exp() - (recip * cosines).exp() + E + 20.0;
 }
 
 // functions 1
-fn schwefel<const N: usize>(input: VectorN<N>) -> f64 {
-	let absolutes = input.coordinates.map(f64::abs);
-	return absolutes.map(|a| a.powi(2)).sum() + absolutes.product();
+fn schwefel(input: &[Real]) -> Real {
+	let mut squares = 0.0;
+	let mut product = 1.0;
+	for &a in input {
+		squares += a * a;
+		product *= a.abs();
+	}
+	return squares + product;
 }
 
 // functions 1
-fn brown<const N: usize>(input: VectorN<N>) -> f64 {
-	return input.coordinates.map(|a| a.powi(2)).array_windows::<2>().map(|&[a, a_1]| {
-		return a.powf(a_1 + 1.0) + a_1.powf(a + 1.0);
-	}).sum();
+fn brown(input: &[Real]) -> Real {
+	let Some((&first, rest)) = input.split_first() else {
+		return 0.0;
+	};
+	// Every square is used by two neighbouring terms, it's carried over to the next one
+	let mut square = first * first;
+	let mut sum = 0.0;
+	for &a_1 in rest {
+		let square_1 = a_1 * a_1;
+		sum += square.powf(square_1 + 1.0) + square_1.powf(square + 1.0);
+		square = square_1;
+	}
+	return sum;
 }
 
 // functions 2
-fn rastrigin<const N: usize>(input: VectorN<N>) -> f64 {
-	return input.coordinates.map(|a| {
-		return a.powi(2) - 10.0 * (TAU * a).cos() + 10.0;
-	}).sum();
+fn rastrigin(input: &[Real]) -> Real {
+	return input.map_sum(|a| a * a - 10.0 * (TAU * a).cos() + 10.0);
 }
 
 // functions 2
-fn schwefel2<const N: usize>(input: VectorN<N>) -> f64 {
-	return input.coordinates.map(|a| {
-		return (a * a.abs().sqrt().sin()).abs();
-	}).sum();
+fn schwefel2(input: &[Real]) -> Real {
+	return input.map_sum(|a| (a * a.abs().sqrt().sin()).abs());
 }
 
 // functions 2
-fn solomon<const N: usize>(input: VectorN<N>) -> f64 {
-	let sum_of_squares = input.coordinates.map(|a| a.powi(2)).sum();
-	return 1.0 - (TAU * sum_of_squares.sqrt()).cos() + 0.1 * sum_of_squares.sqrt();
+fn solomon(input: &[Real]) -> Real {
+	let magnitude = input.magnitude();
+	return 1.0 - (TAU * magnitude).cos() + 0.1 * magnitude;
 }
 
 
@@ -61,18 +103,22 @@ pub enum Functions<const N: usize> {
 
 impl<const N: usize> Functions<N> {
 	pub fn make_from_name(name: &str) -> Self {
+		return Self::from_name(name).unwrap_or_else(|| panic!("Nonexistent function passed: `{name}`"));
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
 		match name {
-			"ackley" => return Self::Ackley,
-			"schwefel" => return Self::Schwefel,
-			"brown" => return Self::Brown,
-			"rastrigin" => return Self::Rastrigin,
-			"schwefel2" => return Self::Schwefel2,
-			"solomon" => return Self::Solomon,
-			_ => panic!("Nonexistent function passed: `{name}`"),
+			"ackley" => return Some(Self::Ackley),
+			"schwefel" => return Some(Self::Schwefel),
+			"brown" => return Some(Self::Brown),
+			"rastrigin" => return Some(Self::Rastrigin),
+			"schwefel2" => return Some(Self::Schwefel2),
+			"solomon" => return Some(Self::Solomon),
+			_ => return None,
 		}
 	}
 
-	pub fn get_bounds(self) -> (f64, f64) {
+	pub fn get_bounds(self) -> (Real, Real) {
 		match self {
 			Self::Ackley => return (-32.0, 32.0),
 			Self::Schwefel => return (-10.0, 10.0),
@@ -83,7 +129,12 @@ impl<const N: usize> Functions<N> {
 		}
 	}
 
-	pub fn calculate(self, input: VectorN<N>) -> f64 {
+	pub fn calculate(self, input: VectorN<Real, N>) -> Real {
+		return self.calculate_dynamic(&input.coordinates);
+	}
+
+	// Any number of dimensions, N is ignored. The `dispatch` benchmark group compares it with calculate
+	pub fn calculate_dynamic(self, input: &[Real]) -> Real {
 		match self {
 			Functions::Ackley => return ackley(input),
 			Functions::Schwefel => return schwefel(input),
@@ -93,4 +144,116 @@ impl<const N: usize> Functions<N> {
 			Functions::Solomon => return solomon(input),
 		}
 	}
-}
\ No newline at end of file
+
+	pub fn evaluate_batch(self, inputs: &[VectorN<Real, N>]) -> Vec<Real> {
+		let mut values = Vec::with_capacity(inputs.len());
+		self.evaluate_batch_into(inputs, &mut values);
+		return values;
+	}
+
+	// Replaces the contents of values, which doesn't allocate once it has grown to the population size
+	pub fn evaluate_batch_into(self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+		values.clear();
+		#[cfg(feature = "gpu")]
+		if let Some(gpu) = crate::gpu::active() {
+			values.extend(gpu.evaluate(self, inputs));
+			return;
+		}
+		values.extend(inputs.iter().map(|&input| self.calculate(input)));
+	}
+
+	// The same as evaluate_batch, with the inputs split into one batch per rayon thread
+	pub fn par_evaluate_batch(self, inputs: &[VectorN<Real, N>]) -> Vec<Real> {
+		let mut values = Vec::with_capacity(inputs.len());
+		self.par_evaluate_batch_into(inputs, &mut values);
+		return values;
+	}
+
+	pub fn par_evaluate_batch_into(self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+		#[cfg(feature = "gpu")]
+		if crate::gpu::active().is_some() {
+			return self.evaluate_batch_into(inputs, values); // Already parallel, and one submission is cheaper than several
+		}
+		par_evaluate_each(&self, inputs, values);
+	}
+}
+
+impl<const N: usize> Function<N> for Functions<N> {
+	fn evaluate(&self, input: VectorN<Real, N>) -> Real {
+		return self.calculate(input);
+	}
+
+	fn evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+		Functions::evaluate_batch_into(*self, inputs, values);
+	}
+
+	fn par_evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+		Functions::par_evaluate_batch_into(*self, inputs, values);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{real::{self, Real}, vector::VectorN};
+
+	use super::Functions;
+
+	const ALL: [&str; 6] = ["ackley", "schwefel", "brown", "rastrigin", "schwefel2", "solomon"];
+
+	// Relative, as the values span several orders of magnitude, and loose enough for the f32 feature
+	fn assert_close(actual: Real, expected: f64) {
+		let tolerance = 64.0 * real::to_f64(Real::EPSILON) * expected.abs().max(1.0);
+		assert!((real::to_f64(actual) - expected).abs() <= tolerance, "{actual} is not {expected}");
+	}
+
+	fn evaluate<const N: usize>(name: &str, coordinates: [f64; N]) -> Real {
+		return Functions::<N>::make_from_name(name).calculate(VectorN::new(coordinates.map(real::from_f64)));
+	}
+
+	#[test]
+	fn optimum_test() {
+		for name in ALL {
+			assert_close(evaluate(name, [0.0; 30]), 0.0);
+		}
+	}
+
+	// Reference values computed separately, straight from the textbook definitions
+	#[test]
+	fn reference_values_test() {
+		let expected = [5.972029779887098, 6.25, 22.65783081199238, 25.25, 3.141821346333399, 1.4856480107627097];
+		for (name, expected) in ALL.into_iter().zip(expected) {
+			assert_close(evaluate(name, [1.0, -2.0, 0.5]), expected);
+		}
+		let expected = [9.102322154590986, 28.1055, 680.6794465641626, 80.68, 7.857391492830365, 0.5954643443490077];
+		for (name, expected) in ALL.into_iter().zip(expected) {
+			assert_close(evaluate(name, [0.3, -0.7, 2.5, 1.1, -4.2]), expected);
+		}
+	}
+
+	#[test]
+	fn single_dimension_test() {
+		assert_close(evaluate("brown", [3.0]), 0.0);
+		assert_close(evaluate("rastrigin", [0.5]), 20.25);
+		assert_close(evaluate("schwefel", [-3.0]), 12.0);
+	}
+
+	#[test]
+	fn dynamic_matches_const_test() {
+		let coordinates = vec![0.3, -0.7, 2.5, 1.1, -4.2].into_iter().map(real::from_f64).collect::<Vec<_>>();
+		for name in ALL {
+			let function = Functions::<5>::make_from_name(name);
+			assert_eq!(function.calculate(VectorN::new(coordinates.clone().try_into().unwrap())), function.calculate_dynamic(&coordinates));
+		}
+	}
+
+	#[test]
+	fn batch_matches_single_test() {
+		let inputs = (0..37).map(|index| VectorN::new([0.1, -0.2, 0.3, -0.4, 0.5, -0.6, 0.7, -0.8, 0.9, -1.0, 1.1].map(|a| a * index as Real))).collect::<Vec<_>>();
+		for name in ALL {
+			let function = Functions::<11>::make_from_name(name);
+			let singles = inputs.iter().map(|&input| function.calculate(input)).collect::<Vec<_>>();
+			assert_eq!(singles, function.evaluate_batch(&inputs));
+			assert_eq!(singles, function.par_evaluate_batch(&inputs));
+		}
+	}
+}
@@ -0,0 +1,218 @@
+// Evaluation of whole populations in a compute shader, built with `--features gpu`. Once enable() is called every
+// Functions::evaluate_batch call, i.e. every iteration of every optimizer, uploads the positions and reads back the
+// values. The shader works in f32, as f64 is missing on most GPUs, so without `--features f32` the values are rounded
+// to about 7 significant digits
+
+use std::{borrow::Cow, sync::{mpsc, OnceLock}};
+
+use wgpu::util::DeviceExt;
+
+use crate::{functions::Functions, real::Real, vector::VectorN};
+
+const SHADER: &str = r#"
+struct Parameters {
+    function: u32,
+    count: u32,
+    dimensions: u32,
+    padding: u32,
+}
+
+@group(0) @binding(0) var<uniform> parameters: Parameters;
+@group(0) @binding(1) var<storage, read> positions: array<f32>;
+@group(0) @binding(2) var<storage, read_write> values: array<f32>;
+
+const TAU: f32 = 6.283185307179586;
+const E: f32 = 2.718281828459045;
+
+fn ackley(start: u32, dimensions: u32) -> f32 {
+    var squares = 0.0;
+    var cosines = 0.0;
+    for (var i = start; i < start + dimensions; i++) {
+        squares += positions[i] * positions[i];
+        cosines += cos(TAU * positions[i]);
+    }
+    let n = f32(dimensions);
+    return -20.0 * exp(-0.2 * sqrt(squares / n)) - exp(cosines / n) + E + 20.0;
+}
+
+fn schwefel(start: u32, dimensions: u32) -> f32 {
+    var squares = 0.0;
+    var product = 1.0;
+    for (var i = start; i < start + dimensions; i++) {
+        squares += positions[i] * positions[i];
+        product *= abs(positions[i]);
+    }
+    return squares + product;
+}
+
+fn brown(start: u32, dimensions: u32) -> f32 {
+    var sum = 0.0;
+    for (var i = start; i + 1u < start + dimensions; i++) {
+        let a = positions[i] * positions[i];
+        let a_1 = positions[i + 1u] * positions[i + 1u];
+        sum += pow(a, a_1 + 1.0) + pow(a_1, a + 1.0);
+    }
+    return sum;
+}
+
+fn rastrigin(start: u32, dimensions: u32) -> f32 {
+    var sum = 0.0;
+    for (var i = start; i < start + dimensions; i++) {
+        sum += positions[i] * positions[i] - 10.0 * cos(TAU * positions[i]) + 10.0;
+    }
+    return sum;
+}
+
+fn schwefel2(start: u32, dimensions: u32) -> f32 {
+    var sum = 0.0;
+    for (var i = start; i < start + dimensions; i++) {
+        sum += abs(positions[i] * sin(sqrt(abs(positions[i]))));
+    }
+    return sum;
+}
+
+fn solomon(start: u32, dimensions: u32) -> f32 {
+    var squares = 0.0;
+    for (var i = start; i < start + dimensions; i++) {
+        squares += positions[i] * positions[i];
+    }
+    return 1.0 - cos(TAU * sqrt(squares)) + 0.1 * sqrt(squares);
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= parameters.count {
+        return;
+    }
+    let start = index * parameters.dimensions;
+    var value = 0.0;
+    switch parameters.function {
+        case 0u: { value = ackley(start, parameters.dimensions); }
+        case 1u: { value = schwefel(start, parameters.dimensions); }
+        case 2u: { value = brown(start, parameters.dimensions); }
+        case 3u: { value = rastrigin(start, parameters.dimensions); }
+        case 4u: { value = schwefel2(start, parameters.dimensions); }
+        default: { value = solomon(start, parameters.dimensions); }
+    }
+    values[index] = value;
+}
+"#;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+static EVALUATOR: OnceLock<GpuEvaluator> = OnceLock::new();
+
+pub(crate) struct GpuEvaluator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuEvaluator {
+    fn new() -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        })).unwrap_or_else(|| panic!("No GPU adapter found"));
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("objective evaluation"),
+            required_features: wgpu::Features::empty(),
+            required_limits: adapter.limits(),
+            memory_hints: wgpu::MemoryHints::Performance,
+        }, None)).unwrap_or_else(|error| panic!("Could not open the GPU: {error}"));
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("benchmark functions"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("benchmark functions"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        return Self { device, queue, pipeline };
+    }
+
+    #[allow(clippy::unnecessary_cast)] // Real may be f32 already
+    pub(crate) fn evaluate<const N: usize>(&self, function: Functions<N>, inputs: &[VectorN<Real, N>]) -> Vec<Real> {
+        if inputs.is_empty() || N == 0 {
+            return inputs.iter().map(|&input| function.calculate(input)).collect();
+        }
+        let function_index: u32 = match function {
+            Functions::Ackley => 0,
+            Functions::Schwefel => 1,
+            Functions::Brown => 2,
+            Functions::Rastrigin => 3,
+            Functions::Schwefel2 => 4,
+            Functions::Solomon => 5,
+        };
+        let parameters = [function_index, inputs.len() as u32, N as u32, 0];
+        let positions = inputs.iter().flat_map(|input| input.coordinates.map(|coordinate| coordinate as f32)).collect::<Vec<f32>>();
+        let values_size = (inputs.len() * size_of::<f32>()) as u64;
+
+        let parameters_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("parameters"),
+            contents: bytemuck::cast_slice(&parameters),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let positions_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("positions"),
+            contents: bytemuck::cast_slice(&positions),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let values_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("values"),
+            size: values_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: values_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: parameters_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: positions_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: values_buffer.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((inputs.len() as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&values_buffer, 0, &readback_buffer, 0, values_size);
+        let submission = self.queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = mpsc::channel();
+        readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| sender.send(result).unwrap());
+        self.device.poll(wgpu::Maintain::wait_for(submission)).panic_on_timeout();
+        receiver.recv().unwrap().unwrap_or_else(|error| panic!("Could not read the values back from the GPU: {error}"));
+        let values = bytemuck::cast_slice::<u8, f32>(&readback_buffer.slice(..).get_mapped_range()).iter().map(|&value| value as Real).collect();
+        readback_buffer.unmap();
+        return values;
+    }
+}
+
+// Panics if there is no usable GPU
+pub fn enable() {
+    EVALUATOR.get_or_init(GpuEvaluator::new);
+}
+
+pub(crate) fn active() -> Option<&'static GpuEvaluator> {
+    return EVALUATOR.get();
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// The comfort zone coefficient shrinks linearly from the first to the second over the run
+const COMFORT_ZONE: (Real, Real) = (1.0, 0.00004);
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub grasshopper_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub attraction_intensity: Real, // f of s(r). 0.5 in the paper
+    pub attractive_length_scale: Real, // l of s(r). 1.5 in the paper
+    pub parallel: bool, // Moves and evaluates the grasshoppers on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.grasshopper_count < 2 {
+            return Err("There must be at least two grasshoppers, to interact with each other");
+        }
+        if self.attraction_intensity.is_nan() || self.attraction_intensity < 0.0 {
+            return Err("Intensity of attraction can't be negative");
+        }
+        if self.attractive_length_scale.is_nan() || self.attractive_length_scale <= 0.0 {
+            return Err("Attractive length scale must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The social force between two grasshoppers at distance r. Attraction where it's positive, repulsion where it's
+// negative, and the comfort zone in between
+pub fn social_force(distance: Real, attraction_intensity: Real, attractive_length_scale: Real) -> Real {
+    return attraction_intensity * (-distance / attractive_length_scale).exp() - (-distance).exp();
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    swarm: Vec<VectorN<Real, N>>, // The update is deterministic, so the grasshoppers are only their positions
+    pub best_solution: VectorN<Real, N>, // The target all grasshoppers move around
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    next: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(grasshopper_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            grasshopper_count, function, bounds,
+            attraction_intensity: 0.5,
+            attractive_length_scale: 1.5,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            swarm: Vec::with_capacity(parameters.grasshopper_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            next: Vec::with_capacity(parameters.grasshopper_count),
+            values: Vec::with_capacity(parameters.grasshopper_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_swarm(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.swarm, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.swarm, &mut self.values);
+        }
+    }
+
+    // X_i = c * sum over j of c * (ub - lb) / 2 * s(d_ij) * (x_j - x_i) / d_ij + T. As in the authors' implementation, the
+    // distances given to s are mapped into [2, 4), where s doesn't vanish yet
+    fn move_swarm(&mut self, iteration_count: usize) {
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let comfort_zone = COMFORT_ZONE.0 - progress * (COMFORT_ZONE.0 - COMFORT_ZONE.1);
+        let parameters = &*self.parameters;
+        let half_width = (parameters.bounds.1 - parameters.bounds.0) / 2.0;
+        let swarm = &self.swarm;
+        let target = self.best_solution;
+        let move_grasshopper = |(index, next): (usize, &mut VectorN<Real, N>)| {
+            let position = swarm[index];
+            let mut social = VectorN::default();
+            for (other_index, &other) in swarm.iter().enumerate() {
+                let distance = (other - position).norm_l2();
+                if other_index == index || distance == 0.0 {
+                    continue;
+                }
+                let force = social_force(2.0 + distance % 2.0, parameters.attraction_intensity, parameters.attractive_length_scale);
+                social += (other - position) * (comfort_zone * half_width * force / distance);
+            }
+            *next = social * comfort_zone + target;
+            next.clamp(parameters.bounds);
+        };
+        self.next.clear();
+        self.next.resize(swarm.len(), VectorN::default());
+        if parameters.parallel {
+            self.next.par_iter_mut().enumerate().for_each(move_grasshopper);
+        } else {
+            self.next.iter_mut().enumerate().for_each(move_grasshopper);
+        }
+        std::mem::swap(&mut self.swarm, &mut self.next);
+    }
+
+    // The values of the grasshoppers, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.swarm.len();
+        // Takes the first of equally good grasshoppers
+        let (best_index, &best_value) = values.iter().enumerate().min_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap();
+        if Fitness::minimize(best_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_value;
+            self.best_solution = self.swarm[best_index];
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.swarm.iter().copied(), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_swarm(iteration_count);
+        self.evaluate_swarm();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_swarm(iteration_count);
+        positions.extend_from_slice(&self.swarm);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.swarm.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.grasshopper_count {
+            let mut random_source = agent_random_source::<RngType>(population_seed, index);
+            self.swarm.push(VectorN::random_uniform(self.parameters.bounds, &mut random_source));
+        }
+        self.evaluate_swarm();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.swarm.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend_from_slice(&self.swarm);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::{social_force, WorldState};
+
+    #[test]
+    fn comfort_zone_test() {
+        // Repulsion close by, attraction further away
+        assert!(social_force(0.5, 0.5, 1.5) < 0.0);
+        assert!(social_force(3.0, 0.5, 1.5) > 0.0);
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert_eq!(world.evaluation_count(), 20 + 20 * 200);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub wolf_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub a_bounds: (Real, Real), // a at the first iteration and after the last, decreases linearly. 2 to 0 in the paper
+    pub parallel: bool, // Moves and evaluates the wolves on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.wolf_count < 3 {
+            return Err("The pack needs at least three wolves, for the alpha, beta and delta");
+        }
+        if !(self.a_bounds.0 >= 0.0 && self.a_bounds.1 >= 0.0) {
+            return Err("a can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Wolf<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    random_source: RngType, // Seeded from the population seed and the wolf's index, so wolves can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Wolf<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole pack at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, random_source };
+    }
+
+    // To the average of the positions the alpha, beta and delta each suggest, with a random A and C per coordinate
+    fn move_wolf<F>(&mut self, parameters: &Parameters<N, F>, leaders: &[VectorN<Real, N>; 3], a: Real) {
+        let mut new_position = VectorN::default();
+        for leader in leaders {
+            for dimension in 0..N {
+                let coefficient_a = a * (2.0 * self.random_source.gen::<Real>() - 1.0);
+                let coefficient_c = 2.0 * self.random_source.gen::<Real>();
+                let distance = (coefficient_c * leader[dimension] - self.position[dimension]).abs();
+                new_position[dimension] += leader[dimension] - coefficient_a * distance;
+            }
+        }
+        self.position = new_position / 3.0;
+        self.position.clamp(parameters.bounds);
+    }
+}
+
+// Pushes the worse leaders down, the first of equally good positions stays ahead
+fn update_leaders<const N: usize>(leaders: &mut [(Real, VectorN<Real, N>); 3], value: Real, position: VectorN<Real, N>) {
+    if let Some(rank) = leaders.iter().position(|&(leader_value, _)| Fitness::minimize(value) < Fitness::minimize(leader_value)) {
+        leaders[rank..].rotate_right(1);
+        leaders[rank] = (value, position);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    pack: Vec<Wolf<N, RngType>>,
+    leaders: [(Real, VectorN<Real, N>); 3], // The alpha, beta and delta, the best three positions found so far
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(wolf_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            wolf_count, function, bounds,
+            a_bounds: (2.0, 0.0),
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            pack: Vec::with_capacity(parameters.wolf_count),
+            leaders: [(Real::INFINITY, VectorN::default()); 3],
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.wolf_count),
+            values: Vec::with_capacity(parameters.wolf_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // Best first
+    pub fn leaders(&self) -> &[(Real, VectorN<Real, N>); 3] {
+        return &self.leaders;
+    }
+
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.pack.iter().map(|wolf| wolf.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    fn move_pack(&mut self, iteration_count: usize) {
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let a = self.parameters.a_bounds.0 + (self.parameters.a_bounds.1 - self.parameters.a_bounds.0) * progress;
+        let parameters = &*self.parameters;
+        let leaders = self.leaders.map(|(_, position)| position);
+        let move_wolf = |wolf: &mut Wolf<N, RngType>| wolf.move_wolf(parameters, &leaders, a);
+        if parameters.parallel {
+            self.pack.par_iter_mut().for_each(move_wolf);
+        } else {
+            self.pack.iter_mut().for_each(move_wolf);
+        }
+    }
+
+    // The values of the moved pack, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.leaders[0].0;
+        self.evaluation_count += self.pack.len();
+        for (wolf, &value) in self.pack.iter().zip(values) {
+            update_leaders(&mut self.leaders, value, wolf.position);
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.pack.iter().map(|wolf| wolf.position), previous_best_value, self.leaders[0].1, self.leaders[0].0);
+        self.iteration += 1;
+    }
+
+    fn evaluate_initial_population(&mut self) {
+        self.evaluation_count = self.pack.len();
+        self.evaluate_positions();
+        for (wolf, &value) in self.pack.iter().zip(&self.values) {
+            update_leaders(&mut self.leaders, value, wolf.position);
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_pack(iteration_count);
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_pack(iteration_count);
+        positions.extend(self.pack.iter().map(|wolf| wolf.position));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+    }
+
+    fn reset(&mut self) {
+        self.leaders = [(Real::INFINITY, VectorN::default()); 3];
+        self.iteration = 0;
+        self.pack.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.wolf_count {
+            self.pack.push(Wolf::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_initial_population();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.leaders[0].1;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.leaders[0].0;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.pack.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.pack.iter().map(|wolf| wolf.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn leaders_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        world.do_all_iterations(200);
+        let [alpha, beta, delta] = world.leaders().map(|(value, _)| value);
+        assert!(alpha <= beta && beta <= delta);
+        assert!(world.best_solution_value() < 1e-3);
+    }
+}
@@ -0,0 +1,102 @@
+use clap::{error::ErrorKind, CommandFactory};
+
+use crate::{apply_overrides, check_eval_budget, dashboard::Dashboard, matfile::MatWriter, metrics, output::{BatchSummary, RunRecordWriter, SummaryPrinter}, parse_algorithm_arguments, pool::WorkerPool, queue_batch, store, target_value_for, BatchOutputs, Config};
+
+// Every combination of one value per swept parameter, as (parameter, value) pairs
+fn grid_cells(grid: &[String]) -> Vec<Vec<(String, String)>> {
+    let mut cells = vec![Vec::new()];
+    for entry in grid {
+        let Some((parameter, values)) = entry.split_once('=') else {
+            panic!("Invalid grid entry: `{entry}`, expected `parameter=value,value,...`");
+        };
+        cells = cells.into_iter().flat_map(|cell| {
+            return values.split(',').map(move |value| {
+                let mut cell = cell.clone();
+                cell.push((parameter.to_string(), value.to_string()));
+                return cell;
+            });
+        }).collect();
+    }
+    return cells;
+}
+
+pub fn run(config: &Config, grid: &[String], algorithm_arguments: &[String], thread_count: usize) {
+    let base_command = parse_algorithm_arguments(algorithm_arguments);
+    let tries = config.try_count.unwrap();
+    let store = config.store.as_ref().map(|path| store::open(path, config.store_trace));
+    let mut printer = SummaryPrinter::new(config.output_format, false);
+    let mut run_records = config.run_records.as_deref().map(RunRecordWriter::new);
+    let mut mat_writer = config.mat_output.as_deref().map(MatWriter::new);
+    let pool = WorkerPool::new(thread_count);
+    // Never drawn, it keeps the progress of the cells for the metrics
+    let dashboard = config.metrics_listen.as_ref().map(|listen_address| {
+        let dashboard = Dashboard::new();
+        metrics::spawn(listen_address, dashboard.clone(), pool.queue_length());
+        return dashboard;
+    });
+
+    // Every cell is queued up front, the results are then taken in order
+    let mut pending_cells = Vec::new();
+    for cell in grid_cells(grid) {
+        let cell_description = cell.iter().map(|(parameter, value)| format!("{parameter}={value}")).collect::<Vec<_>>().join(" ");
+        for function_name in &config.functions {
+            // The grid values are applied last, so they win over --override
+            let cell_overrides = cell.iter().map(|(parameter, value)| format!("{function_name}:{parameter}={value}")).collect::<Vec<_>>();
+            let command = apply_overrides(&apply_overrides(&base_command, &config.overrides, function_name), &cell_overrides, function_name);
+            let target_value = target_value_for(&config.target_values, function_name);
+            check_eval_budget(&command, function_name, config.eval_budget, config.world_options()).unwrap_or_else(|message| Config::command().error(ErrorKind::InvalidValue, message).exit());
+
+            // Identifies the cell across invocations. serde_json sorts object keys, so the text is stable
+            let cell_key = serde_json::json!({
+                "function": function_name,
+                "runs": tries,
+                "dimensions": config.dimensions,
+                "eval_budget": config.eval_budget,
+                "target_value": target_value,
+                "configuration": &command,
+            }).to_string();
+            if store.as_ref().is_some_and(|store| store.completed_cells.contains(&cell_key)) {
+                eprintln!("Skipping completed cell {cell_description} for {function_name}");
+                continue;
+            }
+
+            let batch_sender = store.as_ref().map(|store| store.sender.begin_cell(&command, config.eval_budget, cell_key.clone()));
+            let outputs = BatchOutputs {
+                progress: dashboard.as_ref().map(|dashboard| dashboard.add_function(&format!("{function_name} {cell_description}"), tries)),
+                store: batch_sender.clone().map(|batch_sender| (batch_sender, function_name.clone())),
+                record_traces: config.output_trace || config.mat_output.is_some(),
+                record_populations: config.mat_output.is_some(),
+            };
+            let pending = queue_batch(&pool, &command, config.eval_budget, function_name, target_value, tries, config.world_options(), outputs);
+            pending_cells.push((cell_description.clone(), function_name, command, target_value, batch_sender, pending));
+        }
+    }
+
+    for (cell_description, function_name, command, target_value, batch_sender, pending) in pending_cells {
+        let result = pending.wait();
+        if let Some(batch_sender) = batch_sender {
+            batch_sender.complete_cell();
+        }
+        let mut summary = BatchSummary::new(function_name, &command, config.dimensions, config.eval_budget, target_value, result);
+        summary.grid_cell = Some(cell_description);
+        if let Some(run_records) = &mut run_records {
+            run_records.add(&summary);
+        }
+        if let Some(mat_writer) = &mut mat_writer {
+            mat_writer.add(&summary);
+        }
+        printer.add(summary);
+    }
+    if let Some(run_records) = run_records {
+        run_records.finish();
+    }
+    if let Some(mat_writer) = mat_writer {
+        mat_writer.finish();
+    }
+
+    drop(pool);
+    if let Some(store) = store {
+        store.close();
+    }
+    printer.finish();
+}
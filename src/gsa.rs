@@ -0,0 +1,289 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub agent_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub initial_gravity: Real, // G0, the gravitational constant at the first iteration. 100 in the paper
+    pub gravity_decay: Real, // α, G = G0 * e^(-α * t / T). 20 in the paper
+    pub parallel: bool, // Moves and evaluates the agents on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.agent_count < 2 {
+            return Err("There must be at least two agents, for them to attract each other");
+        }
+        if self.initial_gravity.is_nan() || self.initial_gravity <= 0.0 {
+            return Err("Initial gravity must be positive");
+        }
+        if self.gravity_decay.is_nan() || self.gravity_decay < 0.0 {
+            return Err("Gravity decay can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The masses of agents with the given values, summing to 1. The best agent is the heaviest and the worst one weighs
+// nothing. All agents weigh the same if they're equally good, agents with a NaN or infinite value weigh nothing
+pub fn masses(values: &[Real], masses: &mut Vec<Real>) {
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let best = finite.clone().min_by_key(|&value| Fitness::minimize(value));
+    let worst = finite.max_by_key(|&value| Fitness::minimize(value));
+    masses.clear();
+    match (best, worst) {
+        (Some(best), Some(worst)) if best < worst => {
+            masses.extend(values.iter().map(|&value| if value.is_finite() { (value - worst) / (best - worst) } else { 0.0 }));
+        },
+        _ => masses.extend(values.iter().map(|&value| if value.is_finite() || best.is_none() { 1.0 } else { 0.0 })),
+    }
+    let total = masses.iter().sum::<Real>();
+    for mass in masses.iter_mut() {
+        *mass /= total;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Agent<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    velocity: VectorN<Real, N>,
+    random_source: RngType, // Seeded from the population seed and the agent's index, so agents can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Agent<N, RngType> {
+    // Not evaluated yet, the world evaluates all agents at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, velocity: VectorN::default(), random_source };
+    }
+
+    // Pulled by each of the heaviest agents, other than itself, with a random fraction of G times its mass over the
+    // distance between them. Keeps a random fraction of its velocity per coordinate
+    fn move_agent<F>(&mut self, parameters: &Parameters<N, F>, index: usize, previous_positions: &[VectorN<Real, N>], masses: &[Real], heaviest: &[usize], gravity: Real) {
+        let mut acceleration = VectorN::default();
+        for &other in heaviest.iter().filter(|&&other| other != index) {
+            let difference = previous_positions[other] - self.position;
+            let distance = difference.norm_l2();
+            acceleration += difference * (self.random_source.gen::<Real>() * gravity * masses[other] / (distance + Real::EPSILON));
+        }
+        self.velocity = VectorN::random_uniform((0.0, 1.0), &mut self.random_source) * self.velocity + acceleration;
+        self.position += self.velocity;
+        self.position.clamp(parameters.bounds);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    agents: Vec<Agent<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>, // Of the agents where they are now
+    masses: Vec<Real>,
+    heaviest: Vec<usize>, // Indices of the agents, heaviest first
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(agent_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            agent_count, function, bounds,
+            initial_gravity: 100.0,
+            gravity_decay: 20.0,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            agents: Vec::with_capacity(parameters.agent_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.agent_count),
+            values: Vec::with_capacity(parameters.agent_count),
+            masses: Vec::with_capacity(parameters.agent_count),
+            heaviest: Vec::with_capacity(parameters.agent_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.agents.iter().map(|agent| agent.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // Only the heaviest agents attract the others, from all of them at the start down to the heaviest one at the end.
+    // G decays exponentially over the run
+    fn move_agents(&mut self, iteration_count: usize) {
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let gravity = self.parameters.initial_gravity * (-self.parameters.gravity_decay * progress).exp();
+        let agent_count = self.agents.len();
+        let attracting_count = (agent_count as Real - progress * (agent_count - 1) as Real).round() as usize;
+        masses(&self.values, &mut self.masses);
+        self.heaviest.clear();
+        self.heaviest.extend(0..agent_count);
+        let masses = &self.masses;
+        // Stable, so the first of equally heavy agents stays ahead
+        self.heaviest.sort_by(|&first, &second| masses[second].total_cmp(&masses[first]));
+        self.positions.clear();
+        self.positions.extend(self.agents.iter().map(|agent| agent.position));
+        let parameters = &*self.parameters;
+        let previous_positions = &self.positions;
+        let heaviest = &self.heaviest[..attracting_count];
+        let move_agent = |(index, agent): (usize, &mut Agent<N, RngType>)| agent.move_agent(parameters, index, previous_positions, masses, heaviest, gravity);
+        if parameters.parallel {
+            self.agents.par_iter_mut().enumerate().for_each(move_agent);
+        } else {
+            self.agents.iter_mut().enumerate().for_each(move_agent);
+        }
+    }
+
+    // The values of the agents, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.agents.len();
+        // Takes the first of equally good agents
+        let (best_index, &best_value) = values.iter().enumerate().min_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap();
+        if Fitness::minimize(best_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_value;
+            self.best_solution = self.agents[best_index].position;
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.agents.iter().map(|agent| agent.position), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_agents(iteration_count);
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_agents(iteration_count);
+        positions.extend(self.agents.iter().map(|agent| agent.position));
+    }
+
+    // The masses of the next iteration come from these values
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.values.clear();
+        self.values.extend_from_slice(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.agents.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.agent_count {
+            self.agents.push(Agent::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.agents.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.agents.iter().map(|agent| agent.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer, real::Real};
+
+    use super::{masses, WorldState};
+
+    #[test]
+    fn gravity_test() {
+        let mut weights = Vec::new();
+        masses(&[1.0, 3.0, 2.0, Real::NAN], &mut weights);
+        assert_eq!(weights, [1.0, 0.0, 0.5, 0.0].map(|mass: Real| mass / 1.5));
+        masses(&[5.0, 5.0], &mut weights);
+        assert_eq!(weights, [0.5, 0.5]);
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(30, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
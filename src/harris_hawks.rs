@@ -0,0 +1,283 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub hawk_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub parallel: bool, // Moves and evaluates the hawks on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.hawk_count == 0 {
+            return Err("There must be at least one hawk");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The hawks before the move, read by every hawk
+struct Flock<'a, const N: usize> {
+    positions: &'a [VectorN<Real, N>],
+    mean: VectorN<Real, N>,
+    rabbit: VectorN<Real, N>, // The best position found so far
+    progress: Real,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hawk<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    dive_evaluations: usize, // Of the last move, 0 if the new position still has to be evaluated
+    random_source: RngType, // Seeded from the population seed and the hawk's index, so hawks can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Hawk<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole flock at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, dive_evaluations: 0, random_source };
+    }
+
+    // The escaping energy of the rabbit decides between perching somewhere to explore and the four besiege strategies.
+    // The energy is random in [-2, 2] at the start and shrinks to 0 over the run
+    fn move_hawk<F: Function<N>>(&mut self, parameters: &Parameters<N, F>, flock: &Flock<N>) {
+        let energy = 2.0 * (2.0 * self.random_source.gen::<Real>() - 1.0) * (1.0 - flock.progress);
+        self.dive_evaluations = 0;
+        if energy.abs() >= 1.0 {
+            self.perch(parameters, flock);
+        } else {
+            // J, the random jump strength of the rabbit
+            let jump = 2.0 * (1.0 - self.random_source.gen::<Real>());
+            let escaped = self.random_source.gen::<Real>() >= 0.5;
+            let distance = |from: VectorN<Real, N>| (flock.rabbit * jump - from).abs();
+            match (escaped, energy.abs() >= 0.5) {
+                // Soft besiege
+                (true, true) => self.position = flock.rabbit - self.position - distance(self.position) * energy,
+                // Hard besiege
+                (true, false) => self.position = flock.rabbit - (flock.rabbit - self.position).abs() * energy,
+                // Soft and hard besiege with progressive rapid dives
+                (false, true) => self.dive(parameters, flock.rabbit - distance(self.position) * energy),
+                (false, false) => self.dive(parameters, flock.rabbit - distance(flock.mean) * energy),
+            }
+        }
+        self.position.clamp(parameters.bounds);
+    }
+
+    // To a random position relative to a random hawk or to the rabbit and the middle of the flock, with even odds
+    fn perch<F>(&mut self, parameters: &Parameters<N, F>, flock: &Flock<N>) {
+        let (lower, upper) = parameters.bounds;
+        if self.random_source.gen::<Real>() >= 0.5 {
+            let other = flock.positions[self.random_source.gen_range(0..flock.positions.len())];
+            let distance = (other - self.position * (2.0 * self.random_source.gen::<Real>())).abs();
+            self.position = other - distance * self.random_source.gen::<Real>();
+        } else {
+            let offset = self.random_source.gen::<Real>() * (lower + self.random_source.gen::<Real>() * (upper - lower));
+            self.position = flock.rabbit - flock.mean - offset;
+        }
+    }
+
+    // Tries the besiege position, then a Lévy flight from it, and only goes to either if it's better. Both are evaluated
+    // here, so the world doesn't evaluate the hawk again
+    fn dive<F: Function<N>>(&mut self, parameters: &Parameters<N, F>, mut besiege: VectorN<Real, N>) {
+        besiege.clamp(parameters.bounds);
+        let besiege_value = parameters.function.evaluate(besiege);
+        self.dive_evaluations = 1;
+        if Fitness::minimize(besiege_value) < Fitness::minimize(self.function_value) {
+            self.position = besiege;
+            self.function_value = besiege_value;
+            return;
+        }
+        let flight = VectorN::random_uniform((0.0, 1.0), &mut self.random_source) * VectorN::random_levy(1.5, 0.01, &mut self.random_source);
+        let mut dive = besiege + flight;
+        dive.clamp(parameters.bounds);
+        let dive_value = parameters.function.evaluate(dive);
+        self.dive_evaluations = 2;
+        if Fitness::minimize(dive_value) < Fitness::minimize(self.function_value) {
+            self.position = dive;
+            self.function_value = dive_value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    flock: Vec<Hawk<N, RngType>>,
+    pub best_solution: VectorN<Real, N>, // The rabbit
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(hawk_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            hawk_count, function, bounds,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            flock: Vec::with_capacity(parameters.hawk_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.hawk_count),
+            values: Vec::with_capacity(parameters.hawk_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // The hawks that didn't dive, the others already know their values
+    fn evaluate_flock(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.flock.iter().filter(|hawk| hawk.dive_evaluations == 0).map(|hawk| hawk.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+        self.evaluation_count += self.positions.len();
+        for (hawk, &value) in self.flock.iter_mut().filter(|hawk| hawk.dive_evaluations == 0).zip(&self.values) {
+            hawk.function_value = value;
+        }
+        // Takes the first of equally good hawks
+        let best_hawk = self.flock.iter().min_by_key(|hawk| Fitness::minimize(hawk.function_value)).unwrap();
+        if Fitness::minimize(best_hawk.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_hawk.function_value;
+            self.best_solution = best_hawk.position;
+        }
+    }
+
+    fn move_flock(&mut self, iteration_count: usize) {
+        self.positions.clear();
+        self.positions.extend(self.flock.iter().map(|hawk| hawk.position));
+        let flock = Flock {
+            positions: &self.positions,
+            mean: self.positions.iter().fold(VectorN::default(), |sum, &position| sum + position) / self.positions.len() as Real,
+            rabbit: self.best_solution,
+            progress: (self.iteration as Real / iteration_count as Real).min(1.0),
+        };
+        let parameters = &*self.parameters;
+        let move_hawk = |hawk: &mut Hawk<N, RngType>| hawk.move_hawk(parameters, &flock);
+        if parameters.parallel {
+            self.flock.par_iter_mut().for_each(move_hawk);
+        } else {
+            self.flock.iter_mut().for_each(move_hawk);
+        }
+        self.evaluation_count += self.flock.iter().map(|hawk| hawk.dive_evaluations).sum::<usize>();
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // Diving hawks evaluate their own moves, so the iteration can't be split into a proposal and its values
+    fn do_iteration(&mut self, iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.move_flock(iteration_count);
+        self.evaluate_flock();
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.flock.iter().map(|hawk| hawk.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.flock.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.hawk_count {
+            self.flock.push(Hawk::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_flock();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // Every hawk that dives may evaluate twice
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * self.flock.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.flock.iter().map(|hawk| hawk.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn besiege_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert!((20 + 20 * 200..=20 + 40 * 200).contains(&world.evaluation_count()));
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,193 @@
+// The `serve-http` subcommand, batch runs submitted and followed over HTTP so other services can use the optimizers:
+//
+//     POST /jobs              {"function": "ackley", "algorithm": "bats", "configuration": {"bat_count": 20, ...}, "runs": 16}
+//                             -> 201 {"id": 0}
+//     GET  /jobs              -> the status of every job
+//     GET  /jobs/{id}         -> {"id": 0, "state": "running", "progress": {"completed_runs": 3, ...}, ...}
+//     GET  /jobs/{id}/result  -> the summary as printed by `--output-format json`, once the job is finished
+//     GET  /metrics           -> the progress of every job for Prometheus, see metrics.rs
+//
+// The configuration is the one of the summaries, the algorithm's flags with underscores. A job may also have
+// "dimensions" (20 by default), "eval_budget" and "target_value". The function may be the URL of a remote objective, with
+// "objective": {"bounds": [-5, 5], "batch": true, "concurrency": 4, "timeout": 10, "retries": 3} as its --objective-*
+// flags. Jobs share one pool of --threads workers and are kept until the server stops. Errors are {"error": message}
+
+use std::{panic::{self, AssertUnwindSafe}, sync::{Arc, Mutex}};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use swarm_optimizers::optimizer::DIMENSIONS;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{dashboard::{Dashboard, ProgressReporter, ProgressSnapshot}, metrics, output::BatchSummary, pool::WorkerPool, queue_batch, BatchOutputs, OptimizationAlgorithmCommand, RandomGenerator, RemoteArguments, WorldOptions};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobRequest {
+    function: String,
+    algorithm: String,
+    configuration: serde_json::Map<String, Value>,
+    runs: usize,
+    #[serde(default = "default_dimensions")]
+    dimensions: usize,
+    #[serde(default)]
+    eval_budget: Option<usize>,
+    #[serde(default)]
+    target_value: Option<f64>,
+    #[serde(default)]
+    objective: RemoteArguments,
+}
+
+fn default_dimensions() -> usize {
+    return 20;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum JobState {
+    Running,
+    Finished,
+    Failed, // A run panicked
+}
+
+struct Job {
+    function: String,
+    algorithm: String,
+    state: JobState,
+    progress: ProgressReporter,
+    summary: Option<BatchSummary>,
+}
+
+#[derive(Serialize)]
+struct JobStatus<'a> {
+    id: usize,
+    function: &'a str,
+    algorithm: &'a str,
+    state: JobState,
+    progress: ProgressSnapshot,
+}
+
+impl Job {
+    fn status(&self, id: usize) -> JobStatus<'_> {
+        return JobStatus { id, function: &self.function, algorithm: &self.algorithm, state: self.state, progress: self.progress.snapshot() };
+    }
+}
+
+type Jobs = Arc<Mutex<Vec<Job>>>;
+
+// An error response
+type Failure = (u16, String);
+
+pub fn serve(listen_address: &str, thread_count: usize) {
+    let server = Server::http(listen_address).unwrap_or_else(|error| panic!("Could not listen on {listen_address}: {error}"));
+    eprintln!("Serving the HTTP API on {}", server.server_addr());
+    let pool = WorkerPool::new(thread_count);
+    let dashboard = Dashboard::new(); // Never drawn, it keeps the progress of the jobs
+    let jobs: Jobs = Arc::new(Mutex::new(Vec::new()));
+    let queue_length = pool.queue_length();
+    for mut request in server.incoming_requests() {
+        if request.method() == &Method::Get && request.url().split('?').next() == Some("/metrics") {
+            let _ = request.respond(Response::from_string(metrics::render(&dashboard, &queue_length)).with_header(metrics::content_type()));
+            continue;
+        }
+        let (status, body) = match route(&mut request, &pool, &dashboard, &jobs) {
+            Ok(response) => response,
+            Err((status, message)) => (status, json!({ "error": message })),
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
+        let _ = request.respond(response); // Only fails if the client is gone
+    }
+}
+
+fn route(request: &mut Request, pool: &WorkerPool, dashboard: &Dashboard, jobs: &Jobs) -> Result<(u16, Value), Failure> {
+    let path = request.url().split('?').next().unwrap_or_default().to_string();
+    let segments = path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>();
+    match (request.method(), segments.as_slice()) {
+        (Method::Post, ["jobs"]) => return submit(request, pool, dashboard, jobs),
+        (Method::Get, ["jobs"]) => {
+            let jobs = jobs.lock().unwrap();
+            let statuses = jobs.iter().enumerate().map(|(id, job)| job.status(id)).collect::<Vec<_>>();
+            return Ok((200, serde_json::to_value(statuses).unwrap()));
+        },
+        (Method::Get, ["jobs", id]) => {
+            let jobs = jobs.lock().unwrap();
+            let id = parse_id(id, &jobs)?;
+            return Ok((200, serde_json::to_value(jobs[id].status(id)).unwrap()));
+        },
+        (Method::Get, ["jobs", id, "result"]) => {
+            let jobs = jobs.lock().unwrap();
+            let id = parse_id(id, &jobs)?;
+            match (&jobs[id].summary, jobs[id].state) {
+                (Some(summary), _) => return Ok((200, serde_json::to_value(summary).unwrap())),
+                (None, JobState::Failed) => return Err((500, format!("Job {id} failed"))),
+                (None, _) => return Err((409, format!("Job {id} is still running"))),
+            }
+        },
+        (_, ["jobs"] | ["jobs", _] | ["jobs", _, "result"]) => return Err((405, "Method not allowed".to_string())),
+        _ => return Err((404, format!("No such endpoint: {path}"))),
+    }
+}
+
+fn parse_id(id: &str, jobs: &[Job]) -> Result<usize, Failure> {
+    return id.parse::<usize>().ok().filter(|&id| id < jobs.len()).ok_or_else(|| (404, format!("No such job: {id}")));
+}
+
+fn submit(request: &mut Request, pool: &WorkerPool, dashboard: &Dashboard, jobs: &Jobs) -> Result<(u16, Value), Failure> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body).map_err(|error| (400, error.to_string()))?;
+    let job: JobRequest = serde_json::from_str(&body).map_err(|error| (400, format!("Invalid job: {error}")))?;
+    if job.runs == 0 {
+        return Err((400, "A job needs at least one run".to_string()));
+    }
+    if !DIMENSIONS.contains(&job.dimensions) {
+        return Err((400, format!("supported dimensions are {DIMENSIONS:?}")));
+    }
+    let command = serde_json::from_value::<OptimizationAlgorithmCommand>(json!({ job.algorithm.clone(): job.configuration }))
+        .map_err(|error| (400, format!("Invalid configuration for {}: {}", job.algorithm, error)))?;
+    if !command.is_algorithm() {
+        return Err((400, format!("Not an optimization algorithm: {}", job.algorithm)));
+    }
+
+    let progress = dashboard.add_function(&job.function, job.runs);
+    let options = WorldOptions {
+        random_generator: RandomGenerator::Xoshiro,
+        parallel_agents: false,
+        dimensions: job.dimensions,
+        lockstep: 1,
+        remote: job.objective,
+    };
+    let outputs = BatchOutputs { progress: Some(progress.clone()), ..BatchOutputs::default() };
+    // Invalid functions and parameters panic before anything is queued
+    let pending = panic::catch_unwind(AssertUnwindSafe(|| queue_batch(pool, &command, job.eval_budget, &job.function, job.target_value, job.runs, options, outputs)))
+        .map_err(|payload| (400, panic_message(payload)))?;
+
+    let mut locked_jobs = jobs.lock().unwrap();
+    let id = locked_jobs.len();
+    locked_jobs.push(Job { function: job.function.clone(), algorithm: job.algorithm, state: JobState::Running, progress, summary: None });
+    let jobs = jobs.clone();
+    std::thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| pending.wait()));
+        let mut jobs = jobs.lock().unwrap();
+        let finished = &mut jobs[id];
+        match result {
+            Ok(result) => {
+                finished.summary = Some(BatchSummary::new(&job.function, &command, job.dimensions, job.eval_budget, job.target_value, result));
+                finished.state = JobState::Finished;
+            },
+            Err(_) => finished.state = JobState::Failed,
+        }
+    });
+    return Ok((201, json!({ "id": id })));
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    return "Invalid job".to_string();
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub initial_count: usize, // Weeds in the first colony
+    pub max_population: usize, // Weeds that survive the competitive exclusion
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub seed_range: (usize, usize), // Seeds of the worst and of the best weed, the others in between by their values. (0, 5) in the paper
+    pub sigma_bounds: (Real, Real), // Initial and final standard deviation of seed dispersal, as fractions of the width of the bounds
+    pub modulation_index: Real, // The exponent of the standard deviation schedule, 3 in the paper
+    pub parallel: bool, // Evaluates the seeds on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.initial_count == 0 {
+            return Err("There must be at least one weed");
+        }
+        if self.max_population < self.initial_count {
+            return Err("Maximum population can't be smaller than the initial one");
+        }
+        if self.seed_range.0 > self.seed_range.1 || self.seed_range.1 == 0 {
+            return Err("Incorrect order of seed counts or no seeds at all");
+        }
+        if !(self.sigma_bounds.1 >= 0.0 && self.sigma_bounds.0 >= self.sigma_bounds.1) {
+            return Err("Standard deviations must shrink and can't be negative");
+        }
+        if self.modulation_index.is_nan() || self.modulation_index <= 0.0 {
+            return Err("Modulation index must be positive");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The seeds of a weed, from the worst to the best value of the colony linearly
+pub fn seed_count(value: Real, best_value: Real, worst_value: Real, seed_range: (usize, usize)) -> usize {
+    let span = worst_value - best_value;
+    // An even colony reproduces as fast as it can
+    if span.is_nan() || span <= 0.0 {
+        return seed_range.1;
+    }
+    let share = ((worst_value - value) / span).clamp(0.0, 1.0);
+    return seed_range.0 + (share * (seed_range.1 - seed_range.0) as Real) as usize;
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    colony: Vec<(Real, VectorN<Real, N>)>, // Sorted, best first
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    seeds: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(initial_count: usize, max_population: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            initial_count, max_population, function, bounds,
+            seed_range: (0, 5),
+            sigma_bounds: (0.05, 0.00001),
+            modulation_index: 3.0,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            colony: Vec::with_capacity(parameters.max_population * (parameters.seed_range.1 + 1)),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            seeds: Vec::with_capacity(parameters.max_population * parameters.seed_range.1),
+            values: Vec::with_capacity(parameters.max_population * parameters.seed_range.1),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_seeds(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.seeds, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.seeds, &mut self.values);
+        }
+    }
+
+    // Scattered normally around their weeds, with a standard deviation that falls as (1 - progress)^n
+    fn spread_seeds(&mut self, iteration_count: usize) {
+        let parameters = &*self.parameters;
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let (initial_sigma, final_sigma) = parameters.sigma_bounds;
+        let sigma = ((1.0 - progress).powf(parameters.modulation_index) * (initial_sigma - final_sigma) + final_sigma) * (parameters.bounds.1 - parameters.bounds.0);
+        let best_value = self.colony[0].0;
+        let worst_value = self.colony[self.colony.len() - 1].0;
+        self.seeds.clear();
+        for &(value, weed) in &self.colony {
+            for _ in 0..seed_count(value, best_value, worst_value, parameters.seed_range) {
+                let mut seed = VectorN::random_gaussian(weed, sigma, &mut self.random_generator);
+                seed.clamp(parameters.bounds);
+                self.seeds.push(seed);
+            }
+        }
+    }
+
+    // The values of the seeds, in order. They grow into weeds and the best of the whole colony survive
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.seeds.len();
+        self.colony.extend(values.iter().copied().zip(self.seeds.iter().copied()));
+        // Stable, so older weeds stay ahead of equally good seeds
+        self.colony.sort_by_key(|&(value, _)| Fitness::minimize(value));
+        self.colony.truncate(self.parameters.max_population);
+        if Fitness::minimize(self.colony[0].0) < Fitness::minimize(self.best_solution_value) {
+            (self.best_solution_value, self.best_solution) = self.colony[0];
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.colony.iter().map(|&(_, weed)| weed), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.spread_seeds(iteration_count);
+        self.evaluate_seeds();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.spread_seeds(iteration_count);
+        positions.extend_from_slice(&self.seeds);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    // The first colony is evaluated like seeds of nothing
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.colony.clear();
+        self.seeds.clear();
+        for _ in 0..self.parameters.initial_count {
+            self.seeds.push(VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator));
+        }
+        self.evaluate_seeds();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // A full colony of weeds that all get the most seeds
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.parameters.max_population * self.parameters.seed_range.1;
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.colony.iter().map(|&(_, weed)| weed));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::{seed_count, WorldState};
+
+    #[test]
+    fn colony_test() {
+        assert_eq!(seed_count(1.0, 1.0, 3.0, (0, 5)), 5);
+        assert_eq!(seed_count(3.0, 1.0, 3.0, (0, 5)), 0);
+        assert_eq!(seed_count(2.0, 2.0, 2.0, (1, 4)), 4);
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(10, 20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(100);
+        let mut colony = Vec::new();
+        world.population(&mut colony);
+        assert_eq!(colony.len(), 20);
+        assert!(world.evaluation_count() <= 10 + 100 * 20 * 5);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
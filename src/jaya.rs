@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// The algorithm has no parameters of its own
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub agent_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub parallel: bool, // Moves and evaluates the agents on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.agent_count == 0 {
+            return Err("There must be at least one agent");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Agent<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    candidate: VectorN<Real, N>, // Kept if it's better
+    random_source: RngType, // Seeded from the population seed and the agent's index, so agents can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Agent<N, RngType> {
+    // Not evaluated yet, the world evaluates all agents at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, candidate: position, random_source };
+    }
+
+    // x' = x + r1 * (best - |x|) - r2 * (worst - |x|), with r1 and r2 drawn for every coordinate
+    fn propose<F>(&mut self, parameters: &Parameters<N, F>, best: VectorN<Real, N>, worst: VectorN<Real, N>) {
+        let towards_best = VectorN::random_uniform((0.0, 1.0), &mut self.random_source);
+        let away_from_worst = VectorN::random_uniform((0.0, 1.0), &mut self.random_source);
+        let magnitude = self.position.abs();
+        self.candidate = self.position + towards_best * (best - magnitude) - away_from_worst * (worst - magnitude);
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // Keeps the candidate if it's better, the first of equally good positions stays
+    fn take_candidate(&mut self, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.function_value) {
+            self.position = self.candidate;
+            self.function_value = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    agents: Vec<Agent<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(agent_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            agent_count, function, bounds,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            agents: Vec::with_capacity(parameters.agent_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.agent_count),
+            values: Vec::with_capacity(parameters.agent_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_candidates(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.agents.iter().map(|agent| agent.candidate));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // Towards the best agent of the population and away from the worst one
+    fn move_agents(&mut self) {
+        let worst = self.agents.iter().max_by_key(|agent| Fitness::minimize(agent.function_value)).unwrap().position;
+        let parameters = &*self.parameters;
+        let best = self.best_solution;
+        let propose = |agent: &mut Agent<N, RngType>| agent.propose(parameters, best, worst);
+        if parameters.parallel {
+            self.agents.par_iter_mut().for_each(propose);
+        } else {
+            self.agents.iter_mut().for_each(propose);
+        }
+    }
+
+    // The values of the candidates, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.agents.len();
+        for (agent, &value) in self.agents.iter_mut().zip(values) {
+            agent.take_candidate(value);
+        }
+        // Takes the first of equally good agents
+        let best_agent = self.agents.iter().min_by_key(|agent| Fitness::minimize(agent.function_value)).unwrap();
+        if Fitness::minimize(best_agent.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_agent.function_value;
+            self.best_solution = best_agent.position;
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.agents.iter().map(|agent| agent.position), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.move_agents();
+        self.evaluate_candidates();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_agents();
+        positions.extend(self.agents.iter().map(|agent| agent.candidate));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.agents.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.agent_count {
+            self.agents.push(Agent::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_candidates();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.agents.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.agents.iter().map(|agent| agent.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn victory_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(100);
+        assert_eq!(world.evaluation_count(), 20 + 20 * 100);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub krill_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub max_induced_speed: Real, // N_max, of the motion induced by the other krill. 0.01 in the paper
+    pub foraging_speed: Real, // V_f, towards the food and the krill's own best position. 0.02 in the paper
+    pub max_diffusion: Real, // D_max, of the random diffusion at the start, falls to 0 over the run. 0.005 in the paper
+    pub time_constant: Real, // C_t, the time step is C_t times the summed widths of the bounds. In (0, 2]
+    pub parallel: bool, // Moves and evaluates the krill on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.krill_count < 2 {
+            return Err("There must be at least two krill, to induce each other's motion");
+        }
+        let speeds = [self.max_induced_speed, self.foraging_speed, self.max_diffusion];
+        if speeds.iter().any(|speed| speed.is_nan() || *speed < 0.0) {
+            return Err("Speeds can't be negative");
+        }
+        if !(self.time_constant > 0.0 && self.time_constant <= 2.0) {
+            return Err("Time constant must be in (0, 2]");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// The herd before the move, read by every krill
+struct Herd<'a, const N: usize> {
+    positions: &'a [VectorN<Real, N>],
+    values: &'a [Real],
+    best: (Real, VectorN<Real, N>), // Found so far by the whole herd
+    worst_value: Real, // Of the herd now
+    food: (Real, VectorN<Real, N>), // The centre of the herd weighted by how good the krill are
+    progress: Real, // I / I_max
+}
+
+impl<const N: usize> Herd<'_, N> {
+    // K^_ij, the difference of the values scaled by the difference between the worst and best values. 0 if it isn't a
+    // number, so a NaN or infinite krill doesn't affect the others
+    fn normalized(&self, value: Real, other_value: Real) -> Real {
+        let difference = (value - other_value) / (self.worst_value - self.best.0);
+        if difference.is_finite() {
+            return difference;
+        }
+        return 0.0;
+    }
+}
+
+// X^_ij, the unit vector from one position to the other, or 0 if they're the same
+fn direction<const N: usize>(from: VectorN<Real, N>, to: VectorN<Real, N>) -> VectorN<Real, N> {
+    let difference = to - from;
+    return difference / (difference.norm_l2() + Real::EPSILON);
+}
+
+#[derive(Clone, Debug)]
+pub struct Krill<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    induced: VectorN<Real, N>, // N_i, the last motion induced by the others
+    foraging: VectorN<Real, N>, // F_i, the last foraging motion
+    best_position: VectorN<Real, N>,
+    best_value: Real,
+    random_source: RngType, // Seeded from the population seed and the krill's index, so krill can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Krill<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole herd at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self {
+            position,
+            function_value: Real::INFINITY,
+            induced: VectorN::default(),
+            foraging: VectorN::default(),
+            best_position: position,
+            best_value: Real::INFINITY,
+            random_source,
+        };
+    }
+
+    // By the induced motion, the foraging motion and random diffusion, then the crossover and mutation operators
+    fn move_krill<F>(&mut self, parameters: &Parameters<N, F>, herd: &Herd<N>) {
+        let inertia = 0.9 - 0.8 * herd.progress;
+        // Attracted by the better neighbours within the sensing distance, repelled by the worse ones, and attracted by
+        // the best krill
+        let distances = herd.positions.iter().map(|&other| (other - self.position).norm_l2());
+        let sensing_distance = distances.clone().sum::<Real>() / (5 * herd.positions.len()) as Real;
+        let mut local = VectorN::default();
+        for ((&other, &other_value), distance) in herd.positions.iter().zip(herd.values).zip(distances) {
+            if distance > 0.0 && distance < sensing_distance {
+                local += direction(self.position, other) * herd.normalized(self.function_value, other_value);
+            }
+        }
+        let best_coefficient = 2.0 * (self.random_source.gen::<Real>() + herd.progress);
+        let target = direction(self.position, herd.best.1) * (best_coefficient * herd.normalized(self.function_value, herd.best.0));
+        self.induced = (local + target) * parameters.max_induced_speed + self.induced * inertia;
+        // Attracted by the food, less over the run, and by the krill's own best position
+        let food_coefficient = 2.0 * (1.0 - herd.progress);
+        let food = direction(self.position, herd.food.1) * (food_coefficient * herd.normalized(self.function_value, herd.food.0));
+        let own_best = direction(self.position, self.best_position) * herd.normalized(self.function_value, self.best_value);
+        self.foraging = (food + own_best) * parameters.foraging_speed + self.foraging * inertia;
+        let diffusion = VectorN::random_uniform((-1.0, 1.0), &mut self.random_source) * (parameters.max_diffusion * (1.0 - herd.progress));
+        let time_step = parameters.time_constant * (parameters.bounds.1 - parameters.bounds.0) * N as Real;
+        self.position += (self.induced + self.foraging + diffusion) * time_step;
+        // The worse the krill is, the likelier each of its coordinates is taken from a random krill, and the less likely
+        // it's mutated around the best position
+        let relative_value = herd.normalized(self.function_value, herd.best.0);
+        let crossover_probability = 0.2 * relative_value;
+        let mutation_probability = 0.05 / relative_value;
+        let krill_count = herd.positions.len();
+        for dimension in 0..N {
+            if self.random_source.gen::<Real>() < crossover_probability {
+                self.position[dimension] = herd.positions[self.random_source.gen_range(0..krill_count)][dimension];
+            }
+            if self.random_source.gen::<Real>() < mutation_probability {
+                let first = herd.positions[self.random_source.gen_range(0..krill_count)][dimension];
+                let second = herd.positions[self.random_source.gen_range(0..krill_count)][dimension];
+                self.position[dimension] = herd.best.1[dimension] + self.random_source.gen::<Real>() * (first - second);
+            }
+        }
+        self.position.clamp(parameters.bounds);
+    }
+
+    fn take_value(&mut self, value: Real) {
+        self.function_value = value;
+        if Fitness::minimize(value) < Fitness::minimize(self.best_value) {
+            self.best_value = value;
+            self.best_position = self.position;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    herd: Vec<Krill<N, RngType>>,
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(krill_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            krill_count, function, bounds,
+            max_induced_speed: 0.01,
+            foraging_speed: 0.02,
+            max_diffusion: 0.005,
+            time_constant: 0.5,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            herd: Vec::with_capacity(parameters.krill_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.krill_count),
+            values: Vec::with_capacity(parameters.krill_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.herd.iter().map(|krill| krill.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // The centre of the herd, each krill weighing the inverse of its value. The paper's 1/K only works with positive
+    // values, so they're shifted to make the best krill weigh 1. Evaluated, since the krill compare themselves with it
+    fn food(&mut self) -> (Real, VectorN<Real, N>) {
+        let mut centre = VectorN::default();
+        let mut total_weight = 0.0;
+        for krill in &self.herd {
+            let weight = 1.0 / (krill.function_value - self.best_solution_value + 1.0);
+            if weight.is_finite() {
+                centre += krill.position * weight;
+                total_weight += weight;
+            }
+        }
+        if total_weight == 0.0 {
+            return (self.best_solution_value, self.best_solution);
+        }
+        centre /= total_weight;
+        self.evaluation_count += 1;
+        let value = self.parameters.function.evaluate(centre);
+        if Fitness::minimize(value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = value;
+            self.best_solution = centre;
+        }
+        return (value, centre);
+    }
+
+    fn move_herd(&mut self, iteration_count: usize) {
+        let food = self.food();
+        self.positions.clear();
+        self.positions.extend(self.herd.iter().map(|krill| krill.position));
+        self.values.clear();
+        self.values.extend(self.herd.iter().map(|krill| krill.function_value));
+        let worst_value = self.values.iter().copied().filter(|value| value.is_finite()).max_by_key(|&value| Fitness::minimize(value)).unwrap_or(self.best_solution_value);
+        let parameters = &*self.parameters;
+        let herd = Herd {
+            positions: &self.positions,
+            values: &self.values,
+            best: (self.best_solution_value, self.best_solution),
+            worst_value,
+            food,
+            progress: (self.iteration as Real / iteration_count as Real).min(1.0),
+        };
+        let move_krill = |krill: &mut Krill<N, RngType>| krill.move_krill(parameters, &herd);
+        if parameters.parallel {
+            self.herd.par_iter_mut().for_each(move_krill);
+        } else {
+            self.herd.iter_mut().for_each(move_krill);
+        }
+    }
+
+    // The values of the herd, in order
+    fn take_values(&mut self, values: &[Real]) {
+        self.evaluation_count += self.herd.len();
+        for (krill, &value) in self.herd.iter_mut().zip(values) {
+            krill.take_value(value);
+        }
+        // Takes the first of equally good krill
+        let best_krill = self.herd.iter().min_by_key(|krill| Fitness::minimize(krill.function_value)).unwrap();
+        if Fitness::minimize(best_krill.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_krill.function_value;
+            self.best_solution = best_krill.position;
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // The food is evaluated before the herd moves, so the iteration can't be split into a proposal and its values
+    fn do_iteration(&mut self, iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.move_herd(iteration_count);
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.herd.iter().map(|krill| krill.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.herd.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.krill_count {
+            self.herd.push(Krill::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // Including the food
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.herd.len() + 1;
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.herd.iter().map(|krill| krill.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn herd_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(25, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert_eq!(world.evaluation_count(), 25 + 26 * 200);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -1,8 +1,58 @@
-#![feature(generic_arg_infer)]
-#![feature(array_windows)]
 #![allow(clippy::needless_return)]
+#![allow(clippy::too_many_arguments)]
 
+pub mod abc;
+pub mod aco;
+pub mod annealing;
+pub mod bacteria;
 pub mod bats;
+pub mod experiment;
+pub mod fitness;
 pub mod functions;
+pub mod optimizer;
+pub mod real;
 pub mod vector;
-pub mod butterflies;
\ No newline at end of file
+pub mod butterflies;
+pub mod cem;
+pub mod crows;
+pub mod cuckoo;
+pub mod dragonflies;
+pub mod flower_pollination;
+pub mod fss;
+pub mod grasshoppers;
+pub mod grey_wolf;
+pub mod gsa;
+pub mod harris_hawks;
+pub mod iwo;
+pub mod jaya;
+pub mod krill;
+pub mod moth_flame;
+pub mod nelder_mead;
+pub mod pattern_search;
+pub mod random_search;
+pub mod salps;
+pub mod sine_cosine;
+pub mod tlbo;
+pub mod umda;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(any(feature = "python", feature = "ffi", feature = "wasm"))]
+mod dynamic;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "zmq")]
+pub mod zeromq;
+#[cfg(feature = "argmin")]
+pub mod argmin_solver;
+#[cfg(feature = "arrow")]
+pub mod records;
+#[cfg(feature = "tuning")]
+pub mod tuning;
+#[cfg(feature = "viz")]
+pub mod viz;
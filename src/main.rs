@@ -1,80 +1,843 @@
-#![feature(generic_arg_infer)]
 #![allow(clippy::needless_return)]
+#![allow(clippy::too_many_arguments)]
 
-use swarm_optimizers::{bats, butterflies, functions::Functions};
+mod ask_tell;
+mod collect;
+mod dashboard;
+mod distributed;
+mod grid_search;
+mod http_server;
+mod matfile;
+mod metrics;
+mod output;
+mod pool;
+mod store;
 
-const FN_SIZE: usize = 20;
+use dashboard::{Dashboard, ProgressReporter};
+use matfile::MatWriter;
+use output::{BatchSummary, OutputFormat, RunRecordWriter, SummaryPrinter};
+use pool::WorkerPool;
+use store::{BatchSender, RunRecord};
+use swarm_optimizers::{abc, aco, annealing, bacteria, bats, butterflies, cem, crows, cuckoo, dragonflies, fitness::Fitness, flower_pollination, fss, functions::{Function, Functions}, grasshoppers, grey_wolf, gsa, harris_hawks, iwo, jaya, krill, moth_flame, nelder_mead, optimizer::{run_lockstep, FromParameters, InitialVelocity, Movement, Optimizer, RunLength, DIMENSIONS}, pattern_search, random_search, real::{self, Real}, remote::{RemoteObjective, RemoteOptions}, salps, sine_cosine, tlbo, umda, vector::VectorN};
 
-use std::ops::AddAssign;
-use clap::{Parser, Subcommand};
-use rand::{rngs::StdRng, thread_rng, SeedableRng};
+use std::{ops::AddAssign, path::PathBuf, sync::{mpsc::{self, Receiver}, Arc}, time::Duration};
+#[cfg(feature = "zmq")]
+use std::{collections::HashMap, sync::{LazyLock, Mutex}};
+#[cfg(feature = "zmq")]
+use swarm_optimizers::zeromq::{ZmqObjective, ZmqOptions};
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser, Clone, Debug)]
+#[command(subcommand_negates_reqs = true)] // Lets `completions` run without --functions
 struct Config {
     #[arg(long = "functions", value_delimiter = ',', num_args = 1.., required = true)]
     functions: Vec<String>,
 
     #[arg(long = "try-count")]
     try_count: Option<usize>,
+
+    // Shows a live terminal dashboard instead of printing results as they come
+    #[arg(long = "watch", requires = "try_count")]
+    watch: bool,
+
+    // Serves Prometheus metrics of the batches on http://ADDRESS/metrics while they run, see metrics.rs
+    #[arg(long = "metrics-listen", requires = "try_count")]
+    metrics_listen: Option<String>,
+
+    // Appends every batch run to an SQLite database, see store.rs for the schema
+    #[arg(long = "store", requires = "try_count")]
+    store: Option<String>,
+
+    // Also stores the best value after every iteration of every run
+    #[arg(long = "store-trace", requires = "store")]
+    store_trace: bool,
+
+    // Worker threads, defaults to all cores
+    #[arg(long = "threads", value_parser = clap::value_parser!(u32).range(1..))]
+    threads: Option<u32>,
+
+    // Either a single value applied to every function or `function=value` entries, e.g. `--target-value 0.001,rastrigin=0.5`
+    #[arg(long = "target-value", value_delimiter = ',')]
+    target_values: Vec<String>,
+
+    // Algorithm parameters for a single function, as `function:parameter=value`, e.g. `--override rastrigin:initial-loudness=2.0`.
+    // Entries are separated by commas, except inside a JSON value such as `ackley:bounds=[-5,5]`, see split_overrides
+    #[arg(long = "override")]
+    overrides: Vec<String>,
+
+    // Replaces the algorithm's iteration count, so algorithms using a different number of evaluations per iteration are compared fairly
+    #[arg(long = "eval-budget")]
+    eval_budget: Option<usize>,
+
+    // How batch results are printed, `json` and `jsonl` are what `collect` reads
+    #[arg(long = "output-format", value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    // Moves and evaluates the agents of a run on all cores. Worth it for expensive functions or large populations, when
+    // there are fewer runs than cores
+    #[arg(long = "parallel-agents")]
+    parallel_agents: bool,
+
+    // Adds the best value after every iteration of every run to json and jsonl output, for convergence curves
+    #[arg(long = "output-trace", requires = "try_count")]
+    output_trace: bool,
+
+    // Also writes every run with its seed and best solution to this Arrow IPC or .parquet file, see records.rs
+    #[arg(long = "run-records", requires = "try_count")]
+    run_records: Option<PathBuf>,
+
+    // Also writes every run with its convergence trace and final population to this MAT-file, see matfile.rs. The traces
+    // and populations then show up in json and jsonl output too
+    #[arg(long = "mat-output", requires = "try_count")]
+    mat_output: Option<PathBuf>,
+
+    // Evaluates every population in a compute shader, in single precision
+    #[cfg(feature = "gpu")]
+    #[arg(long = "gpu")]
+    gpu: bool,
+    
+    // Runs of a batch advanced together by each worker thread, with the agents of all of them evaluated in a single batch
+    // per iteration. Pays off with --gpu, a single submission then serves the whole group. The results are the same either way
+    #[arg(long = "lockstep", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    lockstep: u32,
+
+    // Dimensions of the search space, one of DIMENSIONS
+    #[arg(long = "dimensions", default_value_t = 20, value_parser = parse_dimensions)]
+    dimensions: usize,
+
+    // The random number generator of the optimizers
+    #[arg(long = "rng", value_enum, default_value_t = RandomGenerator::Xoshiro)]
+    rng: RandomGenerator,
+
+    #[command(flatten)]
+    remote: RemoteArguments,
     
     #[command(subcommand)]
     command: OptimizationAlgorithmCommand,
 }
 
-#[derive(Subcommand, Clone, Debug)]
+impl Config {
+    // Config::try_parse_from with the --override entries split, the front ends and the workers of serve parse through it
+    fn try_parse_arguments<T: Into<std::ffi::OsString> + Clone>(arguments: impl IntoIterator<Item = T>) -> Result<Self, clap::Error> {
+        let mut config = Self::try_parse_from(arguments)?;
+        config.overrides = split_overrides(&config.overrides);
+        return Ok(config);
+    }
+
+    fn world_options(&self) -> WorldOptions {
+        return WorldOptions {
+            random_generator: self.rng,
+            parallel_agents: self.parallel_agents,
+            dimensions: self.dimensions,
+            lockstep: self.lockstep as usize,
+            remote: self.remote,
+        };
+    }
+}
+
+fn parse_dimensions(value: &str) -> Result<usize, String> {
+    let dimensions = value.parse::<usize>().map_err(|error| error.to_string())?;
+    if !DIMENSIONS.contains(&dimensions) {
+        return Err(format!("supported dimensions are {DIMENSIONS:?}"));
+    }
+    return Ok(dimensions);
+}
+
+fn parse_bounds(value: &str) -> Result<(Real, Real), String> {
+    let Some((lower, upper)) = value.split_once(',') else {
+        return Err("expected `lower,upper`".to_string());
+    };
+    return Ok((lower.trim().parse().map_err(|_| format!("invalid lower bound `{lower}`"))?, upper.trim().parse().map_err(|_| format!("invalid upper bound `{upper}`"))?));
+}
+
+// Parameters like movements are written the way their flags take them, also in the summaries and overrides
+mod display_string {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.collect_str(value);
+    }
+
+    pub fn deserialize<'de, T: FromStr<Err: Display>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        return String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom);
+    }
+}
+
+// How functions given as http:// or https:// URLs are evaluated, see swarm_optimizers::remote for the protocol, e.g.
+// `--functions http://localhost:9000/evaluate --objective-bounds=-5,5 --objective-batch`. With `--features zmq`, functions
+// given as tcp:// or ipc:// endpoints are bound for ZeroMQ workers to connect to, see swarm_optimizers::zeromq
+#[derive(Args, Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RemoteArguments {
+    // The search box of every coordinate, required for remote functions
+    #[arg(long = "objective-bounds", value_parser = parse_bounds, allow_hyphen_values = true)]
+    bounds: Option<(Real, Real)>,
+
+    // Sends every population in a single request
+    #[arg(long = "objective-batch")]
+    batch: bool,
+
+    // Requests in flight at once for each run
+    #[arg(long = "objective-concurrency", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    concurrency: u32,
+
+    // Seconds before a request is given up and retried
+    #[arg(long = "objective-timeout", default_value_t = 30.0)]
+    timeout: f64,
+
+    #[arg(long = "objective-retries", default_value_t = 2)]
+    retries: u32,
+}
+
+impl Default for RemoteArguments {
+    fn default() -> Self {
+        return Self { bounds: None, batch: false, concurrency: 1, timeout: 30.0, retries: 2 };
+    }
+}
+
+impl RemoteArguments {
+    fn options(&self) -> RemoteOptions {
+        return RemoteOptions {
+            batch: self.batch,
+            concurrency: self.concurrency as usize,
+            timeout: Duration::try_from_secs_f64(self.timeout).unwrap_or_else(|_| {
+                Config::command().error(ErrorKind::InvalidValue, format!("invalid objective timeout '{}'", self.timeout)).exit();
+            }),
+            retries: self.retries,
+        };
+    }
+
+    #[cfg(feature = "zmq")]
+    fn zmq_options(&self) -> ZmqOptions {
+        let options = self.options();
+        return ZmqOptions { batch: options.batch, timeout: options.timeout, retries: options.retries };
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum RandomGenerator {
+    Xoshiro, // Xoshiro256++, cheap enough to not show up in profiles of cheap functions
+    Std, // rand's StdRng, a cryptographically secure generator
+}
+
+// How the worlds are set up, apart from the algorithm's own parameters
+#[derive(Clone, Copy, Debug)]
+struct WorldOptions {
+    random_generator: RandomGenerator,
+    parallel_agents: bool,
+    dimensions: usize,
+    lockstep: usize, // Runs per pool task
+    remote: RemoteArguments,
+}
+
+#[derive(Subcommand, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 enum OptimizationAlgorithmCommand {
     Bats {
         #[arg(long = "bat-num-iters")]
-        bat_num_iters: usize,
+        bat_num_iters: Option<usize>,
 
         #[arg(long = "bat-count")]
         bat_count: usize,
         
         #[arg(long = "frequency-left-bound")]
-        frequency_left_bound: f64,
+        frequency_left_bound: Real,
 
         #[arg(long = "frequency-right-bound")]
-        frequency_right_bound: f64,
+        frequency_right_bound: Real,
         
         #[arg(long = "initial-pulse-rate")]
-        initial_pulse_rate: f64,
+        initial_pulse_rate: Real,
 
         #[arg(long = "pulse-rate-factor")]
-        pulse_rate_factor: f64,
+        pulse_rate_factor: Real,
 
         #[arg(long = "initial-loudness")]
-        initial_loudness: f64,
+        initial_loudness: Real,
 
         #[arg(long = "loudness-cooling-rate")]
-        loudness_cooling_rate: f64
+        loudness_cooling_rate: Real,
+
+        // The velocity update, see swarm_optimizers::optimizer::Movement. The published one makes the bats diverge
+        #[arg(long = "movement", default_value_t = Movement::Negated)]
+        #[serde(with = "display_string")]
+        movement: Movement,
+
+        // See swarm_optimizers::optimizer::InitialVelocity
+        #[arg(long = "initial-velocity", default_value_t = InitialVelocity::PositiveUnit)]
+        #[serde(with = "display_string")]
+        initial_velocity: InitialVelocity,
+
+        // Improvements of the personal and global bests by less than this are ignored
+        #[arg(long = "improvement-tolerance", default_value_t = 0.0)]
+        improvement_tolerance: Real,
+
+        // The random walk is then relative to the width of the search space, the loudness being a fraction of it
+        #[arg(long = "scale-walk-to-bounds")]
+        scale_walk_to_bounds: bool,
     },
 
     Butterflies {
         #[arg(long = "butterfly-num-iters")]
-        butterfly_num_iters: usize,
+        butterfly_num_iters: Option<usize>,
 
         #[arg(long = "butterfly-count")]
         butterfly_count: usize,
 
         #[arg(long = "fragrance-multiplier")]
-        fragrance_multiplier: f64,
+        fragrance_multiplier: Real,
 
         #[arg(long = "fragrance-exponent-left-bound")]
-        fragrance_exponent_left_bound: f64,
+        fragrance_exponent_left_bound: Real,
 
         #[arg(long = "fragrance-exponent-right-bound")]
-        fragrance_exponent_right_bound: f64,
+        fragrance_exponent_right_bound: Real,
 
         #[arg(long = "local-search-chance")]
-        local_search_chance: f64
+        local_search_chance: Real,
+
+        // The position update, see swarm_optimizers::optimizer::Movement
+        #[arg(long = "movement", default_value_t = Movement::Standard)]
+        #[serde(with = "display_string")]
+        movement: Movement,
+
+        // Only used by the inertia-damped movement, see swarm_optimizers::optimizer::InitialVelocity
+        #[arg(long = "initial-velocity", default_value_t = InitialVelocity::Zero)]
+        #[serde(with = "display_string")]
+        initial_velocity: InitialVelocity,
+
+        // Improvements of the global best by less than this are ignored
+        #[arg(long = "improvement-tolerance", default_value_t = 0.0)]
+        improvement_tolerance: Real,
+    },
+
+    GreyWolf {
+        #[arg(long = "grey-wolf-num-iters")]
+        grey_wolf_num_iters: Option<usize>,
+
+        #[arg(long = "wolf-count")]
+        wolf_count: usize,
+
+        // a decreases linearly from the initial to the final value over the run
+        #[arg(long = "initial-a", default_value_t = 2.0)]
+        initial_a: Real,
+
+        #[arg(long = "final-a", default_value_t = 0.0)]
+        final_a: Real,
+    },
+
+    Cuckoo {
+        #[arg(long = "cuckoo-num-iters")]
+        cuckoo_num_iters: Option<usize>,
+
+        #[arg(long = "nest-count")]
+        nest_count: usize,
+
+        #[arg(long = "abandon-probability", default_value_t = 0.25)]
+        abandon_probability: Real,
+
+        // Of the Lévy flights
+        #[arg(long = "step-scale", default_value_t = 0.01)]
+        step_scale: Real,
+
+        #[arg(long = "levy-exponent", default_value_t = 1.5)]
+        levy_exponent: Real,
+    },
+
+    Abc {
+        #[arg(long = "abc-num-iters")]
+        abc_num_iters: Option<usize>,
+
+        // Food sources, with an employed and an onlooker bee for each
+        #[arg(long = "bee-count")]
+        bee_count: usize,
+
+        // Failed improvements after which a source is abandoned to a scout
+        #[arg(long = "limit")]
+        limit: usize,
+    },
+
+    // A single solution, so --parallel-agents has no effect
+    Annealing {
+        #[arg(long = "annealing-num-iters")]
+        annealing_num_iters: Option<usize>,
+
+        #[arg(long = "initial-temperature", default_value_t = 10.0)]
+        initial_temperature: Real,
+
+        // The temperature is multiplied by it after every iteration
+        #[arg(long = "cooling-rate", default_value_t = 0.99)]
+        cooling_rate: Real,
+
+        // The step size at the start of the run as a fraction of the width of the bounds, shrinks to --min-radius by its end
+        #[arg(long = "initial-radius", default_value_t = 0.1)]
+        initial_radius: Real,
+
+        // The step size at the end of the run as a fraction of the width of the bounds
+        #[arg(long = "min-radius", default_value_t = 0.001)]
+        min_radius: Real,
+
+        #[arg(long = "moves-per-iteration", default_value_t = 1)]
+        moves_per_iteration: usize,
+    },
+
+    FlowerPollination {
+        #[arg(long = "flower-num-iters")]
+        flower_num_iters: Option<usize>,
+
+        #[arg(long = "flower-count")]
+        flower_count: usize,
+
+        // The chance of global pollination
+        #[arg(long = "switch-probability", default_value_t = 0.8)]
+        switch_probability: Real,
+
+        // Of the Lévy flights of global pollination
+        #[arg(long = "step-scale", default_value_t = 0.01)]
+        step_scale: Real,
+
+        #[arg(long = "levy-exponent", default_value_t = 1.5)]
+        levy_exponent: Real,
+    },
+
+    MothFlame {
+        #[arg(long = "moth-flame-num-iters")]
+        moth_flame_num_iters: Option<usize>,
+
+        #[arg(long = "moth-count")]
+        moth_count: usize,
+
+        // b, of the logarithmic spiral the moths fly around the flames on
+        #[arg(long = "spiral-shape", default_value_t = 1.0)]
+        spiral_shape: Real,
+    },
+
+    Salps {
+        #[arg(long = "salp-num-iters")]
+        salp_num_iters: Option<usize>,
+
+        #[arg(long = "salp-count")]
+        salp_count: usize,
+    },
+
+    Aco {
+        #[arg(long = "aco-num-iters")]
+        aco_num_iters: Option<usize>,
+
+        #[arg(long = "ant-count")]
+        ant_count: usize,
+
+        #[arg(long = "archive-size")]
+        archive_size: usize,
+
+        // How strongly the ants prefer the better solutions of the archive, smaller is greedier
+        #[arg(long = "q", default_value_t = 0.5)]
+        q: Real,
+
+        // The width of the sampling kernels relative to the spread of the archive
+        #[arg(long = "xi", default_value_t = 0.85)]
+        xi: Real,
+    },
+
+    SineCosine {
+        #[arg(long = "sine-cosine-num-iters")]
+        sine_cosine_num_iters: Option<usize>,
+
+        #[arg(long = "agent-count")]
+        agent_count: usize,
+
+        // a, the amplitude of the moves at the start, goes down to 0 over the run
+        #[arg(long = "amplitude", default_value_t = 2.0)]
+        amplitude: Real,
+    },
+
+    Gsa {
+        #[arg(long = "gsa-num-iters")]
+        gsa_num_iters: Option<usize>,
+
+        #[arg(long = "agent-count")]
+        agent_count: usize,
+
+        // G0, the gravitational constant at the start
+        #[arg(long = "initial-gravity", default_value_t = 100.0)]
+        initial_gravity: Real,
+
+        // α, G decays as e^(-α * progress)
+        #[arg(long = "gravity-decay", default_value_t = 20.0)]
+        gravity_decay: Real,
+    },
+
+    Dragonflies {
+        #[arg(long = "dragonfly-num-iters")]
+        dragonfly_num_iters: Option<usize>,
+
+        #[arg(long = "dragonfly-count")]
+        dragonfly_count: usize,
+
+        #[arg(long = "separation-weight", default_value_t = 0.1)]
+        separation_weight: Real,
+
+        #[arg(long = "alignment-weight", default_value_t = 0.1)]
+        alignment_weight: Real,
+
+        #[arg(long = "cohesion-weight", default_value_t = 0.1)]
+        cohesion_weight: Real,
+
+        #[arg(long = "food-weight", default_value_t = 1.0)]
+        food_weight: Real,
+
+        #[arg(long = "enemy-weight", default_value_t = 0.1)]
+        enemy_weight: Real,
+    },
+
+    Krill {
+        #[arg(long = "krill-num-iters")]
+        krill_num_iters: Option<usize>,
+
+        #[arg(long = "krill-count")]
+        krill_count: usize,
+
+        // N_max, of the motion induced by the other krill
+        #[arg(long = "max-induced-speed", default_value_t = 0.01)]
+        max_induced_speed: Real,
+
+        // V_f, towards the food and the krill's own best position
+        #[arg(long = "foraging-speed", default_value_t = 0.02)]
+        foraging_speed: Real,
+
+        // D_max, of the random diffusion at the start
+        #[arg(long = "max-diffusion", default_value_t = 0.005)]
+        max_diffusion: Real,
+
+        // C_t, scales the time step, in (0, 2]
+        #[arg(long = "time-constant", default_value_t = 0.5)]
+        time_constant: Real,
+    },
+
+    Fss {
+        #[arg(long = "fss-num-iters")]
+        fss_num_iters: Option<usize>,
+
+        #[arg(long = "fish-count")]
+        fish_count: usize,
+
+        // The largest weight a fish can reach
+        #[arg(long = "weight-scale", default_value_t = 5000.0)]
+        weight_scale: Real,
+
+        // The individual step at the start and at the end, as fractions of the width of the bounds
+        #[arg(long = "initial-step", default_value_t = 0.1)]
+        initial_step: Real,
+
+        #[arg(long = "final-step", default_value_t = 0.001)]
+        final_step: Real,
+    },
+
+    // Teaching-learning based optimization, which has no parameters to tune
+    Tlbo {
+        #[arg(long = "tlbo-num-iters")]
+        tlbo_num_iters: Option<usize>,
+
+        // Learners in the class
+        #[arg(long = "tlbo-count")]
+        tlbo_count: usize,
+    },
+
+    HarrisHawks {
+        #[arg(long = "harris-hawks-num-iters")]
+        harris_hawks_num_iters: Option<usize>,
+
+        #[arg(long = "hawk-count")]
+        hawk_count: usize,
+    },
+
+    // The simplex method, restarted from a random point whenever the simplex collapses. A local search baseline, see
+    // nelder_mead::polish for refining the result of another algorithm
+    NelderMead {
+        #[arg(long = "nelder-mead-num-iters")]
+        nelder_mead_num_iters: Option<usize>,
+
+        // Of the initial simplex, as a fraction of the width of the bounds
+        #[arg(long = "initial-size", default_value_t = 0.1)]
+        initial_size: Real,
+
+        // Relative to the width of the bounds, below it the simplex restarts
+        #[arg(long = "tolerance", default_value_t = 1e-10)]
+        tolerance: Real,
+    },
+
+    // Uniform samples within the bounds, the baseline every algorithm should beat with the same --eval-budget
+    RandomSearch {
+        #[arg(long = "random-search-num-iters")]
+        random_search_num_iters: Option<usize>,
+
+        // Evaluated together in every iteration
+        #[arg(long = "sample-count", default_value_t = 100)]
+        sample_count: usize,
+    },
+
+    // Compass search: polls a step along and against every axis, moves to the best point if it's better and halves the
+    // step if none is. Deterministic apart from the starting points
+    PatternSearch {
+        #[arg(long = "pattern-search-num-iters")]
+        pattern_search_num_iters: Option<usize>,
+
+        // As a fraction of the width of the bounds
+        #[arg(long = "initial-step", default_value_t = 0.1)]
+        initial_step: Real,
+
+        #[arg(long = "shrink", default_value_t = 0.5)]
+        shrink: Real,
+
+        // Relative to the width of the bounds, below it the search restarts
+        #[arg(long = "tolerance", default_value_t = 1e-10)]
+        tolerance: Real,
+    },
+
+    Grasshoppers {
+        #[arg(long = "grasshopper-num-iters")]
+        grasshopper_num_iters: Option<usize>,
+
+        #[arg(long = "grasshopper-count")]
+        grasshopper_count: usize,
+
+        // f, the strength of the attraction between grasshoppers
+        #[arg(long = "attraction-intensity", default_value_t = 0.5)]
+        attraction_intensity: Real,
+
+        // l, how far the attraction reaches
+        #[arg(long = "attractive-length-scale", default_value_t = 1.5)]
+        attractive_length_scale: Real,
+    },
+
+    // Bacterial foraging. An iteration is one chemotactic step, the reproduction and elimination-dispersal loops around
+    // them repeat for as long as the run goes on
+    Bacteria {
+        #[arg(long = "bacteria-num-iters")]
+        bacteria_num_iters: Option<usize>,
+
+        #[arg(long = "bacterium-count")]
+        bacterium_count: usize,
+
+        // Between reproductions
+        #[arg(long = "chemotactic-steps", default_value_t = 50)]
+        chemotactic_steps: usize,
+
+        // The most steps in the same direction after a tumble
+        #[arg(long = "swim-length", default_value_t = 4)]
+        swim_length: usize,
+
+        // Between elimination-dispersal events
+        #[arg(long = "reproduction-steps", default_value_t = 4)]
+        reproduction_steps: usize,
+
+        #[arg(long = "elimination-probability", default_value_t = 0.25)]
+        elimination_probability: Real,
+
+        // As a fraction of the width of the bounds
+        #[arg(long = "step-size", default_value_t = 0.01)]
+        step_size: Real,
+    },
+
+    // Invasive weed optimization
+    Iwo {
+        #[arg(long = "iwo-num-iters")]
+        iwo_num_iters: Option<usize>,
+
+        // In the first colony
+        #[arg(long = "weed-count")]
+        weed_count: usize,
+
+        // That survive the competitive exclusion
+        #[arg(long = "max-weed-count")]
+        max_weed_count: usize,
+
+        // Of the worst weed
+        #[arg(long = "min-seeds", default_value_t = 0)]
+        min_seeds: usize,
+
+        // Of the best weed
+        #[arg(long = "max-seeds", default_value_t = 5)]
+        max_seeds: usize,
+
+        // Standard deviations of seed dispersal, as fractions of the width of the bounds
+        #[arg(long = "initial-sigma", default_value_t = 0.05)]
+        initial_sigma: Real,
+
+        #[arg(long = "final-sigma", default_value_t = 0.00001)]
+        final_sigma: Real,
+
+        // How fast the standard deviation falls
+        #[arg(long = "modulation-index", default_value_t = 3.0)]
+        modulation_index: Real,
+    },
+
+    // Towards the best agent and away from the worst one, with no parameters to tune
+    Jaya {
+        #[arg(long = "jaya-num-iters")]
+        jaya_num_iters: Option<usize>,
+
+        #[arg(long = "jaya-count")]
+        jaya_count: usize,
+    },
+
+    Crows {
+        #[arg(long = "crow-num-iters")]
+        crow_num_iters: Option<usize>,
+
+        #[arg(long = "crow-count")]
+        crow_count: usize,
+
+        // That a followed crow notices and flies somewhere random instead
+        #[arg(long = "awareness-probability", default_value_t = 0.1)]
+        awareness_probability: Real,
+
+        // How far past the hiding place a follower may fly
+        #[arg(long = "flight-length", default_value_t = 2.0)]
+        flight_length: Real,
+    },
+
+    // The cross-entropy method, with a Gaussian of independent coordinates fitted to the best samples
+    Cem {
+        #[arg(long = "cem-num-iters")]
+        cem_num_iters: Option<usize>,
+
+        // Per generation
+        #[arg(long = "sample-count")]
+        sample_count: usize,
+
+        // Of the samples the distribution is fitted to
+        #[arg(long = "elite-fraction", default_value_t = 0.2)]
+        elite_fraction: Real,
+
+        // Weight of the fitted distribution against the previous one
+        #[arg(long = "smoothing", default_value_t = 0.7)]
+        smoothing: Real,
+    },
+
+    // Continuous univariate marginal distribution algorithm, an estimation of distribution algorithm that fits a normal
+    // distribution to every coordinate of the best part of the population
+    Umda {
+        #[arg(long = "umda-num-iters")]
+        umda_num_iters: Option<usize>,
+
+        #[arg(long = "population-size")]
+        population_size: usize,
+
+        // Of the population the next distribution is fitted to
+        #[arg(long = "selection-ratio", default_value_t = 0.5)]
+        selection_ratio: Real,
+    },
+
+    // Prints a completion script, e.g. `swarm_optimizers completions bash > /etc/bash_completion.d/swarm_optimizers`
+    #[serde(skip)]
+    Completions {
+        shell: clap_complete::Shell,
+    },
+
+    // Hands the batch runs out to `worker` processes instead of running them here, e.g.
+    // `swarm_optimizers --functions ackley,rastrigin --try-count 64 serve --listen 0.0.0.0:7878 bats --bat-count 20 ...`
+    Serve {
+        #[arg(long = "listen")]
+        listen: String,
+
+        // Runs handed to a worker at once
+        #[arg(long = "chunk-size", default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..))]
+        chunk_size: u32,
+
+        // The algorithm subcommand and its flags, forwarded to the workers as they are
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        algorithm_arguments: Vec<String>,
+    },
+
+    // Connects to a `serve` coordinator and runs the batches it sends until it disconnects
+    Worker {
+        #[arg(long = "connect")]
+        connect: String,
+    },
+
+    // Runs the batches other services submit over HTTP, see http_server.rs for the API, e.g.
+    // `swarm_optimizers serve-http --listen 127.0.0.1:8080 --threads 8`
+    ServeHttp {
+        #[arg(long = "listen")]
+        listen: String,
+    },
+
+    // Aggregates `--output-format json`/`jsonl` files into tables, plots and reports, e.g.
+    // `swarm_optimizers collect --input 'output_bats/*' --output summary.csv --report report.html`
+    #[serde(skip)]
+    Collect {
+        #[command(flatten)]
+        arguments: Box<collect::CollectArguments>,
+    },
+
+    // Runs a batch for every combination of the given parameter values, e.g.
+    // `swarm_optimizers --functions ackley --try-count 64 --store sweep.db grid-search --grid pulse-rate-factor=0.1,0.5,0.9 --grid loudness-cooling-rate=0.1,0.9 bats --bat-count 20 ...`
+    // Cells already completed in the store are skipped, so an interrupted sweep continues when the same command is run again
+    GridSearch {
+        // `parameter=value,value,...`, repeated for every swept parameter
+        #[arg(long = "grid", required = true)]
+        grid: Vec<String>,
+
+        // The algorithm subcommand and its flags
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        algorithm_arguments: Vec<String>,
+    },
+
+    // Scores the configurations an external tuner asks for, one JSON line on stdin answered by one on stdout, see
+    // swarm_optimizers::tuning for the protocol. The parameters of an ask override the flags for every function, e.g.
+    // `swarm_optimizers --functions ackley,rastrigin --try-count 16 ask-tell bats --bat-num-iters 500 ...`
+    AskTell {
+        // The algorithm subcommand and its flags
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        algorithm_arguments: Vec<String>,
+    },
+}
+
+impl OptimizationAlgorithmCommand {
+    fn is_algorithm(&self) -> bool {
+        return !matches!(self, Self::Completions { .. } | Self::Collect { .. } | Self::Serve { .. } | Self::Worker { .. } | Self::ServeHttp { .. } | Self::GridSearch { .. } | Self::AskTell { .. });
+    }
+}
+
+// For subcommands wrapping an algorithm subcommand, like `serve` and `grid-search`
+fn parse_algorithm_arguments(algorithm_arguments: &[String]) -> OptimizationAlgorithmCommand {
+    let arguments = std::iter::once(env!("CARGO_BIN_NAME").to_string()).chain(algorithm_arguments.iter().cloned());
+    let command = Config::try_parse_from(arguments).unwrap_or_else(|error| error.exit()).command;
+    if !command.is_algorithm() {
+        Config::command().error(ErrorKind::InvalidSubcommand, "expected an optimization algorithm subcommand").exit();
     }
+    return command;
+}
+
+// The outcome of a single run, enough to reproduce it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RunResult {
+    pub seed: u64,
+    pub best_value: f64,
+    pub best_solution: Vec<f64>,
+    pub evaluations: usize,
+    pub evaluations_to_target: Option<usize>, // None if the target was not reached or there was none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<(usize, f64)>>, // (evaluations, best value) after each iteration, only with --output-trace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub population: Option<Vec<Vec<f64>>>, // The final positions of the agents, only with --mat-output
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct BatchRunData {
     pub min_result: f64,
-    pub max_result: f64,
+    pub max_result: f64, // A run that ended on NaN is the worst
     pub average: f64,
     pub run_count: u32,
+    pub success_count: u32, // Runs that reached the target value
+    pub evaluations_to_target: usize, // Summed over successful runs only
+    pub runs: Vec<RunResult>, // Every run, for statistics beyond min/avg/max
 }
 
 impl BatchRunData {
@@ -84,175 +847,937 @@ impl BatchRunData {
             max_result: f64::MIN,
             average: 0.0,
             run_count: 0,
+            success_count: 0,
+            evaluations_to_target: 0,
+            runs: Vec::new(),
         };
     }
+
+    fn success_rate(&self) -> f64 {
+        return self.success_count as f64 / self.run_count as f64;
+    }
+
+    fn average_evaluations_to_target(&self) -> f64 {
+        return self.evaluations_to_target as f64 / self.success_count as f64;
+    }
 }
 
 impl AddAssign for BatchRunData {
     fn add_assign(&mut self, other: Self) {
-        if other.max_result > self.max_result {
+        if Fitness::minimize(other.max_result) > Fitness::minimize(self.max_result) {
             self.max_result = other.max_result;
         }
-        if other.min_result < self.min_result {
+        if Fitness::minimize(other.min_result) < Fitness::minimize(self.min_result) {
             self.min_result = other.min_result;
         }
         let self_sum = self.average * self.run_count as f64;
         let other_sum = other.average * other.run_count as f64;
         self.run_count += other.run_count;
         self.average = (self_sum + other_sum) / self.run_count as f64;
+        self.success_count += other.success_count;
+        self.evaluations_to_target += other.evaluations_to_target;
+        self.runs.extend(other.runs);
     }
 }
 
-impl AddAssign<f64> for BatchRunData {
-    fn add_assign(&mut self, rhs: f64) {
-        if rhs > self.max_result {
-            self.max_result = rhs;
+impl AddAssign<RunResult> for BatchRunData {
+    fn add_assign(&mut self, rhs: RunResult) {
+        if Fitness::minimize(rhs.best_value) > Fitness::minimize(self.max_result) {
+            self.max_result = rhs.best_value;
         }
-        if rhs < self.min_result {
-            self.min_result = rhs;
+        if Fitness::minimize(rhs.best_value) < Fitness::minimize(self.min_result) {
+            self.min_result = rhs.best_value;
         }
         let previous_sum = self.average * self.run_count as f64;
         self.run_count += 1;
-        self.average = (previous_sum + rhs) / self.run_count as f64;
-        
+        self.average = (previous_sum + rhs.best_value) / self.run_count as f64;
+        if let Some(evaluations) = rhs.evaluations_to_target {
+            self.success_count += 1;
+            self.evaluations_to_target += evaluations;
+        }
+        self.runs.push(rhs);
     }
 }
 
+// Entries naming a function take precedence over a bare global value
+fn parse_target_value(entry: &str, value: &str) -> f64 {
+    return value.parse().unwrap_or_else(|_| {
+        Config::command().error(ErrorKind::InvalidValue, format!("invalid target value '{entry}', expected 'value' or 'function=value'")).exit();
+    });
+}
+
+fn target_value_for(target_values: &[String], function_name: &str) -> Option<f64> {
+    let mut global_target = None;
+    for entry in target_values {
+        match entry.split_once('=') {
+            Some((name, value)) => {
+                if name == function_name {
+                    return Some(parse_target_value(entry, value));
+                }
+            },
+            None => global_target = Some(parse_target_value(entry, entry)),
+        }
+    }
+    return global_target;
+}
+
+// Splits the values of --override on the commas outside of JSON arrays, objects and strings
+fn split_overrides(values: &[String]) -> Vec<String> {
+    let mut entries = Vec::new();
+    for value in values {
+        let (mut depth, mut in_string, mut escaped, mut start) = (0, false, false, 0);
+        for (index, character) in value.char_indices() {
+            match character {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '[' | '{' if !in_string => depth += 1,
+                ']' | '}' if !in_string => depth -= 1,
+                ',' if !in_string && depth == 0 => {
+                    entries.push(value[start..index].to_string());
+                    start = index + 1;
+                },
+                _ => {},
+            }
+        }
+        entries.push(value[start..].to_string());
+    }
+    return entries;
+}
+
+// The function and parameter of an override, with the value
+fn split_override(entry: &str) -> (&str, &str, &str) {
+    let Some((name, parameter, value)) = entry.split_once('=').and_then(|(function_and_parameter, value)| {
+        let (name, parameter) = function_and_parameter.split_once(':')?;
+        return Some((name, parameter, value));
+    }) else {
+        Config::command().error(ErrorKind::InvalidValue, format!("invalid override '{entry}', expected 'function:parameter=value'")).exit();
+    };
+    return (name, parameter, value);
+}
+
+// Every override must name one of --functions, apply_overrides only sees the function it patches and skips the others
+fn check_override_functions(config: &Config) {
+    for entry in &config.overrides {
+        let (name, _, _) = split_override(entry);
+        if !config.functions.iter().any(|function_name| function_name == name) {
+            Config::command().error(ErrorKind::InvalidValue, format!("the override '{entry}' is for '{name}', which is not in --functions")).exit();
+        }
+    }
+}
+
+// Parameters are patched through the serialized form, so every algorithm subcommand supports overrides without extra code
+fn apply_overrides(command: &OptimizationAlgorithmCommand, overrides: &[String], function_name: &str) -> OptimizationAlgorithmCommand {
+    let mut serialized = serde_json::to_value(command).unwrap();
+    let parameters = serialized.as_object_mut().and_then(|variant| variant.values_mut().next()).and_then(|parameters| parameters.as_object_mut()).unwrap();
+    for entry in overrides {
+        let (name, parameter, value) = split_override(entry);
+        if name != function_name {
+            continue;
+        }
+        let field = parameters.get_mut(&parameter.replace('-', "_")).unwrap_or_else(|| {
+            Config::command().error(ErrorKind::InvalidValue, format!("unknown parameter '{parameter}' in the override '{entry}'")).exit();
+        });
+        // Anything that isn't JSON is taken as a string, e.g. `ackley:movement=negated`
+        *field = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+    }
+    return serde_json::from_value(serialized).unwrap_or_else(|error| {
+        Config::command().error(ErrorKind::InvalidValue, format!("invalid override for {function_name}: {error}")).exit();
+    });
+}
+
+fn get_run_length(eval_budget: Option<usize>, num_iters: Option<usize>, num_iters_flag: &str) -> RunLength {
+    match (eval_budget, num_iters) {
+        (Some(_), Some(_)) => Config::command().error(ErrorKind::ArgumentConflict, format!("the argument '--eval-budget' cannot be used with '{num_iters_flag}'")).exit(),
+        (Some(budget), None) => return RunLength::Evaluations(budget),
+        (None, Some(iterations)) => return RunLength::Iterations(iterations),
+        (None, None) => Config::command().error(ErrorKind::MissingRequiredArgument, format!("either '--eval-budget' or '{num_iters_flag}' is required")).exit(),
+    }
+}
+
+// Optional consumers of the individual runs of a batch
+#[derive(Clone, Default)]
+struct BatchOutputs {
+    progress: Option<ProgressReporter>,
+    store: Option<(BatchSender, String)>, // Together with the function name
+    record_traces: bool, // For the summaries, the store has its own setting
+    record_populations: bool,
+}
+
+// The runs of a batch queued on the pool, summed up in whatever order they finish
+struct PendingBatch {
+    receiver: Receiver<RunResult>,
+    run_count: usize,
+}
+
+impl PendingBatch {
+    fn wait(self) -> BatchRunData {
+        let mut run_stats = BatchRunData::new();
+        for _ in 0..self.run_count {
+            run_stats += self.receiver.recv().expect("A run of the batch panicked");
+        }
+        return run_stats;
+    }
+}
+
+// Every run builds its own population from the shared parameters. Each task takes up to `lockstep` runs
+fn queue_runs<const N: usize, World: FromParameters<N> + 'static>(pool: &WorkerPool, parameters: Arc<World::Parameters>, function: Objective<N>, run_length: RunLength, target_value: Option<f64>, run_count: usize, lockstep: usize, outputs: BatchOutputs) -> PendingBatch {
+    let (sender, receiver) = mpsc::channel();
+    let mut remaining_runs = run_count;
+    while remaining_runs > 0 {
+        let group_size = remaining_runs.min(lockstep);
+        remaining_runs -= group_size;
+        let parameters = parameters.clone();
+        let function = function.clone();
+        let outputs = outputs.clone();
+        let sender = sender.clone();
+        pool.submit(move || {
+            if let Some(progress) = &outputs.progress {
+                progress.start();
+            }
+            let store_traces = outputs.store.as_ref().is_some_and(|(store, _)| store.record_traces);
+            let record_traces = store_traces || outputs.record_traces;
+            // Every run gets its own seed, so any of them can be reproduced from the store
+            let seeds = (0..group_size).map(|_| thread_rng().gen::<u64>()).collect::<Vec<_>>();
+            let mut worlds = seeds.iter().map(|&seed| World::from_parameters(parameters.clone(), seed)).collect::<Vec<_>>();
+
+            let mut traces = vec![Vec::new(); group_size];
+            let target_value_real = target_value.map(real::from_f64);
+            let evaluations_to_target = match worlds.as_mut_slice() {
+                // A world of its own keeps its --parallel-agents evaluation
+                [world] => vec![world.run_observed(run_length, target_value_real, |world| {
+                    if record_traces {
+                        traces[0].push((world.evaluation_count(), real::to_f64(world.best_solution_value())));
+                    }
+                })],
+                worlds => run_lockstep(worlds, &function, run_length, target_value_real, |index, world| {
+                    if record_traces {
+                        traces[index].push((world.evaluation_count(), real::to_f64(world.best_solution_value())));
+                    }
+                }),
+            };
+
+            for (((seed, world), evaluations_to_target), trace) in seeds.into_iter().zip(&worlds).zip(evaluations_to_target).zip(traces) {
+                let run = RunResult {
+                    seed,
+                    best_value: real::to_f64(world.best_solution_value()),
+                    best_solution: world.best_solution().coordinates.map(real::to_f64).to_vec(),
+                    evaluations: world.evaluation_count(),
+                    evaluations_to_target,
+                    trace: outputs.record_traces.then(|| trace.clone()),
+                    population: outputs.record_populations.then(|| {
+                        let mut positions = Vec::new();
+                        world.population(&mut positions);
+                        return positions.iter().map(|position| position.coordinates.map(real::to_f64).to_vec()).collect();
+                    }),
+                };
+                if let Some(progress) = &outputs.progress {
+                    progress.report_run(run.best_value, run.evaluations);
+                }
+                if let Some((store, function_name)) = &outputs.store {
+                    store.send(RunRecord {
+                        function_name: function_name.clone(),
+                        target_value,
+                        run: run.clone(),
+                        trace: store_traces.then_some(trace),
+                    });
+                }
+                sender.send(run).unwrap();
+            }
+        });
+    }
+    return PendingBatch { receiver, run_count };
+}
+
+// What is done with the worlds of an algorithm subcommand, so the parameters are gathered once for every mode
+trait WorldConsumer {
+    type Output;
+    fn consume<const N: usize, World: FromParameters<N> + 'static>(self, parameters: Arc<World::Parameters>, function: Objective<N>, run_length: RunLength, options: WorldOptions) -> Self::Output;
+}
+
+// The function the worlds of the command line minimize
+#[derive(Clone, Debug)]
+enum Objective<const N: usize> {
+    Builtin(Functions<N>),
+    Remote(Arc<RemoteObjective>), // Shared by all runs of a batch
+    #[cfg(feature = "zmq")]
+    Zmq(Arc<ZmqObjective>), // Shared by all batches using the endpoint
+}
+
+impl<const N: usize> Function<N> for Objective<N> {
+    fn evaluate(&self, input: VectorN<Real, N>) -> Real {
+        match self {
+            Self::Builtin(function) => return function.evaluate(input),
+            Self::Remote(function) => return function.evaluate(input),
+            #[cfg(feature = "zmq")]
+            Self::Zmq(function) => return function.evaluate(input),
+        }
+    }
+
+    fn evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        match self {
+            Self::Builtin(function) => function.evaluate_batch_into(inputs, values),
+            Self::Remote(function) => function.evaluate_batch_into(inputs, values),
+            #[cfg(feature = "zmq")]
+            Self::Zmq(function) => function.evaluate_batch_into(inputs, values),
+        }
+    }
+
+    fn par_evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        match self {
+            Self::Builtin(function) => function.par_evaluate_batch_into(inputs, values),
+            Self::Remote(function) => function.par_evaluate_batch_into(inputs, values),
+            #[cfg(feature = "zmq")]
+            Self::Zmq(function) => function.par_evaluate_batch_into(inputs, values),
+        }
+    }
+}
+
+fn build_world<Consumer: WorldConsumer>(command: &OptimizationAlgorithmCommand, function_name: &str, eval_budget: Option<usize>, options: WorldOptions, consumer: Consumer) -> Consumer::Output {
+    match options.dimensions {
+        2 => return build_world_in::<2, Consumer>(command, function_name, eval_budget, options, consumer),
+        10 => return build_world_in::<10, Consumer>(command, function_name, eval_budget, options, consumer),
+        20 => return build_world_in::<20, Consumer>(command, function_name, eval_budget, options, consumer),
+        30 => return build_world_in::<30, Consumer>(command, function_name, eval_budget, options, consumer),
+        50 => return build_world_in::<50, Consumer>(command, function_name, eval_budget, options, consumer),
+        100 => return build_world_in::<100, Consumer>(command, function_name, eval_budget, options, consumer),
+        dimensions => unreachable!("{dimensions} dimensions passed parse_dimensions"),
+    }
+}
+
+// An endpoint can only be bound once, batches running at the same time share its workers
+#[cfg(feature = "zmq")]
+static BOUND_ZMQ_OBJECTIVES: LazyLock<Mutex<HashMap<String, Arc<ZmqObjective>>>> = LazyLock::new(Default::default);
+
+// Once every batch is done, so the warnings don't end up between the results
+fn report_dropped_answers() {
+    #[cfg(feature = "zmq")]
+    for objective in BOUND_ZMQ_OBJECTIVES.lock().unwrap().values() {
+        if objective.unreadable_answers() > 0 {
+            eprintln!("Dropped {} unreadable answers from the workers of {}", objective.unreadable_answers(), objective.endpoint());
+        }
+    }
+}
+
+// Functions given as URLs or ZeroMQ endpoints
+fn remote_objective<const N: usize>(function_name: &str, arguments: &RemoteArguments) -> Option<Objective<N>> {
+    if function_name.starts_with("http://") || function_name.starts_with("https://") {
+        return Some(Objective::Remote(Arc::new(RemoteObjective::new(function_name, arguments.options()))));
+    }
+    #[cfg(feature = "zmq")]
+    if function_name.starts_with("tcp://") || function_name.starts_with("ipc://") {
+        let mut bound = BOUND_ZMQ_OBJECTIVES.lock().unwrap();
+        let objective = bound.entry(function_name.to_string()).or_insert_with(|| Arc::new(ZmqObjective::bind(function_name, arguments.zmq_options())));
+        return Some(Objective::Zmq(objective.clone()));
+    }
+    return None;
+}
+
+fn build_world_in<const N: usize, Consumer: WorldConsumer>(command: &OptimizationAlgorithmCommand, function_name: &str, eval_budget: Option<usize>, options: WorldOptions, consumer: Consumer) -> Consumer::Output {
+    let (function, bounds) = match remote_objective::<N>(function_name, &options.remote) {
+        Some(function) => (function, options.remote.bounds.unwrap_or_else(|| {
+            Config::command().error(ErrorKind::MissingRequiredArgument, format!("the remote function '{function_name}' requires '--objective-bounds'")).exit();
+        })),
+        None => {
+            let function = Functions::<N>::make_from_name(function_name);
+            (Objective::Builtin(function), function.get_bounds())
+        },
+    };
+    match options.random_generator {
+        RandomGenerator::Xoshiro => return build_world_with::<N, Xoshiro256PlusPlus, Consumer>(command, function, bounds, eval_budget, options, consumer),
+        RandomGenerator::Std => return build_world_with::<N, StdRng, Consumer>(command, function, bounds, eval_budget, options, consumer),
+    }
+}
+
+fn build_world_with<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync + 'static, Consumer: WorldConsumer>(command: &OptimizationAlgorithmCommand, function: Objective<N>, bounds: (Real, Real), eval_budget: Option<usize>, options: WorldOptions, consumer: Consumer) -> Consumer::Output {
+    match *command {
+        OptimizationAlgorithmCommand::Bats { bat_num_iters, 
+            bat_count, 
+            frequency_left_bound, 
+            frequency_right_bound, 
+            initial_pulse_rate, 
+            pulse_rate_factor, 
+            initial_loudness , 
+            loudness_cooling_rate,
+            movement,
+            initial_velocity,
+            improvement_tolerance,
+            scale_walk_to_bounds,
+        } => {
+            let parameters = bats::Parameters {
+                bat_count,
+                function: function.clone(),
+                bounds,
+                frequency_bounds: (frequency_left_bound, frequency_right_bound),
+                initial_pulse_rate,
+                pulse_rate_factor,
+                initial_loudness,
+                loudness_cool_factor: loudness_cooling_rate,
+                movement,
+                initial_velocity,
+                improvement_tolerance,
+                scale_walk_to_bounds,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, bats::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, bat_num_iters, "--bat-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Butterflies { butterfly_num_iters, 
+            butterfly_count, 
+            fragrance_multiplier, 
+            fragrance_exponent_left_bound,
+            fragrance_exponent_right_bound, 
+            local_search_chance,
+            movement,
+            initial_velocity,
+            improvement_tolerance,
+        } => {
+            let parameters = butterflies::Parameters {
+                population_size: butterfly_count,
+                function: function.clone(),
+                bounds,
+                fragrance_multiplier,
+                fragrance_exponent_bounds: (fragrance_exponent_left_bound, fragrance_exponent_right_bound),
+                local_search_chance,
+                movement,
+                initial_velocity,
+                improvement_tolerance,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, butterflies::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, butterfly_num_iters, "--butterfly-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::GreyWolf { grey_wolf_num_iters, wolf_count, initial_a, final_a } => {
+            let parameters = grey_wolf::Parameters {
+                wolf_count,
+                function: function.clone(),
+                bounds,
+                a_bounds: (initial_a, final_a),
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, grey_wolf::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, grey_wolf_num_iters, "--grey-wolf-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Cuckoo { cuckoo_num_iters, nest_count, abandon_probability, step_scale, levy_exponent } => {
+            let parameters = cuckoo::Parameters {
+                nest_count,
+                function: function.clone(),
+                bounds,
+                abandon_probability,
+                step_scale,
+                levy_exponent,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, cuckoo::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, cuckoo_num_iters, "--cuckoo-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Abc { abc_num_iters, bee_count, limit } => {
+            let parameters = abc::Parameters {
+                bee_count,
+                function: function.clone(),
+                bounds,
+                limit,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, abc::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, abc_num_iters, "--abc-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Annealing { annealing_num_iters, initial_temperature, cooling_rate, initial_radius, min_radius, moves_per_iteration } => {
+            let parameters = annealing::Parameters {
+                function: function.clone(),
+                bounds,
+                initial_temperature,
+                cooling_rate,
+                initial_radius,
+                min_radius,
+                moves_per_iteration,
+            };
+            parameters.validate();
+            return consumer.consume::<N, annealing::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, annealing_num_iters, "--annealing-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::FlowerPollination { flower_num_iters, flower_count, switch_probability, step_scale, levy_exponent } => {
+            let parameters = flower_pollination::Parameters {
+                flower_count,
+                function: function.clone(),
+                bounds,
+                switch_probability,
+                step_scale,
+                levy_exponent,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, flower_pollination::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, flower_num_iters, "--flower-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::MothFlame { moth_flame_num_iters, moth_count, spiral_shape } => {
+            let parameters = moth_flame::Parameters {
+                moth_count,
+                function: function.clone(),
+                bounds,
+                spiral_shape,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, moth_flame::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, moth_flame_num_iters, "--moth-flame-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Salps { salp_num_iters, salp_count } => {
+            let parameters = salps::Parameters {
+                salp_count,
+                function: function.clone(),
+                bounds,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, salps::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, salp_num_iters, "--salp-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Aco { aco_num_iters, ant_count, archive_size, q, xi } => {
+            let parameters = aco::Parameters {
+                ant_count,
+                archive_size,
+                function: function.clone(),
+                bounds,
+                q,
+                xi,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, aco::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, aco_num_iters, "--aco-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::SineCosine { sine_cosine_num_iters, agent_count, amplitude } => {
+            let parameters = sine_cosine::Parameters {
+                agent_count,
+                function: function.clone(),
+                bounds,
+                amplitude,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, sine_cosine::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, sine_cosine_num_iters, "--sine-cosine-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Gsa { gsa_num_iters, agent_count, initial_gravity, gravity_decay } => {
+            let parameters = gsa::Parameters {
+                agent_count,
+                function: function.clone(),
+                bounds,
+                initial_gravity,
+                gravity_decay,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, gsa::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, gsa_num_iters, "--gsa-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Dragonflies { dragonfly_num_iters, dragonfly_count, separation_weight, alignment_weight, cohesion_weight, food_weight, enemy_weight } => {
+            let parameters = dragonflies::Parameters {
+                dragonfly_count,
+                function: function.clone(),
+                bounds,
+                separation_weight,
+                alignment_weight,
+                cohesion_weight,
+                food_weight,
+                enemy_weight,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, dragonflies::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, dragonfly_num_iters, "--dragonfly-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Krill { krill_num_iters, krill_count, max_induced_speed, foraging_speed, max_diffusion, time_constant } => {
+            let parameters = krill::Parameters {
+                krill_count,
+                function: function.clone(),
+                bounds,
+                max_induced_speed,
+                foraging_speed,
+                max_diffusion,
+                time_constant,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, krill::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, krill_num_iters, "--krill-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Fss { fss_num_iters, fish_count, weight_scale, initial_step, final_step } => {
+            let parameters = fss::Parameters {
+                fish_count,
+                function: function.clone(),
+                bounds,
+                weight_scale,
+                step_bounds: (initial_step, final_step),
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, fss::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, fss_num_iters, "--fss-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Tlbo { tlbo_num_iters, tlbo_count } => {
+            let parameters = tlbo::Parameters {
+                learner_count: tlbo_count,
+                function: function.clone(),
+                bounds,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, tlbo::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, tlbo_num_iters, "--tlbo-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::HarrisHawks { harris_hawks_num_iters, hawk_count } => {
+            let parameters = harris_hawks::Parameters {
+                hawk_count,
+                function: function.clone(),
+                bounds,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, harris_hawks::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, harris_hawks_num_iters, "--harris-hawks-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::NelderMead { nelder_mead_num_iters, initial_size, tolerance } => {
+            let parameters = nelder_mead::Parameters {
+                function: function.clone(),
+                bounds,
+                initial_size,
+                tolerance,
+            };
+            parameters.validate();
+            return consumer.consume::<N, nelder_mead::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, nelder_mead_num_iters, "--nelder-mead-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::RandomSearch { random_search_num_iters, sample_count } => {
+            let parameters = random_search::Parameters {
+                sample_count,
+                function: function.clone(),
+                bounds,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, random_search::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, random_search_num_iters, "--random-search-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::PatternSearch { pattern_search_num_iters, initial_step, shrink, tolerance } => {
+            let parameters = pattern_search::Parameters {
+                function: function.clone(),
+                bounds,
+                initial_step,
+                shrink,
+                tolerance,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, pattern_search::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, pattern_search_num_iters, "--pattern-search-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Grasshoppers { grasshopper_num_iters, grasshopper_count, attraction_intensity, attractive_length_scale } => {
+            let parameters = grasshoppers::Parameters {
+                grasshopper_count,
+                function: function.clone(),
+                bounds,
+                attraction_intensity,
+                attractive_length_scale,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, grasshoppers::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, grasshopper_num_iters, "--grasshopper-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Bacteria { bacteria_num_iters, bacterium_count, chemotactic_steps, swim_length, reproduction_steps, elimination_probability, step_size } => {
+            let parameters = bacteria::Parameters {
+                bacterium_count,
+                function: function.clone(),
+                bounds,
+                chemotactic_steps,
+                swim_length,
+                reproduction_steps,
+                elimination_probability,
+                step_size,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, bacteria::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, bacteria_num_iters, "--bacteria-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Iwo { iwo_num_iters, weed_count, max_weed_count, min_seeds, max_seeds, initial_sigma, final_sigma, modulation_index } => {
+            let parameters = iwo::Parameters {
+                initial_count: weed_count,
+                max_population: max_weed_count,
+                function: function.clone(),
+                bounds,
+                seed_range: (min_seeds, max_seeds),
+                sigma_bounds: (initial_sigma, final_sigma),
+                modulation_index,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, iwo::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, iwo_num_iters, "--iwo-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Jaya { jaya_num_iters, jaya_count } => {
+            let parameters = jaya::Parameters {
+                agent_count: jaya_count,
+                function: function.clone(),
+                bounds,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, jaya::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, jaya_num_iters, "--jaya-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Crows { crow_num_iters, crow_count, awareness_probability, flight_length } => {
+            let parameters = crows::Parameters {
+                crow_count,
+                function: function.clone(),
+                bounds,
+                awareness_probability,
+                flight_length,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, crows::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, crow_num_iters, "--crow-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Cem { cem_num_iters, sample_count, elite_fraction, smoothing } => {
+            let parameters = cem::Parameters {
+                sample_count,
+                function: function.clone(),
+                bounds,
+                elite_fraction,
+                smoothing,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, cem::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, cem_num_iters, "--cem-num-iters"), options);
+        },
+
+        OptimizationAlgorithmCommand::Umda { umda_num_iters, population_size, selection_ratio } => {
+            let parameters = umda::Parameters {
+                population_size,
+                function: function.clone(),
+                bounds,
+                selection_ratio,
+                parallel: options.parallel_agents,
+            };
+            parameters.validate();
+            return consumer.consume::<N, umda::WorldState<N, RngType, Objective<N>>>(Arc::new(parameters), function, get_run_length(eval_budget, umda_num_iters, "--umda-num-iters"), options);
+        },
+
+        _ => unreachable!("Not an optimization algorithm"),
+    }
+}
+
+struct BatchConsumer<'a> {
+    pool: &'a WorkerPool,
+    target_value: Option<f64>,
+    run_count: usize,
+    outputs: BatchOutputs,
+}
+
+impl WorldConsumer for BatchConsumer<'_> {
+    type Output = PendingBatch;
+    fn consume<const N: usize, World: FromParameters<N> + 'static>(self, parameters: Arc<World::Parameters>, function: Objective<N>, run_length: RunLength, options: WorldOptions) -> Self::Output {
+        return queue_runs::<N, World>(self.pool, parameters, function, run_length, self.target_value, self.run_count, options.lockstep, self.outputs);
+    }
+}
+
+// Returns as soon as the runs are queued, so the batches of several functions or configurations share the pool
+fn queue_batch(pool: &WorkerPool, command: &OptimizationAlgorithmCommand, eval_budget: Option<usize>, function_name: &str, target_value: Option<f64>, run_count: usize, options: WorldOptions, outputs: BatchOutputs) -> PendingBatch {
+    return build_world(command, function_name, eval_budget, options, BatchConsumer { pool, target_value, run_count, outputs });
+}
+
+struct SingleConsumer<'a> {
+    function_name: &'a str,
+    target_value: Option<f64>,
+}
+
+impl WorldConsumer for SingleConsumer<'_> {
+    type Output = ();
+    fn consume<const N: usize, World: FromParameters<N> + 'static>(self, parameters: Arc<World::Parameters>, _function: Objective<N>, run_length: RunLength, _options: WorldOptions) {
+        run_single(World::from_parameters(parameters, thread_rng().gen()), self.function_name, run_length, self.target_value);
+    }
+}
+
+struct InitialEvaluations;
+
+impl WorldConsumer for InitialEvaluations {
+    type Output = usize;
+    fn consume<const N: usize, World: FromParameters<N> + 'static>(self, parameters: Arc<World::Parameters>, _function: Objective<N>, _run_length: RunLength, _options: WorldOptions) -> usize {
+        return World::from_parameters(parameters, 0).evaluation_count();
+    }
+}
+
+// Runs stop before an iteration that would go over --eval-budget, but the initial population is evaluated whatever the
+// budget, so a budget it doesn't fit in is rejected before anything runs. Evaluates one initial population
+fn check_eval_budget(command: &OptimizationAlgorithmCommand, function_name: &str, eval_budget: Option<usize>, options: WorldOptions) -> Result<(), String> {
+    let Some(budget) = eval_budget else {
+        return Ok(());
+    };
+    let initial_evaluations = build_world(command, function_name, eval_budget, options, InitialEvaluations);
+    if initial_evaluations > budget {
+        return Err(format!("invalid value '{budget}' for '--eval-budget': the initial population takes {initial_evaluations} evaluations of {function_name}"));
+    }
+    return Ok(());
+}
+
+fn run_single<const N: usize, World: Optimizer<N>>(mut world: World, function_name: &str, run_length: RunLength, target_value: Option<f64>) {
+    let evaluations_to_target = world.run(run_length, target_value.map(real::from_f64));
+    if let Some(target) = target_value {
+        match evaluations_to_target {
+            Some(evaluations) => println!("{}: Reached target {} after {} evaluations", function_name, target, evaluations),
+            None => println!("{}: Did not reach target {}", function_name, target),
+        }
+    }
+    println!("{}: Found optimum at {} = {}", function_name, world.best_solution(), world.best_solution_value());
+}
+
 fn main() {
-    let config = Config::parse();
+    let config = Config::try_parse_arguments(std::env::args_os()).unwrap_or_else(|error| error.exit());
+    let thread_count = config.threads.map_or_else(num_cpus::get, |threads| threads as usize);
+    #[cfg(feature = "gpu")]
+    if config.gpu {
+        swarm_optimizers::gpu::enable();
+    }
+    match &config.command {
+        OptimizationAlgorithmCommand::Completions { shell } => {
+            clap_complete::generate(*shell, &mut Config::command(), env!("CARGO_BIN_NAME"), &mut std::io::stdout());
+            return;
+        },
+        OptimizationAlgorithmCommand::Collect { arguments } => {
+            collect::run(arguments);
+            return;
+        },
+        OptimizationAlgorithmCommand::Worker { connect } => {
+            distributed::run_worker(connect, thread_count);
+            return;
+        },
+        OptimizationAlgorithmCommand::ServeHttp { listen } => {
+            http_server::serve(listen, thread_count);
+            return;
+        },
+        _ => {},
+    }
     if config.functions.is_empty() {
-        panic!("No functions given");
+        Config::command().error(ErrorKind::MissingRequiredArgument, "the following required arguments were not provided:\n  --functions <FUNCTIONS>...").exit();
     }
+    check_override_functions(&config);
+    if let OptimizationAlgorithmCommand::Serve { listen, chunk_size, algorithm_arguments } = &config.command {
+        if config.try_count.is_none() {
+            Config::command().error(ErrorKind::MissingRequiredArgument, "serve requires --try-count").exit();
+        }
+        // The runs happen on the workers, which only send back the summed results of their chunks
+        let local_outputs = [
+            ("--store", config.store.is_some()),
+            ("--store-trace", config.store_trace),
+            ("--watch", config.watch),
+            ("--run-records", config.run_records.is_some()),
+            ("--mat-output", config.mat_output.is_some()),
+            ("--metrics-listen", config.metrics_listen.is_some()),
+        ];
+        if let Some((flag, _)) = local_outputs.iter().find(|(_, given)| *given) {
+            Config::command().error(ErrorKind::ArgumentConflict, format!("the argument '{flag}' cannot be used with 'serve'")).exit();
+        }
+        distributed::serve(&config, listen, *chunk_size as usize, algorithm_arguments);
+        return;
+    }
+    if let OptimizationAlgorithmCommand::GridSearch { grid, algorithm_arguments } = &config.command {
+        if config.try_count.is_none() {
+            Config::command().error(ErrorKind::MissingRequiredArgument, "grid-search requires --try-count").exit();
+        }
+        grid_search::run(&config, grid, algorithm_arguments, thread_count);
+        report_dropped_answers();
+        return;
+    }
+    if let OptimizationAlgorithmCommand::AskTell { algorithm_arguments } = &config.command {
+        if config.try_count.is_none() {
+            Config::command().error(ErrorKind::MissingRequiredArgument, "ask-tell requires --try-count").exit();
+        }
+        ask_tell::run(&config, algorithm_arguments, thread_count);
+        report_dropped_answers();
+        return;
+    }
+    let options = config.world_options();
     let test_functions = config.functions.into_iter().map(|s| {
-        return (Functions::<FN_SIZE>::make_from_name(&s), s);
+        let command = apply_overrides(&config.command, &config.overrides, &s);
+        check_eval_budget(&command, &s, config.eval_budget, options).unwrap_or_else(|message| Config::command().error(ErrorKind::InvalidValue, message).exit());
+        return (target_value_for(&config.target_values, &s), command, s);
     }).collect::<Vec<_>>();
 
     if let Some(tries) = config.try_count {
-        for (function, function_name) in test_functions {
-            let bounds = function.get_bounds();
-            let tries_per_thread = tries.div_ceil(num_cpus::get());
-            let mut threads = Vec::with_capacity(num_cpus::get());
-            
-            match config.command {
-                OptimizationAlgorithmCommand::Bats { bat_num_iters, 
-                    bat_count, 
-                    frequency_left_bound, 
-                    frequency_right_bound, 
-                    initial_pulse_rate, 
-                    pulse_rate_factor, 
-                    initial_loudness , 
-                    loudness_cooling_rate
-                } => {
-                    let world = bats::WorldState::new(
-                        bat_count,
-                        function,
-                        bounds,
-                        (frequency_left_bound, frequency_right_bound),
-                        initial_pulse_rate,
-                        pulse_rate_factor,
-                        initial_loudness, 
-                        loudness_cooling_rate,
-                        StdRng::from_rng(thread_rng()).unwrap()
-                    );
-                    for _ in 0..num_cpus::get() {
-                        let mut thread_world = world.clone();
-                        threads.push(std::thread::spawn(move || {
-                            let mut run_stats = BatchRunData::new();
-                            for _ in 0..tries_per_thread {
-                                thread_world.do_all_iterations(bat_num_iters);
-                                run_stats += function.calculate(thread_world.best_solution);
-                                thread_world.reset();
-                            }
-                            return run_stats;
-                        }));
-                    }
-                },
+        // Also kept for the metrics, without drawing it
+        let dashboard = (config.watch || config.metrics_listen.is_some()).then(Dashboard::new);
+        let progress_reporters = test_functions.iter().map(|(_, _, function_name)| {
+            return dashboard.as_ref().map(|dashboard| dashboard.add_function(function_name, tries));
+        }).collect::<Vec<_>>();
+        let dashboard_thread = dashboard.as_ref().filter(|_| config.watch).map(Dashboard::spawn);
+        // The dashboard owns the terminal, so results wait until it is closed
+        let mut printer = SummaryPrinter::new(config.output_format, config.watch);
+        let mut run_records = config.run_records.as_deref().map(RunRecordWriter::new);
+        let mut mat_writer = config.mat_output.as_deref().map(MatWriter::new);
+        let store = config.store.as_ref().map(|path| store::open(path, config.store_trace));
+        let pool = WorkerPool::new(thread_count);
+        if let (Some(listen_address), Some(dashboard)) = (&config.metrics_listen, &dashboard) {
+            metrics::spawn(listen_address, dashboard.clone(), pool.queue_length());
+        }
 
-                OptimizationAlgorithmCommand::Butterflies { butterfly_num_iters, 
-                    butterfly_count, 
-                    fragrance_multiplier, 
-                    fragrance_exponent_left_bound,
-                    fragrance_exponent_right_bound, 
-                    local_search_chance 
-                } => {
-                    let world = butterflies::WorldState::new(
-                        butterfly_count,
-                        function,
-                        bounds,
-                        fragrance_multiplier,
-                        (fragrance_exponent_left_bound, fragrance_exponent_right_bound),
-                        local_search_chance,
-                        StdRng::from_rng(thread_rng()).unwrap()
-                    );
-                    for _ in 0..num_cpus::get() {
-                        let mut thread_world = world.clone();
-                        threads.push(std::thread::spawn(move || {
-                            let mut run_stats = BatchRunData::new();
-                            for _ in 0..tries_per_thread {
-                                thread_world.do_all_iterations(butterfly_num_iters);
-                                run_stats += function.calculate(thread_world.best_solution);
-                                thread_world.reset();
-                            }
-                            return run_stats;
-                        }));
-                    }
-                },
-            }
-            
-            let result = threads.into_iter().map(|handle| handle.join().unwrap()).reduce(|mut a, b| {
-                a += b;
-                return a;
-            }).unwrap();
-            println!("{}: Finished {} runs. Max solution is {}. Average solution is {}. Min solution is {}.", function_name, result.run_count, result.max_result, result.average, result.min_result);
+        let mut pending_batches = Vec::new();
+        for ((target_value, command, function_name), progress) in test_functions.into_iter().zip(progress_reporters) {
+            let outputs = BatchOutputs {
+                progress,
+                store: store.as_ref().map(|store| (store.sender.begin_batch(&command, config.eval_budget), function_name.clone())),
+                record_traces: config.output_trace || config.mat_output.is_some(),
+                record_populations: config.mat_output.is_some(),
+            };
+            let pending = queue_batch(&pool, &command, config.eval_budget, &function_name, target_value, tries, options, outputs);
+            pending_batches.push((function_name, target_value, command, pending));
         }
-    } else {
-        let mut threads = Vec::new();
-        for (function, function_name) in test_functions {
-            let bounds = function.get_bounds();
-            match config.command {
-                OptimizationAlgorithmCommand::Bats { bat_num_iters, bat_count, frequency_left_bound, frequency_right_bound, initial_pulse_rate, pulse_rate_factor, initial_loudness, loudness_cooling_rate } => {
-                    threads.push(std::thread::spawn(move || {
-                        let mut world = bats::WorldState::new(
-                            bat_count,
-                            function,
-                            bounds,
-                            (frequency_left_bound, frequency_right_bound),
-                            initial_pulse_rate,
-                            pulse_rate_factor,
-                            initial_loudness, 
-                            loudness_cooling_rate,
-                            StdRng::from_rng(thread_rng()).unwrap()
-                        );
-                        world.do_all_iterations(bat_num_iters);
-                        println!("{}: Found optimum at {:?} = {}", function_name, world.best_solution.coordinates, function.calculate(world.best_solution));
-                    }));
-                },
-                OptimizationAlgorithmCommand::Butterflies { 
-                    butterfly_num_iters, 
-                    butterfly_count, 
-                    fragrance_multiplier, 
-                    fragrance_exponent_left_bound,
-                    fragrance_exponent_right_bound, 
-                    local_search_chance 
-                } => {
-                    threads.push(std::thread::spawn(move || {
-                        let mut world = butterflies::WorldState::new(
-                            butterfly_count,
-                            function,
-                            bounds,
-                            fragrance_multiplier,
-                            (fragrance_exponent_left_bound, fragrance_exponent_right_bound),
-                            local_search_chance,
-                            StdRng::from_rng(thread_rng()).unwrap()
-                        );
-                        world.do_all_iterations(butterfly_num_iters);
-                        println!("{}: Found optimum at {:?} = {}", function_name, world.best_solution.coordinates, function.calculate(world.best_solution));
-                    }));
-                },
+        for (function_name, target_value, command, pending) in pending_batches {
+            let result = pending.wait();
+            let summary = BatchSummary::new(&function_name, &command, config.dimensions, config.eval_budget, target_value, result);
+            if let Some(run_records) = &mut run_records {
+                run_records.add(&summary);
+            }
+            if let Some(mat_writer) = &mut mat_writer {
+                mat_writer.add(&summary);
             }
+            printer.add(summary);
+        }
+        if let Some(run_records) = run_records {
+            run_records.finish();
         }
-        for thread in threads {
-            thread.join().unwrap();
+        if let Some(mat_writer) = mat_writer {
+            mat_writer.finish();
         }
+
+        drop(pool);
+        if let Some(store) = store {
+            store.close();
+        }
+        if let (Some(dashboard), Some(dashboard_thread)) = (dashboard, dashboard_thread) {
+            dashboard.finish();
+            dashboard_thread.join().unwrap();
+        }
+        printer.finish();
+    } else {
+        // Results are printed as the functions finish. At most --threads of them run at once, on a rayon pool so an idle
+        // thread steals queued functions from the busy ones, and --parallel-agents shares the same threads
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(thread_count.min(test_functions.len())).build().unwrap();
+        let eval_budget = config.eval_budget;
+        pool.scope(|scope| {
+            for (target_value, command, function_name) in test_functions {
+                scope.spawn(move |_| {
+                    build_world(&command, &function_name, eval_budget, options, SingleConsumer { function_name: &function_name, target_value });
+                });
+            }
+        });
     }
+    report_dropped_answers();
 }
@@ -0,0 +1,148 @@
+// The runs of the summaries as a MATLAB Level 5 MAT-file for --mat-output, readable by `load` in MATLAB and Octave and by
+// scipy.io.loadmat. It holds two struct arrays, with runs(k).batch indexing batches:
+//
+//     batches(b): function, algorithm, configuration (the JSON of the summary), grid_cell ('' outside grid-search),
+//                 target_value, eval_budget (NaN if there was none)
+//     runs(k):    batch, seed (uint64), best_value, best_solution (1 x dimensions), evaluations,
+//                 evaluations_to_target (NaN if the target wasn't reached), trace (iterations x 2, evaluations and
+//                 best value after each iteration), population (agents x dimensions, the final positions)
+
+use std::{fs, path::{Path, PathBuf}};
+
+use crate::output::BatchSummary;
+
+// Data types of the elements
+const MI_INT8: u32 = 1;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_DOUBLE: u32 = 9;
+const MI_UINT64: u32 = 13;
+const MI_MATRIX: u32 = 14;
+
+// Classes of the arrays
+const MX_STRUCT_CLASS: u32 = 2;
+const MX_CHAR_CLASS: u32 = 4;
+const MX_DOUBLE_CLASS: u32 = 6;
+const MX_UINT64_CLASS: u32 = 15;
+
+const FIELD_NAME_LENGTH: usize = 32; // Including the terminating NUL
+
+enum MatValue {
+    Double { rows: usize, columns: usize, values: Vec<f64> }, // Column-major
+    UInt64(u64),
+    Char(String),
+    Struct { fields: &'static [&'static str], elements: Vec<Vec<MatValue>> }, // A 1 x elements array, values in field order
+}
+
+impl MatValue {
+    fn scalar(value: f64) -> Self {
+        return Self::Double { rows: 1, columns: 1, values: vec![value] };
+    }
+
+    fn row(values: &[f64]) -> Self {
+        return Self::Double { rows: 1, columns: values.len(), values: values.to_vec() };
+    }
+
+    // From row-major rows of equal length
+    fn matrix(rows: &[Vec<f64>]) -> Self {
+        let columns = rows.first().map_or(0, Vec::len);
+        let values = (0..columns).flat_map(|column| rows.iter().map(move |row| row[column])).collect();
+        return Self::Double { rows: rows.len(), columns, values };
+    }
+}
+
+fn write_element(output: &mut Vec<u8>, data_type: u32, data: &[u8]) {
+    output.extend_from_slice(&data_type.to_le_bytes());
+    output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    output.extend_from_slice(data);
+    output.resize(output.len().next_multiple_of(8), 0);
+}
+
+fn write_matrix(output: &mut Vec<u8>, name: &str, value: &MatValue) {
+    let (class, dimensions) = match value {
+        MatValue::Double { rows, columns, .. } => (MX_DOUBLE_CLASS, [*rows, *columns]),
+        MatValue::UInt64(_) => (MX_UINT64_CLASS, [1, 1]),
+        MatValue::Char(text) => (MX_CHAR_CLASS, [1, text.encode_utf16().count()]),
+        MatValue::Struct { elements, .. } => (MX_STRUCT_CLASS, [1, elements.len()]),
+    };
+    let mut contents = Vec::new();
+    write_element(&mut contents, MI_UINT32, &[class.to_le_bytes(), [0; 4]].concat());
+    write_element(&mut contents, MI_INT32, &dimensions.iter().flat_map(|&dimension| (dimension as i32).to_le_bytes()).collect::<Vec<_>>());
+    write_element(&mut contents, MI_INT8, name.as_bytes());
+    match value {
+        MatValue::Double { values, .. } => write_element(&mut contents, MI_DOUBLE, &values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>()),
+        MatValue::UInt64(value) => write_element(&mut contents, MI_UINT64, &value.to_le_bytes()),
+        MatValue::Char(text) => write_element(&mut contents, MI_UINT16, &text.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>()),
+        MatValue::Struct { fields, elements } => {
+            write_element(&mut contents, MI_INT32, &(FIELD_NAME_LENGTH as i32).to_le_bytes());
+            let mut names = vec![0; fields.len() * FIELD_NAME_LENGTH];
+            for (field, slot) in fields.iter().zip(names.chunks_mut(FIELD_NAME_LENGTH)) {
+                slot[..field.len()].copy_from_slice(field.as_bytes());
+            }
+            write_element(&mut contents, MI_INT8, &names);
+            for element in elements {
+                for value in element {
+                    write_matrix(&mut contents, "", value);
+                }
+            }
+        },
+    }
+    write_element(output, MI_MATRIX, &contents);
+}
+
+fn optional(value: Option<impl Into<f64>>) -> MatValue {
+    return MatValue::scalar(value.map_or(f64::NAN, Into::into));
+}
+
+const BATCH_FIELDS: &[&str] = &["function", "algorithm", "configuration", "grid_cell", "target_value", "eval_budget"];
+const RUN_FIELDS: &[&str] = &["batch", "seed", "best_value", "best_solution", "evaluations", "evaluations_to_target", "trace", "population"];
+
+// Written once everything is done, like the run records
+pub struct MatWriter {
+    path: PathBuf,
+    batches: Vec<Vec<MatValue>>,
+    runs: Vec<Vec<MatValue>>,
+}
+
+impl MatWriter {
+    pub fn new(path: &Path) -> Self {
+        return Self { path: path.to_path_buf(), batches: Vec::new(), runs: Vec::new() };
+    }
+
+    pub fn add(&mut self, summary: &BatchSummary) {
+        self.batches.push(vec![
+            MatValue::Char(summary.function.clone()),
+            MatValue::Char(summary.algorithm.clone()),
+            MatValue::Char(serde_json::Value::from(summary.configuration.clone()).to_string()),
+            MatValue::Char(summary.grid_cell.clone().unwrap_or_default()),
+            optional(summary.target_value),
+            optional(summary.eval_budget.map(|budget| budget as f64)),
+        ]);
+        let batch = self.batches.len(); // 1-based, like MATLAB's indices
+        for run in &summary.result.runs {
+            let trace = run.trace.iter().flatten().map(|&(evaluations, value)| vec![evaluations as f64, value]).collect::<Vec<_>>();
+            self.runs.push(vec![
+                MatValue::scalar(batch as f64),
+                MatValue::UInt64(run.seed),
+                MatValue::scalar(run.best_value),
+                MatValue::row(&run.best_solution),
+                MatValue::scalar(run.evaluations as f64),
+                optional(run.evaluations_to_target.map(|evaluations| evaluations as f64)),
+                MatValue::matrix(&trace),
+                MatValue::matrix(run.population.as_deref().unwrap_or_default()),
+            ]);
+        }
+    }
+
+    pub fn finish(self) {
+        let mut output = format!("MATLAB 5.0 MAT-file, Platform: {}, Created by: {}", std::env::consts::OS, env!("CARGO_PKG_NAME")).into_bytes();
+        output.resize(116, b' ');
+        output.extend_from_slice(&[0; 8]); // No subsystem data
+        output.extend_from_slice(&0x0100u16.to_le_bytes());
+        output.extend_from_slice(b"IM"); // Little-endian
+        write_matrix(&mut output, "batches", &MatValue::Struct { fields: BATCH_FIELDS, elements: self.batches });
+        write_matrix(&mut output, "runs", &MatValue::Struct { fields: RUN_FIELDS, elements: self.runs });
+        fs::write(&self.path, output).unwrap_or_else(|error| panic!("Could not write {}: {}", self.path.display(), error));
+    }
+}
@@ -0,0 +1,65 @@
+// Prometheus metrics of the batches, served on http://ADDRESS/metrics by --metrics-listen and on /metrics of serve-http,
+// so long sweeps and servers can be watched from the usual dashboards. Every batch is a series, labelled with its index
+// in the invocation and its function:
+//
+//     swarm_runs_completed_total{batch="0",function="ackley"} 12
+//     swarm_queued_tasks 40
+
+use std::{fmt::Write, sync::{atomic::{AtomicUsize, Ordering}, Arc}};
+
+use tiny_http::{Header, Response, Server};
+
+use crate::dashboard::{Dashboard, ProgressSnapshot};
+
+// Name, type, help and value of every metric with a series per batch
+type BatchMetric = (&'static str, &'static str, &'static str, fn(&ProgressSnapshot) -> f64);
+
+const BATCH_METRICS: [BatchMetric; 5] = [
+    ("swarm_runs_completed_total", "counter", "Runs of the batch that finished", |snapshot| snapshot.completed_runs as f64),
+    ("swarm_runs_planned", "gauge", "Runs of the batch in total", |snapshot| snapshot.total_runs as f64),
+    ("swarm_evaluations_total", "counter", "Objective evaluations of the finished runs", |snapshot| snapshot.evaluations as f64),
+    ("swarm_evaluations_per_second", "gauge", "Evaluations per second of the batch so far", |snapshot| snapshot.evaluations_per_second),
+    ("swarm_best_value", "gauge", "Best value found by the finished runs", |snapshot| snapshot.best_value),
+];
+
+// In the text exposition format
+pub fn render(dashboard: &Dashboard, queue_length: &AtomicUsize) -> String {
+    let snapshots = dashboard.snapshots();
+    let mut text = String::new();
+    for (name, kind, help, value) in BATCH_METRICS {
+        writeln!(text, "# HELP {name} {help}\n# TYPE {name} {kind}").unwrap();
+        for (batch, (function, snapshot)) in snapshots.iter().enumerate() {
+            writeln!(text, "{}{{batch=\"{}\",function=\"{}\"}} {}", name, batch, escape_label(function), format_value(value(snapshot))).unwrap();
+        }
+    }
+    writeln!(text, "# HELP swarm_queued_tasks Tasks waiting for a worker thread\n# TYPE swarm_queued_tasks gauge").unwrap();
+    writeln!(text, "swarm_queued_tasks {}", queue_length.load(Ordering::Relaxed)).unwrap();
+    return text;
+}
+
+pub fn content_type() -> Header {
+    return Header::from_bytes(&b"Content-Type"[..], &b"text/plain; version=0.0.4"[..]).unwrap();
+}
+
+// Answers every request with the metrics, until the program exits
+pub fn spawn(listen_address: &str, dashboard: Dashboard, queue_length: Arc<AtomicUsize>) {
+    let server = Server::http(listen_address).unwrap_or_else(|error| panic!("Could not listen on {listen_address}: {error}"));
+    eprintln!("Serving metrics on http://{}/metrics", server.server_addr());
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = Response::from_string(render(&dashboard, &queue_length)).with_header(content_type());
+            let _ = request.respond(response); // Only fails if the scraper is gone
+        }
+    });
+}
+
+fn escape_label(value: &str) -> String {
+    return value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_infinite() {
+        return if value > 0.0 { "+Inf" } else { "-Inf" }.to_string();
+    }
+    return value.to_string(); // NaN is written as Prometheus expects it
+}
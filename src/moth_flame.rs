@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::{consts::TAU, Real}, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub moth_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub spiral_shape: Real, // b, of the logarithmic spiral the moths fly around the flames on
+    pub parallel: bool, // Moves and evaluates the moths on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.moth_count == 0 {
+            return Err("There must be at least one moth");
+        }
+        if !self.spiral_shape.is_finite() {
+            return Err("Spiral shape must be finite");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Moth<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    random_source: RngType, // Seeded from the population seed and the moth's index, so moths can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Moth<N, RngType> {
+    // Not evaluated yet, the world evaluates all moths at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, random_source };
+    }
+
+    // Along a logarithmic spiral around the flame, at a random point t in [a, 1] per coordinate. a goes from -1 to -2, so
+    // the moths get closer to the flames over the run
+    fn move_moth<F>(&mut self, parameters: &Parameters<N, F>, flame: VectorN<Real, N>, a: Real) {
+        for dimension in 0..N {
+            let distance = (flame[dimension] - self.position[dimension]).abs();
+            let t = (a - 1.0) * self.random_source.gen::<Real>() + 1.0;
+            self.position[dimension] = distance * (parameters.spiral_shape * t).exp() * (TAU * t).cos() + flame[dimension];
+        }
+        self.position.clamp(parameters.bounds);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    moths: Vec<Moth<N, RngType>>,
+    flames: Vec<(Real, VectorN<Real, N>)>, // The best positions found so far, as many as there are moths, best first
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(moth_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            moth_count, function, bounds,
+            spiral_shape: 1.0,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            moths: Vec::with_capacity(parameters.moth_count),
+            flames: Vec::with_capacity(2 * parameters.moth_count),
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.moth_count),
+            values: Vec::with_capacity(parameters.moth_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_positions(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.moths.iter().map(|moth| moth.position));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+    }
+
+    // The first moths follow their own flames, the rest share the last one. There are fewer flames as the run goes on,
+    // from all of them down to only the best
+    fn move_moths(&mut self, iteration_count: usize) {
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let moth_count = self.moths.len();
+        let flame_count = (moth_count as Real - progress * (moth_count - 1) as Real).round() as usize;
+        let a = -1.0 - progress;
+        let parameters = &*self.parameters;
+        let flames = &self.flames;
+        let move_moth = |(index, moth): (usize, &mut Moth<N, RngType>)| moth.move_moth(parameters, flames[index.min(flame_count - 1)].1, a);
+        if parameters.parallel {
+            self.moths.par_iter_mut().enumerate().for_each(move_moth);
+        } else {
+            self.moths.iter_mut().enumerate().for_each(move_moth);
+        }
+    }
+
+    // The best of the flames and the moved moths become the new flames. The sort is stable, so older flames stay ahead
+    // of equally good moths
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.flames.first().map_or(Real::INFINITY, |flame| flame.0);
+        self.evaluation_count += self.moths.len();
+        self.flames.extend(values.iter().zip(&self.moths).map(|(&value, moth)| (value, moth.position)));
+        self.flames.sort_by_key(|&(value, _)| Fitness::minimize(value));
+        self.flames.truncate(self.moths.len());
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.moths.iter().map(|moth| moth.position), previous_best_value, self.flames[0].1, self.flames[0].0);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_moths(iteration_count);
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_moths(iteration_count);
+        positions.extend(self.moths.iter().map(|moth| moth.position));
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.flames.clear();
+        self.moths.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.moth_count {
+            self.moths.push(Moth::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_positions();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.flames[0].1;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.flames[0].0;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.moths.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.moths.iter().map(|moth| moth.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{fitness::Fitness, functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn flames_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(200);
+        assert_eq!(world.flames.len(), 20);
+        assert!(world.flames.is_sorted_by_key(|&(value, _)| Fitness::minimize(value)));
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// The standard coefficients of the moves
+const REFLECTION: Real = 1.0;
+const EXPANSION: Real = 2.0;
+const CONTRACTION: Real = 0.5;
+const SHRINK: Real = 0.5;
+
+// N + 1 points and their values, best first
+#[derive(Clone, Debug)]
+pub struct Simplex<const N: usize> {
+    vertices: Vec<(Real, VectorN<Real, N>)>,
+}
+
+impl<const N: usize> Simplex<N> {
+    // The start and one point per axis, size away from it. Towards the lower bound where the upper one is too close.
+    // Takes N evaluations, the value of the start is already known
+    pub fn around<F: Function<N>>(function: &F, start: VectorN<Real, N>, start_value: Real, size: Real, bounds: (Real, Real)) -> Self {
+        let mut vertices = Vec::with_capacity(N + 1);
+        vertices.push((start_value, start));
+        for dimension in 0..N {
+            let mut vertex = start;
+            if vertex[dimension] + size <= bounds.1 {
+                vertex[dimension] += size;
+            } else {
+                vertex[dimension] -= size;
+            }
+            vertex.clamp(bounds);
+            vertices.push((function.evaluate(vertex), vertex));
+        }
+        let mut simplex = Self { vertices };
+        simplex.sort();
+        return simplex;
+    }
+
+    // The best vertex and its value
+    pub fn best(&self) -> (Real, VectorN<Real, N>) {
+        return self.vertices[0];
+    }
+
+    // The largest distance from the best vertex to another one
+    pub fn diameter(&self) -> Real {
+        let best = self.vertices[0].1;
+        return self.vertices[1..].iter().map(|&(_, vertex)| (vertex - best).norm_l2()).fold(0.0, Real::max);
+    }
+
+    // Stable, so older vertices stay ahead of equally good new ones
+    fn sort(&mut self) {
+        self.vertices.sort_by_key(|&(value, _)| Fitness::minimize(value));
+    }
+
+    // Replaces the worst vertex by its reflection through the centroid of the others, an expansion or a contraction of
+    // it, or shrinks the whole simplex towards the best vertex if none of them is good enough. Returns the evaluations it
+    // took, between 1 and N + 2
+    pub fn step<F: Function<N>>(&mut self, function: &F, bounds: (Real, Real)) -> usize {
+        let (worst_value, worst) = self.vertices[N];
+        let second_worst_value = self.vertices[N - 1].0;
+        let best_value = self.vertices[0].0;
+        let centroid = self.vertices[..N].iter().fold(VectorN::default(), |sum, &(_, vertex)| sum + vertex) / N as Real;
+        let evaluate = |point: VectorN<Real, N>| {
+            let mut point = point;
+            point.clamp(bounds);
+            return (function.evaluate(point), point);
+        };
+        let reflected = evaluate(centroid + (centroid - worst) * REFLECTION);
+        let better = |first: Real, second: Real| Fitness::minimize(first) < Fitness::minimize(second);
+        if better(reflected.0, best_value) {
+            let expanded = evaluate(centroid + (reflected.1 - centroid) * EXPANSION);
+            self.vertices[N] = if better(expanded.0, reflected.0) { expanded } else { reflected };
+            self.sort();
+            return 2;
+        }
+        if better(reflected.0, second_worst_value) {
+            self.vertices[N] = reflected;
+            self.sort();
+            return 1;
+        }
+        // Outside the simplex if the reflection is at least better than the worst vertex, inside otherwise
+        let (contracted, reference_value) = if better(reflected.0, worst_value) {
+            (evaluate(centroid + (reflected.1 - centroid) * CONTRACTION), reflected.0)
+        } else {
+            (evaluate(centroid + (worst - centroid) * CONTRACTION), worst_value)
+        };
+        if better(contracted.0, reference_value) {
+            self.vertices[N] = contracted;
+            self.sort();
+            return 2;
+        }
+        let best = self.vertices[0].1;
+        for vertex in &mut self.vertices[1..] {
+            *vertex = evaluate(best + (vertex.1 - best) * SHRINK);
+        }
+        self.sort();
+        return 2 + N;
+    }
+}
+
+// Runs the simplex method from a solution found by another optimizer, e.g. the best solution of a swarm, until the
+// budget of evaluations runs out or the simplex collapses. The simplex starts at 1% of the width of the bounds. Returns
+// the best solution, never worse than the start, its value and the evaluations it took
+pub fn polish<const N: usize, F: Function<N>>(function: &F, start: VectorN<Real, N>, start_value: Real, bounds: (Real, Real), evaluation_budget: usize) -> (VectorN<Real, N>, Real, usize) {
+    if evaluation_budget < 2 * N + 2 {
+        return (start, start_value, 0);
+    }
+    let width = bounds.1 - bounds.0;
+    let mut simplex = Simplex::around(function, start, start_value, 0.01 * width, bounds);
+    let mut evaluations = N;
+    while evaluations + N + 2 <= evaluation_budget && simplex.diameter() > Real::EPSILON * width {
+        evaluations += simplex.step(function, bounds);
+    }
+    let (value, solution) = simplex.best();
+    return (solution, value, evaluations);
+}
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub initial_size: Real, // Of the simplex around a random start, as a fraction of the width of the bounds
+    pub tolerance: Real, // The simplex restarts from a new random point once it's smaller than this fraction of the width of the bounds
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.initial_size.is_nan() || self.initial_size <= 0.0 {
+            return Err("Initial size must be positive");
+        }
+        if self.tolerance.is_nan() || self.tolerance < 0.0 {
+            return Err("Tolerance can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    simplex: Simplex<N>,
+    pub best_solution: VectorN<Real, N>, // Over all restarts
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    restart_count: usize, // Since the last reset
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            function, bounds,
+            initial_size: 0.1,
+            tolerance: 1e-10,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            simplex: Simplex { vertices: Vec::with_capacity(N + 1) },
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            restart_count: 0,
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    pub fn restart_count(&self) -> usize {
+        return self.restart_count;
+    }
+
+    // A new simplex around a uniformly random point
+    fn start_simplex(&mut self) {
+        let parameters = &*self.parameters;
+        let start = VectorN::random_uniform(parameters.bounds, &mut self.random_generator);
+        let start_value = parameters.function.evaluate(start);
+        let size = parameters.initial_size * (parameters.bounds.1 - parameters.bounds.0);
+        self.simplex = Simplex::around(&parameters.function, start, start_value, size, parameters.bounds);
+        self.evaluation_count += N + 1;
+        self.update_best();
+    }
+
+    fn update_best(&mut self) {
+        let (value, solution) = self.simplex.best();
+        if Fitness::minimize(value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = value;
+            self.best_solution = solution;
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // A single move of the simplex, so the evaluations of an iteration depend on the move
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.simplex.step(&self.parameters.function, self.parameters.bounds);
+        self.update_best();
+        if self.simplex.diameter() <= self.parameters.tolerance * (self.parameters.bounds.1 - self.parameters.bounds.0) {
+            self.restart_count += 1;
+            self.start_simplex();
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.simplex.vertices.iter().map(|&(_, vertex)| vertex), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.restart_count = 0;
+        self.start_simplex();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // A shrink, then a restart
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * N + 3;
+    }
+
+    // The vertices of the simplex
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.simplex.vertices.iter().map(|&(_, vertex)| vertex));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::{Function, Functions}, optimizer::Optimizer, vector::VectorN};
+
+    use super::{polish, WorldState};
+
+    #[test]
+    fn simplex_test() {
+        let function = Functions::<2>::make_from_name("ackley");
+        let start = VectorN::new([0.3, -0.2]);
+        let start_value = function.evaluate(start);
+        let (solution, value, evaluations) = polish(&function, start, start_value, function.get_bounds(), 500);
+        assert!(evaluations <= 500);
+        assert!(value < 1e-3);
+        assert_eq!(function.evaluate(solution), value);
+        let mut world = WorldState::<2, _>::new(function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(2000);
+        assert!(world.best_solution_value() < initial_best);
+        assert!(world.restart_count() > 0);
+    }
+}
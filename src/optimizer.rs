@@ -0,0 +1,652 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use rand::{distributions::Standard, Rng, SeedableRng};
+
+use crate::{functions::Function, real::Real, vector::VectorN};
+
+// The dimensions the front ends compile the optimizers for, every entry is another copy of every algorithm. Only the
+// objectives have a path for any dimension, the `dispatch` benchmark group compares it to these
+pub const DIMENSIONS: [usize; 6] = [2, 10, 20, 30, 50, 100];
+
+#[derive(Clone, Copy, Debug)]
+pub enum RunLength {
+    Iterations(usize),
+    // Stops before an iteration that could go over the budget. The initial population is evaluated and counted whatever
+    // the budget, so it alone can go over a budget smaller than it, which the command line rejects
+    Evaluations(usize),
+}
+
+// The update rule of the algorithms that pull agents towards a target. The published formulas differ in sign: bats are
+// pushed away from the best by (x - x*) * f, which makes them diverge, butterflies move towards it by (r²g* - x) * f.
+// Written as `standard`, `negated`, `normalized-direction` or `inertia-damped=0.7` on the command line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Movement {
+    Standard, // As published
+    Negated, // The published attraction with its sign flipped
+    NormalizedDirection, // Towards the target by the scale, whatever the distance
+    InertiaDamped(Real), // Towards the target, keeping the previous velocity times the weight instead of all or none of it
+}
+
+impl Movement {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if let Movement::InertiaDamped(weight) = *self {
+            if !(0.0..1.0).contains(&weight) {
+                return Err("Inertia weight must be at least 0 and below 1");
+            }
+        }
+        return Ok(());
+    }
+
+    // What gets added to the velocity. published_sign is the sign of towards_target in the published formula
+    pub fn attraction<const N: usize>(&self, towards_target: VectorN<Real, N>, scale: Real, published_sign: Real) -> VectorN<Real, N> {
+        match *self {
+            Movement::Standard => return towards_target * (published_sign * scale),
+            Movement::Negated => return towards_target * (-published_sign * scale),
+            Movement::NormalizedDirection => {
+                let distance = towards_target.norm_l2();
+                if distance == 0.0 {
+                    return VectorN::default();
+                }
+                return towards_target * (scale / distance);
+            },
+            Movement::InertiaDamped(_) => return towards_target * scale,
+        }
+    }
+}
+
+impl fmt::Display for Movement {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Movement::Standard => return formatter.write_str("standard"),
+            Movement::Negated => return formatter.write_str("negated"),
+            Movement::NormalizedDirection => return formatter.write_str("normalized-direction"),
+            Movement::InertiaDamped(weight) => return write!(formatter, "inertia-damped={weight}"),
+        }
+    }
+}
+
+impl FromStr for Movement {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let movement = match value.split_once('=') {
+            None if value == "standard" => Movement::Standard,
+            None if value == "negated" => Movement::Negated,
+            None if value == "normalized-direction" => Movement::NormalizedDirection,
+            Some(("inertia-damped", weight)) => Movement::InertiaDamped(weight.parse().map_err(|_| format!("invalid inertia weight `{weight}`"))?),
+            _ => return Err(format!("unknown movement `{value}`, expected standard, negated, normalized-direction or inertia-damped=WEIGHT")),
+        };
+        movement.check()?;
+        return Ok(movement);
+    }
+}
+
+// The velocities of a new population, for the algorithms that keep one. The spreads are fractions of the width of the
+// bounds. Written as `positive-unit`, `zero`, `uniform=0.1` or `gaussian=0.1` on the command line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InitialVelocity {
+    PositiveUnit, // Every coordinate in [0, 1), whatever the bounds
+    Zero,
+    Uniform(Real), // Every coordinate in (-spread, spread)
+    Gaussian(Real), // Every coordinate with mean 0 and spread as the standard deviation
+}
+
+impl InitialVelocity {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if let InitialVelocity::Uniform(spread) | InitialVelocity::Gaussian(spread) = *self {
+            if !(spread > 0.0 && spread.is_finite()) {
+                return Err("Initial velocity spread must be positive");
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn sample<const N: usize, RngType: Rng>(&self, bounds: (Real, Real), random_source: &mut RngType) -> VectorN<Real, N> {
+        let width = bounds.1 - bounds.0;
+        match *self {
+            InitialVelocity::PositiveUnit => return VectorN::random_from(&Standard, random_source),
+            InitialVelocity::Zero => return VectorN::default(),
+            InitialVelocity::Uniform(spread) => return VectorN::random_uniform((-spread * width, spread * width), random_source),
+            InitialVelocity::Gaussian(spread) => return VectorN::random_gaussian(VectorN::default(), spread * width, random_source),
+        }
+    }
+}
+
+impl fmt::Display for InitialVelocity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitialVelocity::PositiveUnit => return formatter.write_str("positive-unit"),
+            InitialVelocity::Zero => return formatter.write_str("zero"),
+            InitialVelocity::Uniform(spread) => return write!(formatter, "uniform={spread}"),
+            InitialVelocity::Gaussian(spread) => return write!(formatter, "gaussian={spread}"),
+        }
+    }
+}
+
+impl FromStr for InitialVelocity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let spread = |spread: &str| spread.parse().map_err(|_| format!("invalid initial velocity spread `{spread}`"));
+        let initial_velocity = match value.split_once('=') {
+            None if value == "positive-unit" => InitialVelocity::PositiveUnit,
+            None if value == "zero" => InitialVelocity::Zero,
+            Some(("uniform", value)) => InitialVelocity::Uniform(spread(value)?),
+            Some(("gaussian", value)) => InitialVelocity::Gaussian(spread(value)?),
+            _ => return Err(format!("unknown initial velocity `{value}`, expected positive-unit, zero, uniform=SPREAD or gaussian=SPREAD")),
+        };
+        initial_velocity.check()?;
+        return Ok(initial_velocity);
+    }
+}
+
+// The generator of the agent at index in a population, so every agent has its own stream whatever order the agents are
+// moved in. population_seed is drawn from the world's generator once per population
+pub fn agent_random_source<RngType: SeedableRng>(population_seed: u64, index: usize) -> RngType {
+    return RngType::seed_from_u64(population_seed.wrapping_add(index as u64)); // seed_from_u64 scrambles neighbouring seeds
+}
+
+// Shared by every WorldState so the run loops and the batch machinery don't care which algorithm is used. The worlds keep
+// the positions and values of an iteration in buffers reused by the next one, so iterations don't allocate
+pub trait Optimizer<const N: usize> {
+    // iteration_count is the planned length of the run since the last reset, for algorithms with schedules
+    fn do_iteration(&mut self, iteration_count: usize);
+    fn iteration(&self) -> usize; // Iterations since the last reset, the number of the next one
+    fn reset(&mut self);
+    fn reseed(&mut self, seed: u64); // Takes effect from the next reset
+    fn best_solution(&self) -> VectorN<Real, N>;
+    fn best_solution_value(&self) -> Real;
+    fn evaluation_count(&self) -> usize; // Since the last reset, including the initial population
+    fn evaluations_per_iteration(&self) -> usize; // Upper bound for a single do_iteration call
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>); // Appends the current positions of the agents
+
+    // The two halves of an iteration, for run_lockstep: propose moves the agents and appends the positions to evaluate,
+    // accept takes their values in the same order. Worlds that can't split an iteration do all of it in propose
+    fn propose(&mut self, iteration_count: usize, _positions: &mut Vec<VectorN<Real, N>>) {
+        self.do_iteration(iteration_count);
+    }
+    fn accept(&mut self, _values: &[Real]) {}
+
+    fn do_all_iterations(&mut self, iterations: usize) where Self: Sized {
+        self.run(RunLength::Iterations(iterations), None);
+    }
+
+    // Stops early once the best known solution reaches the target. Returns the evaluations it took, or None if it was never reached
+    fn run(&mut self, length: RunLength, target_value: Option<Real>) -> Option<usize> where Self: Sized {
+        return self.run_observed(length, target_value, |_| {});
+    }
+
+    // Like run, calling the observer after every iteration, e.g. to record convergence traces
+    fn run_observed<Observer: FnMut(&Self)>(&mut self, length: RunLength, target_value: Option<Real>, mut observer: Observer) -> Option<usize> where Self: Sized {
+        let iteration_count = planned_iterations(self, length);
+        loop {
+            if let Some(target) = target_value {
+                if self.best_solution_value() <= target {
+                    return Some(self.evaluation_count());
+                }
+            }
+            let has_budget_left = match length {
+                RunLength::Iterations(_) => self.iteration() < iteration_count,
+                RunLength::Evaluations(budget) => self.evaluation_count() + self.evaluations_per_iteration() <= budget,
+            };
+            if !has_budget_left {
+                return None;
+            }
+            self.do_iteration(iteration_count);
+            observer(self);
+        }
+    }
+}
+
+// Where a run of the given length from the world's current state ends, counted from the last reset
+fn planned_iterations<const N: usize>(world: &impl Optimizer<N>, length: RunLength) -> usize {
+    match length {
+        RunLength::Iterations(iterations) => return world.iteration() + iterations,
+        RunLength::Evaluations(budget) => return world.iteration() + budget.saturating_sub(world.evaluation_count()) / world.evaluations_per_iteration(),
+    }
+}
+
+// For worlds whose iterations are the innermost steps of nested loops, like the chemotactic steps of bacteria between
+// reproductions: the index in every loop at the given iteration, outermost first. The outermost loop starts over once it's
+// done, so a run can be longer than all of the loops together
+pub fn nested_iteration<const K: usize>(iteration: usize, loop_lengths: [usize; K]) -> [usize; K] {
+    let mut indices = [0; K];
+    let mut remaining = iteration;
+    for (index, &length) in indices.iter_mut().zip(&loop_lengths).rev() {
+        *index = remaining % length;
+        remaining /= length;
+    }
+    return indices;
+}
+
+// What every world keeps true after an iteration: the agents are within the bounds, the best value is the value of the
+// best solution and it never gets worse. Panics with the iteration and the agent otherwise. The best solution is evaluated
+// once more, so this is only for deterministic objectives
+#[cfg(feature = "invariants")]
+pub(crate) fn check_invariants<const N: usize>(function: &impl Function<N>, iteration: usize, bounds: (Real, Real), positions: impl Iterator<Item = VectorN<Real, N>>, previous_best_value: Real, best_solution: VectorN<Real, N>, best_solution_value: Real) {
+    for (agent, position) in positions.enumerate() {
+        if !position.iter().all(|coordinate| (bounds.0..=bounds.1).contains(coordinate)) {
+            panic!("Invariant violated in iteration {iteration}: agent {agent} is out of bounds at {position}");
+        }
+    }
+    if best_solution_value > previous_best_value {
+        panic!("Invariant violated in iteration {iteration}: the best value got worse, from {previous_best_value} to {best_solution_value}");
+    }
+    if best_solution_value.is_finite() {
+        let value = function.evaluate(best_solution);
+        if value.total_cmp(&best_solution_value).is_ne() {
+            panic!("Invariant violated in iteration {iteration}: the best value {best_solution_value} is not the value of the best solution, {value}");
+        }
+    }
+}
+
+// Worlds whose configuration lives behind an Arc, so the runs of a batch share it instead of cloning a template world
+pub trait FromParameters<const N: usize>: Optimizer<N> + Sized {
+    // Everything about a run that isn't random. The Parameters of every algorithm have a check returning the first
+    // problem with them, if any, and a validate panicking with it
+    type Parameters: Send + Sync;
+
+    // A fresh population, with everything random drawn from the seed. Panics on invalid parameters
+    fn from_parameters(parameters: Arc<Self::Parameters>, seed: u64) -> Self;
+}
+
+// Advances all worlds one iteration at a time, with the positions of every world evaluated in a single batch of
+// runs x agents, e.g. one GPU submission per iteration for the whole group. Every world stops exactly where run_observed
+// would stop it, so the results are the same as running them one after another. The observer gets the index of the world
+pub fn run_lockstep<const N: usize, World: Optimizer<N>, Objective: Function<N> + ?Sized, Observer: FnMut(usize, &World)>(worlds: &mut [World], function: &Objective, length: RunLength, target_value: Option<Real>, mut observer: Observer) -> Vec<Option<usize>> {
+    let iteration_counts = worlds.iter().map(|world| planned_iterations(world, length)).collect::<Vec<_>>();
+    let mut evaluations_to_target = vec![None; worlds.len()];
+    let mut running = (0..worlds.len()).collect::<Vec<_>>();
+    let mut positions = Vec::new();
+    let mut values = Vec::new();
+    let mut ranges = Vec::with_capacity(worlds.len());
+    while !running.is_empty() {
+        running.retain(|&index| {
+            let world = &worlds[index];
+            if let Some(target) = target_value {
+                if world.best_solution_value() <= target {
+                    evaluations_to_target[index] = Some(world.evaluation_count());
+                    return false;
+                }
+            }
+            return match length {
+                RunLength::Iterations(_) => world.iteration() < iteration_counts[index],
+                RunLength::Evaluations(budget) => world.evaluation_count() + world.evaluations_per_iteration() <= budget,
+            };
+        });
+
+        positions.clear();
+        ranges.clear();
+        for &index in &running {
+            let start = positions.len();
+            worlds[index].propose(iteration_counts[index], &mut positions);
+            ranges.push(start..positions.len());
+        }
+        function.evaluate_batch_into(&positions, &mut values);
+        for (&index, range) in running.iter().zip(&ranges) {
+            worlds[index].accept(&values[range.clone()]);
+            observer(index, &worlds[index]);
+        }
+    }
+    return evaluations_to_target;
+}
+
+#[cfg(test)]
+mod test {
+    use std::{alloc::{GlobalAlloc, Layout, System}, cell::Cell, sync::Arc};
+
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{bats, butterflies, functions::{Function, Functions}, optimizer::{nested_iteration, run_lockstep, FromParameters, InitialVelocity, Movement, Optimizer, RunLength}, real::Real, vector::VectorN};
+
+    // Counts the allocations of the current thread only, as the tests run in parallel
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+            return unsafe { System.alloc(layout) };
+        }
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(pointer, layout) };
+        }
+        unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+            return unsafe { System.realloc(pointer, layout, new_size) };
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations_of_iterations<World: Optimizer<10>>(world: &mut World) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        for _ in 0..100 {
+            world.do_iteration(100);
+        }
+        return ALLOCATIONS.with(Cell::get) - before;
+    }
+
+    #[test]
+    fn allocation_free_iterations_test() {
+        let function = Functions::<10>::make_from_name("rastrigin");
+        let mut bats = bats::WorldState::new(20, function, function.get_bounds(), (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, Xoshiro256PlusPlus::seed_from_u64(0));
+        let mut butterflies = butterflies::WorldState::new(20, function, function.get_bounds(), 0.1, (0.1, 0.3), 0.8, Xoshiro256PlusPlus::seed_from_u64(0));
+        assert_eq!(0, allocations_of_iterations(&mut bats));
+        assert_eq!(0, allocations_of_iterations(&mut butterflies));
+    }
+
+    #[test]
+    fn movement_test() {
+        for text in ["standard", "negated", "normalized-direction", "inertia-damped=0.5"] {
+            assert_eq!(text.parse::<Movement>().unwrap().to_string(), text);
+        }
+        assert!("inertia-damped=1".parse::<Movement>().is_err());
+        assert!("sideways".parse::<Movement>().is_err());
+
+        let towards_target = VectorN::new([3.0, 4.0]);
+        assert_eq!(Movement::Standard.attraction(towards_target, 2.0, -1.0).coordinates, [-6.0, -8.0]);
+        assert_eq!(Movement::Negated.attraction(towards_target, 2.0, -1.0).coordinates, [6.0, 8.0]);
+        assert!(Movement::NormalizedDirection.attraction(towards_target, 2.0, -1.0).approx_eq(&VectorN::new([1.2, 1.6]), 1e-6, 1e-6));
+        assert_eq!(Movement::NormalizedDirection.attraction(VectorN::<_, 2>::default(), 2.0, -1.0).coordinates, [0.0, 0.0]);
+        assert_eq!(Movement::InertiaDamped(0.5).attraction(towards_target, 2.0, -1.0).coordinates, [6.0, 8.0]);
+    }
+
+    #[test]
+    fn parallel_moves_test() {
+        // Every agent has its own generator, so moving them on the rayon pool changes nothing
+        let function = Functions::<10>::make_from_name("rastrigin");
+        let bat_parameters = |parallel| bats::Parameters {
+            bat_count: 20, function, bounds: function.get_bounds(), frequency_bounds: (0.0, 2.0), initial_pulse_rate: 0.5,
+            pulse_rate_factor: 0.9, initial_loudness: 1.0, loudness_cool_factor: 0.9, movement: Movement::Negated, initial_velocity: InitialVelocity::PositiveUnit,
+            improvement_tolerance: 0.0, scale_walk_to_bounds: false, parallel,
+        };
+        let butterfly_parameters = |parallel| butterflies::Parameters {
+            population_size: 20, function, bounds: function.get_bounds(), fragrance_multiplier: 0.1, fragrance_exponent_bounds: (0.1, 0.3),
+            local_search_chance: 0.8, movement: Movement::Standard, initial_velocity: InitialVelocity::Zero,
+            improvement_tolerance: 0.0, parallel,
+        };
+        let bats = |parallel| bats::WorldState::<10, Xoshiro256PlusPlus>::from_parameters(Arc::new(bat_parameters(parallel)), 7);
+        let butterflies = |parallel| butterflies::WorldState::<10, Xoshiro256PlusPlus>::from_parameters(Arc::new(butterfly_parameters(parallel)), 7);
+        let (mut sequential_bats, mut parallel_bats) = (bats(false), bats(true));
+        let (mut sequential_butterflies, mut parallel_butterflies) = (butterflies(false), butterflies(true));
+        for world in [&mut sequential_bats, &mut parallel_bats] {
+            world.do_all_iterations(50);
+        }
+        for world in [&mut sequential_butterflies, &mut parallel_butterflies] {
+            world.do_all_iterations(50);
+        }
+        assert_eq!(sequential_bats.best_solution.coordinates, parallel_bats.best_solution.coordinates);
+        assert_eq!(sequential_butterflies.best_solution.coordinates, parallel_butterflies.best_solution.coordinates);
+    }
+
+    #[test]
+    fn iteration_counter_test() {
+        let function = Functions::<10>::make_from_name("rastrigin");
+        let mut bats = bats::WorldState::new(20, function, function.get_bounds(), (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, Xoshiro256PlusPlus::seed_from_u64(0));
+        bats.run(RunLength::Iterations(30), None);
+        bats.run(RunLength::Evaluations(bats.evaluation_count() + 20 * 20), None);
+        assert_eq!(bats.iteration(), 50);
+        bats.reset();
+        assert_eq!(bats.iteration(), 0);
+
+        let mut worlds = (0..3).map(|seed| butterflies::WorldState::new(20, function, function.get_bounds(), 0.1, (0.1, 0.3), 0.8, Xoshiro256PlusPlus::seed_from_u64(seed))).collect::<Vec<_>>();
+        run_lockstep(&mut worlds, &function, RunLength::Iterations(10), None, |_, _| {});
+        assert!(worlds.iter().all(|world| world.iteration() == 10));
+
+        assert_eq!(nested_iteration(0, [4, 3]), [0, 0]);
+        assert_eq!(nested_iteration(5, [4, 3]), [1, 2]);
+        assert_eq!(nested_iteration(13, [4, 3]), [0, 1]);
+    }
+
+    #[test]
+    fn initial_velocity_test() {
+        for text in ["positive-unit", "zero", "uniform=0.25", "gaussian=0.1"] {
+            assert_eq!(text.parse::<InitialVelocity>().unwrap().to_string(), text);
+        }
+        assert!("uniform=0".parse::<InitialVelocity>().is_err());
+        assert!("gaussian".parse::<InitialVelocity>().is_err());
+
+        let mut random_source = Xoshiro256PlusPlus::seed_from_u64(0);
+        let velocity: VectorN<_, 100> = InitialVelocity::Uniform(0.25).sample((-2.0, 2.0), &mut random_source);
+        assert!(velocity.iter().all(|coordinate| (-1.0..1.0).contains(coordinate)) && velocity.iter().any(|&coordinate| coordinate < 0.0));
+        assert_eq!(InitialVelocity::Zero.sample::<3, _>((-2.0, 2.0), &mut random_source).coordinates, [0.0; 3]);
+    }
+
+    #[test]
+    fn improvement_tolerance_test() {
+        // Nothing improves on the initial population by a million
+        let function = Functions::<10>::make_from_name("rastrigin");
+        let parameters = butterflies::Parameters {
+            population_size: 20, function, bounds: function.get_bounds(), fragrance_multiplier: 0.1, fragrance_exponent_bounds: (0.1, 0.3),
+            local_search_chance: 0.8, movement: Movement::Standard, initial_velocity: InitialVelocity::Zero,
+            improvement_tolerance: 1e6, parallel: false,
+        };
+        let mut world = butterflies::WorldState::<10, Xoshiro256PlusPlus>::from_parameters(Arc::new(parameters), 3);
+        let initial_best = world.best_solution_value;
+        world.do_all_iterations(50);
+        assert_eq!(world.best_solution_value, initial_best);
+    }
+
+    // A world that loses track of its best solution, as the objective changes under it
+    #[cfg(feature = "invariants")]
+    #[test]
+    #[should_panic(expected = "is not the value of the best solution")]
+    fn invariants_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Drifting(AtomicUsize);
+
+        impl Function<2> for Drifting {
+            fn evaluate(&self, input: VectorN<Real, 2>) -> Real {
+                return input.norm_l2() + self.0.fetch_add(1, Ordering::Relaxed) as Real;
+            }
+        }
+
+        let function = Functions::<2>::make_from_name("rastrigin");
+        let mut bats = bats::WorldState::new(20, function, function.get_bounds(), (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, Xoshiro256PlusPlus::seed_from_u64(0));
+        bats.do_all_iterations(100);
+        let mut butterflies = butterflies::WorldState::new(20, function, function.get_bounds(), 0.1, (0.1, 0.3), 0.8, Xoshiro256PlusPlus::seed_from_u64(0));
+        butterflies.do_all_iterations(100);
+
+        let mut drifting = bats::WorldState::new(20, Drifting(AtomicUsize::new(0)), (-5.0, 5.0), (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, Xoshiro256PlusPlus::seed_from_u64(0));
+        drifting.do_all_iterations(1);
+    }
+
+    // Iterations split into propose and accept, with the positions evaluated in between, must run exactly like do_iteration from the same state
+    fn assert_split_iterations_match<const N: usize, World: Optimizer<N> + Clone>(world: &World, function: &impl Function<N>, iteration_count: usize) {
+        let (mut whole, mut split) = (world.clone(), world.clone());
+        let (mut positions, mut values) = (Vec::new(), Vec::new());
+        for _ in 0..iteration_count {
+            whole.do_iteration(iteration_count);
+            positions.clear();
+            split.propose(iteration_count, &mut positions);
+            function.evaluate_batch_into(&positions, &mut values);
+            split.accept(&values);
+            assert_same_state(&whole, &split);
+        }
+    }
+
+    // Worlds that must run the same, e.g. from the same seed with and without parallel moves
+    fn assert_runs_match<const N: usize, World: Optimizer<N>>(mut first: World, mut second: World, iteration_count: usize) {
+        first.do_all_iterations(iteration_count);
+        second.do_all_iterations(iteration_count);
+        assert_same_state(&first, &second);
+    }
+
+    fn assert_same_state<const N: usize, World: Optimizer<N>>(first: &World, second: &World) {
+        assert_eq!((first.iteration(), first.evaluation_count()), (second.iteration(), second.evaluation_count()));
+        assert_eq!(first.best_solution_value(), second.best_solution_value());
+        assert_eq!(first.best_solution().coordinates, second.best_solution().coordinates);
+        let (mut first_population, mut second_population) = (Vec::new(), Vec::new());
+        first.population(&mut first_population);
+        second.population(&mut second_population);
+        assert!(first_population.iter().map(|position| position.coordinates).eq(second_population.iter().map(|position| position.coordinates)));
+    }
+
+    // What every algorithm has to pass, one test per algorithm: iterations split into propose and accept run like whole ones,
+    // moving the agents on the rayon pool changes nothing, and the parameter checks accept the defaults of new
+    // and reject the bounds and the parameters the algorithm can't run with
+    macro_rules! conformance_tests {
+        (@parallel $parameters:ident, true) => {
+            let world = |parallel| WorldState::<10, Xoshiro256PlusPlus>::from_parameters(Arc::new(Parameters { parallel, ..$parameters.clone() }), 7);
+            assert_runs_match(world(false), world(true), 20);
+        };
+        (@parallel $parameters:ident, false) => {};
+        ($($module:ident: |$function:ident, $bounds:ident, $random_source:ident| $world:expr, parallel: $parallel:tt, rejects: { $($field:ident: $value:expr => $error:literal),* $(,)? };)*) => {
+            mod conformance {
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $module() {
+                        use crate::$module::{Parameters, WorldState};
+
+                        let $function = Functions::<10>::make_from_name("ackley");
+                        let $bounds = $function.get_bounds();
+                        let $random_source = Xoshiro256PlusPlus::seed_from_u64(0);
+                        let world: WorldState<10, Xoshiro256PlusPlus> = $world;
+                        assert_split_iterations_match(&world, &world.parameters().function, 20);
+                        let parameters = world.parameters().clone();
+                        conformance_tests!(@parallel parameters, $parallel);
+                        assert_eq!(parameters.check(), Ok(()));
+                        assert_eq!(Parameters { bounds: (1.0, 1.0), ..parameters.clone() }.check(), Err("Incorrect order of bounds or zero size"));
+                        $(assert_eq!(Parameters { $field: $value, ..parameters.clone() }.check(), Err($error));)*
+                    }
+                )*
+            }
+        };
+    }
+
+    conformance_tests! {
+        abc: |function, bounds, random_source| WorldState::new(20, function, bounds, 50, random_source), parallel: true, rejects: {
+            bee_count: 1 => "The colony needs at least two food sources, every move uses another one",
+            limit: 0 => "The abandonment limit must be positive",
+        };
+        aco: |function, bounds, random_source| WorldState::new(10, 50, function, bounds, random_source), parallel: true, rejects: {
+            ant_count: 0 => "There must be at least one ant",
+            archive_size: 1 => "The archive needs at least two solutions, for the kernels to have a width",
+            q: 0.0 => "q must be positive",
+            xi: Real::NAN => "xi must be positive",
+        };
+        annealing: |function, bounds, random_source| WorldState::new(function, bounds, 10.0, 0.99, random_source), parallel: false, rejects: {
+            initial_temperature: 0.0 => "Initial temperature must be positive",
+            cooling_rate: 1.0 => "Cooling rate must be between 0 and 1, exclusive",
+            initial_radius: -1.0 => "Initial radius must be positive",
+            min_radius: 0.2 => "Minimum radius must be positive and can't be above the initial one",
+            moves_per_iteration: 0 => "There must be at least one move per iteration",
+        };
+        bacteria: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            bacterium_count: 1 => "There must be at least two bacteria, for the healthier half to split",
+            reproduction_steps: 0 => "There must be at least one chemotactic step and one reproduction",
+            elimination_probability: 1.5 => "Elimination probability must be between 0 and 1",
+            step_size: 0.0 => "Step size must be positive",
+        };
+        bats: |function, bounds, random_source| WorldState::new(20, function, bounds, (0.0, 2.0), 0.5, 0.9, 1.0, 0.9, random_source), parallel: true, rejects: {
+            frequency_bounds: (2.0, 0.0) => "Incorrect order of frequency bounds or zero size",
+            bat_count: 0 => "The population can't be empty",
+        };
+        butterflies: |function, bounds, random_source| WorldState::new(20, function, bounds, 0.1, (0.1, 0.3), 0.8, random_source), parallel: true, rejects: {
+            fragrance_exponent_bounds: (0.3, 0.1) => "Incorrect order of fragrance bounds",
+            local_search_chance: 1.5 => "Local search chance must be between 0 and 1",
+        };
+        cem: |function, bounds, random_source| WorldState::new(50, function, bounds, random_source), parallel: true, rejects: {
+            sample_count: 0 => "There must be at least one sample per generation",
+            elite_fraction: 0.0 => "Elite fraction must be in (0, 1]",
+            smoothing: 1.5 => "Smoothing must be in (0, 1]",
+        };
+        crows: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            crow_count: 1 => "There must be at least two crows, to follow each other",
+            awareness_probability: 1.1 => "Awareness probability must be between 0 and 1",
+            flight_length: 0.0 => "Flight length must be positive",
+        };
+        cuckoo: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            nest_count: 0 => "There must be at least one nest",
+            abandon_probability: 1.5 => "Abandon probability must be between 0 and 1",
+            step_scale: 0.0 => "Step scale must be positive",
+            levy_exponent: 2.5 => "Lévy exponent must be in (0, 2]",
+        };
+        dragonflies: |function, bounds, random_source| WorldState::new(30, function, bounds, random_source), parallel: true, rejects: {
+            dragonfly_count: 0 => "There must be at least one dragonfly",
+            enemy_weight: -0.1 => "Weights can't be negative",
+        };
+        flower_pollination: |function, bounds, random_source| WorldState::new(20, function, bounds, 0.8, random_source), parallel: true, rejects: {
+            flower_count: 0 => "There must be at least one flower",
+            switch_probability: -0.1 => "Switch probability must be between 0 and 1",
+            step_scale: 0.0 => "Step scale must be positive",
+            levy_exponent: 0.0 => "Lévy exponent must be in (0, 2]",
+        };
+        fss: |function, bounds, random_source| WorldState::new(30, function, bounds, random_source), parallel: true, rejects: {
+            fish_count: 0 => "There must be at least one fish",
+            weight_scale: 1.0 => "Weight scale must be greater than 1",
+            step_bounds: (0.1, 0.0) => "Steps must be positive",
+        };
+        grasshoppers: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            grasshopper_count: 1 => "There must be at least two grasshoppers, to interact with each other",
+            attraction_intensity: -0.5 => "Intensity of attraction can't be negative",
+            attractive_length_scale: 0.0 => "Attractive length scale must be positive",
+        };
+        grey_wolf: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            wolf_count: 2 => "The pack needs at least three wolves, for the alpha, beta and delta",
+            a_bounds: (2.0, -0.5) => "a can't be negative",
+        };
+        gsa: |function, bounds, random_source| WorldState::new(30, function, bounds, random_source), parallel: true, rejects: {
+            agent_count: 1 => "There must be at least two agents, for them to attract each other",
+            initial_gravity: 0.0 => "Initial gravity must be positive",
+            gravity_decay: -1.0 => "Gravity decay can't be negative",
+        };
+        harris_hawks: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            hawk_count: 0 => "There must be at least one hawk",
+        };
+        iwo: |function, bounds, random_source| WorldState::new(10, 20, function, bounds, random_source), parallel: true, rejects: {
+            initial_count: 0 => "There must be at least one weed",
+            max_population: 5 => "Maximum population can't be smaller than the initial one",
+            seed_range: (3, 1) => "Incorrect order of seed counts or no seeds at all",
+            sigma_bounds: (0.01, 0.05) => "Standard deviations must shrink and can't be negative",
+            modulation_index: 0.0 => "Modulation index must be positive",
+        };
+        jaya: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            agent_count: 0 => "There must be at least one agent",
+        };
+        krill: |function, bounds, random_source| WorldState::new(25, function, bounds, random_source), parallel: true, rejects: {
+            krill_count: 1 => "There must be at least two krill, to induce each other's motion",
+            foraging_speed: -0.02 => "Speeds can't be negative",
+            time_constant: 0.0 => "Time constant must be in (0, 2]",
+        };
+        moth_flame: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            moth_count: 0 => "There must be at least one moth",
+            spiral_shape: Real::NAN => "Spiral shape must be finite",
+        };
+        nelder_mead: |function, bounds, random_source| WorldState::new(function, bounds, random_source), parallel: false, rejects: {
+            initial_size: 0.0 => "Initial size must be positive",
+            tolerance: -1e-9 => "Tolerance can't be negative",
+        };
+        pattern_search: |function, bounds, random_source| WorldState::new(function, bounds, random_source), parallel: true, rejects: {
+            initial_step: 0.0 => "Initial step must be positive",
+            shrink: 1.0 => "Shrink factor must be between 0 and 1",
+            tolerance: -1e-9 => "Tolerance can't be negative",
+        };
+        random_search: |function, bounds, random_source| WorldState::new(30, function, bounds, random_source), parallel: true, rejects: {
+            sample_count: 0 => "There must be at least one sample per iteration",
+        };
+        salps: |function, bounds, random_source| WorldState::new(30, function, bounds, random_source), parallel: true, rejects: {
+            salp_count: 0 => "There must be at least one salp",
+        };
+        sine_cosine: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            agent_count: 0 => "There must be at least one agent",
+            amplitude: -2.0 => "Amplitude must be positive",
+        };
+        tlbo: |function, bounds, random_source| WorldState::new(20, function, bounds, random_source), parallel: true, rejects: {
+            learner_count: 1 => "There must be at least two learners, to learn from each other",
+        };
+        umda: |function, bounds, random_source| WorldState::new(50, function, bounds, random_source), parallel: true, rejects: {
+            population_size: 1 => "There must be at least two individuals, to fit a distribution to",
+            selection_ratio: 0.0 => "Selection ratio must be in (0, 1]",
+        };
+    }
+}
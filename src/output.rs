@@ -0,0 +1,154 @@
+use std::{fs::File, path::{Path, PathBuf}};
+
+use arrow::ipc::writer::FileWriter;
+use clap::ValueEnum;
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use swarm_optimizers::records::{RunRecord, RunRecorder};
+
+use crate::{BatchRunData, OptimizationAlgorithmCommand};
+
+const RUN_RECORD_BATCH_SIZE: usize = 4096;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text, // One human readable line per function
+    Json, // A single array of summaries, printed once everything is done
+    Jsonl, // One summary object per line, as soon as the function is done
+}
+
+// The machine readable form of a summary line, read back by `collect`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub function: String,
+    pub algorithm: String,
+    pub configuration: serde_json::Map<String, serde_json::Value>, // The subcommand's parameters, after overrides, and the dimensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_cell: Option<String>, // The swept values, only in grid-search
+    pub eval_budget: Option<usize>,
+    pub target_value: Option<f64>,
+    #[serde(flatten)]
+    pub result: BatchRunData,
+}
+
+impl BatchSummary {
+    pub fn new(function_name: &str, command: &OptimizationAlgorithmCommand, dimensions: usize, eval_budget: Option<usize>, target_value: Option<f64>, result: BatchRunData) -> Self {
+        let serialized = serde_json::to_value(command).unwrap();
+        let (algorithm, configuration) = serialized.as_object().and_then(|variant| variant.iter().next()).unwrap();
+        let mut configuration = configuration.as_object().unwrap().clone();
+        configuration.insert("dimensions".to_string(), dimensions.into());
+        return Self {
+            function: function_name.to_string(),
+            algorithm: algorithm.clone(),
+            configuration,
+            grid_cell: None,
+            eval_budget,
+            target_value,
+            result,
+        };
+    }
+}
+
+pub fn format_summary(function_name: &str, result: &BatchRunData, target_value: Option<f64>) -> String {
+    let mut summary = format!("{}: Finished {} runs. Max solution is {}. Average solution is {}. Min solution is {}.", function_name, result.run_count, result.max_result, result.average, result.min_result);
+    if target_value.is_some() {
+        summary += &format!(" Success rate: {} ({}/{}).", result.success_rate(), result.success_count, result.run_count);
+        if result.success_count > 0 {
+            summary += &format!(" Average evaluations to target: {}.", result.average_evaluations_to_target());
+        }
+    }
+    return summary;
+}
+
+pub struct SummaryPrinter {
+    format: OutputFormat,
+    hold: bool, // Keeps everything until finish(), e.g. while the dashboard owns the terminal
+    held: Vec<BatchSummary>,
+}
+
+impl SummaryPrinter {
+    pub fn new(format: OutputFormat, hold: bool) -> Self {
+        return Self {
+            format,
+            hold: hold || format == OutputFormat::Json,
+            held: Vec::new(),
+        };
+    }
+
+    pub fn add(&mut self, summary: BatchSummary) {
+        if self.hold {
+            self.held.push(summary);
+        } else {
+            self.print(&summary);
+        }
+    }
+
+    pub fn finish(self) {
+        if self.format == OutputFormat::Json {
+            println!("{}", serde_json::to_string_pretty(&self.held).unwrap());
+            return;
+        }
+        for summary in &self.held {
+            self.print(summary);
+        }
+    }
+
+    fn print(&self, summary: &BatchSummary) {
+        match self.format {
+            OutputFormat::Text => {
+                let line = format_summary(&summary.function, &summary.result, summary.target_value);
+                match &summary.grid_cell {
+                    Some(grid_cell) => println!("{} {}", grid_cell, line),
+                    None => println!("{}", line),
+                }
+            },
+            OutputFormat::Json | OutputFormat::Jsonl => println!("{}", serde_json::to_string(summary).unwrap()),
+        }
+    }
+}
+
+// The individual runs of the summaries for --run-records, Parquet if the file name ends with .parquet and an Arrow IPC
+// file otherwise. Written once everything is done
+pub struct RunRecordWriter {
+    path: PathBuf,
+    recorder: RunRecorder,
+}
+
+impl RunRecordWriter {
+    pub fn new(path: &Path) -> Self {
+        return Self { path: path.to_path_buf(), recorder: RunRecorder::new(RUN_RECORD_BATCH_SIZE) };
+    }
+
+    pub fn add(&mut self, summary: &BatchSummary) {
+        for run in &summary.result.runs {
+            self.recorder.push(&RunRecord {
+                function: &summary.function,
+                algorithm: &summary.algorithm,
+                seed: run.seed,
+                best_value: run.best_value,
+                evaluations: run.evaluations,
+                evaluations_to_target: run.evaluations_to_target,
+                best_solution: &run.best_solution,
+            });
+        }
+    }
+
+    pub fn finish(self) {
+        let file = File::create(&self.path).unwrap_or_else(|error| panic!("Could not create {}: {}", self.path.display(), error));
+        let batches = self.recorder.finish();
+        let result = if self.path.extension().is_some_and(|extension| extension == "parquet") {
+            ArrowWriter::try_new(file, RunRecorder::schema(), None).and_then(|mut writer| {
+                batches.iter().try_for_each(|batch| writer.write(batch))?;
+                return writer.close().map(|_| ());
+            }).map_err(|error| error.to_string())
+        } else {
+            FileWriter::try_new(file, &RunRecorder::schema()).and_then(|mut writer| {
+                batches.iter().try_for_each(|batch| writer.write(batch))?;
+                return writer.finish();
+            }).map_err(|error| error.to_string())
+        };
+        if let Err(error) = result {
+            panic!("Could not write the runs to {}: {}", self.path.display(), error);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// Only the starting points are random, the search from them is deterministic
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub initial_step: Real, // As a fraction of the width of the bounds
+    pub shrink: Real, // The step is multiplied by it after a poll that found nothing better, in (0, 1)
+    pub tolerance: Real, // The search restarts from a new random point once the step is smaller than this fraction of the width of the bounds
+    pub parallel: bool, // Evaluates the poll on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.initial_step.is_nan() || self.initial_step <= 0.0 {
+            return Err("Initial step must be positive");
+        }
+        if !(self.shrink > 0.0 && self.shrink < 1.0) {
+            return Err("Shrink factor must be between 0 and 1");
+        }
+        if self.tolerance.is_nan() || self.tolerance < 0.0 {
+            return Err("Tolerance can't be negative");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    centre: VectorN<Real, N>, // The point polled around
+    centre_value: Real, // Infinite right after a restart, so the first poll moves to its best point
+    step: Real, // In the units of the bounds
+    pub best_solution: VectorN<Real, N>, // Over all restarts
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    restart_count: usize, // Since the last reset
+    poll: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            function, bounds,
+            initial_step: 0.1,
+            shrink: 0.5,
+            tolerance: 1e-10,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            centre: VectorN::default(),
+            centre_value: Real::INFINITY,
+            step: 0.0,
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            restart_count: 0,
+            poll: Vec::with_capacity(2 * N),
+            values: Vec::with_capacity(2 * N),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    pub fn restart_count(&self) -> usize {
+        return self.restart_count;
+    }
+
+    // A uniformly random centre with the initial step, not evaluated
+    fn restart(&mut self) {
+        let parameters = &*self.parameters;
+        self.centre = VectorN::random_uniform(parameters.bounds, &mut self.random_generator);
+        self.centre_value = Real::INFINITY;
+        self.step = parameters.initial_step * (parameters.bounds.1 - parameters.bounds.0);
+    }
+
+    // One step along and against every axis from the centre
+    fn build_poll(&mut self) {
+        self.poll.clear();
+        for dimension in 0..N {
+            for direction in [1.0, -1.0] {
+                let mut point = self.centre;
+                point[dimension] += direction * self.step;
+                point.clamp(self.parameters.bounds);
+                self.poll.push(point);
+            }
+        }
+    }
+
+    fn evaluate_poll(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.poll, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.poll, &mut self.values);
+        }
+    }
+
+    // The values of the poll, in order. Moves to the best point if it's better than the centre and shrinks the step if
+    // none is
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.poll.len();
+        // Takes the first of equally good points
+        let (best_index, &best_value) = values.iter().enumerate().min_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap();
+        if Fitness::minimize(best_value) < Fitness::minimize(self.centre_value) {
+            self.centre = self.poll[best_index];
+            self.centre_value = best_value;
+        } else {
+            self.step *= self.parameters.shrink;
+        }
+        if Fitness::minimize(self.centre_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = self.centre_value;
+            self.best_solution = self.centre;
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.poll.iter().copied(), previous_best_value, self.best_solution, self.best_solution_value);
+        if self.step < self.parameters.tolerance * (self.parameters.bounds.1 - self.parameters.bounds.0) {
+            self.restart_count += 1;
+            self.restart();
+        }
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.build_poll();
+        self.evaluate_poll();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.build_poll();
+        positions.extend_from_slice(&self.poll);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    // The first centre is evaluated, so there's a best solution before the first iteration
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.restart_count = 0;
+        self.restart();
+        self.centre_value = self.parameters.function.evaluate(self.centre);
+        self.evaluation_count = 1;
+        self.best_solution = self.centre;
+        self.best_solution_value = self.centre_value;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * N;
+    }
+
+    // The centre
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.push(self.centre);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn compass_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(500);
+        assert_eq!(world.evaluation_count(), 1 + 20 * 500);
+        assert!(world.best_solution_value() < initial_best);
+        // The same start polls the same points
+        let mut first = WorldState::<10, _>::new(function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(1));
+        let mut second = first.clone();
+        first.do_all_iterations(100);
+        second.do_all_iterations(100);
+        assert_eq!(first.best_solution_value(), second.best_solution_value());
+    }
+}
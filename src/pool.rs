@@ -0,0 +1,96 @@
+use std::{panic::{self, AssertUnwindSafe}, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc::{self, Receiver, Sender}, Arc, Mutex}, thread::JoinHandle};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+// A fixed set of threads running queued tasks in order. One pool serves every batch of an invocation, so a sweep over
+// many functions with a few runs each keeps all cores busy instead of waiting for the slowest run of every function
+pub struct WorkerPool {
+    sender: Option<Sender<Task>>,
+    workers: Vec<JoinHandle<()>>,
+    queued: Arc<AtomicUsize>, // Submitted tasks no thread has picked up yet
+    panicked: Arc<AtomicBool>, // Whether any task panicked, reported when the pool is dropped
+}
+
+impl WorkerPool {
+    pub fn new(thread_count: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let queued = Arc::new(AtomicUsize::new(0));
+        let panicked = Arc::new(AtomicBool::new(false));
+        let workers = (0..thread_count).map(|_| {
+            let receiver = receiver.clone();
+            let queued = queued.clone();
+            let panicked = panicked.clone();
+            return std::thread::spawn(move || run_tasks(&receiver, &queued, &panicked));
+        }).collect();
+        return Self {
+            sender: Some(sender),
+            workers,
+            queued,
+            panicked,
+        };
+    }
+
+    pub fn submit(&self, task: impl FnOnce() + Send + 'static) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.sender.as_ref().unwrap().send(Box::new(task)).unwrap();
+    }
+
+    // Read by the metrics while the pool works
+    pub fn queue_length(&self) -> Arc<AtomicUsize> {
+        return self.queued.clone();
+    }
+}
+
+fn run_tasks(receiver: &Mutex<Receiver<Task>>, queued: &AtomicUsize, panicked: &AtomicBool) {
+    loop {
+        // The lock is released before running the task, so the others can pick up the next ones
+        let task = receiver.lock().unwrap().recv();
+        match task {
+            Ok(task) => {
+                queued.fetch_sub(1, Ordering::Relaxed);
+                // The thread stays to run the rest of the queue, otherwise tasks waiting for it would never run.
+                // Whatever the task captured is dropped while unwinding, so anyone waiting for its results stops waiting
+                if panic::catch_unwind(AssertUnwindSafe(task)).is_err() {
+                    panicked.store(true, Ordering::Relaxed);
+                }
+            },
+            Err(_) => return, // The pool was dropped and the queue is empty
+        }
+    }
+}
+
+// Finishes the queued tasks before returning. A panic in a task doesn't stop the others and is passed on here
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+        if self.panicked.load(Ordering::Relaxed) && !std::thread::panicking() {
+            panic!("A task of the worker pool panicked");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc;
+
+    use super::WorkerPool;
+
+    #[test]
+    fn panic_test() {
+        let pool = WorkerPool::new(1);
+        let (sender, receiver) = mpsc::channel();
+        let panicking_sender = sender.clone();
+        pool.submit(move || {
+            let _sender = panicking_sender;
+            panic!("A failing run");
+        });
+        pool.submit(move || sender.send(1).unwrap());
+        // The only thread survives the panic and runs the next task, then every sender is gone
+        assert_eq!(receiver.iter().collect::<Vec<_>>(), [1]);
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(pool))).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+// The `swarm_optimizers` Python module, built with `--features python`, e.g. `maturin develop --release` (see pyproject.toml):
+//
+//     import numpy as np
+//     from swarm_optimizers import BatOptimizer
+//
+//     optimizer = BatOptimizer(lambda x: float(np.sum(x ** 2)), dimensions=10, bounds=(-5.0, 5.0), seed=1)
+//     optimizer.run(iterations=1000)
+//     optimizer.best_solution, optimizer.best_value, optimizer.history
+//
+// The objective is either a callable taking the coordinates as a NumPy array, or the name of a built in benchmark function.
+// Dimensions are limited to optimizer::DIMENSIONS, like on the command line
+
+use std::sync::{Arc, Mutex};
+
+use numpy::{PyArray1, PyArrayMethods};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::{thread_rng, Rng};
+
+use crate::{dynamic::{build_world, BatSettings, ButterflySettings, DynamicWorld, ObjectiveSpecification, WorldFactory}, optimizer::RunLength, real::{self, Real}};
+
+struct CallableObjective {
+    callable: Py<PyAny>,
+    error: Mutex<Option<PyErr>>, // The first exception raised by the callable, raised again once the run is over
+}
+
+impl CallableObjective {
+    fn call(&self, coordinates: &[Real]) -> Real {
+        return Python::with_gil(|py| {
+            let mut error = self.error.lock().unwrap();
+            if error.is_some() {
+                return Real::INFINITY; // The rest of the run is wasted either way
+            }
+            let coordinates = PyArray1::from_iter(py, coordinates.iter().map(|&coordinate| real::to_f64(coordinate)));
+            match self.callable.call1(py, (coordinates,)).and_then(|value| value.extract::<f64>(py)) {
+                Ok(value) => return real::from_f64(value),
+                Err(raised) => {
+                    *error = Some(raised);
+                    return Real::INFINITY;
+                },
+            }
+        });
+    }
+
+    fn take_error(&self) -> PyResult<()> {
+        return match self.error.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        };
+    }
+}
+
+// What the constructors got as the objective, with the callable kept for its exceptions
+fn extract_objective(objective: &Bound<PyAny>) -> PyResult<(ObjectiveSpecification, Option<Arc<CallableObjective>>)> {
+    if let Ok(name) = objective.extract::<String>() {
+        return Ok((ObjectiveSpecification::builtin(&name).map_err(PyValueError::new_err)?, None));
+    }
+    if !objective.is_callable() {
+        return Err(PyValueError::new_err("The objective must be callable or the name of a benchmark function"));
+    }
+    let callable = Arc::new(CallableObjective { callable: objective.clone().unbind(), error: Mutex::new(None) });
+    let evaluated = callable.clone();
+    return Ok((ObjectiveSpecification::Callable(Arc::new(move |coordinates: &[Real]| evaluated.call(coordinates))), Some(callable)));
+}
+
+// The methods shared by BatOptimizer and ButterflyOptimizer
+#[pyclass(name = "Optimizer", module = "swarm_optimizers", subclass)]
+pub struct PythonOptimizer {
+    world: Box<dyn DynamicWorld>,
+    objective: Option<Arc<CallableObjective>>, // For the exceptions of callables
+    history: Vec<(usize, f64)>, // (evaluations, best value) after every iteration since the last reset
+}
+
+impl PythonOptimizer {
+    fn new(factory: &impl WorldFactory, objective: &Bound<PyAny>, dimensions: usize, bounds: Option<(f64, f64)>, seed: Option<u64>) -> PyResult<Self> {
+        let (objective, callable) = extract_objective(objective)?;
+        let bounds = objective.bounds(bounds).map_err(PyValueError::new_err)?;
+        let seed = seed.unwrap_or_else(|| thread_rng().gen());
+        let world = build_world(factory, &objective, dimensions, bounds, seed).map_err(PyValueError::new_err)?;
+        if let Some(callable) = &callable {
+            callable.take_error()?; // From evaluating the initial population
+        }
+        return Ok(Self { world, objective: callable, history: Vec::new() });
+    }
+}
+
+#[pymethods]
+impl PythonOptimizer {
+    // Exactly one of iterations and eval_budget. Returns the evaluations it took to reach target_value, or None
+    #[pyo3(signature = (iterations = None, eval_budget = None, target_value = None))]
+    fn run(&mut self, py: Python<'_>, iterations: Option<usize>, eval_budget: Option<usize>, target_value: Option<f64>) -> PyResult<Option<usize>> {
+        let length = match (iterations, eval_budget) {
+            (Some(iterations), None) => RunLength::Iterations(iterations),
+            (None, Some(budget)) => RunLength::Evaluations(budget),
+            _ => return Err(PyValueError::new_err("exactly one of iterations and eval_budget is required")),
+        };
+        let (world, history) = (&mut self.world, &mut self.history);
+        // Callables take the GIL back for every evaluation
+        let evaluations_to_target = py.allow_threads(|| world.run(length, target_value.map(real::from_f64), &mut |evaluations, value| history.push((evaluations, value))));
+        if let Some(objective) = &self.objective {
+            objective.take_error()?;
+        }
+        return Ok(evaluations_to_target);
+    }
+
+    // A fresh population, continuing the random sequence
+    fn reset(&mut self, py: Python<'_>) -> PyResult<()> {
+        let world = &mut self.world;
+        py.allow_threads(|| world.reset());
+        self.history.clear();
+        if let Some(objective) = &self.objective {
+            objective.take_error()?;
+        }
+        return Ok(());
+    }
+
+    #[getter]
+    fn best_solution<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        return PyArray1::from_vec(py, self.world.best_solution());
+    }
+
+    #[getter]
+    fn best_value(&self) -> f64 {
+        return self.world.best_solution_value();
+    }
+
+    #[getter]
+    fn evaluation_count(&self) -> usize {
+        return self.world.evaluation_count();
+    }
+
+    // An array of (evaluations, best value) rows, one per iteration
+    #[getter]
+    fn history<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let flat = self.history.iter().flat_map(|&(evaluations, value)| [evaluations as f64, value]).collect::<Vec<_>>();
+        return Ok(PyArray1::from_vec(py, flat).reshape([self.history.len(), 2])?.into_any());
+    }
+}
+
+// The defaults are the ones of run_sweep.sh
+#[pyclass(extends = PythonOptimizer, module = "swarm_optimizers")]
+pub struct BatOptimizer;
+
+#[pymethods]
+impl BatOptimizer {
+    #[new]
+    #[pyo3(signature = (objective, dimensions, bounds = None, bat_count = 20, frequency_bounds = (0.0, 1.0), initial_pulse_rate = 0.7, pulse_rate_factor = 0.5, initial_loudness = 1.4, loudness_cooling_rate = 0.5, scale_walk_to_bounds = false, improvement_tolerance = 0.0, seed = None))]
+    fn new(objective: &Bound<PyAny>, dimensions: usize, bounds: Option<(f64, f64)>, bat_count: usize, frequency_bounds: (f64, f64), initial_pulse_rate: f64, pulse_rate_factor: f64, initial_loudness: f64, loudness_cooling_rate: f64, scale_walk_to_bounds: bool, improvement_tolerance: f64, seed: Option<u64>) -> PyResult<(Self, PythonOptimizer)> {
+        let settings = BatSettings {
+            bat_count,
+            frequency_bounds: (real::from_f64(frequency_bounds.0), real::from_f64(frequency_bounds.1)),
+            initial_pulse_rate: real::from_f64(initial_pulse_rate),
+            pulse_rate_factor: real::from_f64(pulse_rate_factor),
+            initial_loudness: real::from_f64(initial_loudness),
+            loudness_cooling_rate: real::from_f64(loudness_cooling_rate),
+            scale_walk_to_bounds,
+            improvement_tolerance: real::from_f64(improvement_tolerance),
+        };
+        return Ok((Self, PythonOptimizer::new(&settings, objective, dimensions, bounds, seed)?));
+    }
+}
+
+#[pyclass(extends = PythonOptimizer, module = "swarm_optimizers")]
+pub struct ButterflyOptimizer;
+
+#[pymethods]
+impl ButterflyOptimizer {
+    #[new]
+    #[pyo3(signature = (objective, dimensions, bounds = None, butterfly_count = 20, fragrance_multiplier = 0.5, fragrance_exponent_bounds = (0.1, 0.3), local_search_chance = 0.5, improvement_tolerance = 0.0, seed = None))]
+    fn new(objective: &Bound<PyAny>, dimensions: usize, bounds: Option<(f64, f64)>, butterfly_count: usize, fragrance_multiplier: f64, fragrance_exponent_bounds: (f64, f64), local_search_chance: f64, improvement_tolerance: f64, seed: Option<u64>) -> PyResult<(Self, PythonOptimizer)> {
+        let settings = ButterflySettings {
+            butterfly_count,
+            fragrance_multiplier: real::from_f64(fragrance_multiplier),
+            fragrance_exponent_bounds: (real::from_f64(fragrance_exponent_bounds.0), real::from_f64(fragrance_exponent_bounds.1)),
+            local_search_chance: real::from_f64(local_search_chance),
+            improvement_tolerance: real::from_f64(improvement_tolerance),
+        };
+        return Ok((Self, PythonOptimizer::new(&settings, objective, dimensions, bounds, seed)?));
+    }
+}
+
+#[pymodule]
+fn swarm_optimizers(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PythonOptimizer>()?;
+    module.add_class::<BatOptimizer>()?;
+    module.add_class::<ButterflyOptimizer>()?;
+    return Ok(());
+}
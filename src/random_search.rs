@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// The null baseline: every algorithm should beat uniform sampling with the same budget
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub sample_count: usize, // Per iteration. Only decides how the budget is split into batches, not the search itself
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub parallel: bool, // Evaluates the samples on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.sample_count == 0 {
+            return Err("There must be at least one sample per iteration");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    samples: Vec<VectorN<Real, N>>, // Of the last iteration
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(sample_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            sample_count, function, bounds,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            samples: Vec::with_capacity(parameters.sample_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            values: Vec::with_capacity(parameters.sample_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // Drawn from the world's generator, they're cheap next to the evaluations
+    fn draw_samples(&mut self) {
+        self.samples.clear();
+        for _ in 0..self.parameters.sample_count {
+            self.samples.push(VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator));
+        }
+    }
+
+    fn evaluate_samples(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.samples, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.samples, &mut self.values);
+        }
+    }
+
+    // The values of the samples, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.samples.len();
+        // Takes the first of equally good samples
+        let (best_index, &best_value) = values.iter().enumerate().min_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap();
+        if Fitness::minimize(best_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_value;
+            self.best_solution = self.samples[best_index];
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.samples.iter().copied(), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.draw_samples();
+        self.evaluate_samples();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.draw_samples();
+        positions.extend_from_slice(&self.samples);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.draw_samples();
+        self.evaluate_samples();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.parameters.sample_count;
+    }
+
+    // The samples of the last iteration
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend_from_slice(&self.samples);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::{Optimizer, RunLength}};
+
+    use super::WorldState;
+
+    #[test]
+    fn budget_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(30, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.run(RunLength::Evaluations(3000), None);
+        assert!(world.evaluation_count() <= 3000);
+        assert!(world.evaluation_count() > 3000 - 30);
+        assert!(world.best_solution_value() <= initial_best);
+        let (lower, upper) = function.get_bounds();
+        assert!(world.best_solution().coordinates.iter().all(|&coordinate| (lower..=upper).contains(&coordinate)));
+    }
+}
@@ -0,0 +1,24 @@
+// The floating point type of positions, velocities, parameters and function values. Built with `--features f32`
+// everything is single precision, for objectives that are themselves single precision or sweeps that don't need the last
+// digits. There are no hand-written SIMD kernels for either type, how fast the loops run is up to the compiler
+
+#[cfg(not(feature = "f32"))]
+pub type Real = f64;
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
+
+#[cfg(feature = "f32")]
+pub type Real = f32;
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
+
+// Results and targets cross the library boundary as f64 in either build
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f64(value: Real) -> f64 {
+    return value as f64;
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn from_f64(value: f64) -> Real {
+    return value as Real;
+}
@@ -0,0 +1,210 @@
+// Per-run results as Arrow record batches, built with `--features arrow` (part of the command line program), so large
+// experiments reach dataframes and Parquet without a detour through text. With `--features polars` the runs are also
+// available as a Polars DataFrame. One row per run:
+//
+//     function, algorithm: utf8    dimensions, seed, evaluations: uint64    best_value: float64
+//     evaluations_to_target: uint64, null if the target wasn't reached     best_solution: list<float64>
+
+use std::sync::{Arc, LazyLock};
+
+use arrow::{array::{ArrayRef, Float64Builder, ListBuilder, RecordBatch, StringBuilder, UInt64Builder}, datatypes::{DataType, Field, Schema, SchemaRef}};
+
+use crate::{optimizer::Optimizer, real};
+
+static SCHEMA: LazyLock<SchemaRef> = LazyLock::new(|| Arc::new(Schema::new(vec![
+    Field::new("function", DataType::Utf8, false),
+    Field::new("algorithm", DataType::Utf8, false),
+    Field::new("dimensions", DataType::UInt64, false),
+    Field::new("seed", DataType::UInt64, false),
+    Field::new("best_value", DataType::Float64, false),
+    Field::new("evaluations", DataType::UInt64, false),
+    Field::new("evaluations_to_target", DataType::UInt64, true),
+    Field::new("best_solution", DataType::List(Arc::new(Field::new_list_field(DataType::Float64, true))), false),
+])));
+
+// The outcome of a single run
+#[derive(Clone, Debug)]
+pub struct RunRecord<'a> {
+    pub function: &'a str,
+    pub algorithm: &'a str,
+    pub seed: u64,
+    pub best_value: f64,
+    pub evaluations: usize,
+    pub evaluations_to_target: Option<usize>,
+    pub best_solution: &'a [f64],
+}
+
+// Collects runs into record batches of batch_size rows, so a long experiment doesn't keep a builder growing forever
+pub struct RunRecorder {
+    batch_size: usize,
+    batches: Vec<RecordBatch>,
+    rows: usize, // In the builders
+    function: StringBuilder,
+    algorithm: StringBuilder,
+    dimensions: UInt64Builder,
+    seed: UInt64Builder,
+    best_value: Float64Builder,
+    evaluations: UInt64Builder,
+    evaluations_to_target: UInt64Builder,
+    best_solution: ListBuilder<Float64Builder>,
+}
+
+impl RunRecorder {
+    pub fn new(batch_size: usize) -> Self {
+        if batch_size == 0 {
+            panic!("Record batches need at least one row");
+        }
+        return Self {
+            batch_size,
+            batches: Vec::new(),
+            rows: 0,
+            function: StringBuilder::new(),
+            algorithm: StringBuilder::new(),
+            dimensions: UInt64Builder::with_capacity(batch_size),
+            seed: UInt64Builder::with_capacity(batch_size),
+            best_value: Float64Builder::with_capacity(batch_size),
+            evaluations: UInt64Builder::with_capacity(batch_size),
+            evaluations_to_target: UInt64Builder::with_capacity(batch_size),
+            best_solution: ListBuilder::new(Float64Builder::new()),
+        };
+    }
+
+    pub fn schema() -> SchemaRef {
+        return SCHEMA.clone();
+    }
+
+    pub fn push(&mut self, run: &RunRecord) {
+        self.function.append_value(run.function);
+        self.algorithm.append_value(run.algorithm);
+        self.dimensions.append_value(run.best_solution.len() as u64);
+        self.seed.append_value(run.seed);
+        self.best_value.append_value(run.best_value);
+        self.evaluations.append_value(run.evaluations as u64);
+        self.evaluations_to_target.append_option(run.evaluations_to_target.map(|evaluations| evaluations as u64));
+        self.best_solution.values().append_slice(run.best_solution);
+        self.best_solution.append(true);
+        self.rows += 1;
+        if self.rows == self.batch_size {
+            self.flush();
+        }
+    }
+
+    // A world at the end of its run, started from the seed
+    pub fn record<const N: usize>(&mut self, function: &str, algorithm: &str, seed: u64, world: &impl Optimizer<N>, evaluations_to_target: Option<usize>) {
+        let best_solution = world.best_solution().coordinates.map(real::to_f64);
+        self.push(&RunRecord {
+            function,
+            algorithm,
+            seed,
+            best_value: real::to_f64(world.best_solution_value()),
+            evaluations: world.evaluation_count(),
+            evaluations_to_target,
+            best_solution: &best_solution,
+        });
+    }
+
+    fn flush(&mut self) {
+        if self.rows == 0 {
+            return;
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.function.finish()),
+            Arc::new(self.algorithm.finish()),
+            Arc::new(self.dimensions.finish()),
+            Arc::new(self.seed.finish()),
+            Arc::new(self.best_value.finish()),
+            Arc::new(self.evaluations.finish()),
+            Arc::new(self.evaluations_to_target.finish()),
+            Arc::new(self.best_solution.finish()),
+        ];
+        self.batches.push(RecordBatch::try_new(Self::schema(), columns).unwrap());
+        self.rows = 0;
+    }
+
+    // Every run recorded so far, the last batch possibly shorter
+    pub fn finish(mut self) -> Vec<RecordBatch> {
+        self.flush();
+        return self.batches;
+    }
+
+    #[cfg(feature = "polars")]
+    pub fn into_dataframe(self) -> polars::prelude::PolarsResult<polars::prelude::DataFrame> {
+        return record_batches_to_dataframe(&self.finish());
+    }
+}
+
+// Batches with the schema of RunRecorder, column by column
+#[cfg(feature = "polars")]
+pub fn record_batches_to_dataframe(batches: &[RecordBatch]) -> polars::prelude::PolarsResult<polars::prelude::DataFrame> {
+    use arrow::array::{AsArray, ListArray};
+    use arrow::datatypes::{Float64Type, UInt64Type};
+    use polars::prelude::{Column, DataFrame, NamedFrom, Series};
+
+    let strings = |index: usize| batches.iter().flat_map(|batch| batch.column(index).as_string::<i32>().iter().map(|value| value.unwrap_or_default().to_string())).collect::<Vec<_>>();
+    let integers = |index: usize| batches.iter().flat_map(|batch| batch.column(index).as_primitive::<UInt64Type>().iter()).collect::<Vec<_>>();
+    let solutions = batches.iter().flat_map(|batch| {
+        let lists: &ListArray = batch.column(7).as_list::<i32>();
+        return lists.iter().map(|list| {
+            let coordinates = list.map(|list| list.as_primitive::<Float64Type>().values().to_vec()).unwrap_or_default();
+            return Series::new("".into(), coordinates);
+        }).collect::<Vec<_>>();
+    }).collect::<Vec<_>>();
+    let best_values = batches.iter().flat_map(|batch| batch.column(4).as_primitive::<Float64Type>().values().to_vec()).collect::<Vec<_>>();
+
+    let columns: Vec<Column> = vec![
+        Series::new("function".into(), strings(0)).into(),
+        Series::new("algorithm".into(), strings(1)).into(),
+        Series::new("dimensions".into(), integers(2)).into(),
+        Series::new("seed".into(), integers(3)).into(),
+        Series::new("best_value".into(), best_values).into(),
+        Series::new("evaluations".into(), integers(5)).into(),
+        Series::new("evaluations_to_target".into(), integers(6)).into(),
+        Series::new("best_solution".into(), solutions).into(),
+    ];
+    return DataFrame::new(batches.iter().map(RecordBatch::num_rows).sum(), columns);
+}
+
+#[cfg(test)]
+mod test {
+    use arrow::{array::AsArray, datatypes::{Float64Type, UInt64Type}};
+
+    use super::{RunRecord, RunRecorder};
+
+    fn record(recorder: &mut RunRecorder, seed: u64) {
+        recorder.push(&RunRecord {
+            function: "ackley",
+            algorithm: "bats",
+            seed,
+            best_value: seed as f64 * 0.5,
+            evaluations: 100,
+            evaluations_to_target: seed.is_multiple_of(2).then_some(40),
+            best_solution: &[seed as f64, -1.0],
+        });
+    }
+
+    #[test]
+    fn batches_test() {
+        let mut recorder = RunRecorder::new(2);
+        for seed in 0..5 {
+            record(&mut recorder, seed);
+        }
+        let batches = recorder.finish();
+        assert_eq!(batches.iter().map(|batch| batch.num_rows()).collect::<Vec<_>>(), [2, 2, 1]);
+        let targets = batches[1].column(6).as_primitive::<UInt64Type>();
+        assert_eq!(targets.iter().collect::<Vec<_>>(), [Some(40), None]);
+        let solutions = batches[2].column(7).as_list::<i32>();
+        assert_eq!(solutions.value(0).as_primitive::<Float64Type>().values().to_vec(), [4.0, -1.0]);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn dataframe_test() {
+        let mut recorder = RunRecorder::new(2);
+        for seed in 0..3 {
+            record(&mut recorder, seed);
+        }
+        let dataframe = recorder.into_dataframe().unwrap();
+        assert_eq!(dataframe.shape(), (3, 8));
+        assert_eq!(dataframe.column("evaluations_to_target").unwrap().null_count(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+// Objectives living in another service, built with `--features remote` (part of the command line program). Every
+// evaluation POSTs the coordinates to the URL as a JSON array and reads the value back as a JSON number:
+//
+//     POST /evaluate  [0.5, -1.25, 3.0]  ->  12.75
+//
+// In batch mode a whole population goes in one request, an array of coordinate arrays answered by an array of values in
+// the same order. Requests are retried after connection errors, timeouts and 429 or 5xx responses. An evaluation failing
+// every attempt panics, like the other invalid inputs of the optimizers
+
+use std::{fmt, thread, time::Duration};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{functions::Function, real::{self, Real}, vector::VectorN};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RemoteOptions {
+    pub batch: bool, // One request per population instead of one per agent
+    pub concurrency: usize, // Requests in flight at once, the population is split between them
+    pub timeout: Duration, // Of a single attempt
+    pub retries: u32, // Attempts after the first one, with the wait doubling from 100 ms
+}
+
+impl Default for RemoteOptions {
+    fn default() -> Self {
+        return Self { batch: false, concurrency: 1, timeout: Duration::from_secs(30), retries: 2 };
+    }
+}
+
+pub struct RemoteObjective {
+    url: String,
+    options: RemoteOptions,
+    agent: ureq::Agent, // Keeps the connections alive between evaluations
+}
+
+impl fmt::Debug for RemoteObjective {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return formatter.debug_struct("RemoteObjective").field("url", &self.url).field("options", &self.options).finish();
+    }
+}
+
+impl RemoteObjective {
+    pub fn new(url: &str, options: RemoteOptions) -> Self {
+        if options.concurrency == 0 {
+            panic!("A remote objective needs a concurrency of at least 1");
+        }
+        let agent = ureq::AgentBuilder::new().timeout(options.timeout).max_idle_connections_per_host(options.concurrency).build();
+        return Self { url: url.to_string(), options, agent };
+    }
+
+    pub fn url(&self) -> &str {
+        return &self.url;
+    }
+
+    fn post<T: DeserializeOwned>(&self, body: &Value) -> T {
+        let mut wait = Duration::from_millis(100);
+        let mut attempt = 0;
+        loop {
+            let error = match self.agent.post(&self.url).send_json(body) {
+                Ok(response) => match response.into_json::<T>() {
+                    Ok(value) => return value,
+                    Err(error) => panic!("Unexpected response from the objective at {}: {}", self.url, error),
+                },
+                Err(ureq::Error::Status(status, response)) if status != 429 && status < 500 => {
+                    panic!("The objective at {} rejected the request with {}: {}", self.url, status, response.into_string().unwrap_or_default());
+                },
+                Err(error) => error,
+            };
+            if attempt == self.options.retries {
+                panic!("The objective at {} failed after {} attempts: {}", self.url, attempt + 1, error);
+            }
+            attempt += 1;
+            thread::sleep(wait);
+            wait *= 2;
+        }
+    }
+
+    fn request_values<const N: usize>(&self, inputs: &[VectorN<Real, N>], values: &mut [Real]) {
+        if self.options.batch {
+            let body = Value::from(inputs.iter().map(coordinates).collect::<Vec<_>>());
+            let received = self.post::<Vec<f64>>(&body);
+            if received.len() != inputs.len() {
+                panic!("The objective at {} returned {} values for {} inputs", self.url, received.len(), inputs.len());
+            }
+            for (value, received) in values.iter_mut().zip(received) {
+                *value = real::from_f64(received);
+            }
+        } else {
+            for (value, input) in values.iter_mut().zip(inputs) {
+                *value = real::from_f64(self.post::<f64>(&coordinates(input)));
+            }
+        }
+    }
+}
+
+fn coordinates<const N: usize>(input: &VectorN<Real, N>) -> Value {
+    return Value::from(input.coordinates.iter().map(|&coordinate| real::to_f64(coordinate)).collect::<Vec<_>>());
+}
+
+impl<const N: usize> Function<N> for RemoteObjective {
+    fn evaluate(&self, input: VectorN<Real, N>) -> Real {
+        let mut value = [0.0];
+        self.request_values(&[input], &mut value);
+        return value[0];
+    }
+
+    fn evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        values.clear();
+        values.resize(inputs.len(), 0.0);
+        let chunk_size = inputs.len().div_ceil(self.options.concurrency).max(1);
+        if chunk_size == inputs.len() {
+            self.request_values(inputs, values);
+            return;
+        }
+        thread::scope(|scope| {
+            for (inputs, values) in inputs.chunks(chunk_size).zip(values.chunks_mut(chunk_size)) {
+                scope.spawn(|| self.request_values(inputs, values));
+            }
+        });
+    }
+
+    // The requests are already concurrent, rayon threads would only wait on them
+    fn par_evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        self.evaluate_batch_into(inputs, values);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::{BufRead, BufReader, Read, Write}, net::TcpListener, thread, time::Duration};
+
+    use serde_json::Value;
+
+    use crate::{functions::Function, real::{self, Real}, vector::VectorN};
+
+    use super::{RemoteObjective, RemoteOptions};
+
+    // Answers with the sum of the coordinates, or of every input of a batch, after failing the first requests with a 503
+    fn serve(failures: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/evaluate", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for (index, stream) in listener.incoming().enumerate() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let sum = |coordinates: &Value| coordinates.as_array().unwrap().iter().map(|coordinate| coordinate.as_f64().unwrap()).sum::<f64>();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let (status, response) = if index < failures {
+                    ("503 Service Unavailable", String::new())
+                } else if request[0].is_array() {
+                    ("200 OK", Value::from(request.as_array().unwrap().iter().map(sum).collect::<Vec<_>>()).to_string())
+                } else {
+                    ("200 OK", sum(&request).to_string())
+                };
+                let mut stream = reader.into_inner();
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, response.len(), response).unwrap();
+            }
+        });
+        return url;
+    }
+
+    fn inputs() -> Vec<VectorN<Real, 3>> {
+        return (0..7).map(|index| VectorN::new([index as f64, 0.5, -2.0].map(real::from_f64))).collect();
+    }
+
+    fn assert_sums(values: &[Real]) {
+        let expected = (0..7).map(|index| real::from_f64(index as f64 - 1.5)).collect::<Vec<_>>();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn concurrent_requests_test() {
+        let objective = RemoteObjective::new(&serve(0), RemoteOptions { concurrency: 3, ..RemoteOptions::default() });
+        let mut values = Vec::new();
+        objective.evaluate_batch_into(&inputs(), &mut values);
+        assert_sums(&values);
+    }
+
+    #[test]
+    fn batch_requests_test() {
+        let objective = RemoteObjective::new(&serve(0), RemoteOptions { batch: true, concurrency: 2, ..RemoteOptions::default() });
+        let mut values = Vec::new();
+        objective.evaluate_batch_into(&inputs(), &mut values);
+        assert_sums(&values);
+    }
+
+    #[test]
+    fn retry_test() {
+        let objective = RemoteObjective::new(&serve(2), RemoteOptions { retries: 2, timeout: Duration::from_secs(5), ..RemoteOptions::default() });
+        assert_eq!(objective.evaluate(inputs()[3]), real::from_f64(1.5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn exhausted_retries_test() {
+        let objective = RemoteObjective::new(&serve(2), RemoteOptions { retries: 1, ..RemoteOptions::default() });
+        objective.evaluate(inputs()[0]);
+    }
+}
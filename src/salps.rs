@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub salp_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub parallel: bool, // Evaluates the chain on the rayon pool. The salps move one after another, each follows the one in front
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.salp_count == 0 {
+            return Err("There must be at least one salp");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+// c1 of the paper, 2e^-(4l/L)^2. Goes from 2 down to almost 0, exploring early and exploiting late
+pub fn leader_coefficient(progress: Real) -> Real {
+    return 2.0 * (-(4.0 * progress).powi(2)).exp();
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    chain: Vec<VectorN<Real, N>>, // The leader first, then the followers
+    pub best_solution: VectorN<Real, N>, // The food source the leader moves around
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(salp_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            salp_count, function, bounds,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            chain: Vec::with_capacity(parameters.salp_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            values: Vec::with_capacity(parameters.salp_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    fn evaluate_chain(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.chain, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.chain, &mut self.values);
+        }
+    }
+
+    // The leader jumps to a random point around the food, scaled by c1, and every follower moves halfway to the salp in
+    // front of it, which has already moved
+    fn move_chain(&mut self, iteration_count: usize) {
+        let progress = (self.iteration as Real / iteration_count as Real).min(1.0);
+        let coefficient = leader_coefficient(progress);
+        let (lower, upper) = self.parameters.bounds;
+        let food = self.best_solution;
+        let leader = &mut self.chain[0];
+        for dimension in 0..N {
+            let step = coefficient * ((upper - lower) * self.random_generator.gen::<Real>() + lower);
+            if self.random_generator.gen::<Real>() < 0.5 {
+                leader[dimension] = food[dimension] - step;
+            } else {
+                leader[dimension] = food[dimension] + step;
+            }
+        }
+        leader.clamp(self.parameters.bounds);
+        for index in 1..self.chain.len() {
+            self.chain[index] = (self.chain[index] + self.chain[index - 1]) / 2.0;
+        }
+    }
+
+    // The values of the chain, in order
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.chain.len();
+        // Takes the first of equally good salps
+        let (best_index, &best_value) = values.iter().enumerate().min_by_key(|&(_, &value)| Fitness::minimize(value)).unwrap();
+        if Fitness::minimize(best_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_value;
+            self.best_solution = self.chain[best_index];
+        }
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.chain.iter().copied(), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, iteration_count: usize) {
+        self.move_chain(iteration_count);
+        self.evaluate_chain();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.move_chain(iteration_count);
+        positions.extend_from_slice(&self.chain);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.chain.clear();
+        for _ in 0..self.parameters.salp_count {
+            self.chain.push(VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator));
+        }
+        self.evaluate_chain();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.chain.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend_from_slice(&self.chain);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::{leader_coefficient, WorldState};
+
+    #[test]
+    fn chain_test() {
+        assert_eq!(leader_coefficient(0.0), 2.0);
+        assert!(leader_coefficient(1.0) < 1e-6);
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(30, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(300);
+        assert_eq!(world.evaluation_count(), 30 * 301);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,304 @@
+// SQLite archive of batch runs, enabled with `--store results.db`. Every invocation appends to the file:
+//
+// CREATE TABLE batches (
+//     id INTEGER PRIMARY KEY,
+//     started_at INTEGER NOT NULL,     -- Unix time in seconds
+//     command_line TEXT NOT NULL,      -- Arguments joined with spaces
+//     algorithm TEXT NOT NULL,         -- Subcommand name, e.g. `bats`
+//     configuration TEXT NOT NULL,     -- JSON object of the subcommand's parameters
+//     eval_budget INTEGER              -- NULL when the run length is given in iterations
+// );
+// CREATE TABLE runs (
+//     id INTEGER PRIMARY KEY,
+//     batch_id INTEGER NOT NULL REFERENCES batches(id),
+//     function TEXT NOT NULL,
+//     seed INTEGER NOT NULL,           -- The u64 seed reinterpreted as i64
+//     best_value REAL NOT NULL,
+//     best_solution TEXT NOT NULL,     -- JSON array of coordinates
+//     evaluations INTEGER NOT NULL,
+//     target_value REAL,               -- NULL without --target-value
+//     evaluations_to_target INTEGER    -- NULL if the target was not reached
+// );
+// CREATE TABLE convergence (           -- Only filled with --store-trace
+//     run_id INTEGER NOT NULL REFERENCES runs(id),
+//     evaluations INTEGER NOT NULL,
+//     best_value REAL NOT NULL         -- Best value after that many evaluations, one row per iteration
+// );
+// CREATE TABLE grid_cells (            -- Cells of `grid-search` whose runs are all stored, skipped when resuming. A cell's
+//                                      -- batch, runs and row are written in one transaction, so an interrupted cell leaves nothing
+//     cell TEXT PRIMARY KEY,           -- Function, run settings and the full algorithm configuration
+//     batch_id INTEGER NOT NULL REFERENCES batches(id)
+// );
+
+use std::{cell::Cell, collections::{HashMap, HashSet}, sync::mpsc::{self, Receiver, Sender}, thread::JoinHandle, time::{SystemTime, UNIX_EPOCH}};
+
+use rusqlite::{params, Connection};
+
+use crate::{OptimizationAlgorithmCommand, RunResult};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS batches (
+    id INTEGER PRIMARY KEY,
+    started_at INTEGER NOT NULL,
+    command_line TEXT NOT NULL,
+    algorithm TEXT NOT NULL,
+    configuration TEXT NOT NULL,
+    eval_budget INTEGER
+);
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    batch_id INTEGER NOT NULL REFERENCES batches(id),
+    function TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    best_value REAL NOT NULL,
+    best_solution TEXT NOT NULL,
+    evaluations INTEGER NOT NULL,
+    target_value REAL,
+    evaluations_to_target INTEGER
+);
+CREATE TABLE IF NOT EXISTS convergence (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    evaluations INTEGER NOT NULL,
+    best_value REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS grid_cells (
+    cell TEXT PRIMARY KEY,
+    batch_id INTEGER NOT NULL REFERENCES batches(id)
+);
+";
+
+const RUNS_PER_TRANSACTION: usize = 256;
+
+pub struct RunRecord {
+    pub function_name: String,
+    pub target_value: Option<f64>,
+    pub run: RunResult,
+    pub trace: Option<Vec<(usize, f64)>>, // (evaluations, best value) after each iteration, only with --store-trace
+}
+
+enum StoreMessage {
+    Batch { batch: usize, configuration: serde_json::Value, eval_budget: Option<usize>, cell: Option<String> },
+    Run { batch: usize, record: RunRecord },
+    CellCompleted { batch: usize },
+}
+
+// A batch of a grid cell, held back until all of its runs arrived
+struct PendingCell {
+    started_at: i64,
+    configuration: serde_json::Value,
+    eval_budget: Option<usize>,
+    cell: String,
+    records: Vec<RunRecord>,
+}
+
+// Owned by the thread queueing the batches, the records are written by a single thread owning the connection
+pub struct StoreSender {
+    sender: Sender<StoreMessage>,
+    record_traces: bool,
+    next_batch: Cell<usize>,
+}
+
+impl StoreSender {
+    // Runs of several batches may be in flight at once, so they are sent through the returned sender
+    pub fn begin_batch(&self, command: &OptimizationAlgorithmCommand, eval_budget: Option<usize>) -> BatchSender {
+        return self.begin(command, eval_budget, None);
+    }
+
+    // Nothing of the batch is written until BatchSender::complete_cell
+    pub fn begin_cell(&self, command: &OptimizationAlgorithmCommand, eval_budget: Option<usize>, cell: String) -> BatchSender {
+        return self.begin(command, eval_budget, Some(cell));
+    }
+
+    fn begin(&self, command: &OptimizationAlgorithmCommand, eval_budget: Option<usize>, cell: Option<String>) -> BatchSender {
+        let batch = self.next_batch.get();
+        self.next_batch.set(batch + 1);
+        self.sender.send(StoreMessage::Batch { batch, configuration: serde_json::to_value(command).unwrap(), eval_budget, cell }).unwrap();
+        return BatchSender {
+            sender: self.sender.clone(),
+            batch,
+            record_traces: self.record_traces,
+        };
+    }
+}
+
+// Cloned into every run of a batch
+#[derive(Clone)]
+pub struct BatchSender {
+    sender: Sender<StoreMessage>,
+    batch: usize,
+    pub record_traces: bool,
+}
+
+impl BatchSender {
+    pub fn send(&self, record: RunRecord) {
+        self.sender.send(StoreMessage::Run { batch: self.batch, record }).unwrap();
+    }
+
+    // Only call once all runs of the cell were sent, for a batch from StoreSender::begin_cell
+    pub fn complete_cell(&self) {
+        self.sender.send(StoreMessage::CellCompleted { batch: self.batch }).unwrap();
+    }
+}
+
+pub struct Store {
+    pub sender: StoreSender,
+    pub writer_thread: JoinHandle<()>,
+    pub completed_cells: HashSet<String>,
+}
+
+impl Store {
+    // Waits until everything sent so far is committed
+    // Every BatchSender must be dropped first
+    pub fn close(self) {
+        drop(self.sender);
+        self.writer_thread.join().unwrap();
+    }
+}
+
+// Creates the tables if needed and starts the writer thread. The thread ends once every sender is dropped
+pub fn open(path: &str, record_traces: bool) -> Store {
+    let connection = Connection::open(path).unwrap_or_else(|error| panic!("Could not open {path}: {error}"));
+    connection.execute_batch(SCHEMA).unwrap();
+    let completed_cells = connection.prepare("SELECT cell FROM grid_cells").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .collect::<Result<HashSet<String>, _>>().unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    let writer_thread = std::thread::spawn(move || write_messages(connection, receiver));
+    return Store {
+        sender: StoreSender { sender, record_traces, next_batch: Cell::new(0) },
+        writer_thread,
+        completed_cells,
+    };
+}
+
+fn write_messages(connection: Connection, receiver: Receiver<StoreMessage>) {
+    let mut batch_ids = HashMap::new(); // Rows in batches, by the number given in begin_batch
+    let mut pending_cells = HashMap::new(); // Batches of grid cells not completed yet, by the same number
+    let mut uncommitted_runs = 0;
+    connection.execute_batch("BEGIN").unwrap();
+    for message in receiver {
+        match message {
+            StoreMessage::Batch { batch, configuration, eval_budget, cell } => {
+                let started_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+                match cell {
+                    Some(cell) => {
+                        pending_cells.insert(batch, PendingCell { started_at, configuration, eval_budget, cell, records: Vec::new() });
+                    },
+                    None => {
+                        batch_ids.insert(batch, write_batch(&connection, started_at, &configuration, eval_budget));
+                    },
+                }
+            },
+            StoreMessage::Run { batch, record } => {
+                if let Some(pending_cell) = pending_cells.get_mut(&batch) {
+                    pending_cell.records.push(record);
+                    continue;
+                }
+                write_run(&connection, batch_ids[&batch], record);
+                uncommitted_runs += 1;
+                if uncommitted_runs == RUNS_PER_TRANSACTION {
+                    connection.execute_batch("COMMIT; BEGIN").unwrap();
+                    uncommitted_runs = 0;
+                }
+            },
+            StoreMessage::CellCompleted { batch } => {
+                let pending_cell = pending_cells.remove(&batch).expect("Completed a batch that isn't a grid cell");
+                let batch_id = write_batch(&connection, pending_cell.started_at, &pending_cell.configuration, pending_cell.eval_budget);
+                for record in pending_cell.records {
+                    write_run(&connection, batch_id, record);
+                }
+                connection.execute("INSERT OR REPLACE INTO grid_cells (cell, batch_id) VALUES (?1, ?2)", params![pending_cell.cell, batch_id]).unwrap();
+                connection.execute_batch("COMMIT; BEGIN").unwrap();
+                uncommitted_runs = 0;
+            },
+        }
+    }
+    // Cells still pending were interrupted, they're run again on the next invocation
+    connection.execute_batch("COMMIT").unwrap();
+}
+
+// Returns the id of the new row
+fn write_batch(connection: &Connection, started_at: i64, configuration: &serde_json::Value, eval_budget: Option<usize>) -> i64 {
+    let (algorithm, parameters) = configuration.as_object().and_then(|variant| variant.iter().next()).unwrap();
+    connection.execute(
+        "INSERT INTO batches (started_at, command_line, algorithm, configuration, eval_budget) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![started_at, std::env::args().collect::<Vec<_>>().join(" "), algorithm, parameters.to_string(), eval_budget.map(|budget| budget as i64)],
+    ).unwrap();
+    return connection.last_insert_rowid();
+}
+
+fn write_run(connection: &Connection, batch_id: i64, record: RunRecord) {
+    connection.execute(
+        "INSERT INTO runs (batch_id, function, seed, best_value, best_solution, evaluations, target_value, evaluations_to_target) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            batch_id, record.function_name, record.run.seed as i64, record.run.best_value, serde_json::to_string(&record.run.best_solution).unwrap(),
+            record.run.evaluations as i64, record.target_value, record.run.evaluations_to_target.map(|evaluations| evaluations as i64),
+        ],
+    ).unwrap();
+    if let Some(trace) = record.trace {
+        let run_id = connection.last_insert_rowid();
+        let mut statement = connection.prepare_cached("INSERT INTO convergence (run_id, evaluations, best_value) VALUES (?1, ?2, ?3)").unwrap();
+        for (evaluations, best_value) in trace {
+            statement.execute(params![run_id, evaluations as i64, best_value]).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::Connection;
+
+    use crate::{parse_algorithm_arguments, RunResult};
+
+    use super::{open, RunRecord};
+
+    fn record(seed: u64) -> RunRecord {
+        let run = RunResult { seed, best_value: 1.0, best_solution: vec![0.0], evaluations: 10, evaluations_to_target: None, trace: None, population: None };
+        return RunRecord { function_name: "ackley".to_string(), target_value: None, run, trace: None };
+    }
+
+    fn count(connection: &Connection, table: &str) -> i64 {
+        return connection.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0)).unwrap();
+    }
+
+    #[test]
+    fn resume_test() {
+        let path = std::env::temp_dir().join(format!("swarm_optimizers_resume_test_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+        let command = parse_algorithm_arguments(&["jaya".to_string(), "--jaya-count".to_string(), "10".to_string()]);
+
+        // Interrupted after some runs of the second cell
+        let store = open(path, false);
+        let completed = store.sender.begin_cell(&command, Some(100), "first".to_string());
+        completed.send(record(0));
+        completed.send(record(1));
+        completed.complete_cell();
+        let interrupted = store.sender.begin_cell(&command, Some(100), "second".to_string());
+        interrupted.send(record(2));
+        drop((completed, interrupted));
+        store.close();
+
+        let connection = Connection::open(path).unwrap();
+        assert_eq!((count(&connection, "batches"), count(&connection, "runs"), count(&connection, "grid_cells")), (1, 2, 1));
+        drop(connection);
+
+        // Resuming runs the second cell again from the start
+        let store = open(path, false);
+        assert_eq!(store.completed_cells.iter().collect::<Vec<_>>(), ["first"]);
+        let resumed = store.sender.begin_cell(&command, Some(100), "second".to_string());
+        resumed.send(record(3));
+        resumed.send(record(4));
+        resumed.complete_cell();
+        drop(resumed);
+        store.close();
+
+        let connection = Connection::open(path).unwrap();
+        assert_eq!((count(&connection, "batches"), count(&connection, "runs"), count(&connection, "grid_cells")), (2, 4, 2));
+        let orphan_runs: i64 = connection.query_row("SELECT COUNT(*) FROM runs WHERE batch_id NOT IN (SELECT batch_id FROM grid_cells)", [], |row| row.get(0)).unwrap();
+        assert_eq!(orphan_runs, 0);
+        drop(connection);
+        std::fs::remove_file(path).unwrap();
+    }
+}
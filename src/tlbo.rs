@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{fitness::Fitness, functions::{Function, Functions}, optimizer::{agent_random_source, FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+// The algorithm has no parameters of its own
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub learner_count: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub parallel: bool, // Moves and evaluates the learners on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.learner_count < 2 {
+            return Err("There must be at least two learners, to learn from each other");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Learner<const N: usize, RngType: Rng> {
+    position: VectorN<Real, N>,
+    function_value: Real,
+    candidate: VectorN<Real, N>, // Of the current phase, kept if it's better
+    random_source: RngType, // Seeded from the population seed and the learner's index, so learners can move in parallel and still be reproducible
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng> Learner<N, RngType> {
+    // Not evaluated yet, the world evaluates the whole class at once
+    fn new<F>(parameters: &Parameters<N, F>, population_seed: u64, index: usize) -> Self {
+        let mut random_source = agent_random_source::<RngType>(population_seed, index);
+        let position = VectorN::random_uniform(parameters.bounds, &mut random_source);
+        return Self { position, function_value: Real::INFINITY, candidate: position, random_source };
+    }
+
+    // Towards the teacher, away from the mean of the class scaled by a teaching factor of 1 or 2
+    fn propose_teacher_phase<F>(&mut self, parameters: &Parameters<N, F>, teacher: VectorN<Real, N>, mean: VectorN<Real, N>) {
+        let teaching_factor = self.random_source.gen_range(1..=2) as Real;
+        let step = VectorN::random_uniform((0.0, 1.0), &mut self.random_source);
+        self.candidate = self.position + step * (teacher - mean * teaching_factor);
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // Towards a random other learner if it's better, away from it if it isn't
+    fn propose_learner_phase<F>(&mut self, parameters: &Parameters<N, F>, index: usize, positions: &[VectorN<Real, N>], values: &[Real]) {
+        let mut partner = self.random_source.gen_range(0..positions.len() - 1);
+        if partner >= index {
+            partner += 1;
+        }
+        let step = VectorN::random_uniform((0.0, 1.0), &mut self.random_source);
+        if Fitness::minimize(values[partner]) < Fitness::minimize(self.function_value) {
+            self.candidate = self.position + step * (positions[partner] - self.position);
+        } else {
+            self.candidate = self.position + step * (self.position - positions[partner]);
+        }
+        self.candidate.clamp(parameters.bounds);
+    }
+
+    // Keeps the candidate if it's better, the first of equally good positions stays
+    fn take_candidate(&mut self, value: Real) {
+        if Fitness::minimize(value) < Fitness::minimize(self.function_value) {
+            self.position = self.candidate;
+            self.function_value = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    class: Vec<Learner<N, RngType>>,
+    pub best_solution: VectorN<Real, N>, // The teacher
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    positions: Vec<VectorN<Real, N>>,
+    previous_values: Vec<Real>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(learner_count: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            learner_count, function, bounds,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            class: Vec::with_capacity(parameters.learner_count),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            positions: Vec::with_capacity(parameters.learner_count),
+            previous_values: Vec::with_capacity(parameters.learner_count),
+            values: Vec::with_capacity(parameters.learner_count),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // Evaluates the candidates, keeps the better ones and updates the teacher
+    fn evaluate_candidates(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.class.iter().map(|learner| learner.candidate));
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.positions, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.positions, &mut self.values);
+        }
+        self.evaluation_count += self.class.len();
+        for (learner, &value) in self.class.iter_mut().zip(&self.values) {
+            learner.take_candidate(value);
+        }
+        // Takes the first of equally good learners
+        let best_learner = self.class.iter().min_by_key(|learner| Fitness::minimize(learner.function_value)).unwrap();
+        if Fitness::minimize(best_learner.function_value) < Fitness::minimize(self.best_solution_value) {
+            self.best_solution_value = best_learner.function_value;
+            self.best_solution = best_learner.position;
+        }
+    }
+
+    fn teacher_phase(&mut self) {
+        let mean = self.class.iter().fold(VectorN::default(), |sum, learner| sum + learner.position) / self.class.len() as Real;
+        let parameters = &*self.parameters;
+        let teacher = self.best_solution;
+        let propose = |learner: &mut Learner<N, RngType>| learner.propose_teacher_phase(parameters, teacher, mean);
+        if parameters.parallel {
+            self.class.par_iter_mut().for_each(propose);
+        } else {
+            self.class.iter_mut().for_each(propose);
+        }
+        self.evaluate_candidates();
+    }
+
+    fn learner_phase(&mut self) {
+        self.positions.clear();
+        self.positions.extend(self.class.iter().map(|learner| learner.position));
+        self.previous_values.clear();
+        self.previous_values.extend(self.class.iter().map(|learner| learner.function_value));
+        let parameters = &*self.parameters;
+        let positions = &self.positions;
+        let values = &self.previous_values;
+        let propose = |(index, learner): (usize, &mut Learner<N, RngType>)| learner.propose_learner_phase(parameters, index, positions, values);
+        if parameters.parallel {
+            self.class.par_iter_mut().enumerate().for_each(propose);
+        } else {
+            self.class.iter_mut().enumerate().for_each(propose);
+        }
+        self.evaluate_candidates();
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    // The class is evaluated after both phases, so the iteration can't be split into a proposal and its values
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.teacher_phase();
+        self.learner_phase();
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.class.iter().map(|learner| learner.position), previous_best_value, self.best_solution, self.best_solution_value);
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.evaluation_count = 0;
+        self.class.clear();
+        let population_seed = self.random_generator.gen();
+        for index in 0..self.parameters.learner_count {
+            self.class.push(Learner::new(&self.parameters, population_seed, index));
+        }
+        self.evaluate_candidates();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    fn evaluations_per_iteration(&self) -> usize {
+        return 2 * self.class.len();
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.class.iter().map(|learner| learner.position));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::WorldState;
+
+    #[test]
+    fn class_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(20, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        world.do_all_iterations(100);
+        assert_eq!(world.evaluation_count(), 20 + 40 * 100);
+        assert!(world.best_solution_value() < initial_best);
+    }
+}
@@ -0,0 +1,141 @@
+// Hyperparameter tuning by an external tuner like Optuna or SMAC, built with `--features tuning` (part of the command line
+// program). The tuner asks for a configuration to be scored with a JSON line and gets one line back, in the same order:
+//
+//     -> {"id": 3, "parameters": {"bat_count": 32, "pulse_rate_factor": 0.7}}
+//     <- {"id": 3, "score": 0.0123, "median": 0.0098, "best": 0.0001, "worst": 0.41, "runs": 16, "evaluations": 320000}
+//     <- {"id": 3, "error": "Unknown parameter in override: `ackley:bat_cuont=32`"}
+//
+// The score is the mean best value of the runs and is minimized, evaluations are summed over the runs. The id can be any
+// JSON value and is sent back as is. serve answers the asks of any TuningTarget, the `ask-tell` subcommand does it on
+// stdin and stdout with the batches of the command line. The loop of an Optuna study:
+//
+//     trial = study.ask()
+//     target.stdin.write(json.dumps({"id": trial.number, "parameters": {"bat_count": trial.suggest_int("bat_count", 5, 100)}}) + "\n")
+//     study.tell(trial, json.loads(target.stdout.readline())["score"])
+
+use std::{io::{self, BufRead, Write}, panic::{self, AssertUnwindSafe}};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ask {
+    #[serde(default)]
+    pub id: Value,
+    #[serde(default)]
+    pub parameters: Map<String, Value>, // Named like the fields of the configuration, e.g. `bat_count`
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Score {
+    pub score: f64,
+    pub median: f64,
+    pub best: f64,
+    pub worst: f64,
+    pub runs: usize,
+    pub evaluations: usize,
+}
+
+impl Score {
+    // From the best value of every run
+    pub fn from_results(best_values: &[f64], evaluations: usize) -> Self {
+        if best_values.is_empty() {
+            panic!("A score needs at least one run");
+        }
+        let mut sorted = best_values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) { (sorted[middle - 1] + sorted[middle]) / 2.0 } else { sorted[middle] };
+        return Self {
+            score: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            median,
+            best: sorted[0],
+            worst: sorted[sorted.len() - 1],
+            runs: sorted.len(),
+            evaluations,
+        };
+    }
+}
+
+// Scores a configuration, with errors for parameters it can't use. Panics are reported as errors too
+pub trait TuningTarget {
+    fn evaluate(&self, parameters: &Map<String, Value>) -> Result<Score, String>;
+}
+
+impl<F: Fn(&Map<String, Value>) -> Result<Score, String>> TuningTarget for F {
+    fn evaluate(&self, parameters: &Map<String, Value>) -> Result<Score, String> {
+        return self(parameters);
+    }
+}
+
+// Answers every line of the input until it ends. Blank lines are skipped
+pub fn serve(target: &impl TuningTarget, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let answer = match serde_json::from_str::<Ask>(&line) {
+            Ok(ask) => {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| target.evaluate(&ask.parameters))).unwrap_or_else(|payload| Err(panic_message(payload)));
+                match result {
+                    Ok(score) => {
+                        let mut answer = json!({ "id": ask.id });
+                        answer.as_object_mut().unwrap().extend(serde_json::to_value(score).unwrap().as_object().unwrap().clone());
+                        answer
+                    },
+                    Err(error) => json!({ "id": ask.id, "error": error }),
+                }
+            },
+            Err(error) => json!({ "id": null, "error": format!("Invalid ask: {error}") }),
+        };
+        writeln!(output, "{answer}")?;
+        output.flush()?; // The tuner waits for the answer before its next ask
+    }
+    return Ok(());
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    return "The target panicked".to_string();
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{Map, Value};
+
+    use super::{serve, Score};
+
+    #[test]
+    fn score_test() {
+        let score = Score::from_results(&[4.0, 1.0, 3.0, 2.0], 40);
+        assert_eq!((score.score, score.median, score.best, score.worst, score.runs), (2.5, 2.5, 1.0, 4.0, 4));
+    }
+
+    #[test]
+    fn serve_test() {
+        // The score is the square of x, a missing x is an error and a negative one panics
+        let target = |parameters: &Map<String, Value>| {
+            let x = parameters.get("x").and_then(Value::as_f64).ok_or_else(|| "x is required".to_string())?;
+            if x < 0.0 {
+                panic!("x must not be negative");
+            }
+            return Ok(Score::from_results(&[x * x], 1));
+        };
+        let input = "{\"id\": 1, \"parameters\": {\"x\": 3}}\n\n{\"id\": \"b\"}\nnot json\n{\"id\": 4, \"parameters\": {\"x\": -1}}\n";
+        let mut output = Vec::new();
+        serve(&target, input.as_bytes(), &mut output).unwrap();
+        let answers = String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str::<Value>(line).unwrap()).collect::<Vec<_>>();
+        assert_eq!(answers.len(), 4);
+        assert_eq!((answers[0]["id"].as_u64(), answers[0]["score"].as_f64()), (Some(1), Some(9.0)));
+        assert_eq!((answers[1]["id"].as_str(), answers[1]["error"].as_str()), (Some("b"), Some("x is required")));
+        assert!(answers[2]["id"].is_null() && answers[2]["error"].as_str().unwrap().starts_with("Invalid ask"));
+        assert_eq!(answers[3]["error"].as_str(), Some("x must not be negative"));
+    }
+}
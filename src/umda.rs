@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use crate::{cem::fit_normal, fitness::Fitness, functions::{Function, Functions}, optimizer::{FromParameters, Optimizer}, real::Real, vector::VectorN};
+
+#[derive(Clone, Debug)]
+pub struct Parameters<const N: usize, F = Functions<N>> {
+    pub population_size: usize,
+    pub function: F,
+    pub bounds: (Real, Real), // lower, upper
+    pub selection_ratio: Real, // Of the population the next distribution is fitted to, at least two individuals are always taken
+    pub parallel: bool, // Evaluates the population on the rayon pool
+}
+
+impl<const N: usize, F> Parameters<N, F> {
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.bounds.0 >= self.bounds.1 {
+            return Err("Incorrect order of bounds or zero size");
+        }
+        if self.population_size < 2 {
+            return Err("There must be at least two individuals, to fit a distribution to");
+        }
+        if !(self.selection_ratio > 0.0 && self.selection_ratio <= 1.0) {
+            return Err("Selection ratio must be in (0, 1]");
+        }
+        return Ok(());
+    }
+
+    pub fn validate(&self) {
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+
+    // The individuals a distribution is fitted to
+    pub fn selected_count(&self) -> usize {
+        return ((self.selection_ratio * self.population_size as Real).round() as usize).clamp(2, self.population_size);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState<const N: usize, RngType: Rng, F = Functions<N>> {
+    parameters: Arc<Parameters<N, F>>,
+    population: Vec<(Real, VectorN<Real, N>)>, // Sorted, best first. The best is kept for the next generation
+    mean: VectorN<Real, N>, // Of the distribution fitted to the selected individuals
+    deviation: VectorN<Real, N>, // Standard deviation of every coordinate, the model is univariate
+    pub best_solution: VectorN<Real, N>,
+    pub best_solution_value: Real,
+    random_generator: RngType,
+    pub evaluation_count: usize, // Objective evaluations since the last reset
+    iteration: usize, // Since the last reset
+    offspring: Vec<VectorN<Real, N>>,
+    values: Vec<Real>,
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> WorldState<N, RngType, F> {
+    pub fn new(population_size: usize, function: F, bounds: (Real, Real), random_source: RngType) -> Self {
+        let parameters = Parameters {
+            population_size, function, bounds,
+            selection_ratio: 0.5,
+            parallel: false,
+        };
+        return Self::with_generator(Arc::new(parameters), random_source);
+    }
+
+    fn with_generator(parameters: Arc<Parameters<N, F>>, random_source: RngType) -> Self {
+        parameters.validate();
+        let mut world = Self {
+            population: Vec::with_capacity(parameters.population_size),
+            mean: VectorN::default(),
+            deviation: VectorN::default(),
+            best_solution: VectorN::default(),
+            best_solution_value: Real::INFINITY,
+            random_generator: random_source,
+            evaluation_count: 0,
+            iteration: 0,
+            offspring: Vec::with_capacity(parameters.population_size),
+            values: Vec::with_capacity(parameters.population_size),
+            parameters,
+        };
+        world.reset();
+        return world;
+    }
+
+    pub fn parameters(&self) -> &Parameters<N, F> {
+        return &self.parameters;
+    }
+
+    // The mean and standard deviations of the last fitted distribution
+    pub fn distribution(&self) -> (VectorN<Real, N>, VectorN<Real, N>) {
+        return (self.mean, self.deviation);
+    }
+
+    // Every individual but the best one, sampled from the distribution and clamped to the bounds
+    fn sample_offspring(&mut self) {
+        self.offspring.clear();
+        for _ in 1..self.parameters.population_size {
+            let noise: VectorN<Real, N> = VectorN::random_from(&StandardNormal, &mut self.random_generator);
+            let mut individual = self.mean + noise * self.deviation;
+            individual.clamp(self.parameters.bounds);
+            self.offspring.push(individual);
+        }
+    }
+
+    fn evaluate_offspring(&mut self) {
+        if self.parameters.parallel {
+            self.parameters.function.par_evaluate_batch_into(&self.offspring, &mut self.values);
+        } else {
+            self.parameters.function.evaluate_batch_into(&self.offspring, &mut self.values);
+        }
+    }
+
+    // The values of the offspring, in order. They replace all but the best individual, then the selected part of the
+    // population decides the next distribution
+    fn take_values(&mut self, values: &[Real]) {
+        #[cfg(feature = "invariants")]
+        let previous_best_value = self.best_solution_value;
+        self.evaluation_count += self.offspring.len();
+        self.population.truncate(1);
+        self.population.extend(values.iter().copied().zip(self.offspring.iter().copied()));
+        // Stable, so the surviving best stays ahead of equally good offspring
+        self.population.sort_by_key(|&(value, _)| Fitness::minimize(value));
+        if Fitness::minimize(self.population[0].0) < Fitness::minimize(self.best_solution_value) {
+            (self.best_solution_value, self.best_solution) = self.population[0];
+        }
+        self.offspring.clear();
+        self.offspring.extend(self.population[..self.parameters.selected_count()].iter().map(|&(_, individual)| individual));
+        (self.mean, self.deviation) = fit_normal(&self.offspring);
+        #[cfg(feature = "invariants")]
+        crate::optimizer::check_invariants(&self.parameters.function, self.iteration, self.parameters.bounds, self.population.iter().map(|&(_, individual)| individual), previous_best_value, self.best_solution, self.best_solution_value);
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> FromParameters<N> for WorldState<N, RngType, F> {
+    type Parameters = Parameters<N, F>;
+
+    fn from_parameters(parameters: Arc<Parameters<N, F>>, seed: u64) -> Self {
+        return Self::with_generator(parameters, RngType::seed_from_u64(seed));
+    }
+}
+
+impl<const N: usize, RngType: Rng + SeedableRng + Clone + Send + Sync, F: Function<N> + Send + Sync> Optimizer<N> for WorldState<N, RngType, F> {
+    fn do_iteration(&mut self, _iteration_count: usize) {
+        self.sample_offspring();
+        self.evaluate_offspring();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+        self.iteration += 1;
+    }
+
+    fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    fn propose(&mut self, _iteration_count: usize, positions: &mut Vec<VectorN<Real, N>>) {
+        self.sample_offspring();
+        positions.extend_from_slice(&self.offspring);
+    }
+
+    fn accept(&mut self, values: &[Real]) {
+        self.take_values(values);
+        self.iteration += 1;
+    }
+
+    // The first population is uniform, one of them stands in for the best individual and is evaluated on its own
+    fn reset(&mut self) {
+        self.best_solution_value = Real::INFINITY;
+        self.iteration = 0;
+        self.population.clear();
+        let first = VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator);
+        self.population.push((self.parameters.function.evaluate(first), first));
+        self.evaluation_count = 1;
+        self.offspring.clear();
+        for _ in 1..self.parameters.population_size {
+            self.offspring.push(VectorN::random_uniform(self.parameters.bounds, &mut self.random_generator));
+        }
+        self.evaluate_offspring();
+        let values = std::mem::take(&mut self.values);
+        self.take_values(&values);
+        self.values = values;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.random_generator = RngType::seed_from_u64(seed);
+    }
+
+    fn best_solution(&self) -> VectorN<Real, N> {
+        return self.best_solution;
+    }
+
+    fn best_solution_value(&self) -> Real {
+        return self.best_solution_value;
+    }
+
+    fn evaluation_count(&self) -> usize {
+        return self.evaluation_count;
+    }
+
+    // The best individual isn't evaluated again
+    fn evaluations_per_iteration(&self) -> usize {
+        return self.parameters.population_size - 1;
+    }
+
+    fn population(&self, positions: &mut Vec<VectorN<Real, N>>) {
+        positions.extend(self.population.iter().map(|&(_, individual)| individual));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use crate::{functions::Functions, optimizer::Optimizer};
+
+    use super::{Parameters, WorldState};
+
+    #[test]
+    fn distribution_test() {
+        let function = Functions::<10>::make_from_name("ackley");
+        let mut world = WorldState::<10, _>::new(50, function, function.get_bounds(), Xoshiro256PlusPlus::seed_from_u64(0));
+        let initial_best = world.best_solution_value();
+        let initial_spread = world.distribution().1.norm_l2();
+        world.do_all_iterations(50);
+        assert_eq!(world.evaluation_count(), 50 + 49 * 50);
+        assert!(world.best_solution_value() < initial_best);
+        assert!(world.distribution().1.norm_l2() < initial_spread);
+        // The best individual survives every generation
+        let mut population = Vec::new();
+        world.population(&mut population);
+        assert_eq!(population[0].coordinates, world.best_solution().coordinates);
+        // At least two individuals are always selected, so the deviation is fitted to more than a point
+        let parameters = world.parameters().clone();
+        assert_eq!(parameters.selected_count(), 25);
+        assert_eq!(Parameters { selection_ratio: 0.01, ..parameters.clone() }.selected_count(), 2);
+        assert_eq!(Parameters { population_size: 2, selection_ratio: 0.3, ..parameters }.selected_count(), 2);
+    }
+}
@@ -1,161 +1,542 @@
-use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use num_traits::{Float, FloatConst};
+use rand::{distributions::{uniform::SampleUniform, Distribution, Standard, Uniform}, Rng};
+use rand_distr::{Cauchy, StandardNormal};
+use std::{fmt::Display, ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign}};
 
 #[derive(Clone, Debug, Copy)]
-pub struct VectorN<const N: usize> {
-	pub coordinates: [f64; N],
+pub struct VectorN<T: Float, const N: usize> {
+	pub coordinates: [T; N],
 }
 
-impl<const N: usize> VectorN<N> {
-	pub fn new(coordinates: [f64; N]) -> Self {
+impl<T: Float, const N: usize> VectorN<T, N> {
+	pub fn new(coordinates: [T; N]) -> Self {
 		return Self {
 			coordinates
 		};
 	}
-	pub fn clamp(&mut self, bounds: (f64, f64)) {
+	pub fn clamp(&mut self, bounds: (T, T)) {
 		for a in &mut self.coordinates {
-			*a = a.clamp(bounds.0, bounds.1);
+			*a = num_traits::clamp(*a, bounds.0, bounds.1);
 		}
 	}
+	// Separate bounds for every dimension
+	pub fn clamp_per_dim(&mut self, lower_bounds: &[T; N], upper_bounds: &[T; N]) {
+		for index in 0..N {
+			self.coordinates[index] = num_traits::clamp(self.coordinates[index], lower_bounds[index], upper_bounds[index]);
+		}
+	}
+	// Mirrors coordinates at the bounds, as often as needed for ones far outside
+	pub fn reflect(&mut self, bounds: (T, T)) {
+		let width = bounds.1 - bounds.0;
+		for a in &mut self.coordinates {
+			let mut offset = (*a - bounds.0).abs() % (width + width);
+			if offset > width {
+				offset = width + width - offset;
+			}
+			*a = bounds.0 + offset;
+		}
+	}
+	// Toroidal, leaving through one bound enters through the other
+	pub fn wrap(&mut self, bounds: (T, T)) {
+		let width = bounds.1 - bounds.0;
+		for a in &mut self.coordinates {
+			let mut offset = (*a - bounds.0) % width;
+			if offset < T::zero() {
+				offset = offset + width;
+			}
+			*a = bounds.0 + offset;
+		}
+	}
+	// From the search box to [0, 1] in every dimension
+	pub fn normalize_to_unit(&self, bounds: (T, T)) -> Self {
+		return Self::new(self.coordinates.map(|a| (a - bounds.0) / (bounds.1 - bounds.0)));
+	}
+	// From [0, 1] in every dimension back to the search box
+	pub fn denormalize_from_unit(&self, bounds: (T, T)) -> Self {
+		return Self::new(self.coordinates.map(|a| bounds.0 + a * (bounds.1 - bounds.0)));
+	}
+	pub fn componentwise_min(&self, other: &Self) -> Self {
+		let mut result = *self;
+		for index in 0..N {
+			result.coordinates[index] = result.coordinates[index].min(other.coordinates[index]);
+		}
+		return result;
+	}
+	pub fn componentwise_max(&self, other: &Self) -> Self {
+		let mut result = *self;
+		for index in 0..N {
+			result.coordinates[index] = result.coordinates[index].max(other.coordinates[index]);
+		}
+		return result;
+	}
+	pub fn abs(&self) -> Self {
+		return Self::new(self.coordinates.map(|a| a.abs()));
+	}
+	// self at t = 0, other at t = 1, extrapolates outside of that
+	pub fn lerp(&self, other: &Self, t: T) -> Self {
+		return *self + (*other - *self) * t;
+	}
+	// Every coordinate within abs_tolerance or within rel_tolerance of the larger magnitude of the two
+	pub fn approx_eq(&self, other: &Self, abs_tolerance: T, rel_tolerance: T) -> bool {
+		return self.coordinates.iter().zip(&other.coordinates).all(|(&a, &b)| {
+			return (a - b).abs() <= abs_tolerance.max(rel_tolerance * a.abs().max(b.abs()));
+		});
+	}
+	pub fn norm_l1(&self) -> T {
+		return self.coordinates.map(|a| a.abs()).sum();
+	}
+	pub fn norm_l2(&self) -> T {
+		return self.coordinates.magnitude();
+	}
+	pub fn norm_linf(&self) -> T {
+		return self.coordinates.iter().fold(T::zero(), |result, a| result.max(a.abs()));
+	}
+	// Euclidean
+	pub fn distance(&self, other: &Self) -> T {
+		return (*self - *other).norm_l2();
+	}
+	pub fn dot(&self, other: &Self) -> T {
+		return (*self * *other).coordinates.sum();
+	}
+	pub fn as_slice(&self) -> &[T] {
+		return &self.coordinates;
+	}
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		return &mut self.coordinates;
+	}
+	pub fn iter(&self) -> std::slice::Iter<'_, T> {
+		return self.coordinates.iter();
+	}
+	pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+		return self.coordinates.iter_mut();
+	}
 }
 
-impl<const N: usize> Add<f64> for VectorN<N> {
-	type Output = VectorN<N>;
-	fn add(self, rhs: f64) -> Self::Output {
-		return VectorN::<N> {
+impl<T: Float, const N: usize> VectorN<T, N> {
+	// Every coordinate drawn independently
+	pub fn random_from<D: Distribution<T>, RngType: Rng>(distribution: &D, random_source: &mut RngType) -> Self {
+		let mut coordinates = [T::zero(); N];
+		coordinates.fill_with(|| distribution.sample(random_source));
+		return Self::new(coordinates);
+	}
+	pub fn random_uniform<RngType: Rng>(bounds: (T, T), random_source: &mut RngType) -> Self where T: SampleUniform {
+		return Self::random_from(&Uniform::from(bounds.0..bounds.1), random_source);
+	}
+	pub fn random_gaussian<RngType: Rng>(mean: Self, sigma: T, random_source: &mut RngType) -> Self where StandardNormal: Distribution<T> {
+		return mean + Self::random_from(&StandardNormal, random_source) * sigma;
+	}
+	// Uniformly distributed on the surface of the unit sphere
+	pub fn random_on_sphere<RngType: Rng>(random_source: &mut RngType) -> Self where StandardNormal: Distribution<T> {
+		loop {
+			let direction = Self::random_from(&StandardNormal, random_source);
+			let norm = direction.norm_l2();
+			if norm > T::zero() {
+				return direction / norm;
+			}
+		}
+	}
+}
+
+// Lanczos approximation with g = 7, only valid from 0.5 up, which is all Mantegna's algorithm needs
+fn gamma(x: f64) -> f64 {
+	const COEFFICIENTS: [f64; 9] = [
+		0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8, 771.323_428_777_653_1, -176.615_029_162_140_6,
+		12.507_343_278_686_905, -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6, 1.505_632_735_149_311_6e-7,
+	];
+	let x = x - 1.0;
+	let t = x + 7.5;
+	let mut series = COEFFICIENTS[0];
+	for (index, coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+		series += coefficient / (x + index as f64);
+	}
+	return (2.0 * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * series;
+}
+
+// Heavy-tailed steps, e.g. for cuckoo search or mutations that occasionally jump far
+impl<T: Float, const N: usize> VectorN<T, N> {
+	// Lévy flight step with stability index `beta` in (0, 2], using Mantegna's algorithm
+	pub fn random_levy<RngType: Rng>(beta: T, scale: T, random_source: &mut RngType) -> Self where StandardNormal: Distribution<T> {
+		if beta <= T::zero() || beta > T::from(2.0).unwrap() {
+			panic!("The Lévy stability index must be in (0, 2], got {}", beta.to_f64().unwrap());
+		}
+		let beta_f64 = beta.to_f64().unwrap();
+		let sigma = (gamma(1.0 + beta_f64) * (std::f64::consts::PI * beta_f64 / 2.0).sin()
+			/ (gamma((1.0 + beta_f64) / 2.0) * beta_f64 * 2.0.powf((beta_f64 - 1.0) / 2.0))).powf(1.0 / beta_f64);
+		let sigma = T::from(sigma).unwrap();
+		let mut coordinates = [T::zero(); N];
+		coordinates.fill_with(|| {
+			let u: T = random_source.sample(StandardNormal);
+			let v: T = random_source.sample(StandardNormal);
+			return scale * u * sigma / v.abs().powf(beta.recip());
+		});
+		return Self::new(coordinates);
+	}
+	pub fn random_cauchy<RngType: Rng>(scale: T, random_source: &mut RngType) -> Self where T: FloatConst, Standard: Distribution<T> {
+		return Self::random_from(&Cauchy::new(T::zero(), scale).unwrap(), random_source);
+	}
+}
+
+impl<T: Float, const N: usize> From<[T; N]> for VectorN<T, N> {
+	fn from(coordinates: [T; N]) -> Self {
+		return Self::new(coordinates);
+	}
+}
+
+impl<T: Float, const N: usize> From<VectorN<T, N>> for [T; N] {
+	fn from(vector: VectorN<T, N>) -> Self {
+		return vector.coordinates;
+	}
+}
+
+// Fails if the slice is not exactly N long
+impl<T: Float, const N: usize> TryFrom<&[T]> for VectorN<T, N> {
+	type Error = std::array::TryFromSliceError;
+	fn try_from(slice: &[T]) -> Result<Self, Self::Error> {
+		return Ok(Self::new(slice.try_into()?));
+	}
+}
+
+#[cfg(feature = "ndarray")]
+impl<T: Float, const N: usize> From<VectorN<T, N>> for ndarray::Array1<T> {
+	fn from(vector: VectorN<T, N>) -> Self {
+		return ndarray::Array1::from(vector.coordinates.to_vec());
+	}
+}
+
+#[cfg(feature = "ndarray")]
+impl<T: Float, const N: usize> TryFrom<ndarray::ArrayView1<'_, T>> for VectorN<T, N> {
+	type Error = std::array::TryFromSliceError;
+	fn try_from(array: ndarray::ArrayView1<'_, T>) -> Result<Self, Self::Error> {
+		return Self::try_from(array.to_vec().as_slice());
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl<T: Float + nalgebra::Scalar, const N: usize> From<VectorN<T, N>> for nalgebra::SVector<T, N> {
+	fn from(vector: VectorN<T, N>) -> Self {
+		return nalgebra::SVector::from(vector.coordinates);
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl<T: Float + nalgebra::Scalar, const N: usize> From<nalgebra::SVector<T, N>> for VectorN<T, N> {
+	fn from(vector: nalgebra::SVector<T, N>) -> Self {
+		return Self::new(vector.into());
+	}
+}
+
+impl<T: Float, const N: usize> Index<usize> for VectorN<T, N> {
+	type Output = T;
+	fn index(&self, index: usize) -> &Self::Output {
+		return &self.coordinates[index];
+	}
+}
+
+impl<T: Float, const N: usize> IndexMut<usize> for VectorN<T, N> {
+	fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+		return &mut self.coordinates[index];
+	}
+}
+
+impl<T: Float, const N: usize> IntoIterator for VectorN<T, N> {
+	type Item = T;
+	type IntoIter = std::array::IntoIter<T, N>;
+	fn into_iter(self) -> Self::IntoIter {
+		return self.coordinates.into_iter();
+	}
+}
+
+impl<'a, T: Float, const N: usize> IntoIterator for &'a VectorN<T, N> {
+	type Item = &'a T;
+	type IntoIter = std::slice::Iter<'a, T>;
+	fn into_iter(self) -> Self::IntoIter {
+		return self.coordinates.iter();
+	}
+}
+
+impl<'a, T: Float, const N: usize> IntoIterator for &'a mut VectorN<T, N> {
+	type Item = &'a mut T;
+	type IntoIter = std::slice::IterMut<'a, T>;
+	fn into_iter(self) -> Self::IntoIter {
+		return self.coordinates.iter_mut();
+	}
+}
+
+// `[1.25, -0.33]`, the precision applies to every coordinate, e.g. `{:.2}`
+impl<T: Float + Display, const N: usize> Display for VectorN<T, N> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "[")?;
+		for (index, a) in self.coordinates.iter().enumerate() {
+			if index > 0 {
+				write!(f, ", ")?;
+			}
+			match f.precision() {
+				Some(precision) => write!(f, "{:.*}", precision, a)?,
+				None => write!(f, "{}", a)?,
+			}
+		}
+		return write!(f, "]");
+	}
+}
+
+impl<T: Float, const N: usize> Add<T> for VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn add(self, rhs: T) -> Self::Output {
+		return Self {
 			coordinates: self.coordinates.map(|a| a + rhs),
 		};
 	}
 }
 
-impl<const N: usize> Mul<f64> for VectorN<N> {
-	type Output = VectorN<N>;
-	fn mul(self, rhs: f64) -> Self::Output {
-		return VectorN::<N> {
+impl<T: Float, const N: usize> Mul<T> for VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn mul(self, rhs: T) -> Self::Output {
+		return Self {
 			coordinates: self.coordinates.map(|a| a * rhs),
 		};
 	}
 }
 
-impl<const N: usize> Div<f64> for VectorN<N> {
-	type Output = VectorN<N>;
-	fn div(self, rhs: f64) -> Self::Output {
-		return VectorN::<N> {
+impl<T: Float, const N: usize> Div<T> for VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn div(self, rhs: T) -> Self::Output {
+		return Self {
 			coordinates: self.coordinates.map(|a| a / rhs),
 		};
 	}
 }
 
-impl<const N: usize> Mul for VectorN<N> {
-	type Output = VectorN<N>;
+impl<T: Float, const N: usize> Mul for VectorN<T, N> {
+	type Output = VectorN<T, N>;
 	fn mul(mut self, rhs: Self) -> Self::Output {
 		for index in 0..N {
-			self.coordinates[index] *= rhs.coordinates[index];
+			self.coordinates[index] = self.coordinates[index] * rhs.coordinates[index];
 		}
 		return self;
 	}
 }
 
-impl<const N: usize> Sub for VectorN<N> {
-	type Output = VectorN<N>;
+impl<T: Float, const N: usize> MulAssign for VectorN<T, N> {
+	fn mul_assign(&mut self, rhs: Self) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] * rhs.coordinates[index];
+		}
+	}
+}
+
+impl<T: Float, const N: usize> Div for VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn div(mut self, rhs: Self) -> Self::Output {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] / rhs.coordinates[index];
+		}
+		return self;
+	}
+}
+
+impl<T: Float, const N: usize> DivAssign for VectorN<T, N> {
+	fn div_assign(&mut self, rhs: Self) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] / rhs.coordinates[index];
+		}
+	}
+}
+
+impl<T: Float, const N: usize> Sub for VectorN<T, N> {
+	type Output = VectorN<T, N>;
 
 	fn sub(mut self, rhs: Self) -> Self::Output {
 		for index in 0..N {
-			self.coordinates[index] -= rhs.coordinates[index];
+			self.coordinates[index] = self.coordinates[index] - rhs.coordinates[index];
 		}
 		return self;
 	}
 }
 
-impl<const N: usize> SubAssign for VectorN<N> {
+impl<T: Float, const N: usize> SubAssign for VectorN<T, N> {
 	fn sub_assign(&mut self, rhs: Self) {
 		for index in 0..N {
-			self.coordinates[index] -= rhs.coordinates[index];
+			self.coordinates[index] = self.coordinates[index] - rhs.coordinates[index];
 		}
 	}
 }
 
-impl<const N: usize> Add for VectorN<N> {
-	type Output = VectorN<N>;
+impl<T: Float, const N: usize> Add for VectorN<T, N> {
+	type Output = VectorN<T, N>;
 
 	fn add(mut self, rhs: Self) -> Self::Output {
 		for index in 0..N {
-			self.coordinates[index] += rhs.coordinates[index];
+			self.coordinates[index] = self.coordinates[index] + rhs.coordinates[index];
 		}
 		return self;
 	}
 }
 
-impl<const N: usize> AddAssign for VectorN<N> {
+impl<T: Float, const N: usize> AddAssign for VectorN<T, N> {
 	fn add_assign(&mut self, rhs: Self) {
 		for index in 0..N {
-			self.coordinates[index] += rhs.coordinates[index];
+			self.coordinates[index] = self.coordinates[index] + rhs.coordinates[index];
+		}
+	}
+}
+
+impl<T: Float, const N: usize> AddAssign<T> for VectorN<T, N> {
+	fn add_assign(&mut self, rhs: T) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] + rhs;
+		}
+	}
+}
+
+impl<T: Float, const N: usize> SubAssign<T> for VectorN<T, N> {
+	fn sub_assign(&mut self, rhs: T) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] - rhs;
+		}
+	}
+}
+
+impl<T: Float, const N: usize> Sub<T> for VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn sub(mut self, rhs: T) -> Self::Output {
+		self -= rhs;
+		return self;
+	}
+}
+
+impl<T: Float, const N: usize> MulAssign<T> for VectorN<T, N> {
+	fn mul_assign(&mut self, rhs: T) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] * rhs;
+		}
+	}
+}
+
+impl<T: Float, const N: usize> DivAssign<T> for VectorN<T, N> {
+	fn div_assign(&mut self, rhs: T) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] / rhs;
+		}
+	}
+}
+
+impl<T: Float, const N: usize> Neg for VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn neg(self) -> Self::Output {
+		return Self {
+			coordinates: self.coordinates.map(|a| -a),
+		};
+	}
+}
+
+// Operators on references, so large vectors don't have to be copied to be combined
+impl<T: Float, const N: usize> Add for &VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn add(self, rhs: Self) -> Self::Output {
+		let mut result = *self;
+		result += rhs;
+		return result;
+	}
+}
+
+impl<T: Float, const N: usize> Sub for &VectorN<T, N> {
+	type Output = VectorN<T, N>;
+	fn sub(self, rhs: Self) -> Self::Output {
+		let mut result = *self;
+		result -= rhs;
+		return result;
+	}
+}
+
+impl<T: Float, const N: usize> AddAssign<&VectorN<T, N>> for VectorN<T, N> {
+	fn add_assign(&mut self, rhs: &Self) {
+		for index in 0..N {
+			self.coordinates[index] = self.coordinates[index] + rhs.coordinates[index];
 		}
 	}
 }
 
-impl <const N: usize> AddAssign<f64> for VectorN<N> {
-	fn add_assign(&mut self, rhs: f64) {
+impl<T: Float, const N: usize> SubAssign<&VectorN<T, N>> for VectorN<T, N> {
+	fn sub_assign(&mut self, rhs: &Self) {
 		for index in 0..N {
-			self.coordinates[index] += rhs;
+			self.coordinates[index] = self.coordinates[index] - rhs.coordinates[index];
 		}
 	}
 }
 
-impl<const N: usize> Default for VectorN<N> {
+impl<T: Float, const N: usize> Default for VectorN<T, N> {
 	fn default() -> Self {
 		return Self {
-			coordinates: [0.0; N],
+			coordinates: [T::zero(); N],
 		};
 	}
 }
 
-pub trait QuickFold {
-	fn sum(&self) -> f64;
-	fn product(&self) -> f64;
-	fn magnitude(&self) -> f64;
+pub trait QuickFold<T> {
+	fn sum(&self) -> T;
+	fn product(&self) -> T;
+	fn magnitude(&self) -> T;
+	// The sum of map applied to every entry, in the same pass
+	fn map_sum(&self, map: impl Fn(T) -> T) -> T;
 }
 
-impl<const N: usize> QuickFold for [f64; N] {
-	fn sum(&self) -> f64 {
-		let mut result = 0.0;
+impl<T: Float> QuickFold<T> for [T] {
+	fn sum(&self) -> T {
+		return self.map_sum(|a| a);
+	}
+	fn product(&self) -> T {
+		let mut result = T::one();
 		for entry in self {
-			result += entry;
+			result = result * *entry;
 		}
 		return result;
 	}
-	fn product(&self) -> f64 {
-		let mut result = 1.0;
+	fn magnitude(&self) -> T {
+		return self.map_sum(|a| a.powi(2)).sqrt();
+	}
+	fn map_sum(&self, map: impl Fn(T) -> T) -> T {
+		let mut result = T::zero();
 		for entry in self {
-			result *= entry;
+			result = result + map(*entry);
 		}
 		return result;
 	}
-	fn magnitude(&self) -> f64 {
-		let mut result = 0.0;
-		for entry in self {
-			result += entry.powi(2);
-		}
-		return result.sqrt();
+}
+
+impl<T: Float, const N: usize> QuickFold<T> for [T; N] {
+	fn sum(&self) -> T {
+		return self.as_slice().sum();
+	}
+	fn product(&self) -> T {
+		return self.as_slice().product();
+	}
+	fn magnitude(&self) -> T {
+		return self.as_slice().magnitude();
+	}
+	fn map_sum(&self, map: impl Fn(T) -> T) -> T {
+		return self.as_slice().map_sum(map);
 	}
 }
 
 #[cfg(test)]
 mod test {
-    use crate::vector::{QuickFold, VectorN};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::vector::{gamma, QuickFold, VectorN};
 
 	#[test]
 	fn add_test() {
-		let a = VectorN::<_> {
+		let a = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
-		let b = VectorN::<_> {
+		let b = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
 		let vecs_added = a + b;
 		let f64_added = a + 1.0;
-		let mut assign_added = a.clone();
+		let mut assign_added = a;
 		assign_added += b;
-		let mut assign_added_f64 = a.clone();
+		let mut assign_added_f64 = a;
 		assign_added_f64 += 2.0;
 
 		assert_eq!(vecs_added.coordinates, [2.0, 4.0, 6.0]);
@@ -166,54 +547,321 @@ mod test {
 
 	#[test]
 	fn sub_test() {
-		let a = VectorN::<_> {
+		let a = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
-		let b = VectorN::<_> {
+		let b = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
 		let vecs_subbed = a - b;
-		let mut subbed_assign = a.clone();
+		let mut subbed_assign = a;
 		subbed_assign -= b;
 		assert_eq!(vecs_subbed.coordinates, [0.0, 0.0, 0.0]);
 		assert_eq!(subbed_assign.coordinates, [0.0, 0.0, 0.0]);
 	}
 
+	#[test]
+	fn scalar_assign_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.0, 2.0, 3.0]
+		};
+		let mut subbed = a;
+		subbed -= 1.0;
+		let mut mulled = a;
+		mulled *= 2.0;
+		let mut divided = a;
+		divided /= 2.0;
+
+		assert_eq!((a - 1.0).coordinates, [0.0, 1.0, 2.0]);
+		assert_eq!(subbed.coordinates, [0.0, 1.0, 2.0]);
+		assert_eq!(mulled.coordinates, [2.0, 4.0, 6.0]);
+		assert_eq!(divided.coordinates, [0.5, 1.0, 1.5]);
+		assert_eq!((-a).coordinates, [-1.0, -2.0, -3.0]);
+	}
+
+	#[test]
+	#[allow(clippy::op_ref)] // Copying would be fine here, the reference operators are what is tested
+	fn reference_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.0, 2.0, 3.0]
+		};
+		let b = VectorN::<_, _> {
+			coordinates: [3.0, 2.0, 1.0]
+		};
+		let mut added = a;
+		added += &b;
+		let mut subbed = a;
+		subbed -= &b;
+
+		assert_eq!((&a + &b).coordinates, [4.0, 4.0, 4.0]);
+		assert_eq!((&a - &b).coordinates, [-2.0, 0.0, 2.0]);
+		assert_eq!(added.coordinates, [4.0, 4.0, 4.0]);
+		assert_eq!(subbed.coordinates, [-2.0, 0.0, 2.0]);
+	}
+
 	#[test]
 	fn mul_test() {
-		let a = VectorN::<_> {
+		let a = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
-		let b = VectorN::<_> {
+		let b = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
 		let vecs_mulled = a * b;
 		let f64_mulled = a * 2.0;
+		let mut assign_mulled = a;
+		assign_mulled *= b;
 
 		assert_eq!(vecs_mulled.coordinates, [1.0, 4.0, 9.0]);
 		assert_eq!(f64_mulled.coordinates, [2.0, 4.0, 6.0]);
+		assert_eq!(assign_mulled.coordinates, [1.0, 4.0, 9.0]);
 	}
 
 	#[test]
 	fn div_test() {
-		let a = VectorN::<_> {
+		let a = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
 
+		let b = VectorN::<_, _> {
+			coordinates: [2.0, 4.0, 1.0]
+		};
+
 		let divided = a / 2.0;
+		let vecs_divided = a / b;
+		let mut assign_divided = a;
+		assign_divided /= b;
 
 		assert_eq!(divided.coordinates, [0.5, 1.0, 1.5]);
+		assert_eq!(vecs_divided.coordinates, [0.5, 0.5, 3.0]);
+		assert_eq!(assign_divided.coordinates, [0.5, 0.5, 3.0]);
+	}
+
+	#[test]
+	fn dot_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.0, 2.0, 3.0]
+		};
+		let b = VectorN::<_, _> {
+			coordinates: [4.0, -5.0, 6.0]
+		};
+
+		assert_eq!(a.dot(&b), 12.0);
 	}
 
 	#[test]
 	fn clamp_test() {
-		let mut a = VectorN::<_> {
+		let mut a = VectorN::<_, _> {
 			coordinates: [1.0, 2.0, 3.0]
 		};
 		a.clamp((1.5, 2.5));
 
 		assert_eq!(a.coordinates, [1.5, 2.0, 2.5]);
 	}
+
+	#[test]
+	fn clamp_per_dim_test() {
+		let mut a = VectorN::<_, _> {
+			coordinates: [1.0, 2.0, 3.0]
+		};
+		a.clamp_per_dim(&[1.5, 0.0, -1.0], &[2.0, 1.0, 4.0]);
+
+		assert_eq!(a.coordinates, [1.5, 1.0, 3.0]);
+	}
+	#[test]
+	fn reflect_test() {
+		let mut a = VectorN::<_, _> {
+			coordinates: [1.0, 2.5, -0.5, 4.5, 2.0]
+		};
+		a.reflect((0.0, 2.0));
+
+		assert_eq!(a.coordinates, [1.0, 1.5, 0.5, 0.5, 2.0]);
+	}
+
+	#[test]
+	fn wrap_test() {
+		let mut a = VectorN::<_, _> {
+			coordinates: [1.0, 2.5, -0.5, 4.5, -4.5]
+		};
+		a.wrap((0.0, 2.0));
+
+		assert_eq!(a.coordinates, [1.0, 0.5, 1.5, 0.5, 1.5]);
+	}
+
+	#[test]
+	fn unit_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [-5.0, 0.0, 2.5, 5.0]
+		};
+		let normalized = a.normalize_to_unit((-5.0, 5.0));
+
+		assert_eq!(normalized.coordinates, [0.0, 0.5, 0.75, 1.0]);
+		assert_eq!(normalized.denormalize_from_unit((-5.0, 5.0)).coordinates, a.coordinates);
+	}
+
+	#[test]
+	fn min_max_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.0, 5.0, -3.0]
+		};
+		let b = VectorN::<_, _> {
+			coordinates: [2.0, 4.0, -3.0]
+		};
+
+		assert_eq!(a.componentwise_min(&b).coordinates, [1.0, 4.0, -3.0]);
+		assert_eq!(a.componentwise_max(&b).coordinates, [2.0, 5.0, -3.0]);
+	}
+
+	#[test]
+	fn lerp_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [0.0, 2.0]
+		};
+		let b = VectorN::<_, _> {
+			coordinates: [4.0, -2.0]
+		};
+
+		assert_eq!(a.lerp(&b, 0.0).coordinates, a.coordinates);
+		assert_eq!(a.lerp(&b, 0.25).coordinates, [1.0, 1.0]);
+		assert_eq!(a.lerp(&b, 1.0).coordinates, b.coordinates);
+	}
+
+	#[test]
+	fn approx_eq_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.0, 1000.0]
+		};
+		let b = VectorN::<_, _> {
+			coordinates: [1.0 + 1e-10, 1000.0 + 1e-7]
+		};
+		let c = VectorN::<_, _> {
+			coordinates: [1.1, 1000.0]
+		};
+
+		assert!(a.approx_eq(&b, 1e-6, 0.0));
+		assert!(a.approx_eq(&b, 0.0, 1e-9));
+		assert!(!a.approx_eq(&b, 1e-12, 1e-12));
+		assert!(!a.approx_eq(&c, 1e-9, 1e-9));
+	}
+
+	#[test]
+	fn norm_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [3.0, -4.0, 0.0]
+		};
+
+		assert_eq!(a.norm_l1(), 7.0);
+		assert_eq!(a.norm_l2(), 5.0);
+		assert_eq!(a.norm_linf(), 4.0);
+	}
+
+	#[test]
+	fn distance_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.0, 2.0, 3.0]
+		};
+		let b = VectorN::<_, _> {
+			coordinates: [4.0, 6.0, 3.0]
+		};
+
+		assert_eq!(a.distance(&b), 5.0);
+		assert_eq!(b.distance(&a), 5.0);
+	}
+
+	#[test]
+	fn index_test() {
+		let mut a = VectorN::<_, _> {
+			coordinates: [1.0, 2.0, 3.0]
+		};
+		a[1] = 5.0;
+		for coordinate in &mut a {
+			*coordinate += 1.0;
+		}
+
+		assert_eq!(a[0], 2.0);
+		assert_eq!(a.iter().copied().collect::<Vec<_>>(), [2.0, 6.0, 4.0]);
+		assert_eq!(a.into_iter().sum::<f64>(), 12.0);
+	}
+
+	#[test]
+	fn conversion_test() {
+		let a = VectorN::from([1.0, 2.0, 3.0]);
+		let array: [f64; 3] = a.into();
+
+		assert_eq!(a.as_slice(), &[1.0, 2.0, 3.0]);
+		assert_eq!(array, [1.0, 2.0, 3.0]);
+		assert_eq!(VectorN::<f64, 3>::try_from(&[1.0, 2.0, 3.0][..]).unwrap().coordinates, [1.0, 2.0, 3.0]);
+		assert!(VectorN::<f64, 3>::try_from(&[1.0, 2.0][..]).is_err());
+	}
+
+	#[cfg(feature = "ndarray")]
+	#[test]
+	fn ndarray_test() {
+		let a = VectorN::from([1.0, 2.0, 3.0]);
+		let array = ndarray::Array1::from(a);
+
+		assert_eq!(VectorN::<f64, 3>::try_from(array.view()).unwrap().coordinates, a.coordinates);
+	}
+
+	#[cfg(feature = "nalgebra")]
+	#[test]
+	fn nalgebra_test() {
+		let a = VectorN::from([1.0, 2.0, 3.0]);
+		let vector = nalgebra::SVector::<f64, 3>::from(a);
+
+		assert_eq!(vector.dot(&vector), a.dot(&a));
+		assert_eq!(VectorN::from(vector).coordinates, a.coordinates);
+	}
+
+	#[test]
+	fn display_test() {
+		let a = VectorN::<_, _> {
+			coordinates: [1.25, -0.333, 3.0]
+		};
+
+		assert_eq!(format!("{}", a), "[1.25, -0.333, 3]");
+		assert_eq!(format!("{:.2}", a), "[1.25, -0.33, 3.00]");
+	}
+
+	#[test]
+	fn f32_test() {
+		let a = VectorN::<f32, _>::new([3.0, 4.0]);
+		let b = VectorN::<f32, _>::new([1.0, 1.0]);
+
+		assert_eq!((a + b).coordinates, [4.0f32, 5.0]);
+		assert_eq!(a.norm_l2(), 5.0f32);
+	}
+
+	#[test]
+	fn random_test() {
+		let mut random_source = StdRng::seed_from_u64(0);
+		let uniform = VectorN::<f64, 100>::random_uniform((-2.0, 3.0), &mut random_source);
+		let gaussian = VectorN::<f64, 1000>::random_gaussian(VectorN::default() + 5.0, 0.5, &mut random_source);
+		let on_sphere = VectorN::<f64, 5>::random_on_sphere(&mut random_source);
+
+		assert!(uniform.iter().all(|a| (-2.0..3.0).contains(a)));
+		assert!((gaussian.coordinates.sum() / 1000.0 - 5.0).abs() < 0.1);
+		assert!((on_sphere.norm_l2() - 1.0).abs() < 1e-12);
+	}
+
+	#[test]
+	fn gamma_test() {
+		assert!((gamma(5.0) - 24.0).abs() < 1e-9);
+		assert!((gamma(0.5) - std::f64::consts::PI.sqrt()).abs() < 1e-9);
+	}
+
+	#[test]
+	fn heavy_tailed_test() {
+		let levy = VectorN::<f64, 50>::random_levy(1.5, 1.0, &mut StdRng::seed_from_u64(0));
+		let scaled_levy = VectorN::<f64, 50>::random_levy(1.5, 2.0, &mut StdRng::seed_from_u64(0));
+		let cauchy = VectorN::<f64, 50>::random_cauchy(1.0, &mut StdRng::seed_from_u64(0));
+		let scaled_cauchy = VectorN::<f64, 50>::random_cauchy(2.0, &mut StdRng::seed_from_u64(0));
+
+		assert!(levy.iter().all(|a| a.is_finite()));
+		assert_eq!((levy * 2.0).coordinates, scaled_levy.coordinates);
+		assert!((cauchy * 2.0).approx_eq(&scaled_cauchy, 0.0, 1e-12));
+	}
+
 	#[test]
 	fn sum_test() {
 		let a = [1.0, 2.0, 3.0];
@@ -225,4 +873,10 @@ mod test {
 		let a = [2.0, 3.0, 4.0];
 		assert_eq!(24.0, a.product());
 	}
+
+	#[test]
+	fn map_sum_test() {
+		let a = [1.0, -2.0, 3.0, 0.5, -1.0, 2.0, 4.0, -3.0, 1.5, 2.5];
+		assert_eq!(a.map(|a: f64| a.abs()).sum(), a.map_sum(f64::abs));
+	}
 }
\ No newline at end of file
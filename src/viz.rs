@@ -0,0 +1,183 @@
+// Plots for notebooks, built with `--features viz`. Everything is drawn to an SVG in memory, which evcxr shows inline:
+//
+//     :dep swarm_optimizers = { path = "...", features = ["viz"] }
+//     let trace = viz::trace(&mut world, RunLength::Iterations(200));
+//     viz::convergence_svg("Ackley", &[("bats", &trace)]).evcxr_display();
+//     viz::swarm_svg("After 200 iterations", &world, (-5.0, 5.0)).evcxr_display();
+//
+// Swarms of more than two dimensions are shown in their first two coordinates
+
+use std::fmt;
+
+use plotters::prelude::*;
+
+use crate::{functions::Function, optimizer::{Optimizer, RunLength}, real::{self, Real}, vector::VectorN};
+
+const SIZE: (u32, u32) = (640, 480);
+const LANDSCAPE_RESOLUTION: usize = 80; // Cells along each axis
+
+pub struct Svg(pub String);
+
+impl Svg {
+    // Shown inline by evcxr when called as the last expression of a cell
+    pub fn evcxr_display(&self) {
+        println!("EVCXR_BEGIN_CONTENT image/svg+xml\n{}\nEVCXR_END_CONTENT", self.0);
+    }
+}
+
+impl fmt::Display for Svg {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return formatter.write_str(&self.0);
+    }
+}
+
+// Runs the world, returning the best value before the first iteration and after every one
+pub fn trace<const N: usize, World: Optimizer<N>>(world: &mut World, length: RunLength) -> Vec<f64> {
+    let mut values = vec![real::to_f64(world.best_solution_value())];
+    world.run_observed(length, None, |world| values.push(real::to_f64(world.best_solution_value())));
+    return values;
+}
+
+// Best value by iteration, one line per named trace. The value axis is logarithmic if every value is positive
+pub fn convergence_svg(title: &str, traces: &[(&str, &[f64])]) -> Svg {
+    let logarithmic = traces.iter().all(|(_, values)| values.iter().all(|&value| value > 0.0));
+    let scaled = |value: f64| if logarithmic { value.log10() } else { value };
+    let finite_values = || traces.iter().flat_map(|(_, values)| values.iter().map(|&value| scaled(value))).filter(|value| value.is_finite());
+    let min = finite_values().fold(f64::INFINITY, f64::min);
+    let max = finite_values().fold(f64::NEG_INFINITY, f64::max);
+    if min > max {
+        panic!("Nothing to plot - every trace is empty or infinite");
+    }
+    let margin = ((max - min) * 0.05).max(f64::EPSILON);
+    let iterations = traces.iter().map(|(_, values)| values.len()).max().unwrap_or(0).max(2);
+
+    let mut svg = String::new();
+    {
+        let area = SVGBackend::with_string(&mut svg, SIZE).into_drawing_area();
+        area.fill(&WHITE).unwrap();
+        let mut chart = ChartBuilder::on(&area)
+            .caption(title, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(70)
+            .build_cartesian_2d(0..iterations - 1, min - margin..max + margin)
+            .unwrap();
+        let value_label = |value: &f64| if logarithmic { format!("{:.0e}", 10f64.powf(*value)) } else { format!("{:.3}", value) };
+        chart.configure_mesh()
+            .x_desc("Iteration")
+            .y_desc("Best value")
+            .y_label_formatter(&value_label)
+            .light_line_style(WHITE)
+            .draw()
+            .unwrap();
+        for (index, (name, values)) in traces.iter().enumerate() {
+            let color = Palette99::pick(index).to_rgba();
+            let points = values.iter().enumerate().map(|(iteration, &value)| (iteration, scaled(value))).filter(|(_, value)| value.is_finite());
+            chart.draw_series(LineSeries::new(points, color.stroke_width(2)))
+                .unwrap()
+                .label(*name)
+                .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2)));
+        }
+        chart.configure_series_labels().background_style(WHITE.mix(0.8)).border_style(BLACK).draw().unwrap();
+        area.present().unwrap();
+    }
+    return Svg(svg);
+}
+
+// The agents in their first two coordinates, with the best solution found so far as a red cross
+pub fn swarm_svg<const N: usize>(title: &str, world: &impl Optimizer<N>, bounds: (Real, Real)) -> Svg {
+    return draw_swarm(title, world, bounds, None::<&dyn Fn(f64, f64) -> f64>);
+}
+
+// Like swarm_svg, over the objective shaded from dark (low) to bright (high)
+pub fn swarm_on_function_svg(title: &str, world: &impl Optimizer<2>, function: &impl Function<2>, bounds: (Real, Real)) -> Svg {
+    let landscape = |x: f64, y: f64| real::to_f64(function.evaluate(VectorN::new([real::from_f64(x), real::from_f64(y)])));
+    return draw_swarm(title, world, bounds, Some(&landscape));
+}
+
+fn draw_swarm<const N: usize>(title: &str, world: &impl Optimizer<N>, bounds: (Real, Real), landscape: Option<&dyn Fn(f64, f64) -> f64>) -> Svg {
+    if N < 2 {
+        panic!("Swarm plots need at least two dimensions");
+    }
+    let (lower, upper) = (real::to_f64(bounds.0), real::to_f64(bounds.1));
+    if lower >= upper {
+        panic!("Lower bound must be lower than upper bound");
+    }
+    let mut positions = Vec::new();
+    world.population(&mut positions);
+    let point = |position: &VectorN<Real, N>| (real::to_f64(position.coordinates[0]), real::to_f64(position.coordinates[1]));
+
+    let mut svg = String::new();
+    {
+        let area = SVGBackend::with_string(&mut svg, SIZE).into_drawing_area();
+        area.fill(&WHITE).unwrap();
+        let mut chart = ChartBuilder::on(&area)
+            .caption(title, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(lower..upper, lower..upper)
+            .unwrap();
+        chart.configure_mesh().x_desc("x0").y_desc("x1").light_line_style(WHITE).draw().unwrap();
+        if let Some(landscape) = landscape {
+            let step = (upper - lower) / LANDSCAPE_RESOLUTION as f64;
+            let mut cells = Vec::with_capacity(LANDSCAPE_RESOLUTION * LANDSCAPE_RESOLUTION);
+            for column in 0..LANDSCAPE_RESOLUTION {
+                for row in 0..LANDSCAPE_RESOLUTION {
+                    let (x, y) = (lower + column as f64 * step, lower + row as f64 * step);
+                    cells.push((x, y, landscape(x + step / 2.0, y + step / 2.0)));
+                }
+            }
+            let min = cells.iter().map(|cell| cell.2).filter(|value| value.is_finite()).fold(f64::INFINITY, f64::min);
+            let max = cells.iter().map(|cell| cell.2).filter(|value| value.is_finite()).fold(f64::NEG_INFINITY, f64::max);
+            chart.draw_series(cells.iter().map(|&(x, y, value)| {
+                let color = ViridisRGB::get_color_normalized(value.clamp(min, max) as f32, min as f32, max.max(min + f64::EPSILON) as f32);
+                return Rectangle::new([(x, y), (x + step, y + step)], color.filled());
+            })).unwrap();
+        }
+        chart.draw_series(positions.iter().map(|position| Circle::new(point(position), 3, BLACK.filled()))).unwrap();
+        chart.draw_series([Cross::new(point(&world.best_solution()), 6, RED.stroke_width(2))]).unwrap();
+        area.present().unwrap();
+    }
+    return Svg(svg);
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{bats, functions::Functions, optimizer::{FromParameters, InitialVelocity, Movement, RunLength}};
+
+    use super::{convergence_svg, swarm_on_function_svg, swarm_svg, trace};
+
+    fn bat_world<const N: usize>() -> bats::WorldState<N, rand_xoshiro::Xoshiro256PlusPlus, Functions<N>> {
+        let parameters = bats::Parameters {
+            bat_count: 10,
+            function: Functions::Ackley,
+            bounds: (-5.0, 5.0),
+            frequency_bounds: (0.0, 2.0),
+            initial_pulse_rate: 0.5,
+            pulse_rate_factor: 0.9,
+            initial_loudness: 1.0,
+            loudness_cool_factor: 0.9,
+            movement: Movement::Negated,
+            initial_velocity: InitialVelocity::PositiveUnit,
+            improvement_tolerance: 0.0,
+            scale_walk_to_bounds: false,
+            parallel: false,
+        };
+        return bats::WorldState::from_parameters(Arc::new(parameters), 1);
+    }
+
+    #[test]
+    fn plots_test() {
+        let mut world = bat_world::<3>();
+        let values = trace(&mut world, RunLength::Iterations(20));
+        assert_eq!(values.len(), 21);
+        let convergence = convergence_svg("Ackley", &[("bats", &values), ("shifted", &[-1.0, 2.0])]);
+        assert!(convergence.0.starts_with("<svg") && convergence.0.contains("bats"));
+        assert_eq!(swarm_svg("Ackley", &world, (-5.0, 5.0)).0.matches("<circle").count(), 10);
+        let landscape = swarm_on_function_svg("Ackley", &bat_world::<2>(), &Functions::Ackley, (-5.0, 5.0));
+        assert!(landscape.0.matches("<rect").count() >= 80 * 80);
+    }
+}
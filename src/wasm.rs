@@ -0,0 +1,148 @@
+// The JavaScript API, built with `--features wasm --no-default-features --target wasm32-unknown-unknown` and
+// wasm-bindgen, e.g. `wasm-pack build --target web -- --features wasm --no-default-features`:
+//
+//     const swarm = Swarm.benchmark("butterflies", "ackley", 2, 1);
+//     swarm.setParameter("butterfly-count", 50);
+//     swarm.step(1);
+//     swarm.population(); // Float64Array of x0, y0, x1, y1, ...
+//
+// Made for watching the swarms move, everything runs on the calling thread
+
+use std::sync::{Arc, Mutex};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{dynamic::{ConfiguredOptimizer, DynamicWorld, ObjectiveSpecification, Settings}, real::{self, Real}};
+
+struct JsObjective {
+    function: js_sys::Function,
+    error: Mutex<Option<String>>, // The first exception thrown by the function, thrown again by the call that caused it
+}
+
+// JavaScript values can't leave their thread, and the module has only one
+unsafe impl Send for JsObjective {}
+unsafe impl Sync for JsObjective {}
+
+impl JsObjective {
+    fn call(&self, coordinates: &[Real]) -> Real {
+        let mut error = self.error.lock().unwrap();
+        if error.is_some() {
+            return Real::INFINITY;
+        }
+        let coordinates = js_sys::Float64Array::from(coordinates.iter().map(|&coordinate| real::to_f64(coordinate)).collect::<Vec<_>>().as_slice());
+        match self.function.call1(&JsValue::NULL, &coordinates) {
+            Ok(value) => match value.as_f64() {
+                Some(value) => return real::from_f64(value),
+                None => *error = Some("The objective returned something else than a number".to_string()),
+            },
+            Err(thrown) => *error = Some(match thrown.dyn_ref::<js_sys::Error>() {
+                Some(thrown) => String::from(thrown.message()),
+                None => thrown.as_string().unwrap_or_else(|| format!("{thrown:?}")),
+            }),
+        }
+        return Real::INFINITY;
+    }
+}
+
+#[wasm_bindgen]
+pub struct Swarm {
+    optimizer: ConfiguredOptimizer,
+    objective: Option<Arc<JsObjective>>,
+    iteration: usize, // Since the last reset
+}
+
+impl Swarm {
+    // Runs something on the world, with the errors of the objective thrown too
+    fn with_world<T>(&mut self, body: impl FnOnce(&mut dyn DynamicWorld) -> T) -> Result<T, JsError> {
+        let result = self.optimizer.world().map(body).map_err(|message| JsError::new(&message));
+        if let Some(error) = self.objective.as_ref().and_then(|objective| objective.error.lock().unwrap().take()) {
+            return Err(JsError::new(&error));
+        }
+        return result;
+    }
+}
+
+#[wasm_bindgen]
+impl Swarm {
+    // `objective` takes a Float64Array of coordinates and returns a number. The algorithm is named like its subcommand, e.g.
+    // "bats" or "grey-wolf", the dimensions one of 2, 10, 20, 30, 50 or 100
+    #[wasm_bindgen(constructor)]
+    pub fn new(algorithm: &str, objective: js_sys::Function, dimensions: usize, lower_bound: f64, upper_bound: f64, seed: u32) -> Result<Swarm, JsError> {
+        let settings = Settings::from_name(algorithm).map_err(|message| JsError::new(&message))?;
+        let objective = Arc::new(JsObjective { function: objective, error: Mutex::new(None) });
+        let evaluated = objective.clone();
+        let specification = ObjectiveSpecification::Callable(Arc::new(move |coordinates: &[Real]| evaluated.call(coordinates)));
+        let bounds = specification.bounds(Some((lower_bound, upper_bound))).map_err(|message| JsError::new(&message))?;
+        return Ok(Self {
+            optimizer: ConfiguredOptimizer::new(settings, specification, dimensions, bounds, seed as u64),
+            objective: Some(objective),
+            iteration: 0,
+        });
+    }
+
+    // One of the benchmark functions of the command line, in its usual bounds
+    pub fn benchmark(algorithm: &str, function: &str, dimensions: usize, seed: u32) -> Result<Swarm, JsError> {
+        let settings = Settings::from_name(algorithm).map_err(|message| JsError::new(&message))?;
+        let specification = ObjectiveSpecification::builtin(function).map_err(|message| JsError::new(&message))?;
+        let bounds = specification.bounds(None).map_err(|message| JsError::new(&message))?;
+        return Ok(Self {
+            optimizer: ConfiguredOptimizer::new(settings, specification, dimensions, bounds, seed as u64),
+            objective: None,
+            iteration: 0,
+        });
+    }
+
+    // By the name of the command line flag, e.g. "bat-count". Starts over with a new population from the original seed
+    #[wasm_bindgen(js_name = setParameter)]
+    pub fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), JsError> {
+        self.iteration = 0;
+        return self.optimizer.set(name, value).map_err(|message| JsError::new(&message));
+    }
+
+    // The run is open ended, so schedules over the planned length of a run stay at their start
+    pub fn step(&mut self, iterations: usize) -> Result<(), JsError> {
+        self.with_world(|world| {
+            for _ in 0..iterations {
+                world.do_iteration(usize::MAX);
+            }
+        })?;
+        self.iteration += iterations;
+        return Ok(());
+    }
+
+    // A fresh population, continuing the random sequence
+    pub fn reset(&mut self) -> Result<(), JsError> {
+        self.iteration = 0;
+        return self.with_world(|world| world.reset());
+    }
+
+    // The coordinates of every agent, one after another
+    pub fn population(&mut self) -> Result<Vec<f64>, JsError> {
+        return self.with_world(|world| world.population());
+    }
+
+    #[wasm_bindgen(js_name = bestSolution)]
+    pub fn best_solution(&mut self) -> Result<Vec<f64>, JsError> {
+        return self.with_world(|world| world.best_solution());
+    }
+
+    #[wasm_bindgen(js_name = bestValue)]
+    pub fn best_value(&mut self) -> Result<f64, JsError> {
+        return self.with_world(|world| world.best_solution_value());
+    }
+
+    #[wasm_bindgen(js_name = evaluationCount)]
+    pub fn evaluation_count(&mut self) -> Result<usize, JsError> {
+        return self.with_world(|world| world.evaluation_count());
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn iteration(&self) -> usize {
+        return self.iteration;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dimensions(&self) -> usize {
+        return self.optimizer.dimensions;
+    }
+}
@@ -0,0 +1,368 @@
+// Objectives evaluated by a pool of worker processes over ZeroMQ, built with `--features zmq`, for simulations taking
+// seconds per evaluation spread over several machines. The objective binds a ROUTER socket and the workers connect to
+// it with REQ sockets. A worker announces itself with an empty message, then every message it sends is the answer to
+// its last task and asks for the next one:
+//
+//     worker -> ""
+//     worker <- {"id": 7, "inputs": [[0.5, -1.25], [3.0, 2.0]]}
+//     worker -> {"id": 7, "values": [1.8125, 13.0]}      or {"id": 7, "error": "simulation diverged"}
+//
+// Tasks go to idle workers in the order they were submitted, one input each or a whole population in batch mode. A task
+// without an answer within the timeout is assumed lost with its worker and handed to another one. Workers can join and
+// leave at any time, evaluations wait until one is available. A task failing every attempt or answered with an error
+// panics, like the other invalid inputs of the optimizers. Answers that aren't JSON can't be matched to a task, they're
+// only counted and their task is handed out again after the timeout. A REQ socket stays stuck if the objective restarts while it
+// waits for a task, so workers should reconnect and announce themselves again after a second or so without one.
+// run_worker is a worker for functions written in Rust
+
+use std::{collections::{HashMap, VecDeque}, fmt, sync::{atomic::{AtomicUsize, Ordering}, mpsc, Arc}, thread, time::{Duration, Instant}};
+
+use serde_json::{json, Value};
+
+use crate::{functions::Function, real::{self, Real}, vector::VectorN};
+
+const POLL_INTERVAL_MS: i64 = 5; // How long the dispatcher waits for workers before checking for new tasks
+const WORKER_RECONNECT_MS: i64 = 1000; // How long run_worker waits for a task before reconnecting
+
+#[derive(Clone, Copy, Debug)]
+pub struct ZmqOptions {
+    pub batch: bool, // One task per population instead of one per agent
+    pub timeout: Duration, // Before a task is given to another worker
+    pub retries: u32, // Workers a task is given to after the first one
+}
+
+impl Default for ZmqOptions {
+    fn default() -> Self {
+        return Self { batch: false, timeout: Duration::from_secs(60), retries: 2 };
+    }
+}
+
+// Inputs of a task, with where its values go
+struct Job {
+    index: usize, // Of the task in its evaluation
+    inputs: Vec<Vec<f64>>,
+    reply: mpsc::Sender<(usize, Result<Vec<f64>, String>)>,
+}
+
+struct Task {
+    job: Job,
+    attempts: u32,
+}
+
+pub struct ZmqObjective {
+    endpoint: String,
+    options: ZmqOptions,
+    jobs: mpsc::Sender<Job>, // To the dispatcher thread, which stops once the objective is dropped
+    in_flight: Arc<AtomicUsize>,
+    idle_workers: Arc<AtomicUsize>,
+    unreadable_answers: Arc<AtomicUsize>,
+}
+
+impl fmt::Debug for ZmqObjective {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return formatter.debug_struct("ZmqObjective").field("endpoint", &self.endpoint).field("options", &self.options).finish();
+    }
+}
+
+impl ZmqObjective {
+    // Binds the endpoint, e.g. `tcp://*:5555`. A wildcard port is resolved, see endpoint
+    pub fn bind(endpoint: &str, options: ZmqOptions) -> Self {
+        let context = zmq::Context::new();
+        let socket = context.socket(zmq::ROUTER).unwrap();
+        socket.set_router_mandatory(true).unwrap(); // Tasks for workers that are gone fail instead of vanishing
+        socket.set_linger(0).unwrap();
+        socket.bind(endpoint).unwrap_or_else(|error| panic!("Could not bind {endpoint}: {error}"));
+        let bound_endpoint = socket.get_last_endpoint().unwrap().unwrap_or_else(|_| endpoint.to_string());
+        let (jobs, received_jobs) = mpsc::channel();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let idle_workers = Arc::new(AtomicUsize::new(0));
+        let unreadable_answers = Arc::new(AtomicUsize::new(0));
+        let mut dispatcher = Dispatcher {
+            socket,
+            options,
+            jobs: received_jobs,
+            pending: VecDeque::new(),
+            in_flight: HashMap::new(),
+            idle: VecDeque::new(),
+            next_id: 0,
+            in_flight_count: in_flight.clone(),
+            idle_count: idle_workers.clone(),
+            unreadable_count: unreadable_answers.clone(),
+        };
+        thread::spawn(move || dispatcher.run());
+        return Self { endpoint: bound_endpoint, options, jobs, in_flight, idle_workers, unreadable_answers };
+    }
+
+    pub fn endpoint(&self) -> &str {
+        return &self.endpoint;
+    }
+
+    // Tasks given to a worker and not answered yet
+    pub fn in_flight(&self) -> usize {
+        return self.in_flight.load(Ordering::Relaxed);
+    }
+
+    // Workers waiting for a task
+    pub fn idle_workers(&self) -> usize {
+        return self.idle_workers.load(Ordering::Relaxed);
+    }
+
+    // Answers dropped since the objective was bound because they weren't JSON
+    pub fn unreadable_answers(&self) -> usize {
+        return self.unreadable_answers.load(Ordering::Relaxed);
+    }
+}
+
+impl<const N: usize> Function<N> for ZmqObjective {
+    fn evaluate(&self, input: VectorN<Real, N>) -> Real {
+        let mut values = Vec::with_capacity(1);
+        self.evaluate_batch_into(&[input], &mut values);
+        return values[0];
+    }
+
+    fn evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        values.clear();
+        values.resize(inputs.len(), 0.0);
+        if inputs.is_empty() {
+            return;
+        }
+        let task_size = if self.options.batch { inputs.len() } else { 1 };
+        let (reply, replies) = mpsc::channel();
+        let mut task_count = 0;
+        for (index, chunk) in inputs.chunks(task_size).enumerate() {
+            let coordinates = chunk.iter().map(|input| input.coordinates.iter().map(|&coordinate| real::to_f64(coordinate)).collect()).collect();
+            self.jobs.send(Job { index, inputs: coordinates, reply: reply.clone() }).expect("The ZeroMQ dispatcher stopped");
+            task_count += 1;
+        }
+        for _ in 0..task_count {
+            let (index, received) = replies.recv().expect("The ZeroMQ dispatcher stopped");
+            let received = received.unwrap_or_else(|error| panic!("The objective at {} failed: {}", self.endpoint, error));
+            let chunk = &mut values[index * task_size..((index + 1) * task_size).min(inputs.len())];
+            if received.len() != chunk.len() {
+                panic!("A worker of {} returned {} values for {} inputs", self.endpoint, received.len(), chunk.len());
+            }
+            for (value, received) in chunk.iter_mut().zip(received) {
+                *value = real::from_f64(received);
+            }
+        }
+    }
+
+    // The tasks are already spread over the workers, rayon threads would only wait on them
+    fn par_evaluate_batch_into(&self, inputs: &[VectorN<Real, N>], values: &mut Vec<Real>) {
+        self.evaluate_batch_into(inputs, values);
+    }
+}
+
+// Owns the socket, matching the tasks of every evaluation in progress with idle workers
+struct Dispatcher {
+    socket: zmq::Socket,
+    options: ZmqOptions,
+    jobs: mpsc::Receiver<Job>,
+    pending: VecDeque<Task>,
+    in_flight: HashMap<u64, (Task, Instant)>, // By the id sent to the worker
+    idle: VecDeque<Vec<u8>>, // Identities of the workers waiting for a task
+    next_id: u64,
+    in_flight_count: Arc<AtomicUsize>,
+    idle_count: Arc<AtomicUsize>,
+    unreadable_count: Arc<AtomicUsize>,
+}
+
+impl Dispatcher {
+    fn run(&mut self) {
+        loop {
+            loop {
+                match self.jobs.try_recv() {
+                    Ok(job) => self.pending.push_back(Task { job, attempts: 0 }),
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => return,
+                }
+            }
+            self.requeue_lost_tasks();
+            self.hand_out_tasks();
+            self.update_counts();
+            if self.socket.poll(zmq::POLLIN, POLL_INTERVAL_MS).unwrap() > 0 {
+                self.receive();
+            }
+        }
+    }
+
+    // Before any reply is sent, so an evaluation that got all of its values sees none of its tasks in flight
+    fn update_counts(&self) {
+        self.in_flight_count.store(self.in_flight.len(), Ordering::Relaxed);
+        self.idle_count.store(self.idle.len(), Ordering::Relaxed);
+    }
+
+    fn requeue_lost_tasks(&mut self) {
+        let timeout = self.options.timeout;
+        let lost = self.in_flight.iter().filter(|(_, (_, sent))| sent.elapsed() > timeout).map(|(&id, _)| id).collect::<Vec<_>>();
+        for id in lost {
+            let (task, _) = self.in_flight.remove(&id).unwrap();
+            self.update_counts();
+            if task.attempts > self.options.retries {
+                let error = format!("no answer after {} attempts", task.attempts);
+                let _ = task.job.reply.send((task.job.index, Err(error))); // The evaluation may be gone with a panicking run
+            } else {
+                self.pending.push_front(task);
+            }
+        }
+    }
+
+    fn hand_out_tasks(&mut self) {
+        while !self.pending.is_empty() {
+            let Some(worker) = self.idle.pop_front() else {
+                return;
+            };
+            let mut task = self.pending.pop_front().unwrap();
+            let id = self.next_id;
+            let message = json!({ "id": id, "inputs": task.job.inputs }).to_string();
+            match self.socket.send_multipart([worker.as_slice(), b"", message.as_bytes()], 0) {
+                Ok(()) => {
+                    self.next_id += 1;
+                    task.attempts += 1;
+                    self.in_flight.insert(id, (task, Instant::now()));
+                },
+                Err(zmq::Error::EHOSTUNREACH) => self.pending.push_front(task), // The worker disconnected while idle
+                Err(error) => panic!("Could not send a task: {error}"),
+            }
+        }
+    }
+
+    fn receive(&mut self) {
+        let frames = self.socket.recv_multipart(0).unwrap();
+        let [worker, _, body] = <[Vec<u8>; 3]>::try_from(frames).unwrap_or_else(|frames| panic!("Unexpected message of {} frames from a worker", frames.len()));
+        if !body.is_empty() {
+            self.accept_answer(&body);
+        }
+        self.idle.push_back(worker);
+    }
+
+    // Answers of tasks that were already answered by another worker are dropped
+    fn accept_answer(&mut self, body: &[u8]) {
+        let Ok(answer) = serde_json::from_slice::<Value>(body) else {
+            self.unreadable_count.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        let Some((task, _)) = answer["id"].as_u64().and_then(|id| self.in_flight.remove(&id)) else {
+            return;
+        };
+        self.update_counts();
+        let result = if let Some(error) = answer["error"].as_str() {
+            Err(format!("a worker reported `{error}`"))
+        } else {
+            answer["values"].as_array()
+                .and_then(|values| values.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+                .ok_or_else(|| format!("unexpected answer `{answer}`"))
+        };
+        let _ = task.job.reply.send((task.job.index, result));
+    }
+}
+
+// Evaluates tasks from the objective at the endpoint, e.g. `tcp://optimizer-host:5555`, until the process exits
+pub fn run_worker<const N: usize>(endpoint: &str, function: &impl Function<N>) {
+    let context = zmq::Context::new();
+    let mut values = Vec::new();
+    loop {
+        let socket = context.socket(zmq::REQ).unwrap();
+        socket.set_linger(0).unwrap();
+        socket.connect(endpoint).unwrap_or_else(|error| panic!("Could not connect to {endpoint}: {error}"));
+        socket.send("", 0).unwrap();
+        // A restarted objective never answers what was sent to the old one, so a worker left waiting starts over
+        while socket.poll(zmq::POLLIN, WORKER_RECONNECT_MS).unwrap() > 0 {
+            let task: Value = serde_json::from_slice(&socket.recv_bytes(0).unwrap()).unwrap();
+            let inputs = task["inputs"].as_array().into_iter().flatten().map(|input| {
+                let coordinates = input.as_array()?.iter().map(|coordinate| coordinate.as_f64().map(real::from_f64)).collect::<Option<Vec<_>>>()?;
+                return Some(VectorN::new(<[Real; N]>::try_from(coordinates).ok()?));
+            }).collect::<Option<Vec<_>>>();
+            let answer = match inputs {
+                Some(inputs) => {
+                    function.evaluate_batch_into(&inputs, &mut values);
+                    json!({ "id": task["id"], "values": values.iter().map(|&value| real::to_f64(value)).collect::<Vec<_>>() })
+                },
+                None => json!({ "id": task["id"], "error": format!("expected inputs of {N} coordinates") }),
+            };
+            socket.send(answer.to_string().as_bytes(), 0).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use crate::{functions::Function, real::{self, Real}, vector::VectorN};
+
+    use super::{run_worker, ZmqObjective, ZmqOptions};
+
+    struct Sum;
+
+    impl Function<3> for Sum {
+        fn evaluate(&self, input: VectorN<Real, 3>) -> Real {
+            return input.coordinates.iter().sum();
+        }
+    }
+
+    fn inputs() -> Vec<VectorN<Real, 3>> {
+        return (0..7).map(|index| VectorN::new([index as f64, 0.5, -2.0].map(real::from_f64))).collect();
+    }
+
+    fn evaluate_with_workers(options: ZmqOptions, worker_count: usize) -> Vec<Real> {
+        let objective = ZmqObjective::bind("tcp://127.0.0.1:*", options);
+        for _ in 0..worker_count {
+            let endpoint = objective.endpoint().to_string();
+            thread::spawn(move || run_worker(&endpoint, &Sum));
+        }
+        let mut values = Vec::new();
+        objective.evaluate_batch_into(&inputs(), &mut values);
+        assert_eq!(objective.in_flight(), 0);
+        return values;
+    }
+
+    fn expected() -> Vec<Real> {
+        return (0..7).map(|index| real::from_f64(index as f64 - 1.5)).collect();
+    }
+
+    #[test]
+    fn worker_pool_test() {
+        assert_eq!(evaluate_with_workers(ZmqOptions::default(), 3), expected());
+    }
+
+    #[test]
+    fn batch_test() {
+        assert_eq!(evaluate_with_workers(ZmqOptions { batch: true, ..ZmqOptions::default() }, 1), expected());
+    }
+
+    #[test]
+    fn lost_task_test() {
+        let objective = ZmqObjective::bind("tcp://127.0.0.1:*", ZmqOptions { timeout: Duration::from_millis(200), ..ZmqOptions::default() });
+        // Takes a task and never answers
+        let context = zmq::Context::new();
+        let silent = context.socket(zmq::REQ).unwrap();
+        silent.connect(objective.endpoint()).unwrap();
+        silent.send("", 0).unwrap();
+        let endpoint = objective.endpoint().to_string();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            run_worker(&endpoint, &Sum);
+        });
+        assert_eq!(Function::<3>::evaluate(&objective, inputs()[3]), real::from_f64(1.5));
+    }
+
+    #[test]
+    fn unreadable_answer_test() {
+        let objective = ZmqObjective::bind("tcp://127.0.0.1:*", ZmqOptions { timeout: Duration::from_millis(200), ..ZmqOptions::default() });
+        // Takes a task and answers with something that isn't JSON
+        let context = zmq::Context::new();
+        let broken = context.socket(zmq::REQ).unwrap();
+        broken.connect(objective.endpoint()).unwrap();
+        broken.send("", 0).unwrap();
+        let endpoint = objective.endpoint().to_string();
+        thread::spawn(move || {
+            broken.recv_bytes(0).unwrap();
+            broken.send("values: 1.5", 0).unwrap();
+            thread::sleep(Duration::from_millis(50));
+            run_worker(&endpoint, &Sum);
+        });
+        assert_eq!(Function::<3>::evaluate(&objective, inputs()[3]), real::from_f64(1.5));
+        assert_eq!(objective.unreadable_answers(), 1);
+    }
+}